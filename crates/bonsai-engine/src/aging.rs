@@ -0,0 +1,172 @@
+//! Old trees cracking and losing branches, for trees left open in the window for a long time
+//!
+//! With `aging_every` set, a grown tree ages every that many seconds of being looked after on
+//! average, see `Tree::tend`, at times drawn from the seed like the storms. Each time the side
+//! branch under the most stress gives: its weight times how far out it carries that weight from
+//! where it forks off. With a chance of `aging_severity` it snaps and falls, and a flush of
+//! attraction points lets the tree grow into the gap. Otherwise it cracks and dies back to
+//! deadwood, dropping its twigs and bleaching to `deadwood_color` like the jin of a bonsai.
+//!
+//! The deadwood is the selection called `deadwood`, so it is saved with the tree and can be
+//! recolored or pruned by name like any other set.
+
+use rand::Rng;
+use rand_chacha::{rand_core::SeedableRng, ChaCha12Rng};
+
+use crate::*;
+
+/// Keeps the rolls for aging apart from every other use of the seed
+const AGING_SALT: u64 = 0x6167_696e_6721;
+/// Name of the selection holding the deadwood
+pub const DEADWOOD: &str = "deadwood";
+/// Wood thinner than this many times `leaf_max_width` is too light to crack
+const THINNEST: f32 = 2.0;
+
+/// What aging did to the tree
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Aging {
+    /// the branch at the node died back to deadwood
+    Cracked(usize),
+    /// the branch at the node snapped off
+    Snapped(usize),
+}
+
+/// Seconds of being looked after until the tree ages the `nth` time, counting from 0
+fn due(config: &Config, nth: u32) -> f32 {
+    let mut rng = ChaCha12Rng::seed_from_u64(config.seed ^ AGING_SALT);
+    // exponentially distributed, like the calm between storms
+    (0..=nth)
+        .map(|_| -config.aging_every * (1.0 - rng.gen::<f32>()).ln())
+        .sum()
+}
+
+/// The side branch under the most stress that can still give, if there is one
+fn stressed(tree: &Tree) -> Option<usize> {
+    let nodes = &tree.nodes;
+    let (pos, alive) = (nodes.pos(), nodes.alive());
+    let deadwood = tree
+        .selection(DEADWOOD)
+        .map_or_else(|| vec![false; nodes.len()], |set| tree.members(set));
+    // where the weight of everything growing from each node lies, sideways
+    let mut sum_x = pos.iter().map(|pos| pos.vec().x).collect::<Vec<_>>();
+    let mut count = alive
+        .iter()
+        .map(|&alive| alive as usize)
+        .collect::<Vec<_>>();
+    for idx in (1..nodes.len()).rev() {
+        if let Some(parent) = nodes.parent(idx).filter(|_| alive[idx]) {
+            sum_x[parent] += sum_x[idx];
+            count[parent] += count[idx];
+        }
+    }
+    let continuations = tree.continuations();
+    (1..nodes.len())
+        .filter(|&idx| alive[idx] && !deadwood[idx])
+        .filter(|&idx| nodes.radius(idx) >= tree.config.leaf_max_width * THINNEST)
+        .filter_map(|idx| {
+            let parent = nodes.parent(idx)?;
+            // the trunk and the leaders carry on from their parent, only side branches give
+            (continuations[parent] != Some(idx)).then_some((idx, parent))
+        })
+        .map(|(idx, parent)| {
+            let center = sum_x[idx] / count[idx].max(1) as f32;
+            let lever = (center - pos[parent].vec().x).abs();
+            (idx, count[idx] as f32 * lever)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(idx, _)| idx)
+}
+
+impl Tree {
+    /// Ages the grown tree once if it has been looked after long enough for it, see `aging`
+    /// The nodes that died are left dead in place, for `PrettyRender::age` of the renderer to
+    /// let them fall before the tree grows into the gap
+    pub fn age(&mut self) -> Option<Aging> {
+        if self.config.aging_every <= 0.0
+            || self.is_growing()
+            || self.tended < due(&self.config, self.aged)
+        {
+            return None;
+        }
+        let mut rng = ChaCha12Rng::seed_from_u64(
+            (self.config.seed ^ AGING_SALT).wrapping_add(self.aged as u64 + 1),
+        );
+        self.aged += 1;
+        let idx = stressed(self)?;
+        if rng.gen::<f32>() < self.config.aging_severity {
+            self.kill_subtree(idx);
+            self.flush();
+            return Some(Aging::Snapped(idx));
+        }
+        // the twigs drop, the wood stays standing
+        let twigs = self
+            .nodes()
+            .enumerate()
+            .filter(|(_, node)| node.alive && node.radius < self.config.leaf_max_width)
+            .map(|(twig, _)| twig)
+            .collect::<Vec<_>>();
+        let branch = self.members(&selection::Selection {
+            name: String::new(),
+            nodes: vec![idx],
+            color: None,
+        });
+        for twig in twigs.into_iter().filter(|&twig| branch[twig]) {
+            self.kill_subtree(twig);
+        }
+        self.select(DEADWOOD, &[idx]);
+        self.recolor_selection(DEADWOOD, Some(self.config.deadwood_color));
+        Some(Aging::Cracked(idx))
+    }
+
+    /// How many times the tree aged so far, see `aging`
+    pub fn aged(&self) -> u32 {
+        self.aged
+    }
+
+    /// Notes that the tree aged `times` already, for a tree loaded with its deadwood
+    pub fn set_aged(&mut self, times: u32) {
+        self.aged = times;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn old_trees_crack_and_snap() {
+        let mut tree = Tree::new_min_growth(
+            Config {
+                seed: 8,
+                num_points: 3000,
+                aging_every: 60.0,
+                aging_severity: 0.0,
+                ..Config::default()
+            },
+            5,
+        );
+        tree.grow_fully(&cancel::CancelToken::new());
+        assert_eq!(tree.age(), None);
+        tree.tend(due(&tree.config, 0));
+        let Some(Aging::Cracked(idx)) = tree.age() else {
+            panic!("the tree didn't crack");
+        };
+        assert_eq!(tree.age(), None);
+        let deadwood = tree.selection(DEADWOOD).unwrap();
+        assert_eq!(deadwood.color, Some(tree.config.deadwood_color));
+        let members = tree.members(deadwood);
+        assert!(tree.node(idx).alive);
+        assert!(tree
+            .nodes()
+            .enumerate()
+            .filter(|(twig, _)| members[*twig])
+            .all(|(_, node)| !node.alive || node.radius >= tree.config.leaf_max_width));
+
+        tree.config.aging_severity = 1.0;
+        tree.tend(due(&tree.config, 1));
+        let Some(Aging::Snapped(idx)) = tree.age() else {
+            panic!("the tree didn't snap");
+        };
+        assert!(!tree.node(idx).alive);
+    }
+}
@@ -0,0 +1,39 @@
+//! An image behind the tree, for compositing it into a photo or painting
+//!
+//! Only where the image comes from lives here, `bonsai_render::background` loads and lays it out.
+
+use std::path::PathBuf;
+
+/// How the image is scaled to the output
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Fit {
+    /// the whole image is shown, bars in the sky color fill the rest
+    Contain,
+    /// the image fills the output, cutting off what sticks out
+    Cover,
+}
+
+impl Fit {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "contain" => Some(Self::Contain),
+            "cover" => Some(Self::Cover),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Contain => "contain",
+            Self::Cover => "cover",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Background {
+    pub path: PathBuf,
+    pub fit: Fit,
+    /// averages the image down to the tree's pixels, so both look alike
+    pub pixelate: bool,
+}
@@ -0,0 +1,47 @@
+//! Stopping long operations early without losing what they finished
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Set by Ctrl-C once `catch_interrupt` is installed, cancels every token
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Shared flag that long operations check between their steps
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed) || INTERRUPTED.load(Ordering::Relaxed)
+    }
+}
+
+/// Makes Ctrl-C cancel all tokens instead of killing the process, a second Ctrl-C still does
+#[cfg(unix)]
+pub fn catch_interrupt() {
+    extern "C" fn on_interrupt(_: libc::c_int) {
+        if INTERRUPTED.swap(true, Ordering::Relaxed) {
+            // only async-signal-safe calls in here
+            unsafe { libc::_exit(130) };
+        }
+    }
+    unsafe {
+        libc::signal(
+            libc::SIGINT,
+            on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    }
+}
+
+#[cfg(not(unix))]
+pub fn catch_interrupt() {}
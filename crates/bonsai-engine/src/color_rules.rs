@@ -0,0 +1,91 @@
+//! Color rules, overriding the palette for nodes that match all of a rule's conditions
+//!
+//! E.g. a sun-bleached trunk where it faces up or a band of lichen at some height.
+//! Rules are checked in order while painting, later rules blend over earlier ones.
+
+use crate::*;
+
+/// What a rule recolors
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Part {
+    Bark,
+    Leaves,
+}
+
+/// A range a fact about a node has to lie in, bounds included
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Condition {
+    /// distance to the root, in nodes
+    Depth { min: usize, max: usize },
+    /// height above the foot of the trunk, in world units
+    Height { min: f32, max: f32 },
+    /// direction the branch grows in, in degrees from straight up
+    Facing { min: f32, max: f32 },
+    /// how early the node grew, 0 for the newest growth and 1 for the root
+    Age { min: f32, max: f32 },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorRule {
+    pub part: Part,
+    pub when: Vec<Condition>,
+    pub color: Color,
+    /// how far the palette color is moved towards the rule's color, 1 replaces it
+    pub amount: f32,
+}
+
+/// What the conditions look at, for one node
+#[derive(Copy, Clone, Debug)]
+pub struct NodeFacts {
+    depth: usize,
+    height: f32,
+    facing: f32,
+    age: f32,
+}
+
+impl NodeFacts {
+    pub fn of(tree: &Tree, node_idx: usize) -> Self {
+        let node = tree.nodes.get(node_idx);
+        let pos = node.pos.vec();
+        let facing = node.parent.map_or(0.0, |parent_idx| {
+            let dir = pos - tree.nodes.pos()[parent_idx].vec();
+            dir.x.atan2(dir.y).abs().to_degrees()
+        });
+        // nodes are stored in the order they grew
+        let age = 1.0 - node_idx as f32 / (tree.nodes.len().max(2) - 1) as f32;
+        Self {
+            depth: node.depth,
+            height: pos.y - tree.config.origin.y,
+            facing,
+            age,
+        }
+    }
+}
+
+impl Condition {
+    fn holds(&self, facts: &NodeFacts) -> bool {
+        match *self {
+            Condition::Depth { min, max } => (min..=max).contains(&facts.depth),
+            Condition::Height { min, max } => (min..=max).contains(&facts.height),
+            Condition::Facing { min, max } => (min..=max).contains(&facts.facing),
+            Condition::Age { min, max } => (min..=max).contains(&facts.age),
+        }
+    }
+}
+
+/// The color of a part of the node after all rules that apply
+pub fn apply(rules: &[ColorRule], part: Part, facts: &NodeFacts, color: Color) -> Color {
+    rules
+        .iter()
+        .filter(|rule| rule.part == part && rule.when.iter().all(|c| c.holds(facts)))
+        .fold(color, |color, rule| {
+            let amount = rule.amount.clamp(0.0, 1.0);
+            let mix = |c: u8, r: u8| (c as f32 + (r as f32 - c as f32) * amount) as u8;
+            Color::new(
+                mix(color.r, rule.color.r),
+                mix(color.g, rule.color.g),
+                mix(color.b, rule.color.b),
+                color.a,
+            )
+        })
+}
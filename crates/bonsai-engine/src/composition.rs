@@ -0,0 +1,293 @@
+use crate::*;
+
+/// Where the trunk stands horizontally within the space of a tree
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    Center,
+    LeftThird,
+    RightThird,
+}
+
+impl Anchor {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "center" => Some(Anchor::Center),
+            "left-third" => Some(Anchor::LeftThird),
+            "right-third" => Some(Anchor::RightThird),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Anchor::Center => "center",
+            Anchor::LeftThird => "left-third",
+            Anchor::RightThird => "right-third",
+        }
+    }
+}
+
+/// What happens when a tree grows past the top of its slot
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    /// the canopy gets cut off
+    Clip,
+    /// zooms out around the foot of the trunk until the whole tree fits
+    Zoom,
+    /// follows the highest growth, letting the ground scroll out at the bottom
+    Scroll,
+}
+
+impl Overflow {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "clip" => Some(Overflow::Clip),
+            "zoom" => Some(Overflow::Zoom),
+            "scroll" => Some(Overflow::Scroll),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Overflow::Clip => "clip",
+            Overflow::Zoom => "zoom",
+            Overflow::Scroll => "scroll",
+        }
+    }
+}
+
+/// How one or more trees are laid out on the output
+#[derive(Debug, Clone)]
+pub struct Composition {
+    /// size of the whole output in pixels
+    pub output_width: f32,
+    pub output_height: f32,
+    /// free space around each tree
+    pub margin: f32,
+    /// fraction of the output height kept free above the trees, e.g. for a clock widget
+    pub top_clearance: f32,
+    /// number of trees spread evenly across the output
+    pub trees: usize,
+    pub anchor: Anchor,
+    /// framing on top of the layout, like the window was panned and zoomed to
+    pub view: View,
+    /// trees zoomed out below this fraction of their size in the layout grow in coarser
+    /// steps, see `Detail`; 0 grows every tree on the screen at full detail
+    pub detail_zoom: f32,
+}
+
+/// Panning and zooming of the whole output, around its middle
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct View {
+    /// movement in output pixels
+    pub pan: Vector2,
+    pub zoom: f32,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self {
+            pan: Vector2::zero(),
+            zoom: 1.0,
+        }
+    }
+}
+
+impl View {
+    /// The camera zoomed around the middle of a `screen` large output and then panned
+    pub fn apply(&self, camera: Camera, screen: Vector2) -> Camera {
+        if *self == Self::default() {
+            return camera;
+        }
+        let center = screen * 0.5;
+        Camera {
+            offset: center + (camera.offset - center) * self.zoom + self.pan,
+            zoom: camera.zoom * self.zoom,
+        }
+    }
+
+    /// This view followed by `other`, as one view
+    pub fn then(&self, other: View) -> View {
+        View {
+            pan: self.pan * other.zoom + other.pan,
+            zoom: self.zoom * other.zoom,
+        }
+    }
+}
+
+/// Where a tree's drawing ends up on the screen, like raylib's `Camera2D` with a zero target
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera {
+    /// screen position of the top left corner of the drawing
+    pub offset: Vector2,
+    pub zoom: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            offset: Vector2::zero(),
+            zoom: 1.0,
+        }
+    }
+}
+
+/// How closely a tree in the window is simulated, from how it is seen.
+/// A garden of many trees stays responsive while only the ones looked at grow every tick
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Detail {
+    /// grows every tick
+    Full,
+    /// far away, grows its steps of `COARSE_TICKS` ticks at once in one of them
+    Coarse,
+    /// off the screen, doesn't grow until it comes back
+    Paused,
+}
+
+/// Ticks a coarse tree waits between growing, see `Detail::Coarse`
+pub const COARSE_TICKS: u64 = 4;
+
+impl Detail {
+    /// Simulation steps in tick number `tick` for a tree growing `speed` steps a tick
+    pub fn steps(self, speed: usize, tick: u64) -> usize {
+        match self {
+            Detail::Full => speed,
+            Detail::Coarse if tick.is_multiple_of(COARSE_TICKS) => speed * COARSE_TICKS as usize,
+            Detail::Coarse | Detail::Paused => 0,
+        }
+    }
+}
+
+/// Space assigned to one tree
+#[derive(Debug, Clone)]
+pub struct Slot {
+    /// config of the tree, sized to fit the slot
+    pub config: Config,
+    /// screen position of the top left corner of the tree's drawing
+    pub offset: Vector2,
+    /// integer upscaling of the tree's pixels
+    pub zoom: f32,
+}
+
+impl Slot {
+    /// Camera drawing the tree at its place on the screen
+    pub fn camera(&self) -> Camera {
+        Camera {
+            offset: self.offset,
+            zoom: self.zoom,
+        }
+    }
+
+    /// Screen position of a point in the tree's world
+    pub fn to_screen(&self, world: Vector2) -> Vector2 {
+        let camera = self.camera();
+        camera.offset + Vector2::new(world.x, self.camera_y(world.y)) * camera.zoom
+    }
+
+    /// Point in the tree's world under a screen position, for a tree drawn with `camera`
+    pub fn to_world(&self, camera: Camera, screen: Vector2) -> Vector2 {
+        let drawing = (screen - camera.offset) / camera.zoom;
+        // the flip of `camera_y` is its own inverse
+        Vector2::new(drawing.x, self.camera_y(drawing.y))
+    }
+
+    /// Like `camera`, but handling a canopy that reaches up to world height `top`
+    /// beyond the top of the screen as the config's `overflow` says
+    pub fn camera_fitting(&self, top: f32) -> Camera {
+        let mut camera = self.camera();
+        let screen_top = camera.offset.y + self.camera_y(top) * camera.zoom;
+        if screen_top >= 0.0 {
+            return camera;
+        }
+        match self.config.overflow {
+            Overflow::Clip => {}
+            Overflow::Scroll => camera.offset.y -= screen_top,
+            Overflow::Zoom => {
+                let foot = Vector2::new(self.config.origin.x, self.camera_y(self.config.origin.y));
+                let ground = camera.offset + foot * camera.zoom;
+                camera.zoom = ground.y / (foot.y - self.camera_y(top));
+                camera.offset = ground - foot * camera.zoom;
+            }
+        }
+        camera
+    }
+
+    /// How closely the tree is simulated when drawn with `camera` on a `screen` large output
+    pub fn detail(&self, camera: Camera, screen: Vector2, detail_zoom: f32) -> Detail {
+        let pixel_width = self.config.pixel_size;
+        let columns = self.config.width as usize / pixel_width + CANVAS_PADDING;
+        let drawing =
+            Vector2::new((columns * pixel_width) as f32, self.camera_y(0.0)) * camera.zoom;
+        let (min, max) = (camera.offset, camera.offset + drawing);
+        if max.x < 0.0 || max.y < 0.0 || min.x > screen.x || min.y > screen.y {
+            Detail::Paused
+        } else if camera.zoom < self.zoom * detail_zoom {
+            Detail::Coarse
+        } else {
+            Detail::Full
+        }
+    }
+
+    /// Vertical position of a world height in the drawing, which the pixel renderer flips
+    /// and pads like in `Composition::layout`
+    fn camera_y(&self, world_y: f32) -> f32 {
+        let pixel_height = self.config.pixel_height();
+        let rows = self.config.height as usize / pixel_height + CANVAS_PADDING + 1;
+        (rows * pixel_height) as f32 - world_y
+    }
+}
+
+impl Composition {
+    /// Splits the output into one slot per tree.
+    ///
+    /// Each tree gets a square area standing on the bottom of its slot, placed at the anchor,
+    /// so wide outputs don't stretch the tree and tall outputs leave space above it.
+    /// The world keeps roughly the size of the base config and gets scaled up by an integer factor,
+    /// so large outputs have the same tree shapes with bigger pixels.
+    pub fn layout(&self, base: &Config) -> Vec<Slot> {
+        let trees = self.trees.max(1);
+        let slot_width = self.output_width / trees as f32;
+        let available_height = self.output_height * (1.0 - self.top_clearance.clamp(0.0, 1.0));
+        let size = (slot_width.min(available_height) - 2.0 * self.margin).max(1.0);
+        let zoom = (size / base.width.max(base.height)).round().max(1.0);
+        let world = size / zoom;
+
+        (0..trees)
+            .map(|idx| {
+                let slot_x = idx as f32 * slot_width;
+                let anchor_x = match self.anchor {
+                    Anchor::Center => slot_width / 2.0,
+                    Anchor::LeftThird => slot_width / 3.0,
+                    Anchor::RightThird => slot_width * 2.0 / 3.0,
+                };
+                let left = (anchor_x - size / 2.0)
+                    .min(slot_width - self.margin - size)
+                    .max(self.margin);
+
+                let mut config = base.clone();
+                config.width = world;
+                config.height = world;
+                config.origin = Vector2::new(
+                    base.origin.x / base.width * world,
+                    base.origin.y / base.height * world,
+                );
+                // the pixel renderer draws the top of the world a bit below the top of the drawing
+                let padding = (CANVAS_PADDING + 1) * config.pixel_height();
+                let top = self.output_height - self.margin - size;
+                let camera = self.view.apply(
+                    Camera {
+                        offset: Vector2::new(slot_x + left, top - padding as f32 * zoom),
+                        zoom,
+                    },
+                    Vector2::new(self.output_width, self.output_height),
+                );
+                Slot {
+                    config,
+                    offset: camera.offset,
+                    zoom: camera.zoom,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,184 @@
+//! Time of day, the sun crossing the sky and the moon lighting the tree at night
+//!
+//! The sun rises on the left at 6, stands above the tree at noon and sets on the right at 18,
+//! shining warm and low towards dawn and dusk. Over the night the moon takes the same path with
+//! a faint cool light, the sky darkens and the shadows deepen. A `DayCycle` lets the hours pass
+//! in the window.
+
+use std::f32::consts::PI;
+
+use crate::*;
+
+/// Hour the sun rises at, it sets twelve hours later
+const SUNRISE: f32 = 6.0;
+
+/// Steps a day's light changes in, so a tree only gets relit every quarter of an hour
+const STAGES: f32 = 96.0;
+
+/// Light, dome and sky colors of the day, at dawn and dusk and at night
+struct Palette {
+    light: Color,
+    dome_sky: Color,
+    dome_ground: Color,
+    sky: Color,
+}
+
+fn palette(day: &Config) -> [Palette; 3] {
+    let hex = |hex| Color::from_hex(hex).unwrap();
+    [
+        Palette {
+            light: Color::WHITE,
+            dome_sky: day.ambient_sky,
+            dome_ground: day.ambient_ground,
+            sky: day.sky,
+        },
+        Palette {
+            light: hex("ffb46e"),
+            dome_sky: hex("ffc9a0"),
+            dome_ground: hex("7a5a4a"),
+            sky: hex("f2b08a"),
+        },
+        Palette {
+            light: hex("7f90c8"),
+            dome_sky: hex("2a3560"),
+            dome_ground: hex("1a1a28"),
+            sky: hex("1b2340"),
+        },
+    ]
+}
+
+/// An hour of the day, from 0 to 24
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TimeOfDay(f32);
+
+impl TimeOfDay {
+    pub fn new(hour: f32) -> Self {
+        Self(hour.rem_euclid(24.0))
+    }
+
+    pub fn hour(self) -> f32 {
+        self.0
+    }
+
+    /// Height of the sun, 1 at noon, 0 at sunrise and sunset and -1 at midnight
+    pub fn sun_height(self) -> f32 {
+        ((self.0 - SUNRISE) / 12.0 * PI).sin()
+    }
+
+    pub fn is_night(self) -> bool {
+        self.sun_height() < 0.0
+    }
+
+    /// Sets the sun or moon, the color of their light, the dome light, how dark the shadows get
+    /// and the sky of this hour. Noon keeps the light and sky of the config as they are
+    pub fn apply(self, config: &mut Config) {
+        let [day, dusk, night] = palette(config);
+        let height = self.sun_height();
+        // the light warms over the hour and a bit around sunrise and sunset,
+        // and turns into moonlight quickly after the sun is gone
+        let (to, amount) = if height >= 0.0 {
+            (&day, (height * 3.0).min(1.0))
+        } else {
+            (&night, (-height * 4.0).min(1.0))
+        };
+        let blend = |of: fn(&Palette) -> Color| of(&dusk).lerp(of(to), amount);
+        // the sun or the moon, whichever is up, crossing from the left to the right
+        let across = (self.0 - SUNRISE).rem_euclid(12.0) / 12.0;
+        config.sun_angle = PI * (1.0 - across);
+        config.sun_elevation = 0.3 + 0.6 * (across * PI).sin();
+        config.sun_color = blend(|p| p.light);
+        config.ambient_sky = blend(|p| p.dome_sky);
+        config.ambient_ground = blend(|p| p.dome_ground);
+        config.sky = blend(|p| p.sky);
+        // the least light the shadows get falls to a quarter over the night
+        let dark = if height < 0.0 { amount } else { 0.0 };
+        config.light_floor *= 1.0 - 0.75 * dark;
+    }
+}
+
+/// The hours of a day passing in a loop
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DayCycle {
+    hour: f32,
+    /// seconds a whole day lasts
+    length: f32,
+    /// the config's looks at noon, which every hour is derived from
+    noon: Noon,
+}
+
+/// What `TimeOfDay::apply` changes about a config, as it was before
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Noon {
+    sky: Color,
+    ambient_sky: Color,
+    ambient_ground: Color,
+    light_floor: f32,
+}
+
+impl DayCycle {
+    /// Starts at `start` o'clock from the light and sky of `config`
+    pub fn new(start: f32, length: f32, config: &Config) -> Self {
+        Self {
+            hour: start.rem_euclid(24.0),
+            length: length.max(1.0),
+            noon: Noon {
+                sky: config.sky,
+                ambient_sky: config.ambient_sky,
+                ambient_ground: config.ambient_ground,
+                light_floor: config.light_floor,
+            },
+        }
+    }
+
+    pub fn time(&self) -> TimeOfDay {
+        TimeOfDay::new((self.hour * STAGES / 24.0).floor() * 24.0 / STAGES)
+    }
+
+    /// Lets `seconds` pass
+    pub fn advance(&mut self, seconds: f32) {
+        self.hour = (self.hour + seconds / self.length * 24.0).rem_euclid(24.0);
+    }
+
+    /// Lights the config like at this time of the day. The sky is that of `config` unless
+    /// `keep_sky` leaves one set by something else, like the seasons passing
+    pub fn apply(&self, config: &mut Config, keep_sky: bool) {
+        if !keep_sky {
+            config.sky = self.noon.sky;
+        }
+        config.ambient_sky = self.noon.ambient_sky;
+        config.ambient_ground = self.noon.ambient_ground;
+        config.light_floor = self.noon.light_floor;
+        self.time().apply(config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noon_keeps_the_colors_and_night_darkens_them() {
+        let config = Config::default();
+        let mut noon = config.clone();
+        TimeOfDay::new(12.0).apply(&mut noon);
+        assert_eq!(noon.sun_color, Color::WHITE);
+        assert_eq!(noon.sky, config.sky);
+        assert_eq!(noon.light_floor, config.light_floor);
+
+        let mut night = config.clone();
+        TimeOfDay::new(0.0).apply(&mut night);
+        assert!(TimeOfDay::new(0.0).is_night());
+        assert!(night.light_floor < config.light_floor);
+        let luma = |c: Color| c.r as u32 + c.g as u32 + c.b as u32;
+        assert!(luma(night.sky) < luma(config.sky));
+        assert!(night.sun_color.b > night.sun_color.r);
+
+        // the cycle starts from the config each time instead of darkening it further
+        let cycle = DayCycle::new(0.0, 60.0, &config);
+        let mut twice = config.clone();
+        cycle.apply(&mut twice, false);
+        cycle.apply(&mut twice, false);
+        assert_eq!(twice.sky, night.sky);
+        assert_eq!(twice.light_floor, night.light_floor);
+    }
+}
@@ -0,0 +1,81 @@
+//! Densities painted by hand, which the attraction points are scattered by instead of the noise
+//! or on top of it, to grow a tree into a heart, a logo or letters
+
+use std::sync::Arc;
+
+/// One density between 0 and 1 per world unit, with y going up like the world's
+#[derive(Debug, Clone, PartialEq)]
+pub struct DensityMap {
+    width: usize,
+    height: usize,
+    values: Vec<f32>,
+}
+
+impl DensityMap {
+    /// Takes the densities row by row from the bottom, `None` unless there is one per unit
+    pub fn new(width: usize, height: usize, values: Vec<f32>) -> Option<Self> {
+        (values.len() == width * height).then_some(Self {
+            width,
+            height,
+            values,
+        })
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    pub fn at(&self, x: usize, y: usize) -> f32 {
+        self.values[x + y * self.width]
+    }
+
+    /// The density at unit `x`, `y` of a world `width` by `height` units the map is stretched over,
+    /// blended between its four closest values so small images don't grow in blocks
+    pub fn stretched(&self, x: usize, y: usize, width: usize, height: usize) -> f32 {
+        // the centers of the world's units on the centers of the map's
+        let across = |unit: usize, world: usize, map: usize| {
+            let at = ((unit as f32 + 0.5) * map as f32 / world.max(1) as f32 - 0.5)
+                .clamp(0.0, map.saturating_sub(1) as f32);
+            let low = at.floor() as usize;
+            (low, (low + 1).min(map.saturating_sub(1)), at - low as f32)
+        };
+        let (x0, x1, tx) = across(x, width, self.width);
+        let (y0, y1, ty) = across(y, height, self.height);
+        let row = |y| self.at(x0, y) * (1.0 - tx) + self.at(x1, y) * tx;
+        row(y0) * (1.0 - ty) + row(y1) * ty
+    }
+}
+
+/// What the attraction points are scattered by
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum DensitySource {
+    /// the noise, thinned out towards the edges by the falloff
+    #[default]
+    Noise,
+    /// a painted density in place of the noise and falloff
+    Map(Arc<DensityMap>),
+    /// the noise and falloff multiplied by a mask, so the crown takes the mask's shape
+    /// but keeps the noise's clumps and gaps
+    Mask(Arc<DensityMap>),
+}
+
+impl DensitySource {
+    /// The painted density, if there is one
+    pub fn map(&self) -> Option<&Arc<DensityMap>> {
+        match self {
+            DensitySource::Noise => None,
+            DensitySource::Map(map) | DensitySource::Mask(map) => Some(map),
+        }
+    }
+
+    /// Whether both scatter the points the same way, without comparing the maps value by value
+    pub fn same_as(&self, other: &DensitySource) -> bool {
+        match (self, other) {
+            (DensitySource::Noise, DensitySource::Noise) => true,
+            (DensitySource::Map(a), DensitySource::Map(b))
+            | (DensitySource::Mask(a), DensitySource::Mask(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
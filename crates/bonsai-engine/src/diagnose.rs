@@ -0,0 +1,154 @@
+//! Telling why a tree came out tiny or bare
+//!
+//! Some parameters quietly stop the growth: tips that see no attraction points, new nodes
+//! that all get rejected, or pruning that cuts off nearly everything. The tree counts what
+//! its steps did, `Tree::warnings` turns that into advice naming the parameter to change.
+
+use std::fmt;
+
+use crate::*;
+
+/// A finished tree that grew fewer nodes than this came out tiny
+const TINY: usize = 40;
+/// Share of the nodes pruning may kill before the tree counts as cut bare
+const OVER_PRUNED: f32 = 0.9;
+
+/// What the steps of a tree did with the nodes they proposed
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Outcome {
+    /// nodes proposed by the tips in the last step
+    pub proposed: usize,
+    /// of those, rejected for rising less than `min_y_growth`
+    pub too_flat: usize,
+    /// rejected for being closer than `node_min_dist` to another node
+    pub crowded: usize,
+    /// rejected for being deeper than `max_depth`
+    pub too_deep: usize,
+    /// nodes killed by pruning over the whole growth
+    pub pruned: usize,
+}
+
+impl Outcome {
+    /// Starts counting the proposals of a new step, the pruning keeps adding up
+    pub(crate) fn next_step(&mut self) {
+        *self = Self {
+            pruned: self.pruned,
+            ..Self::default()
+        };
+    }
+}
+
+/// Something that went wrong with the growth, with the parameter behind it
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Warning {
+    /// the tips saw no attraction points, most of them were never reached
+    OutOfReach { unreached: f32 },
+    /// every proposed node rose less than `min_y_growth`
+    TooFlat,
+    /// every proposed node landed closer than `node_min_dist` to another one
+    Crowded,
+    /// every proposed node went deeper than `max_depth`
+    TooDeep,
+    /// pruning killed most of the nodes
+    OverPruned { pruned: f32 },
+}
+
+impl Warning {
+    /// The config parameter to change
+    pub fn parameter(self) -> &'static str {
+        match self {
+            Warning::OutOfReach { .. } => "attraction_dist",
+            Warning::TooFlat => "min_y_growth",
+            Warning::Crowded => "node_min_dist",
+            Warning::TooDeep => "max_depth",
+            Warning::OverPruned { .. } => "prune_pow",
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::OutOfReach { unreached } => write!(
+                f,
+                "growth stopped with {:.0}% of the attraction points out of reach, raise attraction_dist",
+                unreached * 100.0
+            ),
+            Warning::TooFlat => write!(
+                f,
+                "growth stopped because no new branch rose by min_y_growth, lower it (negative lets branches hang)"
+            ),
+            Warning::Crowded => write!(
+                f,
+                "growth stopped because every new node was closer than node_min_dist to another, lower it or raise grow_dist"
+            ),
+            Warning::TooDeep => write!(
+                f,
+                "growth stopped because the branches reached max_depth nodes, raise it"
+            ),
+            Warning::OverPruned { pruned } => write!(
+                f,
+                "pruning cut off {:.0}% of the branches, lower prune_pow or prune_size_ratio",
+                pruned * 100.0
+            ),
+        }
+    }
+}
+
+impl Tree {
+    /// What looks wrong with the growth so far, empty for a healthy tree
+    pub fn warnings(&self) -> Vec<Warning> {
+        let outcome = &self.outcome;
+        let mut warnings = vec![];
+        // a tree that grew and was then pruned bare is told apart below
+        if !self.growing && self.nodes.len() < TINY {
+            // the checks of a step reject for depth first, then for height, then for crowding
+            let rejected = if outcome.too_deep > 0 {
+                Some(Warning::TooDeep)
+            } else if outcome.too_flat > 0 {
+                Some(Warning::TooFlat)
+            } else if outcome.crowded > 0 {
+                Some(Warning::Crowded)
+            } else {
+                None
+            };
+            match rejected {
+                Some(warning) => warnings.push(warning),
+                None if !self.points.is_empty() => warnings.push(Warning::OutOfReach {
+                    unreached: self.points.len() as f32 / self.config.num_points.max(1) as f32,
+                }),
+                None => {}
+            }
+        }
+        let pruned = outcome.pruned as f32 / self.nodes.len() as f32;
+        if pruned > OVER_PRUNED {
+            warnings.push(Warning::OverPruned { pruned });
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stunted_trees_name_the_parameter() {
+        let grow = |config: Config| {
+            let mut tree = Tree::new(Config { seed: 2, ..config });
+            tree.grow_fully(&cancel::CancelToken::new());
+            tree.warnings()
+        };
+        assert!(grow(Config::default()).is_empty());
+        let short_sighted = grow(Config {
+            attraction_dist: 3.0,
+            ..Config::default()
+        });
+        assert_eq!(short_sighted[0].parameter(), "attraction_dist");
+        let flat = grow(Config {
+            min_y_growth: 5.0,
+            ..Config::default()
+        });
+        assert_eq!(flat, [Warning::TooFlat]);
+    }
+}
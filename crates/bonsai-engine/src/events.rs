@@ -0,0 +1,94 @@
+//! What happens to a tree as it grows, sent to whoever listens
+//!
+//! `Tree::subscribe` hands out a channel the tree sends every node it adds or prunes down, and
+//! word when it stopped growing, so sound effects, telemetry or renderers of their own can follow
+//! along without diffing the node list every frame. The nodes are named by their index in
+//! `Tree::nodes`. Subdividing the finished tree and `Tree::cut_away` number them anew, which
+//! `Event::Renumbered` tells, the indices from before don't name the same nodes after it.
+//!
+//! A clone of a tree grows on its own, nobody listening to the original hears from it.
+
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+/// Something that happened to the tree
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// the node at the index grew
+    NodeAdded(usize),
+    /// the node at the index died, pruned by a heavier branch or cut off with its branch
+    NodePruned(usize),
+    /// the nodes got new indices, see `Tree::nodes` for what they are now
+    Renumbered,
+    /// the tree stopped growing
+    GrowthFinished,
+}
+
+/// The channels of everyone listening to a tree
+#[derive(Debug, Default)]
+pub(crate) struct Subscribers(Vec<Sender<Event>>);
+
+impl Subscribers {
+    pub(crate) fn subscribe(&mut self) -> Receiver<Event> {
+        let (sender, receiver) = channel();
+        self.0.push(sender);
+        receiver
+    }
+
+    /// Sends the event to everyone still listening, forgetting the ones who stopped
+    pub(crate) fn send(&mut self, event: Event) {
+        self.0.retain(|sender| sender.send(event).is_ok());
+    }
+}
+
+impl Clone for Subscribers {
+    /// Nobody listens to a clone yet
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl crate::Tree {
+    /// A channel the tree sends what happens to it on from now on, see `events`.
+    /// Dropping the receiver stops the tree from sending to it
+    pub fn subscribe(&mut self) -> Receiver<Event> {
+        self.subscribers.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn listeners_hear_every_node() {
+        let mut tree = Tree::new(Config {
+            seed: 2,
+            num_points: 2000,
+            subdivide_max_length: 0.0,
+            ..Config::default()
+        });
+        let events = tree.subscribe();
+        tree.grow_fully(&cancel::CancelToken::new());
+        let events = events.try_iter().collect::<Vec<_>>();
+        let added = events
+            .iter()
+            .filter(|event| matches!(event, Event::NodeAdded(_)))
+            .count();
+        let pruned = events
+            .iter()
+            .filter_map(|event| match event {
+                Event::NodePruned(idx) => Some(*idx),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(added + 1, tree.node_count());
+        assert!(pruned.iter().all(|&idx| !tree.node(idx).alive));
+        assert_eq!(
+            pruned.len(),
+            tree.nodes().filter(|node| !node.alive).count()
+        );
+        assert_eq!(events.last(), Some(&Event::GrowthFinished));
+        assert!(tree.clone().subscribers.0.is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+//! How the density of attraction points fades from the middle of the world towards its edges
+//!
+//! The falloff shapes the outline of the crown, the noise only breaks it up.
+//! Distances are measured relative to half the shorter side of the world, so 1 reaches
+//! the middle of the nearest edge.
+
+/// Weight of the attraction point density depending on the distance from the middle of the world
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Falloff {
+    /// Full density within `inner`, fading out smoothly until `outer`.
+    /// The distance is measured as a superellipse: `edge_pow` 2 gives round crowns,
+    /// higher powers square them off towards the corners of the world
+    Ring {
+        edge_pow: f32,
+        inner: f32,
+        outer: f32,
+    },
+    /// `1 / (1 + (distance / radius)^power)`, which never quite reaches zero,
+    /// so a few points always end up far out and grow straggly branches
+    InverseDistance { radius: f32, power: f32 },
+    /// Bell curve around the middle, `sigma` wide
+    Gaussian { sigma: f32 },
+    /// The same density everywhere, the noise alone decides
+    Flat,
+}
+
+impl Default for Falloff {
+    fn default() -> Self {
+        Falloff::Ring {
+            edge_pow: 3.5,
+            inner: 0.7,
+            outer: 1.0,
+        }
+    }
+}
+
+impl Falloff {
+    /// Density weight between 0 and 1 at `dx`, `dy` from the middle of a world
+    /// `width` × `height` large
+    pub fn weight(&self, dx: f32, dy: f32, width: f32, height: f32) -> f32 {
+        let half = 0.5 * width.min(height);
+        match *self {
+            Falloff::Ring {
+                edge_pow,
+                inner,
+                outer,
+            } => {
+                let dist = dx.abs().powf(edge_pow) + dy.abs().powf(edge_pow);
+                let fade0 = (outer * half).powf(edge_pow);
+                let fade1 = (inner / outer).powf(edge_pow) * fade0;
+
+                let v = (dist - fade0) / (fade1 - fade0);
+                let v = v.clamp(0.0, 1.0);
+
+                // sin curve mapping 0,1 to 0,1 to have a smooth gradient
+                (((v - 0.5) * std::f32::consts::PI).sin() + 1.0) * 0.5
+            }
+            Falloff::InverseDistance { radius, power } => {
+                let dist = (dx * dx + dy * dy).sqrt() / half;
+                1.0 / (1.0 + (dist / radius.max(f32::EPSILON)).powf(power))
+            }
+            Falloff::Gaussian { sigma } => {
+                let dist_sqr = (dx * dx + dy * dy) / (half * half);
+                let sigma = sigma.max(f32::EPSILON);
+                (-dist_sqr / (2.0 * sigma * sigma)).exp()
+            }
+            Falloff::Flat => 1.0,
+        }
+    }
+}
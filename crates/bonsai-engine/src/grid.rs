@@ -0,0 +1,106 @@
+//! Uniform spatial hash over positions, so the growth only compares things close to each other
+//!
+//! Cells are `attraction_dist` wide, which makes the attraction and kill queries
+//! look at the 3×3 cells around a position instead of every point of the tree.
+
+use std::collections::HashMap;
+
+use crate::point::{Point, Real};
+
+/// Indices of positions bucketed by the cell they fall in
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Grid {
+    cell: Real,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    len: usize,
+}
+
+impl Grid {
+    /// Empties the grid and changes its cell size, keeping the buckets' memory
+    pub fn reset(&mut self, cell: Real) {
+        self.cell = cell.max(1.0);
+        self.cells.values_mut().for_each(Vec::clear);
+        self.len = 0;
+    }
+
+    /// Cell size the grid was last reset to, 0 before that
+    pub fn cell(&self) -> Real {
+        self.cell
+    }
+
+    /// How many positions have been inserted since the last reset
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    fn key(&self, x: Real, y: Real) -> (i64, i64) {
+        (
+            (x / self.cell).floor() as i64,
+            (y / self.cell).floor() as i64,
+        )
+    }
+
+    pub fn insert(&mut self, idx: usize, pos: Point) {
+        let key = self.key(pos.x, pos.y);
+        self.cells.entry(key).or_default().push(idx);
+        self.len += 1;
+    }
+
+    /// Inserts the positions past the ones already in the grid, for lists that only grow
+    pub fn extend(&mut self, positions: &[Point]) {
+        for (idx, pos) in positions.iter().enumerate().skip(self.len) {
+            self.insert(idx, *pos);
+        }
+    }
+
+    /// Indices of all positions that may be closer than `radius` to `pos`, in no particular order
+    pub fn near(&self, pos: Point, radius: Real) -> impl Iterator<Item = usize> + '_ {
+        let (x0, y0) = self.key(pos.x - radius, pos.y - radius);
+        let (x1, y1) = self.key(pos.x + radius, pos.y + radius);
+        let area = (x1 - x0 + 1).saturating_mul(y1 - y0 + 1);
+        // a huge radius is cheaper to answer by looking at the cells there are
+        let (lookup, scan) = if area as usize <= self.cells.len() {
+            (
+                Some((x0..=x1).flat_map(move |x| (y0..=y1).map(move |y| (x, y)))),
+                None,
+            )
+        } else {
+            (None, Some(self.cells.iter()))
+        };
+        let looked_up = lookup
+            .into_iter()
+            .flatten()
+            .filter_map(|key| self.cells.get(&key));
+        let scanned = scan.into_iter().flatten().filter_map(move |((x, y), idx)| {
+            ((x0..=x1).contains(x) && (y0..=y1).contains(y)).then_some(idx)
+        });
+        looked_up.chain(scanned).flatten().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn near_finds_everything_within_the_radius() {
+        let positions: Vec<_> = (0..400)
+            .map(|i| Point::new((i * 37 % 101) as Real - 50.0, (i * 53 % 97) as Real - 48.0))
+            .collect();
+        let mut grid = Grid::default();
+        grid.reset(20.0);
+        grid.extend(&positions);
+        let center = Point::new(3.5, -7.0);
+        for radius in [0.5, 13.0, 20.0, 45.0, 500.0] {
+            let mut found: Vec<_> = grid
+                .near(center, radius)
+                .filter(|idx| (positions[*idx] - center).length() < radius)
+                .collect();
+            found.sort_unstable();
+            let expected: Vec<_> = (0..positions.len())
+                .filter(|idx| (positions[*idx] - center).length() < radius)
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+}
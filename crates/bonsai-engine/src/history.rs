@@ -0,0 +1,110 @@
+//! What happened to every node while the tree grew, for scrubbing back and forth through it
+//!
+//! A tree keeps the step every node was added in and the step it was pruned or cut off in.
+//! The tree as it stood after any step is made of the nodes added up to then, alive unless they
+//! were pruned by then. Parents always come before their children, so these nodes form a tree of
+//! their own, whose weights and radii are worked out again from them, see `Tree::at_step`.
+//! Nothing gets grown again, the random stream of the growth isn't touched.
+//!
+//! Segments subdivided once the growth stopped take the steps of the node at their end, so the
+//! earlier trees show them subdivided already. Branches `Tree::cut_away` took off are gone from
+//! the history too, and trees put back together from their parts start it over at step 0.
+
+/// Step of a node that is still alive
+const ALIVE: u32 = u32::MAX;
+
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    /// step each node was added in
+    born: Vec<u32>,
+    /// step each node was pruned or cut off in
+    died: Vec<u32>,
+    /// steps the tree grew so far
+    steps: u32,
+}
+
+impl History {
+    /// The history of nodes that were there from the start, the dead ones dead from the start
+    pub(crate) fn of(alive: &[bool]) -> Self {
+        Self {
+            born: vec![0; alive.len()],
+            died: alive
+                .iter()
+                .map(|&alive| if alive { ALIVE } else { 0 })
+                .collect(),
+            steps: 0,
+        }
+    }
+
+    /// Steps the tree grew so far, `Tree::at_step` takes 0 to this many
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// Step node `idx` was added in
+    pub fn born(&self, idx: usize) -> u32 {
+        self.born[idx]
+    }
+
+    /// Whether node `idx` was there after `step`, and alive then
+    pub fn at(&self, idx: usize, step: u32) -> Option<bool> {
+        (self.born[idx] <= step).then(|| self.died[idx] > step)
+    }
+
+    pub(crate) fn next_step(&mut self) {
+        self.steps += 1;
+    }
+
+    /// Notes a node added in this step
+    pub(crate) fn push(&mut self) {
+        self.born.push(self.steps);
+        self.died.push(ALIVE);
+    }
+
+    /// Notes node `idx` dying in this step, unless it already died before
+    pub(crate) fn kill(&mut self, idx: usize) {
+        if self.died[idx] == ALIVE {
+            self.died[idx] = self.steps;
+        }
+    }
+
+    /// Renumbers the nodes, with `old[new]` the node each of the new ones was made from.
+    /// Nodes added in between take the steps of the node they were added for
+    pub(crate) fn renumber(&mut self, old: impl IntoIterator<Item = usize>) {
+        let (born, died) = old
+            .into_iter()
+            .map(|idx| (self.born[idx], self.died[idx]))
+            .unzip();
+        (self.born, self.died) = (born, died);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn every_step_comes_back_as_it_was_grown() {
+        let config = Config {
+            num_points: 2000,
+            // subdivided segments would show up in the earlier steps already
+            subdivide_max_length: 0.0,
+            ..Config::default()
+        };
+        let mut tree = Tree::new(config);
+        let mut grown = vec![];
+        for _ in 0..300 {
+            tree.step();
+            grown.push(tree.clone());
+        }
+        assert!(tree.history().steps() > 50);
+        for step in (0..tree.history().steps()).step_by(7) {
+            let past = tree.at_step(step + 1);
+            let then = &grown[step as usize];
+            assert_eq!(past.node_count(), then.node_count());
+            for (a, b) in past.nodes().zip(then.nodes()) {
+                assert_eq!((a.pos, a.parent, a.alive), (b.pos, b.parent, b.alive));
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Ivy climbing the tree, a second plant grown along the skeleton of the first
+//!
+//! The vine comes out of the soil at the foot of the trunk and winds up around it, following the
+//! branch the trunk carries on into at every fork (see `Tree::continuations`), now and then
+//! sending a runner of its own up a side branch. It lets go where the wood gets too thin to hold
+//! it. The vine has a seed of its own, so it can be added to or taken off a tree without changing
+//! anything else about it.
+
+use std::f32::consts::TAU;
+
+use rand::Rng;
+use rand_chacha::{rand_core::SeedableRng, ChaCha12Rng};
+
+use crate::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ivy {
+    /// How far up the wood it holds on to the vine climbs, from 0 to 1
+    pub reach: f32,
+    /// Seed of the vine, apart from the tree's
+    pub seed: u64,
+    pub leaf: Color,
+    pub stem: Color,
+}
+
+impl Default for Ivy {
+    fn default() -> Self {
+        Self {
+            reach: 0.7,
+            seed: 0,
+            leaf: Color::from_hex("2f6b3a").unwrap(),
+            stem: Color::from_hex("5a4a2e").unwrap(),
+        }
+    }
+}
+
+/// World units the vine climbs for one turn around the wood
+const PITCH: f32 = 45.0;
+/// World units between two points of the stem
+const STEP: f32 = 1.5;
+/// World units between two leaves along the stem
+const LEAF_SPACING: f32 = 7.0;
+/// Chance a side branch at a fork gets a runner of its own
+const RUNNER_CHANCE: f64 = 0.35;
+/// Wood thinner than this many times `leaf_max_width` is too thin to hold the vine
+const THINNEST: f32 = 1.5;
+
+/// A point of the vine, on the segment from the parent of `node` to `node`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct VinePoint {
+    pub node: usize,
+    /// how far along the segment, from 0 at the parent to 1 at the node
+    pub t: f32,
+    /// where it lies on the picture, in world units
+    pub pos: Vector2,
+    /// how far round the front of the wood it is, 1 in front of it and -1 behind
+    pub front: f32,
+}
+
+/// The stems the vine grew and the leaves along them
+#[derive(Clone, Debug, Default)]
+pub struct Vine {
+    pub stems: Vec<Vec<VinePoint>>,
+    /// leaves on their spot of the stem, with how far they stick out of it
+    pub leaves: Vec<(VinePoint, Vector2)>,
+}
+
+/// The vine `ivy` grows on `tree` as it stands
+pub fn climb(tree: &Tree, ivy: &Ivy) -> Vine {
+    let mut vine = Vine::default();
+    let nodes = &tree.nodes;
+    let holds = |idx: usize| {
+        nodes.alive()[idx] && nodes.radius(idx) >= tree.config.leaf_max_width * THINNEST
+    };
+    let continuations = tree.continuations();
+    let Some(first) = continuations
+        .first()
+        .copied()
+        .flatten()
+        .filter(|&idx| holds(idx))
+    else {
+        return vine;
+    };
+    let mut children = vec![vec![]; nodes.len()];
+    for idx in 1..nodes.len() {
+        if let Some(parent) = nodes.parent(idx) {
+            children[parent].push(idx);
+        }
+    }
+    let pos = |idx: usize| nodes.pos()[idx].vec();
+    let mut rng = ChaCha12Rng::seed_from_u64(ivy.seed ^ 0x1e9a_c4b1);
+    // the first stem comes out of the soil, the others branch off at a fork another one passed,
+    // each with how far the vine climbed to get there and where round the wood it was
+    let mut starts = vec![(0, first, 0.0, rng.gen::<f32>() * TAU)];
+    while let Some((fork, first, climbed, turn)) = starts.pop() {
+        // the wood the stem holds on to
+        let mut chain = vec![fork, first];
+        while let Some(next) = continuations[*chain.last().unwrap()].filter(|&next| holds(next)) {
+            chain.push(next);
+        }
+        let length: f32 = chain
+            .windows(2)
+            .map(|pair| (pos(pair[1]) - pos(pair[0])).length())
+            .sum();
+        let reach = (climbed + length) * ivy.reach.clamp(0.0, 1.0);
+        let mut stem = vec![];
+        let mut along = climbed;
+        'climb: for pair in chain.windows(2) {
+            let (parent, node) = (pair[0], pair[1]);
+            let Some(spline) = tree.segment_spline(node, &continuations) else {
+                continue;
+            };
+            let (from, to) = (spline.at(0.0), spline.at(1.0));
+            let segment = (to - from).length();
+            let steps = (segment / STEP).ceil().max(1.0) as usize;
+            let (up, across) = if segment > 0.0 {
+                let up = (to - from).normalized();
+                (up, Vector2::new(-up.y, up.x))
+            } else {
+                (Vector2::new(0.0, 1.0), Vector2::new(1.0, 0.0))
+            };
+            for step in 0..steps {
+                if along >= reach {
+                    break 'climb;
+                }
+                let t = step as f32 / steps as f32;
+                let radius = nodes.radius(parent) + (nodes.radius(node) - nodes.radius(parent)) * t;
+                let angle = turn + along / PITCH * TAU;
+                let point = VinePoint {
+                    node,
+                    t,
+                    pos: spline.at(t) + across * (radius * angle.sin()),
+                    front: angle.cos(),
+                };
+                stem.push(point);
+                // a leaf every so often, on alternating sides of the stem
+                let before = (along / LEAF_SPACING) as usize;
+                along += segment / steps as f32;
+                let after = (along / LEAF_SPACING) as usize;
+                if before != after {
+                    let side = if after.is_multiple_of(2) { 1.0 } else { -1.0 };
+                    let out = across * (side * rng.gen_range(2.0..4.0)) + up * 1.5;
+                    vine.leaves.push((point, out));
+                }
+            }
+            for &side in &children[node] {
+                if Some(side) != continuations[node] && holds(side) && rng.gen_bool(RUNNER_CHANCE) {
+                    starts.push((node, side, along, rng.gen::<f32>() * TAU));
+                }
+            }
+        }
+        if stem.len() > 1 {
+            vine.stems.push(stem);
+        }
+    }
+    vine
+}
@@ -0,0 +1,98 @@
+//! Kusamono, the small accent plant displayed next to a bonsai
+//!
+//! Classical display puts the accent off to one side, on the side the tree leans towards,
+//! so the tree seems to look at it. It stands lower than the tree and much smaller,
+//! in a shallow pot of its own on the same table.
+
+use crate::*;
+use pot::{Pot, Shape};
+
+/// What grows in the accent's pot
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// a tuft of arching grass blades
+    Grass,
+    /// a few stems with leaves and flowers on top
+    Flowering,
+}
+
+impl Kind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "grass" => Some(Self::Grass),
+            "flowering" => Some(Self::Flowering),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Grass => "grass",
+            Self::Flowering => "flowering",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Kusamono {
+    /// height of the plant above its pot, in world units
+    pub height: f32,
+    /// picked from the seed of the tree when not set
+    pub kind: Option<Kind>,
+}
+
+impl Default for Kusamono {
+    fn default() -> Self {
+        Self {
+            height: 70.0,
+            kind: None,
+        }
+    }
+}
+
+/// Free space between the accent's pot and the tree's pot or trunk, in world units
+const GAP: f32 = 25.0;
+
+impl Kusamono {
+    /// The shallow pot the accent stands in
+    pub fn pot(&self, tree: &Tree) -> Pot {
+        let radius = self.height * 0.45;
+        Pot {
+            radius,
+            height: radius * 0.7,
+            taper: 0.75,
+            tilt: tree.config.pot.map_or(Pot::default().tilt, |pot| pot.tilt),
+            color: Color::from_hex("8a5a44").unwrap(),
+            soil: Color::from_hex("4a3528").unwrap(),
+            decorated: false,
+            shape: Shape::Oval,
+        }
+    }
+
+    /// Where the plant leaves its pot, in world units
+    pub fn foot(&self, tree: &Tree) -> Vector2 {
+        let config = &tree.config;
+        let pot = self.pot(tree);
+        // the attraction points still to reach and the nodes already grown together keep
+        // the shape the tree is heading for, so the side doesn't flip while the tree grows
+        let (sum, count) = tree
+            .points
+            .iter()
+            .copied()
+            .chain(tree.nodes.iter().map(|node| node.pos))
+            .fold((0.0, 0), |(sum, count), point| {
+                (sum + point.vec().x, count + 1)
+            });
+        let lean = sum / count.max(1) as f32 - config.origin.x;
+        let side = match lean {
+            lean if lean.abs() > 1.0 => lean.signum(),
+            _ if config.seed & 1 == 0 => 1.0,
+            _ => -1.0,
+        };
+        let trunk = tree.radius_of(&tree.nodes.get(0));
+        let beside = config.pot.map_or(trunk, |pot| pot.radius) + GAP + pot.radius;
+        let x = (config.origin.x + side * beside).clamp(pot.radius, config.width - pot.radius);
+        // stands on the same table as the tree
+        Vector2::new(x, floor(config) + pot.reach_below())
+    }
+}
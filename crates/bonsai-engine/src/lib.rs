@@ -0,0 +1,1787 @@
+//! Grows pixel art bonsai trees with space colonization
+//!
+//! Attraction points are scattered over a noise field, and a tree grows from its origin
+//! towards the points near each branch tip, one `Tree::step` at a time.
+//! Nothing here draws, writes files or runs other programs, that is up to `bonsai-render`,
+//! `bonsai-io` and the app, so the engine builds without raylib or any image codecs.
+//!
+//! ```no_run
+//! use bonsai_engine::{Config, Tree};
+//!
+//! let config = Config::builder().seed(7).num_points(5_000).build();
+//! let mut tree = Tree::new(config);
+//! while tree.is_growing() {
+//!     tree.step();
+//! }
+//! println!("{} nodes", tree.nodes().filter(|node| node.alive).count());
+//! ```
+
+pub mod aging;
+pub mod background;
+pub mod cancel;
+pub mod color_rules;
+pub mod composition;
+pub mod daytime;
+pub mod density;
+pub mod diagnose;
+pub mod events;
+pub mod falloff;
+mod grid;
+pub mod history;
+pub mod ivy;
+pub mod kusamono;
+pub mod math;
+mod memory;
+pub mod morph;
+pub mod name;
+pub mod nest;
+pub mod nodes;
+pub mod noise;
+pub mod pacing;
+pub mod palette;
+#[cfg(feature = "parallel")]
+mod parallel;
+pub mod point;
+pub mod pot;
+pub mod precipitation;
+pub mod roots;
+pub mod scene;
+pub mod scenery;
+pub mod season;
+pub mod seed;
+pub mod selection;
+pub mod snapshot;
+pub mod spline;
+pub mod sprites;
+pub mod stats;
+pub mod storm;
+pub mod tokonoma;
+pub mod volume;
+pub mod wind;
+
+use composition::{Anchor, Composition, Overflow, View};
+use density::{DensityMap, DensitySource};
+use falloff::Falloff;
+pub use math::{Color, Vector2, Vector3};
+use noise::{Noise, NoiseField};
+use point::{Point, Real};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use spline::Spline;
+use std::sync::Arc;
+
+/// Picks random positions weighted by noise that fades out towards the edges of the world,
+/// or by the painted density of the config's `density` source
+pub struct SimplexDensityPRG {
+    buf: Vec<Vec<f32>>,
+    rows: Vec<f32>,
+    sum: f32,
+}
+
+impl SimplexDensityPRG {
+    /// Density map over the world of `config`, made of its noise and shaped by its falloff
+    ///
+    /// A painted density of another size is stretched over the world.
+    pub fn new(config: &Config) -> Self {
+        let (width, height) = (config.width as usize, config.height as usize);
+        let noise = NoiseField::new(config.noise, config.seed);
+        let mut buf = vec![vec![0f32; height]; width];
+        let mut rows = vec![0f32; width];
+        let mut sum = 0f32;
+        for (x, column) in buf.iter_mut().enumerate() {
+            for (y, cell) in column.iter_mut().enumerate() {
+                let mask = match &config.density {
+                    DensitySource::Noise => 1.0,
+                    DensitySource::Map(map) => {
+                        let value = map.stretched(x, y, width, height);
+                        *cell = value;
+                        rows[x] += value;
+                        sum += value;
+                        continue;
+                    }
+                    DensitySource::Mask(map) => map.stretched(x, y, width, height),
+                };
+                let noise_val = noise.at(x as _, y as _);
+                let centering = {
+                    let width = width as f32;
+                    let height = height as f32;
+                    let dx = x as f32 - width / 2.0;
+                    let dy = y as f32 - height / 2.0;
+                    config.falloff.weight(dx, dy, width, height)
+                };
+                let buf_val = noise_val * centering * mask;
+                *cell = buf_val;
+                rows[x] += buf_val;
+                sum += buf_val;
+            }
+        }
+        Self { buf, rows, sum }
+    }
+    /// Relative density of every world unit, indexed `[x][y]` with y going up
+    pub fn weights(&self) -> &[Vec<f32>] {
+        &self.buf
+    }
+    pub fn sample(&self, rand: &mut impl Rng) -> (usize, usize) {
+        let rand = rand.gen::<f32>();
+        assert!((0.0..1.0).contains(&rand));
+        let mut rand = rand * self.sum;
+
+        let mut x = 0;
+        while x < self.buf.len() && rand >= self.rows[x] {
+            rand -= self.rows[x];
+            x += 1;
+        }
+
+        if x == self.buf.len() {
+            x -= 1;
+        }
+
+        let mut y = 0;
+        while y < self.buf[x].len() && rand >= self.buf[x][y] {
+            rand -= self.buf[x][y];
+            y += 1;
+        }
+
+        (x, y)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ColorPalette {
+    /// Leaves, where branches are thin enough to grow them
+    pub leaf: Color,
+    /// Young branches up to `Config::sprout_max_width`
+    pub new_branch: Color,
+    /// Everything thicker, like the trunk, this is the bark color of the shaded render
+    pub old_branch: Color,
+}
+
+impl ColorPalette {
+    /// Moves `amount` of the way towards `other`
+    pub fn lerp(&self, other: ColorPalette, amount: f32) -> ColorPalette {
+        ColorPalette {
+            leaf: self.leaf.lerp(other.leaf, amount),
+            new_branch: self.new_branch.lerp(other.new_branch, amount),
+            old_branch: self.old_branch.lerp(other.old_branch, amount),
+        }
+    }
+}
+
+/// Everything that shapes a tree and how it is drawn, see `Config::builder`
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Where the root is planted, in world units with y pointing up
+    pub origin: Vector2,
+    /// How far a branch tip sees the attraction points it grows towards
+    pub attraction_dist: f32,
+    /// Attraction points this close to a node count as reached and are removed
+    pub kill_dist: f32,
+    /// Length of the segment a tip grows per step
+    pub grow_dist: f32,
+    /// New nodes closer than this to an existing one are dropped
+    pub node_min_dist: f32,
+    /// Segments longer than this get intermediate nodes once growth has finished
+    pub subdivide_max_length: f32,
+    /// Size of the world the attraction points are scattered over
+    pub width: f32,
+    pub height: f32,
+    /// How the attraction points thin out from the middle of the world towards its edges
+    pub falloff: Falloff,
+    /// Noise the attraction points are scattered by
+    pub noise: Noise,
+    /// Whether the attraction points follow the noise and falloff, a painted density or both
+    pub density: DensitySource,
+    /// Branches a node may fork into
+    pub max_children: usize,
+    /// Longest chain of nodes from the root
+    pub max_depth: usize,
+    /// Attraction points scattered before growing
+    pub num_points: usize,
+    /// How much higher a new node has to be than its parent, negative allows hanging branches
+    pub min_y_growth: f32,
+    /// How much a tip keeps the direction of its parent segment instead of turning towards the points
+    pub parent_dir_factor: f32,
+    /// Pull on every new segment, in grow distances, like gravity for `(0, -0.5)`. It pulls the
+    /// harder the further a branch reaches out from the trunk, the trunk itself grows as before.
+    /// Drooping branches need a negative `min_y_growth` to be allowed to grow downwards
+    pub tropism: Vector2,
+    /// Radius of a branch grows with its weight to this power
+    pub weight_display_pow: f32,
+    /// Maximum fraction a branch radius may shrink from one node to the next
+    ///
+    /// 1.0 disables smoothing, smaller values give more gradual tapering
+    pub radius_taper_cap: f32,
+    /// How strongly tips turn away from where many attraction points were reached already,
+    /// so late growth fills the empty parts of the canopy. 0 disables it
+    pub canopy_memory: f32,
+    /// Small branches closer to a big one than its weight to this power get pruned
+    pub prune_pow: f32,
+    /// Branches lighter than this fraction of a nearby branch count as small for pruning
+    pub prune_size_ratio: f32,
+    /// Maximum branch width to grow leaves there
+    pub leaf_max_width: f32,
+    /// Maximum branch width to color the branch green
+    pub sprout_max_width: f32,
+    /// Radius of the leaf blob around a twig
+    pub leaf_size: f32,
+    /// Leaf blobs are pushed randomly up to this far towards or away from the camera
+    pub leaf_depth_jitter: f32,
+    /// Share of the leaf blobs on the tree, lower in autumn and 0 for a bare winter tree
+    pub foliage: f32,
+    pub colors: ColorPalette,
+    /// How far each node may step towards or away from the camera
+    pub node_depth_change: f32,
+    /// Depth range the nodes stay within, and the one a turned volume is drawn with
+    pub node_depth_max: usize,
+    /// Grows the crown in three dimensions so it can be turned, instead of on the picture plane
+    pub volume: Option<volume::Volume>,
+    /// Seed of every random choice, from the attraction points and depth jitter to where
+    /// the leaves go, so the same seed and config always grow and draw the same tree
+    pub seed: u64,
+    /// How big one "pixel" is (in pixels)
+    pub pixel_size: usize,
+    /// Height of one "pixel" relative to its width, e.g. 2.0 for terminal cells
+    pub pixel_aspect: f32,
+    /// Blossom kinds scattered over the canopy, picked by their probability
+    pub leaves: Vec<LeafType>,
+    /// Background color
+    pub sky: Color,
+    /// Direction the sun shines from, in radians counterclockwise from the right
+    pub sun_angle: f32,
+    /// Height of the sun above the picture plane, in radians towards the viewer:
+    /// 0 grazes the tree from the side, π/2 shines straight from behind the camera
+    pub sun_elevation: f32,
+    /// How much of the light comes from the sky dome instead of the sun, softening the shading.
+    /// 0 lights with the sun alone
+    pub ambient: f32,
+    /// Color of the dome light from above
+    pub ambient_sky: Color,
+    /// Color of the light bounced up from the ground
+    pub ambient_ground: Color,
+    /// Color of the sunlight, white for plain daylight
+    pub sun_color: Color,
+    /// Least light a surface turned away from the sun gets when there is no ambient light
+    pub light_floor: f32,
+    /// Simulation steps per tick, see `pacing`
+    pub growth_speed: usize,
+    /// Ticks the trees grow in per second in the window, however fast it draws
+    pub tick_rate: f32,
+    /// Frames per second the window draws at most, 0 draws as fast as it can
+    pub target_fps: u32,
+    /// Waits for the screen's refresh before showing a frame, against tearing
+    pub vsync: bool,
+    /// Counts every frame as a frame of `target_fps` for the growth, however long it took,
+    /// so recordings grow the same on any machine, see `pacing`
+    pub fixed_step: bool,
+    /// Lets the window slow down to a few frames a second while nothing changes, see `idle`
+    pub idle: bool,
+    /// Seconds a season lasts when the window lets them pass
+    pub season_length: f32,
+    /// Seconds a whole day lasts when the window lets the hours pass
+    pub day_length: f32,
+    /// Seconds a finished tree rests in the window before a fresh flush of attraction points
+    /// lets it grow on, see `Tree::flush`. 0 leaves it finished
+    pub regrow_after: f32,
+    /// Share of `num_points` a flush scatters
+    pub regrow_share: f32,
+    /// Draws the text and debug overlays in black and white and colorblind-safe colors
+    pub high_contrast: bool,
+    /// How many trees the output shows and where they are placed
+    pub composition: Composition,
+    /// What the window does when a tree grows past the top of its slot
+    pub overflow: Overflow,
+    /// Palette overrides for parts of the tree, e.g. sun-bleached tops or lichen bands
+    pub color_rules: Vec<color_rules::ColorRule>,
+    /// How much branches and leaves at the back fade into the sky, 0 disables it
+    pub depth_fog: f32,
+    /// How much dense canopy darkens the branches below it, 0 disables it
+    pub canopy_occlusion: f32,
+    /// How dark the shadows are that leaves and branches throw onto the tree away from the sun,
+    /// 0 disables them
+    pub self_shadow: f32,
+    /// How much leaves and branches darken where the surfaces around them stick out further
+    /// towards the camera, in the creases between overlapping blobs. 0 disables it
+    pub ambient_occlusion: f32,
+    /// How far around a pixel the surfaces darkening it are looked for, in canvas pixels
+    pub ambient_occlusion_radius: usize,
+    /// How much darker the striations of the bark and the mottled leaves get, 0 draws them flat
+    pub grain: f32,
+    /// Shades of each color the light is snapped to with ordered dithering, 0 shades smoothly
+    pub shades: usize,
+    /// Color the shadows fall into instead of black, like the blue of a shade on a sunny day
+    pub shadow_tint: Color,
+    /// Colors every pixel is snapped to by its brightness, dithered between the two closest,
+    /// empty keeps the shaded colors, see `palette`
+    pub ramp: Vec<Color>,
+    /// Share of the twigs with leaves that get a blossom or fruit once the tree stopped growing,
+    /// 0 for none
+    pub fruit_density: f32,
+    /// Color of the blossoms or fruit
+    pub fruit_color: Color,
+    /// Darkness of the canopy shadow on the ground, 0 disables it
+    pub ground_shadow: f32,
+    /// How far twigs sway in the wind in the window, in world units, 0 keeps the tree still
+    pub wind: f32,
+    /// How stiff the wood is against the wind, 2 bends every branch half as far, see `wind`
+    pub wind_stiffness: f32,
+    /// Strain the wood takes before a branch snaps in a storm, see `wind::Wind::breaking`
+    pub branch_strength: f32,
+    /// Seconds between storms in the window on average, 0 for none, see `storm`
+    pub storm_every: f32,
+    /// How strong the wind blows at the height of a storm, like `wind`
+    pub storm_wind: f32,
+    /// Snow or rain falling in the window, `None` for dry weather, see `precipitation`
+    pub precipitation: Option<precipitation::Precipitation>,
+    /// How hard it snows or rains, 1 for a downpour
+    pub precipitation_intensity: f32,
+    /// Seconds the snow lying on the tree takes to melt, rain washes it away four times as fast
+    pub snow_melt: f32,
+    /// Chance a grown tree carries a bird's nest in one of its forks, see `nest`
+    pub nest_chance: f32,
+    /// Seconds a grown tree has to be looked after for the eggs in its nest to hatch
+    pub hatch_after: f32,
+    /// Seconds between a grown tree losing a branch to age on average, 0 for never, see `aging`
+    pub aging_every: f32,
+    /// Chance an aging branch snaps off instead of dying back to deadwood
+    pub aging_severity: f32,
+    /// Color the wood of branches that died back bleaches to
+    pub deadwood_color: Color,
+    /// Draw the real moon phase and stars behind dark renders
+    pub night_sky: bool,
+    /// Show the tree's name on a small plate below it in wallpapers, see `name`
+    pub nameplate: bool,
+    /// Pot the tree stands in, hanging below the origin, which needs to leave room for it
+    pub pot: Option<pot::Pot>,
+    /// Roots spreading into the soil below the origin, which needs to leave room for them
+    pub roots: Option<roots::Roots>,
+    /// Accent plant in its own small pot beside the tree
+    pub kusamono: Option<kusamono::Kusamono>,
+    /// Ivy climbing up the trunk and the thicker branches
+    pub ivy: Option<ivy::Ivy>,
+    /// Display alcove around the tree, with a stand below the pot, see `Tokonoma::apply`
+    pub tokonoma: Option<tokonoma::Tokonoma>,
+    /// Sky, hills and ground drawn behind the tree, see `Scenery::apply`. The alcove of a
+    /// `tokonoma` takes its place
+    pub scenery: Option<scenery::Scenery>,
+    /// Number of trees growing side by side in the world instead of one, see `scene`
+    pub forest: Option<usize>,
+    /// Image shown behind the tree instead of the sky
+    pub background: Option<background::Background>,
+    /// Sprites the leaf clusters are drawn with, `None` for plain spheres
+    pub leaf_sprites: Option<sprites::LeafSprites>,
+    /// Grown with coarse parameters for a quick look, see `Config::preview` and `Tree::refine`
+    pub preview: bool,
+}
+
+/// A kind of blossom on the canopy
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LeafType {
+    pub color: Color,
+    /// Size relative to a regular leaf
+    pub size: f32,
+    /// Chance to pick this kind, the probabilities of all kinds should add up to 1
+    pub probability: f32,
+}
+
+/// Extra canvas pixels to the right and above the world, so leaves at the edge don't get cut off.
+///
+/// The top of the world ends up `CANVAS_PADDING + 1` canvas pixels below the top of the drawing.
+pub const CANVAS_PADDING: usize = 10;
+
+/// Seeds `Tree::new_min_growth` tries before settling for a tree that didn't sprout
+const SPROUT_ATTEMPTS: usize = 100;
+
+/// Mixed into the seed of a flush, so its points don't repeat the first scatter
+const FLUSH_SALT: u64 = 0x2f1a_5e0d_c4b7_9e31;
+
+/// Height of the ground the shadows fall on, a pot and its stand lift the tree off it
+pub fn floor(config: &Config) -> f32 {
+    config.origin.y
+        - config.pot.map_or(0.0, |pot| pot.reach_below())
+        - config
+            .tokonoma
+            .map_or(0.0, |tokonoma| tokonoma.stand_height)
+}
+
+impl Config {
+    /// Starts from the default config, for changing just the parameters that matter
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder {
+            config: Config::default(),
+        }
+    }
+    /// Coarse version of the config that grows the rough silhouette of the same tree
+    /// several times faster, with longer segments and fewer attraction points
+    pub fn preview(&self) -> Config {
+        const COARSENESS: f32 = 2.0;
+        Config {
+            attraction_dist: self.attraction_dist * COARSENESS,
+            kill_dist: self.kill_dist * COARSENESS,
+            grow_dist: self.grow_dist * COARSENESS,
+            node_min_dist: self.node_min_dist * COARSENESS,
+            subdivide_max_length: self.subdivide_max_length * COARSENESS,
+            num_points: ((self.num_points as f32 / (COARSENESS * COARSENESS)) as usize).max(1),
+            preview: true,
+            ..self.clone()
+        }
+    }
+    /// How tall one "pixel" is (in pixels)
+    pub fn pixel_height(&self) -> usize {
+        ((self.pixel_size as f32 * self.pixel_aspect).round() as usize).max(1)
+    }
+    /// Random numbers for scattering the leaves, kept apart from the ones of the growth
+    pub fn leaf_rng(&self) -> ChaCha12Rng {
+        ChaCha12Rng::seed_from_u64(self.seed ^ 0x6c65_6166)
+    }
+    pub fn get_leaf_type(&self, rng: &mut ChaCha12Rng) -> LeafType {
+        let mut choice = rng.gen::<f32>();
+        assert!(!self.leaves.is_empty());
+        loop {
+            for leaf in self.leaves.iter() {
+                assert!(leaf.probability > 0.0);
+                choice -= leaf.probability;
+                if choice < 0.0 {
+                    return *leaf;
+                }
+            }
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let colors = ColorPalette {
+            leaf: Color::GREEN,
+            new_branch: Color::GREEN,
+            old_branch: Color::from_hex("8b6354").unwrap(),
+        };
+        Config {
+            origin: Vector2::new(250.0, 20.0),
+            attraction_dist: 20.0,
+            kill_dist: 13.0,
+            grow_dist: 10.0,
+            node_min_dist: 8.0,
+            subdivide_max_length: 15.0,
+            width: 500.0,
+            height: 500.0,
+            falloff: Falloff::default(),
+            noise: Noise::default(),
+            density: DensitySource::Noise,
+            max_children: 3,
+            max_depth: 5000,
+            num_points: 10_000,
+            min_y_growth: 0.0,
+            parent_dir_factor: 0.1,
+            tropism: Vector2::zero(),
+            weight_display_pow: 0.45,
+            radius_taper_cap: 0.35,
+            canopy_memory: 0.0,
+            prune_pow: 0.35,
+            prune_size_ratio: 0.01,
+            leaf_max_width: 1.51,
+            sprout_max_width: 3.5,
+            leaf_size: 20.0,
+            leaf_depth_jitter: 0.4,
+            foliage: 1.0,
+            node_depth_change: 1.0,
+            node_depth_max: 5,
+            volume: None,
+            seed: 0,
+            pixel_size: 6,
+            pixel_aspect: 1.0,
+            colors,
+            sky: Color::from_hex("CFF7E5").unwrap(),
+            sun_angle: 1.0f32.atan2(-2.0),
+            sun_elevation: 0.7f32.acos(),
+            ambient: 0.0,
+            ambient_sky: Color::from_hex("e4f4ff").unwrap(),
+            ambient_ground: Color::from_hex("8a7560").unwrap(),
+            sun_color: Color::WHITE,
+            light_floor: 0.2,
+            growth_speed: 3,
+            tick_rate: 60.0,
+            target_fps: 60,
+            vsync: false,
+            fixed_step: false,
+            idle: true,
+            season_length: 60.0,
+            day_length: 240.0,
+            regrow_after: 0.0,
+            regrow_share: 0.2,
+            high_contrast: false,
+            composition: Composition {
+                output_width: 600.0,
+                output_height: 600.0,
+                margin: 50.0,
+                top_clearance: 0.0,
+                trees: 1,
+                anchor: Anchor::Center,
+                view: View::default(),
+                detail_zoom: 0.5,
+            },
+            overflow: Overflow::Zoom,
+            color_rules: vec![],
+            depth_fog: 0.35,
+            canopy_occlusion: 0.4,
+            self_shadow: 0.3,
+            ambient_occlusion: 0.5,
+            ambient_occlusion_radius: 2,
+            grain: 0.25,
+            shades: 0,
+            shadow_tint: Color::BLACK,
+            ramp: vec![],
+            fruit_density: 0.0,
+            fruit_color: Color::from_hex("c8213f").unwrap(),
+            ground_shadow: 0.8,
+            wind: 0.0,
+            wind_stiffness: 1.0,
+            branch_strength: 0.014,
+            storm_every: 0.0,
+            storm_wind: 12.0,
+            precipitation: None,
+            precipitation_intensity: 0.5,
+            snow_melt: 120.0,
+            nest_chance: 0.02,
+            hatch_after: 7200.0,
+            aging_every: 0.0,
+            aging_severity: 0.3,
+            deadwood_color: Color::from_hex("c9c3b6").unwrap(),
+            night_sky: false,
+            nameplate: false,
+            pot: None,
+            roots: None,
+            kusamono: None,
+            ivy: None,
+            tokonoma: None,
+            scenery: None,
+            forest: None,
+            background: None,
+            leaf_sprites: None,
+            preview: false,
+            leaves: vec![
+                LeafType {
+                    color: Color::from_hex("ffe0e0").unwrap(),
+                    probability: 0.8,
+                    size: 2.5,
+                },
+                LeafType {
+                    color: Color::from_hex("FF5173").unwrap(),
+                    probability: 0.2,
+                    size: 1.0,
+                },
+            ],
+        }
+    }
+}
+
+/// Builds a `Config` one parameter at a time, everything not set keeps its default
+///
+/// ```
+/// let config = bonsai_engine::Config::builder()
+///     .seed(42)
+///     .size(300.0, 400.0)
+///     .attraction_dist(25.0)
+///     .build();
+/// assert_eq!(config.seed, 42);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    /// Same seed, same tree
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = seed;
+        self
+    }
+    /// Size of the world the tree grows in, in world units
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.config.width = width;
+        self.config.height = height;
+        self
+    }
+    /// How the attraction points thin out towards the edges, which shapes the crown
+    pub fn falloff(mut self, falloff: Falloff) -> Self {
+        self.config.falloff = falloff;
+        self
+    }
+    /// Noise the attraction points are scattered by, which decides how clumpy the crown gets
+    pub fn noise(mut self, noise: Noise) -> Self {
+        self.config.noise = noise;
+        self
+    }
+    /// Hand painted density to scatter the attraction points by, instead of the noise and falloff
+    pub fn density_map(mut self, map: DensityMap) -> Self {
+        self.config.density = DensitySource::Map(Arc::new(map));
+        self
+    }
+    /// Mask the noise is multiplied with, to grow the crown into the mask's shape
+    pub fn density_mask(mut self, mask: DensityMap) -> Self {
+        self.config.density = DensitySource::Mask(Arc::new(mask));
+        self
+    }
+    /// Where the root is planted, with y pointing up
+    pub fn origin(mut self, origin: Vector2) -> Self {
+        self.config.origin = origin;
+        self
+    }
+    pub fn num_points(mut self, num_points: usize) -> Self {
+        self.config.num_points = num_points;
+        self
+    }
+    /// How strongly late growth avoids the parts of the canopy that filled up first
+    pub fn canopy_memory(mut self, canopy_memory: f32) -> Self {
+        self.config.canopy_memory = canopy_memory;
+        self
+    }
+    pub fn attraction_dist(mut self, attraction_dist: f32) -> Self {
+        self.config.attraction_dist = attraction_dist;
+        self
+    }
+    pub fn kill_dist(mut self, kill_dist: f32) -> Self {
+        self.config.kill_dist = kill_dist;
+        self
+    }
+    pub fn grow_dist(mut self, grow_dist: f32) -> Self {
+        self.config.grow_dist = grow_dist;
+        self
+    }
+    pub fn node_min_dist(mut self, node_min_dist: f32) -> Self {
+        self.config.node_min_dist = node_min_dist;
+        self
+    }
+    pub fn max_children(mut self, max_children: usize) -> Self {
+        self.config.max_children = max_children;
+        self
+    }
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.config.max_depth = max_depth;
+        self
+    }
+    pub fn parent_dir_factor(mut self, parent_dir_factor: f32) -> Self {
+        self.config.parent_dir_factor = parent_dir_factor;
+        self
+    }
+    /// Pull on the branches, like gravity for `(0, -0.5)`, stronger further out from the trunk
+    pub fn tropism(mut self, tropism: Vector2) -> Self {
+        self.config.tropism = tropism;
+        self
+    }
+    pub fn colors(mut self, colors: ColorPalette) -> Self {
+        self.config.colors = colors;
+        self
+    }
+    /// Replaces the blossom kinds, there has to be at least one
+    pub fn leaves(mut self, leaves: Vec<LeafType>) -> Self {
+        self.config.leaves = leaves;
+        self
+    }
+    pub fn sky(mut self, sky: Color) -> Self {
+        self.config.sky = sky;
+        self
+    }
+    /// Size of one "pixel" in output pixels, and its height relative to its width
+    pub fn pixels(mut self, pixel_size: usize, pixel_aspect: f32) -> Self {
+        self.config.pixel_size = pixel_size;
+        self.config.pixel_aspect = pixel_aspect;
+        self
+    }
+    /// Plants the tree in a pot, raising the origin to leave room for it
+    pub fn pot(mut self, pot: pot::Pot) -> Self {
+        self.config.origin.y = self.config.origin.y.max(pot.reach_below() + 10.0);
+        self.config.pot = Some(pot);
+        self
+    }
+    /// Grows roots below the tree, raising the origin to leave room for them
+    pub fn roots(mut self, roots: roots::Roots) -> Self {
+        self.config.origin.y = self.config.origin.y.max(roots.depth + 10.0);
+        self.config.roots = Some(roots);
+        self
+    }
+    /// Grows the crown in three dimensions, see `volume`
+    pub fn volume(mut self, volume: volume::Volume) -> Self {
+        self.config.volume = Some(volume);
+        self
+    }
+    pub fn build(self) -> Config {
+        self.config
+    }
+}
+
+/// A point on a branch, connected to the node it grew from
+#[derive(Debug, Copy, Clone)]
+pub struct Node {
+    /// dead nodes got pruned and are not drawn
+    pub alive: bool,
+    pub pos: Point,
+    /// index of the node this one grew from, `None` for the root
+    pub parent: Option<usize>,
+    pub child_count: usize,
+    /// distance to root
+    pub depth: usize,
+    /// amount of children attached to this node + 1
+    pub weight: usize,
+    /// display radius, derived from the weight and smoothed along the parent chain
+    pub radius: f32,
+    /// depth towards the back, between 0 and `Config::node_depth_max`,
+    /// or in world units behind the trunk for a tree grown with a `Config::volume`
+    pub z: f32,
+}
+
+impl Node {
+    fn new_root(pos: Point) -> Self {
+        Self {
+            alive: true,
+            pos,
+            parent: None,
+            child_count: 0,
+            depth: 0,
+            weight: 1,
+            radius: 1.5,
+            z: 0.0,
+        }
+    }
+    /// A node `delta` from its parent, `delta_z` further back when growing with a volume
+    fn new_branch(
+        pos: Point,
+        delta_z: f32,
+        parent_idx: usize,
+        parent: Node,
+        config: &Config,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let z = match config.volume {
+            Some(_) => parent.z + delta_z,
+            None => {
+                let z_change = (2.0 * rng.gen::<f32>() - 1.0) * config.node_depth_change;
+                (parent.z + z_change)
+                    .max(0.0)
+                    .min(config.node_depth_max as _)
+            }
+        };
+        Self {
+            alive: true,
+            pos,
+            parent: Some(parent_idx),
+            child_count: 0,
+            depth: parent.depth + 1,
+            weight: 1,
+            radius: 1.5,
+            z,
+        }
+    }
+}
+
+/// A tree growing towards its attraction points, see `Tree::step`
+#[derive(Clone)]
+pub struct Tree {
+    pub(crate) nodes: nodes::Nodes,
+    /// the config the tree grows with, lighting and colors can be changed at any time
+    pub config: Config,
+    /// attraction points not reached yet
+    pub(crate) points: Vec<Point>,
+    /// attraction points reached so far, kept while `canopy_memory` is on
+    pub(crate) memory: memory::CanopyMemory,
+    pub(crate) growing: bool,
+    pub(crate) to_be_added: Vec<Node>,
+    /// randomness of the growth, seeded from the config
+    pub(crate) rng: ChaCha12Rng,
+    /// buffers reused by every simulation step
+    scratch: SimScratch,
+    /// counts up whenever the nodes change, renderers repaint when it differs from what they drew
+    pub(crate) revision: u64,
+    /// what the steps did, for telling why growth stopped
+    pub(crate) outcome: diagnose::Outcome,
+    /// the roots, growing upside down alongside the tree, see `roots`
+    pub(crate) roots: Option<Box<Tree>>,
+    /// named sets of branches, see `selection`
+    pub(crate) selections: Vec<selection::Selection>,
+    /// the steps every node was added and pruned in, see `history`
+    pub(crate) history: history::History,
+    /// seconds the grown tree was looked after, see `nest`
+    pub(crate) tended: f32,
+    /// times the grown tree aged, see `aging`
+    pub(crate) aged: u32,
+    /// channels told what happens to the tree, see `events`
+    pub(crate) subscribers: events::Subscribers,
+}
+
+/// The state a tree that is still growing keeps besides its nodes
+#[derive(Clone, Debug, Default)]
+pub struct Growth {
+    /// attraction points not reached yet
+    pub points: Vec<Point>,
+    /// nodes that get added over the next steps
+    pub pending: Vec<Node>,
+    /// attraction points reached so far, see `Config::canopy_memory`
+    pub consumed: Vec<Point>,
+    /// position in the random stream of the growth
+    pub word_pos: u128,
+}
+
+/// Buffers `Tree::step` keeps between steps, so growing doesn't allocate every frame
+#[derive(Clone, Default)]
+struct SimScratch {
+    new_nodes: Vec<Node>,
+    dead: Vec<bool>,
+    /// the attraction points, rebuilt every step since reached ones get removed
+    point_grid: grid::Grid,
+    /// the nodes, extended as they get added and reset when `subdivide` moves them
+    node_grid: grid::Grid,
+    /// indices of the points near a tip
+    near: Vec<usize>,
+    /// for every node `prune` looked at, whether it is too close to a heavier branch
+    condemned: Vec<bool>,
+    /// nodes whose weight changed since the last `prune`
+    reweighed: Vec<usize>,
+    /// nodes `prune` looks at again
+    recheck: Vec<usize>,
+}
+
+impl SimScratch {
+    /// Forgets everything about the nodes, for when they are renumbered or moved
+    fn forget_nodes(&mut self) {
+        self.node_grid.reset(self.node_grid.cell());
+        self.condemned.clear();
+        self.reweighed.clear();
+    }
+}
+
+impl Tree {
+    /// Grows a tree that makes it at least `iter` nodes in `iter` steps,
+    /// seeds that don't are skipped by counting up
+    pub fn new_min_growth(config: Config, iter: usize) -> Self {
+        Self::new_min_growth_with(config, iter, || true).unwrap()
+    }
+    /// Like `new_min_growth`, calling `step` once the attraction points are placed and after every
+    /// simulation step, so `iter + 1` times per attempt. Gives up with `None` once it returns false.
+    /// Parameters no seed grows with end up with the last stunted tree, see `Tree::warnings`
+    pub fn new_min_growth_with(
+        mut config: Config,
+        iter: usize,
+        mut step: impl FnMut() -> bool,
+    ) -> Option<Self> {
+        for attempt in 1.. {
+            let mut tree = Self::new(config.clone());
+            if !step() {
+                return None;
+            }
+            for _ in 0..iter {
+                tree.step();
+                if !step() {
+                    return None;
+                }
+            }
+            if tree.nodes.len() >= iter || attempt == SPROUT_ATTEMPTS {
+                return Some(tree);
+            }
+            config.seed = config.seed.wrapping_add(1);
+        }
+        unreachable!()
+    }
+    /// Simulates until the tree stops growing, giving up after 10k steps or once cancelled
+    pub fn grow_fully(&mut self, cancel: &cancel::CancelToken) {
+        for _ in 0..10_000 {
+            if !self.is_growing() || cancel.is_cancelled() {
+                break;
+            }
+            self.step();
+        }
+    }
+    /// Scatters the attraction points for `config` and plants the root at its origin,
+    /// nothing has grown yet
+    pub fn new(config: Config) -> Self {
+        let mut rng = ChaCha12Rng::seed_from_u64(config.seed);
+        let points = Self::scatter(&config, &mut rng);
+        let roots = config.roots.map(|roots| Box::new(roots.sprout(&config)));
+        Self {
+            nodes: std::iter::once(Node::new_root(config.origin.into())).collect(),
+            config,
+            points,
+            memory: memory::CanopyMemory::default(),
+            growing: true,
+            to_be_added: vec![],
+            rng,
+            scratch: SimScratch::default(),
+            revision: 0,
+            outcome: diagnose::Outcome::default(),
+            roots,
+            selections: vec![],
+            history: history::History::of(&[true]),
+            tended: 0.0,
+            aged: 0,
+            subscribers: events::Subscribers::default(),
+        }
+    }
+    /// The attraction points a tree grown with `config` starts out with, taken from `rng`
+    fn scatter(config: &Config, rng: &mut ChaCha12Rng) -> Vec<Point> {
+        let prg_map = SimplexDensityPRG::new(config);
+        // nothing grows down into the soil of a pot, nor below the root of a crown with a volume,
+        // where the points would pull the first tips down
+        let soil = match (config.pot, config.volume) {
+            (None, None) => Real::NEG_INFINITY,
+            _ => Real::from(config.origin.y),
+        };
+        (0..config.num_points)
+            .map(|_| {
+                let (x, y) = prg_map.sample(rng);
+                Point::new(x as _, y as _)
+            })
+            .filter(|point| point.y >= soil)
+            .collect()
+    }
+    /// Grows a preview out to full quality with `config`, keeping the branches it already has.
+    /// Segments get subdivided and new attraction points are placed around the existing
+    /// branches only, so growth continues within the skeleton instead of adding new limbs
+    pub fn refine(&mut self, config: &Config) {
+        let reach = Real::from(self.config.attraction_dist);
+        self.config = Config {
+            seed: self.config.seed,
+            preview: false,
+            ..config.clone()
+        };
+        self.subdivide();
+
+        let prg_map = SimplexDensityPRG::new(&self.config);
+        let (pos, alive) = (self.nodes.pos(), self.nodes.alive());
+        self.points.clear();
+        for _ in 0..self.config.num_points {
+            let (x, y) = prg_map.sample(&mut self.rng);
+            let point = Point::new(x as _, y as _);
+            let near = pos
+                .iter()
+                .zip(alive)
+                .any(|(pos, alive)| *alive && (point - *pos).length_sqr() < reach * reach);
+            if near {
+                self.points.push(point);
+            }
+        }
+        self.growing = true;
+        self.revision += 1;
+    }
+    /// Scatters a fresh `regrow_share` of the attraction points over the density map, a second
+    /// flush that lets a finished tree grow on. Points the branches already reach are left out
+    pub fn flush(&mut self) {
+        let config = Config {
+            num_points: (self.config.num_points as f32 * self.config.regrow_share) as usize,
+            ..self.config.clone()
+        };
+        // seeded by the size of the tree, so a saved tree flushes the same after loading
+        let mut rng = ChaCha12Rng::seed_from_u64(
+            (self.config.seed ^ FLUSH_SALT).wrapping_add(self.nodes.len() as u64),
+        );
+        let kill_dist = Real::from(self.config.kill_dist);
+        let mut grid = grid::Grid::default();
+        grid.reset(kill_dist);
+        grid.extend(self.nodes.pos());
+        let (pos, alive) = (self.nodes.pos(), self.nodes.alive());
+        let reached = |point: Point| {
+            grid.near(point, kill_dist)
+                .any(|idx| alive[idx] && (point - pos[idx]).length_sqr() < kill_dist * kill_dist)
+        };
+        let fresh = Self::scatter(&config, &mut rng)
+            .into_iter()
+            .filter(|point| !reached(*point))
+            .collect::<Vec<_>>();
+        if fresh.is_empty() {
+            return;
+        }
+        self.points.extend(fresh);
+        self.growing = true;
+        self.revision += 1;
+    }
+    /// Whether the last step still added branches, to the tree or its roots
+    pub fn is_growing(&self) -> bool {
+        self.growing || self.roots.as_ref().is_some_and(|roots| roots.growing)
+    }
+
+    /// The roots, upside down as they grow, `roots::planted` turns them the right way up
+    pub fn roots(&self) -> Option<&Tree> {
+        self.roots.as_deref()
+    }
+
+    /// The nodes of the tree, every parent comes before its children
+    pub fn nodes(&self) -> impl DoubleEndedIterator<Item = Node> + ExactSizeIterator + '_ {
+        self.nodes.iter()
+    }
+
+    /// How many nodes the tree has, including dead ones
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Attraction points the tree still grows towards
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    /// The node at `idx` in the order of `nodes`
+    pub fn node(&self, idx: usize) -> Node {
+        self.nodes.get(idx)
+    }
+
+    /// Positions of all nodes, indexed like `nodes`
+    pub fn positions(&self) -> &[Point] {
+        self.nodes.pos()
+    }
+
+    /// Counts up whenever the nodes change, so renderers can tell when to repaint
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// What the tree still grows from, `None` once it stopped growing
+    pub fn growth(&self) -> Option<Growth> {
+        self.growing.then(|| Growth {
+            points: self.points.clone(),
+            pending: self.to_be_added.clone(),
+            consumed: self.memory.points().to_vec(),
+            word_pos: self.rng.get_word_pos(),
+        })
+    }
+
+    /// The steps every node was added and pruned in
+    pub fn history(&self) -> &history::History {
+        &self.history
+    }
+
+    /// The tree as it stood after `step` steps of its growth, put together from its history
+    /// instead of growing it again. It doesn't grow on, its roots are those of the tree now
+    pub fn at_step(&self, step: u32) -> Tree {
+        let mut new_idx: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        let mut nodes: Vec<Node> = vec![];
+        for (idx, node) in self.nodes().enumerate() {
+            let Some(alive) = self.history.at(idx, step) else {
+                continue;
+            };
+            // parents are added before their children, so they are there too
+            let parent = node.parent.and_then(|parent| new_idx[parent]);
+            if let Some(parent) = parent {
+                nodes[parent].child_count += 1;
+            }
+            new_idx[idx] = Some(nodes.len());
+            nodes.push(Node {
+                parent,
+                child_count: 0,
+                alive,
+                ..node
+            });
+        }
+        let mut config = self.config.clone();
+        let roots = config.roots.take();
+        let mut tree = Tree::from_parts(config, nodes, None);
+        tree.config.roots = roots;
+        tree.roots = self.roots.clone();
+        tree.selections = self.selections.clone();
+        tree.renumber_selections(|idx| new_idx[idx]);
+        tree.recalculate_weight();
+        tree.smooth_radius();
+        tree
+    }
+
+    /// Puts a tree back together from its nodes, e.g. when loading it.
+    /// With `growth` it continues growing exactly like the tree it was taken from
+    pub fn from_parts(config: Config, nodes: Vec<Node>, growth: Option<Growth>) -> Self {
+        let mut rng = ChaCha12Rng::seed_from_u64(config.seed);
+        let growing = growth.is_some();
+        let Growth {
+            points,
+            pending,
+            consumed,
+            word_pos,
+        } = growth.unwrap_or_default();
+        if growing {
+            rng.set_word_pos(word_pos);
+        }
+        // the roots aren't saved, they grow again the same from the seed
+        let roots = config.roots.map(|roots| {
+            let mut roots = Box::new(roots.sprout(&config));
+            if !growing {
+                roots.grow_fully(&cancel::CancelToken::new());
+            }
+            roots
+        });
+        let nodes: nodes::Nodes = nodes.into_iter().collect();
+        let history = history::History::of(nodes.alive());
+        Self {
+            nodes,
+            config,
+            points,
+            memory: memory::CanopyMemory::from_points(consumed),
+            growing,
+            to_be_added: pending,
+            rng,
+            scratch: SimScratch::default(),
+            revision: 0,
+            outcome: diagnose::Outcome::default(),
+            roots,
+            selections: vec![],
+            history,
+            tended: 0.0,
+            aged: 0,
+            subscribers: events::Subscribers::default(),
+        }
+    }
+
+    pub fn radius_of(&self, node: &Node) -> f32 {
+        node.radius
+    }
+
+    /// Highest point the canopy reaches, in world units
+    pub fn top(&self) -> f32 {
+        self.nodes
+            .iter()
+            .filter(|node| node.alive)
+            .map(|node| node.pos.vec().y + self.radius_of(&node).max(self.config.leaf_size))
+            .fold(self.config.origin.y, f32::max)
+    }
+
+    /// For every node, the heaviest child, which is the one that continues the branch
+    pub fn continuations(&self) -> Vec<Option<usize>> {
+        let mut continuations = vec![];
+        self.continuations_into(&mut continuations);
+        continuations
+    }
+    /// Like `continuations`, but reusing the buffer
+    pub fn continuations_into(&self, continuations: &mut Vec<Option<usize>>) {
+        continuations.clear();
+        continuations.resize(self.nodes.len(), None);
+        for (node_idx, node) in self.nodes.iter().enumerate() {
+            if let Some(parent_idx) = node.parent {
+                let heavier = match continuations[parent_idx] {
+                    Some(other) => node.weight > self.nodes.weight(other),
+                    None => true,
+                };
+                if heavier {
+                    continuations[parent_idx] = Some(node_idx);
+                }
+            }
+        }
+    }
+
+    /// Curve from the parent of a node to the node itself, `None` for the root
+    pub fn segment_spline(
+        &self,
+        node_idx: usize,
+        continuations: &[Option<usize>],
+    ) -> Option<Spline> {
+        let pos = self.nodes.pos();
+        let parent_idx = self.nodes.parent(node_idx)?;
+        let before = self.nodes.parent(parent_idx).map(|idx| pos[idx].vec());
+        let after = continuations[node_idx].map(|idx| pos[idx].vec());
+        Some(Spline::through(
+            before,
+            pos[parent_idx].vec(),
+            pos[node_idx].vec(),
+            after,
+        ))
+    }
+
+    /// Grows every branch tip one segment towards the attraction points near it, removing
+    /// the points that got reached. Does nothing once the tree has stopped growing
+    pub fn step(&mut self) {
+        if let Some(roots) = &mut self.roots {
+            roots.step();
+        }
+        if !self.growing {
+            return;
+        }
+        self.revision += 1;
+        self.history.next_step();
+
+        if !self.to_be_added.is_empty() {
+            let node = self.to_be_added.pop().unwrap();
+            self.nodes.add_child(node.parent.unwrap());
+            self.nodes.push(node);
+            self.history.push();
+            self.subscribers
+                .send(events::Event::NodeAdded(self.nodes.len() - 1));
+            if !self.to_be_added.is_empty() {
+                return;
+            }
+        }
+
+        let mut new_nodes = std::mem::take(&mut self.scratch.new_nodes);
+        new_nodes.clear();
+        let attraction_dist = Real::from(self.config.attraction_dist);
+        let attraction_sqr = attraction_dist * attraction_dist;
+        let grow_dist = Real::from(self.config.grow_dist);
+        let SimScratch {
+            point_grid,
+            node_grid,
+            near,
+            ..
+        } = &mut self.scratch;
+        point_grid.reset(attraction_dist);
+        for (idx, point) in self.points.iter().enumerate() {
+            point_grid.insert(idx, *point);
+        }
+        if node_grid.cell() != point_grid.cell() || node_grid.len() > self.nodes.len() {
+            node_grid.reset(attraction_dist);
+        }
+        node_grid.extend(self.nodes.pos());
+        // flat trees grow on the picture plane, everything in z is zero for them
+        let config = &self.config;
+        let volume = config.volume;
+        let node_z = self.nodes.z();
+        let point_z = |p: Point| volume.map_or(0.0, |volume| volume.depth_of(p, config));
+        let dz_sqr = |a: f32, b: f32| match volume {
+            Some(_) => Real::from((a - b) * (a - b)),
+            None => 0.0,
+        };
+        // where the tip at a node grows to, if there are points near it
+        if config.canopy_memory > 0.0 {
+            self.memory.index(attraction_dist * 2.0);
+        }
+        let nodes = &self.nodes;
+        let (points, memory) = (&self.points, &self.memory);
+        let point_grid = &*point_grid;
+        let propose = |node_idx: usize, near: &mut Vec<usize>| -> Option<(Point, f32)> {
+            if nodes.child_count(node_idx) >= config.max_children || !nodes.alive()[node_idx] {
+                return None;
+            }
+            let (pos, z) = (nodes.pos()[node_idx], node_z[node_idx]);
+            // summed in the order of the points, so the tree doesn't depend on the grid
+            near.clear();
+            near.extend(point_grid.near(pos, attraction_dist));
+            near.sort_unstable();
+            let (sum, sum_z, near_count) = near
+                .iter()
+                .map(|idx| {
+                    let p = points[*idx];
+                    (p - pos, volume.map_or(0.0, |_| point_z(p) - z))
+                })
+                .filter(|(p, dz)| p.length_sqr() + dz_sqr(*dz, 0.0) < attraction_sqr)
+                .fold((Point::zero(), 0.0, 0), |(sum, sum_z, count), (p, dz)| {
+                    (sum + p, sum_z + dz, count + 1)
+                });
+            if near_count == 0 {
+                return None;
+            }
+            let (mut dir, mut dir_z) = volume::unit(sum, sum_z);
+            if config.canopy_memory > 0.0 {
+                let away = memory.away_from(pos, attraction_dist * 2.0, near);
+                (dir, dir_z) = volume::unit(dir + away * Real::from(config.canopy_memory), dir_z);
+            }
+            let avg_dir = dir * grow_dist;
+
+            // in similar dir as parent
+            let (prev_dir, prev_z) = if let Some(parent) = nodes.parent(node_idx) {
+                (pos - nodes.pos()[parent], z - node_z[parent])
+            } else {
+                (Point::new(0.0, grow_dist), 0.0)
+            };
+            let mut delta = avg_dir.lerp(prev_dir, Real::from(config.parent_dir_factor));
+            if config.tropism != Vector2::zero() {
+                // the pull grows with how far out from the trunk the branch reaches
+                let half_width = Real::from(config.width * 0.5).max(1.0);
+                let reach = ((pos.x - Real::from(config.origin.x)).abs() / half_width).min(1.0);
+                delta = delta + Point::from(config.tropism) * (grow_dist * reach);
+            }
+            let avg_z = dir_z * config.grow_dist;
+            let delta_z = avg_z + (prev_z - avg_z) * config.parent_dir_factor;
+            Some((pos + delta, delta_z))
+        };
+        // the new nodes take their randomness in the order of their parents either way,
+        // so a tree grows the same on any number of cores
+        let rng = &mut self.rng;
+        let mut branch = |node_idx: usize, (pos, delta_z): (Point, f32)| {
+            new_nodes.push(Node::new_branch(
+                pos,
+                delta_z,
+                node_idx,
+                nodes.get(node_idx),
+                config,
+                rng,
+            ));
+        };
+        #[cfg(feature = "parallel")]
+        let parallel = nodes.len() >= parallel::MIN_ITEMS;
+        #[cfg(not(feature = "parallel"))]
+        let parallel = false;
+        if parallel {
+            #[cfg(feature = "parallel")]
+            for (node_idx, proposal) in parallel::map(nodes.len(), propose).into_iter().enumerate()
+            {
+                if let Some(proposal) = proposal {
+                    branch(node_idx, proposal);
+                }
+            }
+        } else {
+            for node_idx in 0..nodes.len() {
+                if let Some(proposal) = propose(node_idx, near) {
+                    branch(node_idx, proposal);
+                }
+            }
+        }
+        let node_pos = self.nodes.pos();
+        let kill_dist = Real::from(config.kill_dist);
+        let (min_y_growth, node_min_dist) = (
+            Real::from(config.min_y_growth),
+            Real::from(config.node_min_dist),
+        );
+        let remember = config.canopy_memory > 0.0;
+        self.points.retain(|p| {
+            let z = point_z(*p);
+            let reached = node_grid.near(*p, kill_dist).any(|idx| {
+                (*p - node_pos[idx]).length_sqr() + dz_sqr(z, node_z[idx]) < kill_dist * kill_dist
+            });
+            if reached && remember {
+                self.memory.record(*p);
+            }
+            !reached
+        });
+        let mut has_change = false;
+        let outcome = &mut self.outcome;
+        outcome.next_step();
+        outcome.proposed = new_nodes.len();
+        'outer: for mut node in new_nodes.drain(..) {
+            if node.depth > self.config.max_depth {
+                outcome.too_deep += 1;
+                continue 'outer;
+            }
+            let parent_y = node_pos[node.parent.unwrap()].y;
+            if node.pos.y - parent_y < min_y_growth {
+                if volume.is_none() {
+                    outcome.too_flat += 1;
+                    continue 'outer;
+                }
+                // a crown with a volume levels off instead, towards the points the trunk
+                // passed in front of or behind it, which would hold every tip back otherwise
+                node.pos.y = parent_y + min_y_growth;
+            }
+            if node_grid.near(node.pos, node_min_dist).any(|idx| {
+                (node_pos[idx] - node.pos).length_sqr() + dz_sqr(node_z[idx], node.z)
+                    < node_min_dist * node_min_dist
+            }) {
+                outcome.crowded += 1;
+                continue 'outer;
+            }
+            self.to_be_added.push(node);
+            has_change = true;
+        }
+        self.scratch.new_nodes = new_nodes;
+        self.growing &= has_change;
+
+        self.prune();
+        self.nodes.update_weight(&mut self.scratch.reweighed);
+        self.smooth_radius();
+
+        if !self.growing {
+            self.subdivide();
+            self.subscribers.send(events::Event::GrowthFinished);
+        }
+    }
+    /// Kills small branches that are too close to big branches.
+    /// Weights only grow between prunes: a heavier node can make the nodes within its reach too
+    /// close to it, and a node that got heavier itself may not be too close anymore. So only
+    /// new nodes, reweighed ones found too close before and the nodes within reach of reweighed
+    /// ones are looked at again, the others keep what they were found to be
+    fn prune(&mut self) {
+        let SimScratch {
+            dead: death_node,
+            node_grid,
+            condemned,
+            reweighed,
+            recheck,
+            ..
+        } = &mut self.scratch;
+        let pos = self.nodes.pos();
+        node_grid.extend(pos);
+        let (prune_pow, size_ratio) = (self.config.prune_pow, self.config.prune_size_ratio);
+        let reach_of = |weight: f32| Real::from(weight.powf(prune_pow));
+        let weight = |idx: usize| self.nodes.weight(idx) as f32;
+        // whether `node_idx` is too close to `conflict_idx` to live
+        let too_close = |node_idx: usize, conflict_idx: usize| {
+            let conflict_weight = weight(conflict_idx);
+            weight(node_idx) < size_ratio * conflict_weight
+                && (pos[conflict_idx] - pos[node_idx]).length() < reach_of(conflict_weight)
+        };
+        // no branch reaches further than the heaviest one, the root
+        let reach = (0..pos.len())
+            .map(|idx| self.nodes.weight(idx))
+            .max()
+            .map_or(0.0, |weight| reach_of(weight as f32));
+
+        reweighed.sort_unstable();
+        reweighed.dedup();
+        recheck.clear();
+        recheck.extend(condemned.len()..pos.len());
+        recheck.extend(
+            reweighed
+                .iter()
+                .filter(|&&idx| condemned.get(idx) == Some(&true)),
+        );
+        condemned.resize(pos.len(), false);
+        for &node_idx in recheck.iter() {
+            condemned[node_idx] = node_grid
+                .near(pos[node_idx], reach)
+                .any(|conflict_idx| too_close(node_idx, conflict_idx));
+        }
+        for &conflict_idx in reweighed.iter() {
+            for node_idx in node_grid.near(pos[conflict_idx], reach_of(weight(conflict_idx))) {
+                condemned[node_idx] |= too_close(node_idx, conflict_idx);
+            }
+        }
+        reweighed.clear();
+
+        // transitive adding of dead nodes, parents always come before their children
+        death_node.clear();
+        death_node.resize(pos.len(), false);
+        for node_idx in 0..pos.len() {
+            death_node[node_idx] = condemned[node_idx]
+                || self
+                    .nodes
+                    .parent(node_idx)
+                    .is_some_and(|parent_idx| death_node[parent_idx]);
+        }
+
+        for (node_idx, dead) in death_node.iter().enumerate() {
+            if *dead && self.nodes.alive()[node_idx] {
+                self.nodes.kill(node_idx);
+                self.history.kill(node_idx);
+                self.outcome.pruned += 1;
+                self.subscribers.send(events::Event::NodePruned(node_idx));
+            }
+        }
+    }
+    /// The living node whose branch outline is closest to `pos`, if it is within `reach`.
+    /// The root is never picked, cutting it would leave nothing to grow from
+    pub fn node_at(&self, pos: Point, reach: f32) -> Option<usize> {
+        self.nodes()
+            .enumerate()
+            .filter(|(_, node)| node.alive && node.parent.is_some())
+            .map(|(idx, node)| (idx, (node.pos - pos).length() - Real::from(node.radius)))
+            .filter(|(_, dist)| *dist <= Real::from(reach))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(idx, _)| idx)
+    }
+    /// Kills a node and everything growing from it
+    pub fn kill_subtree(&mut self, node_idx: usize) {
+        self.revision += 1;
+        let mut dead = vec![false; self.nodes.len()];
+        dead[node_idx] = true;
+        // children always come after their parents
+        for idx in node_idx..self.nodes.len() {
+            if let Some(parent_idx) = self.nodes.parent(idx) {
+                dead[idx] |= dead[parent_idx];
+            }
+            if dead[idx] && self.nodes.alive()[idx] {
+                self.subscribers.send(events::Event::NodePruned(idx));
+            }
+            if dead[idx] {
+                self.nodes.kill(idx);
+                self.history.kill(idx);
+            }
+        }
+    }
+    /// The living nodes inside the box between the corners `min` and `max`, without the root
+    pub fn nodes_within(&self, min: Point, max: Point) -> Vec<usize> {
+        self.nodes()
+            .enumerate()
+            .filter(|(_, node)| node.alive && node.parent.is_some())
+            .filter(|(_, node)| {
+                (min.x..=max.x).contains(&node.pos.x) && (min.y..=max.y).contains(&node.pos.y)
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+    /// Takes the nodes and everything growing from them off the tree, and gives back the
+    /// attraction points only they had reached, so the tree grows into the gap again.
+    /// Unlike `kill_subtree` the nodes are gone, dead ones would still reach the points
+    pub fn cut_away(&mut self, cuts: &[usize]) {
+        let mut removed = vec![false; self.nodes.len()];
+        for &idx in cuts.iter().filter(|&&idx| idx > 0) {
+            removed[idx] = true;
+        }
+        // children always come after their parents
+        for idx in 0..self.nodes.len() {
+            if let Some(parent_idx) = self.nodes.parent(idx) {
+                removed[idx] |= removed[parent_idx];
+            }
+        }
+        if !removed.contains(&true) {
+            return;
+        }
+
+        let mut kept = nodes::Nodes::with_capacity(self.nodes.len());
+        let mut gone = vec![];
+        let mut new_idx = vec![0; self.nodes.len()];
+        let mut old_idx = vec![];
+        for (idx, mut node) in self.nodes.iter().enumerate() {
+            if removed[idx] {
+                gone.push((node.pos, node.z));
+                continue;
+            }
+            old_idx.push(idx);
+            node.parent = node.parent.map(|parent_idx| new_idx[parent_idx]);
+            node.child_count = 0;
+            if let Some(parent_idx) = node.parent {
+                kept.add_child(parent_idx);
+            }
+            new_idx[idx] = kept.len();
+            kept.push(node);
+        }
+        self.to_be_added
+            .retain(|node| !removed[node.parent.unwrap()]);
+        for node in self.to_be_added.iter_mut() {
+            node.parent = node.parent.map(|idx| new_idx[idx]);
+        }
+        self.renumber_selections(|idx| (!removed[idx]).then(|| new_idx[idx]));
+
+        // the points are scattered again just like the tree started out, the ones the cut
+        // branches had reached and none of the others did are free again
+        let kill_dist = Real::from(self.config.kill_dist);
+        let volume = self.config.volume;
+        // whether a node at `pos` and `node_z` reached the point at `p` and `z`
+        let reaches = |p: Point, z: f32| {
+            move |(pos, node_z): (Point, f32)| {
+                let dz = volume.map_or(0.0, |_| Real::from(z - node_z));
+                (p - pos).length_sqr() + dz * dz < kill_dist * kill_dist
+            }
+        };
+        let mut kept_grid = grid::Grid::default();
+        kept_grid.reset(kill_dist);
+        kept_grid.extend(kept.pos());
+        let mut gone_grid = grid::Grid::default();
+        gone_grid.reset(kill_dist);
+        for (idx, (pos, _)) in gone.iter().enumerate() {
+            gone_grid.insert(idx, *pos);
+        }
+        let mut rng = ChaCha12Rng::seed_from_u64(self.config.seed);
+        for p in Self::scatter(&self.config, &mut rng) {
+            let z = volume.map_or(0.0, |volume| volume.depth_of(p, &self.config));
+            let freed = gone_grid
+                .near(p, kill_dist)
+                .map(|idx| gone[idx])
+                .any(reaches(p, z))
+                && !kept_grid
+                    .near(p, kill_dist)
+                    .map(|idx| (kept.pos()[idx], kept.z()[idx]))
+                    .any(reaches(p, z));
+            if freed {
+                self.points.push(p);
+            }
+        }
+
+        self.nodes = kept;
+        self.history.renumber(old_idx);
+        self.scratch.forget_nodes();
+        self.recalculate_weight();
+        self.smooth_radius();
+        self.growing = true;
+        self.revision += 1;
+        self.subscribers.send(events::Event::Renumbered);
+    }
+    fn recalculate_weight(&mut self) {
+        self.nodes.recalculate_weight();
+    }
+    /// Inserts intermediate nodes along segments longer than `subdivide_max_length`,
+    /// following the branch spline and interpolating z between the endpoints
+    fn subdivide(&mut self) {
+        let max_length = self.config.subdivide_max_length;
+        if max_length <= 0.0 {
+            return;
+        }
+        let continuations = self.continuations();
+        let mut nodes = nodes::Nodes::with_capacity(self.nodes.len());
+        let mut new_idx = vec![0; self.nodes.len()];
+        let mut old_idx = Vec::with_capacity(self.nodes.len());
+        for (node_idx, mut node) in self.nodes.iter().enumerate() {
+            if let Some(parent_idx) = node.parent {
+                let parent = self.nodes.get(parent_idx);
+                let length = (node.pos - parent.pos).length();
+                let segments = (length / Real::from(max_length)).ceil() as usize;
+                let spline = self.segment_spline(node_idx, &continuations).unwrap();
+
+                let mut prev = new_idx[parent_idx];
+                for i in 1..segments {
+                    let t = i as f32 / segments as f32;
+                    nodes.push(Node {
+                        pos: spline.at(t).into(),
+                        parent: Some(prev),
+                        child_count: 1,
+                        z: parent.z + (node.z - parent.z) * t,
+                        alive: node.alive && parent.alive,
+                        ..node
+                    });
+                    old_idx.push(node_idx);
+                    prev = nodes.len() - 1;
+                }
+                node.parent = Some(prev);
+            }
+            new_idx[node_idx] = nodes.len();
+            nodes.push(node);
+            old_idx.push(node_idx);
+        }
+        for node in self.to_be_added.iter_mut() {
+            node.parent = node.parent.map(|idx| new_idx[idx]);
+        }
+        self.renumber_selections(|idx| Some(new_idx[idx]));
+        self.nodes = nodes;
+        self.history.renumber(old_idx);
+        self.scratch.forget_nodes();
+        self.nodes.recalculate_depth();
+        self.recalculate_weight();
+        self.smooth_radius();
+        self.subscribers.send(events::Event::Renumbered);
+    }
+    /// Limits how abruptly the radius can drop between a node and its parent,
+    /// so forks don't produce lumpy silhouettes
+    fn smooth_radius(&mut self) {
+        let min_ratio = 1.0 - self.config.radius_taper_cap.clamp(0.0, 1.0);
+        // parents always come before their children in the node list
+        for node_idx in 0..self.nodes.len() {
+            let weight = self.nodes.weight(node_idx) as f32;
+            let mut radius = 0.5 + weight.powf(self.config.weight_display_pow);
+            if let Some(parent_idx) = self.nodes.parent(node_idx) {
+                let parent_radius = self.nodes.radius(parent_idx);
+                radius = radius.max(parent_radius * min_ratio).min(parent_radius);
+            }
+            self.nodes.set_radius(node_idx, radius);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Largest distance between the nodes of a tree grown `offset` away from the origin
+    /// and the same tree grown at the origin
+    fn drift(offset: Real) -> Real {
+        let config = Config {
+            seed: 1,
+            ..Config::default()
+        };
+        let shift = Point::new(offset, offset);
+        let mut near = Tree::new(config.clone());
+        let mut far = Tree::new(Config {
+            origin: config.origin + shift.vec(),
+            ..config
+        });
+        for point in far.points.iter_mut() {
+            *point = *point + shift;
+        }
+        for _ in 0..150 {
+            near.step();
+            far.step();
+        }
+        assert_eq!(near.nodes.len(), far.nodes.len());
+        near.nodes
+            .pos()
+            .iter()
+            .zip(far.nodes.pos())
+            .map(|(near, far)| (*far - shift - *near).length())
+            .fold(0.0, Real::max)
+    }
+
+    #[test]
+    fn same_seed_grows_the_same_tree() {
+        let grow = |seed| {
+            let mut tree = Tree::new(Config {
+                seed,
+                ..Config::default()
+            });
+            for _ in 0..150 {
+                tree.step();
+            }
+            tree.nodes()
+                .map(|node| (node.pos.vec(), node.z, node.parent))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(grow(3), grow(3));
+        assert_ne!(grow(3), grow(4));
+    }
+
+    #[test]
+    fn volume_grows_in_depth_and_turns_into_the_hint() {
+        let mut tree = Tree::new(Config {
+            seed: 4,
+            volume: Some(volume::Volume::default()),
+            ..Config::default()
+        });
+        for _ in 0..400 {
+            tree.step();
+        }
+        let depth = tree.nodes().map(|node| node.z.abs()).fold(0.0, f32::max);
+        assert!(depth > tree.config.grow_dist, "grew flat, {} deep", depth);
+        let turned = volume::turned(&tree).unwrap();
+        let max = tree.config.node_depth_max as f32;
+        assert!(turned.nodes().all(|node| (0.0..=max).contains(&node.z)));
+    }
+
+    #[test]
+    fn updated_weights_match_a_full_count() {
+        let mut tree = Tree::new(Config {
+            seed: 6,
+            ..Config::default()
+        });
+        let weights = |nodes: &nodes::Nodes| {
+            (0..nodes.len())
+                .map(|idx| nodes.weight(idx))
+                .collect::<Vec<_>>()
+        };
+        for _ in 0..300 {
+            tree.step();
+            let (mut updated, mut counted) = (tree.nodes.clone(), tree.nodes.clone());
+            updated.update_weight(&mut vec![]);
+            counted.recalculate_weight();
+            assert_eq!(weights(&updated), weights(&counted));
+        }
+    }
+
+    #[test]
+    fn cutting_away_a_branch_frees_its_points_to_grow_into() {
+        let mut tree = Tree::new(Config {
+            seed: 3,
+            ..Config::default()
+        });
+        tree.grow_fully(&cancel::CancelToken::new());
+        let before = tree.node_count();
+        let (min, max) = (Point::new(0.0, 150.0), Point::new(250.0, 500.0));
+        tree.cut_away(&tree.nodes_within(min, max));
+        assert!(tree.node_count() < before);
+        assert!(tree.nodes_within(min, max).is_empty());
+        assert!(!tree.points().is_empty() && tree.is_growing());
+        assert!(tree
+            .nodes()
+            .enumerate()
+            .all(|(idx, node)| node.parent.map_or(idx == 0, |parent| parent < idx)));
+        tree.grow_fully(&cancel::CancelToken::new());
+        assert!(!tree.nodes_within(min, max).is_empty());
+    }
+
+    #[test]
+    fn a_flush_lets_a_finished_tree_grow_on() {
+        let mut tree = Tree::new(Config {
+            seed: 3,
+            ..Config::default()
+        });
+        tree.grow_fully(&cancel::CancelToken::new());
+        let before = tree.node_count();
+        tree.flush();
+        assert!(tree.is_growing());
+        tree.grow_fully(&cancel::CancelToken::new());
+        assert!(tree.node_count() > before);
+    }
+
+    #[test]
+    fn growth_close_to_the_origin_does_not_drift() {
+        assert!(drift(1024.0) < 1e-2);
+    }
+
+    /// Documents why the `f64` feature exists, if this fails f32 is good enough
+    #[cfg(not(feature = "f64"))]
+    #[test]
+    fn f32_growth_far_from_the_origin_drifts() {
+        assert!(drift(1e6) > 1.0);
+    }
+
+    #[cfg(feature = "f64")]
+    #[test]
+    fn f64_growth_far_from_the_origin_does_not_drift() {
+        assert!(drift(1e6) < 1e-6);
+    }
+}
@@ -0,0 +1,219 @@
+//! The small vector and color types the engine works with
+//!
+//! They mirror the parts of raylib's types the trees need, so the engine doesn't have to
+//! link raylib. The renderer converts them at the window boundary.
+
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Vector2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vector2 {
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+    pub const fn zero() -> Self {
+        Self::new(0.0, 0.0)
+    }
+    pub fn length(&self) -> f32 {
+        self.length_sqr().sqrt()
+    }
+    pub fn length_sqr(&self) -> f32 {
+        self.x * self.x + self.y * self.y
+    }
+    pub fn dot(&self, v: Vector2) -> f32 {
+        self.x * v.x + self.y * v.y
+    }
+    /// Same direction with length 1, the zero vector stays zero
+    pub fn normalized(&self) -> Vector2 {
+        let length_sqr = self.length_sqr();
+        if length_sqr == 0.0 {
+            return *self;
+        }
+        *self / length_sqr.sqrt()
+    }
+    /// Moves `amount` of the way towards `v`
+    pub fn lerp(&self, v: Vector2, amount: f32) -> Vector2 {
+        *self + (v - *self) * amount
+    }
+}
+
+impl Add for Vector2 {
+    type Output = Self;
+    fn add(self, v: Self) -> Self {
+        Self::new(self.x + v.x, self.y + v.y)
+    }
+}
+
+impl AddAssign for Vector2 {
+    fn add_assign(&mut self, v: Self) {
+        *self = *self + v;
+    }
+}
+
+impl Sub for Vector2 {
+    type Output = Self;
+    fn sub(self, v: Self) -> Self {
+        Self::new(self.x - v.x, self.y - v.y)
+    }
+}
+
+impl SubAssign for Vector2 {
+    fn sub_assign(&mut self, v: Self) {
+        *self = *self - v;
+    }
+}
+
+impl Mul<f32> for Vector2 {
+    type Output = Self;
+    fn mul(self, f: f32) -> Self {
+        Self::new(self.x * f, self.y * f)
+    }
+}
+
+impl Mul for Vector2 {
+    type Output = Self;
+    fn mul(self, v: Self) -> Self {
+        Self::new(self.x * v.x, self.y * v.y)
+    }
+}
+
+impl Div<f32> for Vector2 {
+    type Output = Self;
+    fn div(self, f: f32) -> Self {
+        Self::new(self.x / f, self.y / f)
+    }
+}
+
+impl Neg for Vector2 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Vector3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vector3 {
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+    pub const fn zero() -> Self {
+        Self::new(0.0, 0.0, 0.0)
+    }
+    pub fn length(&self) -> f32 {
+        self.dot(*self).sqrt()
+    }
+    pub fn dot(&self, v: Vector3) -> f32 {
+        self.x * v.x + self.y * v.y + self.z * v.z
+    }
+    /// Same direction with length 1, the zero vector stays zero
+    pub fn normalized(&self) -> Vector3 {
+        let length = self.length();
+        if length == 0.0 {
+            return *self;
+        }
+        *self * (1.0 / length)
+    }
+    /// Moves `amount` of the way towards `v`
+    pub fn lerp(&self, v: Vector3, amount: f32) -> Vector3 {
+        *self + (v - *self) * amount
+    }
+}
+
+impl Add for Vector3 {
+    type Output = Self;
+    fn add(self, v: Self) -> Self {
+        Self::new(self.x + v.x, self.y + v.y, self.z + v.z)
+    }
+}
+
+impl Sub for Vector3 {
+    type Output = Self;
+    fn sub(self, v: Self) -> Self {
+        Self::new(self.x - v.x, self.y - v.y, self.z - v.z)
+    }
+}
+
+impl Mul<f32> for Vector3 {
+    type Output = Self;
+    fn mul(self, f: f32) -> Self {
+        Self::new(self.x * f, self.y * f, self.z * f)
+    }
+}
+
+impl Div<f32> for Vector3 {
+    type Output = Self;
+    fn div(self, f: f32) -> Self {
+        Self::new(self.x / f, self.y / f, self.z / f)
+    }
+}
+
+impl Neg for Vector3 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+/// 8 bit RGBA color
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::new(255, 255, 255, 255);
+    pub const BLACK: Color = Color::new(0, 0, 0, 255);
+    pub const BLANK: Color = Color::new(0, 0, 0, 0);
+    pub const GRAY: Color = Color::new(130, 130, 130, 255);
+    pub const DARKGRAY: Color = Color::new(80, 80, 80, 255);
+    pub const RED: Color = Color::new(230, 41, 55, 255);
+    pub const MAROON: Color = Color::new(190, 33, 55, 255);
+    pub const GREEN: Color = Color::new(0, 228, 48, 255);
+    pub const BLUE: Color = Color::new(0, 121, 241, 255);
+    pub const BROWN: Color = Color::new(127, 106, 79, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+    /// Opaque color from six hex digits like `"8b6354"`
+    pub fn from_hex(hex: &str) -> Result<Color, std::num::ParseIntError> {
+        let color = u32::from_str_radix(hex, 16)?;
+        Ok(Color::new(
+            (color >> 16) as u8,
+            (color >> 8) as u8,
+            color as u8,
+            255,
+        ))
+    }
+    /// Same color with the alpha set to `alpha` between 0 and 1
+    pub fn fade(&self, alpha: f32) -> Color {
+        Color {
+            a: (255.0 * alpha.clamp(0.0, 1.0)) as u8,
+            ..*self
+        }
+    }
+    /// Moves `amount` of the way towards `other`, channel by channel
+    pub fn lerp(&self, other: Color, amount: f32) -> Color {
+        let amount = amount.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+        Color::new(
+            mix(self.r, other.r),
+            mix(self.g, other.g),
+            mix(self.b, other.b),
+            mix(self.a, other.a),
+        )
+    }
+}
@@ -0,0 +1,64 @@
+//! Where the tree already reached its attraction points
+//!
+//! Early branches tend to keep crowding into the first direction that worked out.
+//! Remembering the reached points lets the tips turn away from those regions,
+//! so late growth fills the parts of the canopy that are still empty.
+
+use crate::{
+    grid::Grid,
+    point::{Point, Real},
+};
+
+/// Reached points around a tip at which it turns away with half the full strength
+const HALF_CROWDED: Real = 8.0;
+
+/// Attraction points that were reached, and a grid to find them again
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CanopyMemory {
+    points: Vec<Point>,
+    grid: Grid,
+}
+
+impl CanopyMemory {
+    pub fn from_points(points: Vec<Point>) -> Self {
+        Self {
+            points,
+            ..Self::default()
+        }
+    }
+
+    /// The reached points, oldest first
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    pub fn record(&mut self, point: Point) {
+        self.points.push(point);
+    }
+
+    /// Brings the grid up to date for queries within `radius`, before [`Self::away_from`]
+    pub fn index(&mut self, radius: Real) {
+        if self.grid.cell() != radius.max(1.0) {
+            self.grid.reset(radius);
+        }
+        self.grid.extend(&self.points);
+    }
+
+    /// Direction away from the reached points within `radius` of `pos`, its length growing
+    /// from 0 towards 1 the more points there are
+    pub fn away_from(&self, pos: Point, radius: Real, near: &mut Vec<usize>) -> Point {
+        // summed in the order of the points, the grid doesn't keep one
+        near.clear();
+        near.extend(self.grid.near(pos, radius));
+        near.sort_unstable();
+        let (sum, count) = near
+            .iter()
+            .map(|idx| pos - self.points[*idx])
+            .filter(|away| away.length_sqr() < radius * radius)
+            .fold((Point::zero(), 0), |(sum, count), away| {
+                (sum + away.normalized(), count + 1)
+            });
+        let count = count as Real;
+        sum.normalized() * (count / (count + HALF_CROWDED))
+    }
+}
@@ -0,0 +1,182 @@
+//! Turning one grown tree into another, for animations between two seeds
+//!
+//! The skeletons are matched from the root outwards: the children of two matched nodes pair
+//! up by the direction they branch off in, heaviest first. A branch without a partner grows
+//! out of, or shrinks back into, the partner of the node it forks from.
+
+use crate::*;
+
+/// Which node of each tree a node of the morph follows
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Pair {
+    Both(usize, usize),
+    /// a node of the first tree, shrinking into the partner of its fork in the second
+    OnlyA(usize, usize),
+    /// a node of the second tree, growing out of the partner of its fork in the first
+    OnlyB(usize, usize),
+}
+
+/// A node of the morph and the node it forks from
+#[derive(Copy, Clone, Debug)]
+struct Joint {
+    pair: Pair,
+    parent: Option<usize>,
+}
+
+/// The correspondence between the skeletons of two trees
+#[derive(Clone)]
+pub struct Morph {
+    a: Tree,
+    b: Tree,
+    /// parents before their children, like the nodes of a tree
+    joints: Vec<Joint>,
+}
+
+impl Morph {
+    /// Matches the skeleton of `a` to the one of `b`, the morph takes `a`'s config
+    pub fn between(a: Tree, b: Tree) -> Self {
+        let children = |tree: &Tree| {
+            let mut children = vec![vec![]; tree.node_count()];
+            for (idx, node) in tree.nodes().enumerate() {
+                if let Some(parent) = node.parent {
+                    children[parent].push(idx);
+                }
+            }
+            children
+        };
+        let (children_a, children_b) = (children(&a), children(&b));
+        let direction = |tree: &Tree, idx: usize| {
+            let node = tree.node(idx);
+            let parent = tree.node(node.parent.unwrap());
+            let step = node.pos.vec() - parent.pos.vec();
+            step.y.atan2(step.x)
+        };
+
+        let mut joints = vec![Joint {
+            pair: Pair::Both(0, 0),
+            parent: None,
+        }];
+        let mut next = 0;
+        while next < joints.len() {
+            let Joint { pair, .. } = joints[next];
+            let parent = Some(next);
+            next += 1;
+            match pair {
+                Pair::Both(node_a, node_b) => {
+                    let heaviest_first = |tree: &Tree, children: &[usize]| {
+                        let mut children = children.to_vec();
+                        children.sort_by_key(|&idx| std::cmp::Reverse(tree.node(idx).weight));
+                        children
+                    };
+                    let mut left = heaviest_first(&b, &children_b[node_b]);
+                    for child_a in heaviest_first(&a, &children_a[node_a]) {
+                        let angle = direction(&a, child_a);
+                        let closest = (0..left.len()).min_by(|&i, &j| {
+                            // a branch of a similar share of the weight, in about the same direction
+                            let share = |tree: &Tree, idx: usize, fork: usize| {
+                                tree.node(idx).weight as f32 / tree.node(fork).weight as f32
+                            };
+                            let off = |idx: usize| {
+                                angle_between(angle, direction(&b, left[idx]))
+                                    + 4.0
+                                        * (share(&a, child_a, node_a)
+                                            - share(&b, left[idx], node_b))
+                                        .abs()
+                            };
+                            off(i).total_cmp(&off(j))
+                        });
+                        let pair = match closest {
+                            Some(closest) => Pair::Both(child_a, left.remove(closest)),
+                            None => Pair::OnlyA(child_a, node_b),
+                        };
+                        joints.push(Joint { pair, parent });
+                    }
+                    for child_b in left {
+                        let pair = Pair::OnlyB(node_a, child_b);
+                        joints.push(Joint { pair, parent });
+                    }
+                }
+                Pair::OnlyA(node_a, fork_b) => {
+                    for &child_a in &children_a[node_a] {
+                        let pair = Pair::OnlyA(child_a, fork_b);
+                        joints.push(Joint { pair, parent });
+                    }
+                }
+                Pair::OnlyB(fork_a, node_b) => {
+                    for &child_b in &children_b[node_b] {
+                        let pair = Pair::OnlyB(fork_a, child_b);
+                        joints.push(Joint { pair, parent });
+                    }
+                }
+            }
+        }
+        Self { a, b, joints }
+    }
+
+    /// The tree `amount` of the way from the first tree to the second
+    pub fn at(&self, amount: f32) -> Tree {
+        let amount = amount.clamp(0.0, 1.0);
+        let mut nodes: Vec<Node> = vec![];
+        for joint in &self.joints {
+            // a branch without a partner is folded into its fork at the other end, gone once it is reached
+            let (from, to, alive) = match joint.pair {
+                Pair::Both(a, b) => {
+                    let (from, to) = (self.a.node(a), self.b.node(b));
+                    (from, to, if amount < 0.5 { from.alive } else { to.alive })
+                }
+                Pair::OnlyA(a, fork) => {
+                    let from = self.a.node(a);
+                    (
+                        from,
+                        vanished(self.b.node(fork), from),
+                        from.alive && amount < 1.0,
+                    )
+                }
+                Pair::OnlyB(fork, b) => {
+                    let to = self.b.node(b);
+                    (
+                        vanished(self.a.node(fork), to),
+                        to,
+                        to.alive && amount > 0.0,
+                    )
+                }
+            };
+            let mix = |from: f32, to: f32| from + (to - from) * amount;
+            let depth = joint.parent.map_or(0, |parent| nodes[parent].depth + 1);
+            if let Some(parent) = joint.parent {
+                nodes[parent].child_count += 1;
+            }
+            nodes.push(Node {
+                alive,
+                pos: from.pos.vec().lerp(to.pos.vec(), amount).into(),
+                parent: joint.parent,
+                child_count: 0,
+                depth,
+                weight: mix(from.weight as f32, to.weight as f32).round().max(1.0) as usize,
+                radius: mix(from.radius, to.radius),
+                z: mix(from.z, to.z),
+            });
+        }
+        // roots would grow again for every moment, the morph goes without them
+        let config = Config {
+            roots: None,
+            ..self.a.config.clone()
+        };
+        Tree::from_parts(config, nodes, None)
+    }
+}
+
+/// Where a branch without a partner starts or ends: at the fork, as thick as the branch itself
+fn vanished(fork: Node, branch: Node) -> Node {
+    Node {
+        pos: fork.pos,
+        z: fork.z,
+        ..branch
+    }
+}
+
+/// Difference between two angles in radians, between 0 and π
+fn angle_between(a: f32, b: f32) -> f32 {
+    let diff = (a - b).rem_euclid(std::f32::consts::TAU);
+    diff.min(std::f32::consts::TAU - diff)
+}
@@ -0,0 +1,138 @@
+//! Poetic names for trees, like "Leaning Ember Pine, age 47"
+//!
+//! The first word comes from how the tree grew: leaning, sprawling, slender or weathered by
+//! cuts. The middle word is drawn from the seed, the kind of tree from the color of its leaves,
+//! and the age is how many nodes its longest branch reaches out from the root. The same tree
+//! always gets the same name.
+
+use rand::Rng;
+use rand_chacha::{rand_core::SeedableRng, ChaCha12Rng};
+
+use crate::*;
+
+/// Keeps the words drawn for the name apart from every other use of the seed
+const NAME_SALT: u64 = 0x6e61_6d65;
+
+/// Words for trees that grew without anything standing out about their shape
+const CALM: &[&str] = &["Quiet", "Patient", "Humble", "Gentle", "Still", "Noble"];
+
+const MIDDLE: &[&str] = &[
+    "Ember", "Moss", "Frost", "Cloud", "River", "Lantern", "Dawn", "Heron", "Mist", "Stone",
+    "Crane", "Moon", "Thunder", "Temple", "Harbor", "Ash", "Willow", "Brook", "Comet", "Shadow",
+];
+
+/// Kinds of tree by the color of their leaves
+const REDDISH: &[&str] = &["Cherry", "Plum", "Azalea"];
+const GOLDEN: &[&str] = &["Maple", "Ginkgo", "Larch"];
+const GREEN: &[&str] = &["Pine", "Juniper", "Cedar", "Elm"];
+const BLUISH: &[&str] = &["Spruce", "Cypress", "Wisteria"];
+
+/// Nodes of the longest branch per year of age
+const NODES_PER_YEAR: usize = 3;
+
+/// The name of the tree as it stands
+pub fn of(tree: &Tree) -> String {
+    let config = &tree.config;
+    let mut rng = ChaCha12Rng::seed_from_u64(config.seed ^ NAME_SALT);
+    let mut pick = |words: &[&'static str]| words[rng.gen_range(0..words.len())];
+    // all drawn up front, so the words don't change with each other
+    let (calm, middle) = (pick(CALM), pick(MIDDLE));
+    let kinds = [pick(REDDISH), pick(GOLDEN), pick(GREEN), pick(BLUISH)];
+
+    let alive = tree.nodes().filter(|node| node.alive).collect::<Vec<_>>();
+    let dead = tree.node_count() - alive.len();
+    let (mut min, mut max) = (config.origin, config.origin);
+    let mut sum = Vector2::zero();
+    for node in &alive {
+        let pos = node.pos.vec();
+        min = Vector2::new(min.x.min(pos.x), min.y.min(pos.y));
+        max = Vector2::new(max.x.max(pos.x), max.y.max(pos.y));
+        sum += pos;
+    }
+    let (width, height) = (max.x - min.x, (max.y - config.origin.y).max(1.0));
+    let lean = match alive.len() {
+        0 => 0.0,
+        count => (sum.x / count as f32 - config.origin.x) / height,
+    };
+    let shape = if lean.abs() > 0.25 {
+        "Leaning"
+    } else if dead > alive.len() / 2 {
+        "Weathered"
+    } else if width > height * 1.4 {
+        "Sprawling"
+    } else if height > width * 1.6 {
+        "Slender"
+    } else {
+        calm
+    };
+    // the leaves drawn most often, the palette's when there are no leaf types
+    let leaf = config
+        .leaves
+        .iter()
+        .max_by(|a, b| a.probability.total_cmp(&b.probability))
+        .map_or(config.colors.leaf, |leaf| leaf.color);
+    let kind = match hue(leaf) {
+        Some(hue) if !(20.0..290.0).contains(&hue) => kinds[0],
+        Some(hue) if hue < 70.0 => kinds[1],
+        Some(hue) if hue >= 170.0 => kinds[3],
+        _ => kinds[2],
+    };
+
+    let longest = alive
+        .iter()
+        .map(|node| node.depth)
+        .max()
+        .unwrap_or_default();
+    format!(
+        "{} {} {}, age {}",
+        shape,
+        middle,
+        kind,
+        longest / NODES_PER_YEAR
+    )
+}
+
+/// Hue of the color in degrees, `None` for grays
+fn hue(color: Color) -> Option<f32> {
+    let (r, g, b) = (color.r as f32, color.g as f32, color.b as f32);
+    let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+    let chroma = max - min;
+    if chroma < 8.0 {
+        return None;
+    }
+    let sector = if max == r {
+        (g - b) / chroma
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+    Some((sector * 60.0).rem_euclid(360.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_come_from_the_tree() {
+        let mut tree = Tree::new_min_growth(
+            Config {
+                seed: 8,
+                num_points: 3000,
+                ..Config::default()
+            },
+            5,
+        );
+        tree.grow_fully(&cancel::CancelToken::new());
+        let name = of(&tree);
+        assert_eq!(name, of(&tree.clone()));
+        assert!(name.contains(", age "));
+        let red = Color::from_hex("e04060").unwrap();
+        tree.config.colors.leaf = red;
+        for leaf in &mut tree.config.leaves {
+            leaf.color = red;
+        }
+        assert!(REDDISH.iter().any(|kind| of(&tree).contains(kind)));
+    }
+}
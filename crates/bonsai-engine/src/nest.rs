@@ -0,0 +1,117 @@
+//! A bird's nest, hidden in a fork of a rare tree
+//!
+//! One grown tree in a while, picked by its seed with a chance of `nest_chance`, carries a nest
+//! with a few eggs in a fork of its middle branches. Trees kept open in the window count the
+//! seconds they were looked after, see `Tree::tend`, and once that adds up to `hatch_after` the
+//! eggs hatch into chicks begging for food. The time is saved with the tree, so it keeps adding
+//! up over the days the window is opened again and the autosave is resumed.
+
+use rand::{seq::SliceRandom, Rng};
+use rand_chacha::{rand_core::SeedableRng, ChaCha12Rng};
+
+use crate::*;
+
+/// Keeps the rolls for the nest apart from every other use of the seed
+const NEST_SALT: u64 = 0x6e65_7374_6567_6773;
+/// Wood thinner than this many times `leaf_max_width` is too thin to carry the nest
+const THINNEST: f32 = 2.0;
+/// Wood thicker than this many times `leaf_max_width` is the trunk, too steep to hold it
+const THICKEST: f32 = 6.0;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Nest {
+    /// the fork it sits in
+    pub node: usize,
+    /// where it sits, in world units
+    pub pos: Vector2,
+    /// how wide it is, in world units
+    pub width: f32,
+    pub eggs: usize,
+    /// whether the eggs hatched into chicks
+    pub hatched: bool,
+}
+
+/// Whether the tree grown from `config` is one of the rare ones with a nest, from the seed alone
+pub fn rolled(config: &Config) -> bool {
+    ChaCha12Rng::seed_from_u64(config.seed ^ NEST_SALT).gen::<f32>() < config.nest_chance
+}
+
+/// Whether the chicks of the tree's nest hatched, before looking for the nest itself
+pub fn hatched(tree: &Tree) -> bool {
+    !tree.is_growing() && rolled(&tree.config) && tree.tended() >= tree.config.hatch_after
+}
+
+/// The nest of a grown tree, if its seed gave it one and it has a fork to hold it
+pub fn find(tree: &Tree) -> Option<Nest> {
+    let config = &tree.config;
+    if tree.is_growing() || !rolled(config) {
+        return None;
+    }
+    let mut rng = ChaCha12Rng::seed_from_u64(config.seed ^ NEST_SALT);
+    // the roll `rolled` took
+    rng.gen::<f32>();
+    let nodes = &tree.nodes;
+    let mut forks = vec![0usize; nodes.len()];
+    for idx in 1..nodes.len() {
+        if let Some(parent) = nodes.parent(idx).filter(|_| nodes.alive()[idx]) {
+            forks[parent] += 1;
+        }
+    }
+    // forks in the middle of the crown, out of reach of the soil and below the twigs
+    let (base, top) = (config.origin.y, tree.top());
+    let candidates = (0..nodes.len())
+        .filter(|&idx| {
+            let (radius, y) = (nodes.radius(idx), nodes.pos()[idx].vec().y);
+            nodes.alive()[idx]
+                && forks[idx] >= 2
+                && radius >= config.leaf_max_width * THINNEST
+                && radius <= config.leaf_max_width * THICKEST
+                && y > base + (top - base) * 0.3
+                && y < base + (top - base) * 0.8
+        })
+        .collect::<Vec<_>>();
+    let &node = candidates.choose(&mut rng)?;
+    let radius = nodes.radius(node);
+    Some(Nest {
+        node,
+        // resting on top of the fork
+        pos: nodes.pos()[node].vec() + Vector2::new(0.0, radius * 0.5),
+        width: radius * 2.0 + config.leaf_size,
+        eggs: rng.gen_range(2..=4),
+        hatched: tree.tended() >= config.hatch_after,
+    })
+}
+
+impl Tree {
+    /// Counts `seconds` more of looking after the grown tree, for hatching its nest
+    pub fn tend(&mut self, seconds: f32) {
+        self.tended += seconds;
+    }
+
+    /// Seconds the grown tree has been looked after
+    pub fn tended(&self) -> f32 {
+        self.tended
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eggs_hatch_once_the_tree_was_looked_after() {
+        let mut tree = Tree::new(Config {
+            seed: 3,
+            num_points: 3000,
+            nest_chance: 1.0,
+            ..Config::default()
+        });
+        tree.grow_fully(&cancel::CancelToken::new());
+        let nest = find(&tree).expect("a grown tree has a fork for the nest");
+        assert!(!nest.hatched && (2..=4).contains(&nest.eggs));
+        tree.tend(tree.config.hatch_after);
+        assert!(hatched(&tree) && find(&tree).unwrap().hatched);
+        tree.config.nest_chance = 0.0;
+        assert_eq!(find(&tree), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! Node storage of a tree as struct of arrays
+//!
+//! The hot loops of the simulation only look at one or two fields of every node, with every field
+//! in its own Vec they run over contiguous memory. Counts and links are stored as `u32`,
+//! which also roughly halves the memory of large trees compared to a Vec of `Node`s.
+
+use crate::*;
+
+/// Stored parent link of the root
+const NO_PARENT: u32 = u32::MAX;
+
+#[derive(Clone, Debug, Default)]
+pub struct Nodes {
+    pos: Vec<Point>,
+    z: Vec<f32>,
+    parent: Vec<u32>,
+    child_count: Vec<u32>,
+    depth: Vec<u32>,
+    weight: Vec<u32>,
+    radius: Vec<f32>,
+    alive: Vec<bool>,
+    /// nodes the weights count, the ones after were added since they were last updated
+    weighed: usize,
+}
+
+impl Nodes {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            pos: Vec::with_capacity(capacity),
+            z: Vec::with_capacity(capacity),
+            parent: Vec::with_capacity(capacity),
+            child_count: Vec::with_capacity(capacity),
+            depth: Vec::with_capacity(capacity),
+            weight: Vec::with_capacity(capacity),
+            radius: Vec::with_capacity(capacity),
+            alive: Vec::with_capacity(capacity),
+            weighed: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos.is_empty()
+    }
+
+    pub fn push(&mut self, node: Node) {
+        self.pos.push(node.pos);
+        self.z.push(node.z);
+        self.parent
+            .push(node.parent.map_or(NO_PARENT, |idx| idx as u32));
+        self.child_count.push(node.child_count as u32);
+        self.depth.push(node.depth as u32);
+        self.weight.push(node.weight as u32);
+        self.radius.push(node.radius);
+        self.alive.push(node.alive);
+    }
+
+    /// All fields of one node
+    pub fn get(&self, idx: usize) -> Node {
+        Node {
+            alive: self.alive[idx],
+            pos: self.pos[idx],
+            parent: self.parent(idx),
+            child_count: self.child_count[idx] as usize,
+            depth: self.depth[idx] as usize,
+            weight: self.weight[idx] as usize,
+            radius: self.radius[idx],
+            z: self.z[idx],
+        }
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Node> + ExactSizeIterator + '_ {
+        (0..self.len()).map(|idx| self.get(idx))
+    }
+
+    pub fn pos(&self) -> &[Point] {
+        &self.pos
+    }
+
+    pub fn z(&self) -> &[f32] {
+        &self.z
+    }
+
+    pub fn alive(&self) -> &[bool] {
+        &self.alive
+    }
+
+    pub fn parent(&self, idx: usize) -> Option<usize> {
+        match self.parent[idx] {
+            NO_PARENT => None,
+            parent => Some(parent as usize),
+        }
+    }
+
+    pub fn child_count(&self, idx: usize) -> usize {
+        self.child_count[idx] as usize
+    }
+
+    pub fn weight(&self, idx: usize) -> usize {
+        self.weight[idx] as usize
+    }
+
+    pub fn radius(&self, idx: usize) -> f32 {
+        self.radius[idx]
+    }
+
+    pub fn kill(&mut self, idx: usize) {
+        self.alive[idx] = false;
+    }
+
+    pub fn add_child(&mut self, idx: usize) {
+        self.child_count[idx] += 1;
+    }
+
+    pub fn set_radius(&mut self, idx: usize, radius: f32) {
+        self.radius[idx] = radius;
+    }
+
+    /// Counts every node and all nodes growing from it, parents always come before their children
+    pub fn recalculate_weight(&mut self) {
+        self.weight.fill(1);
+        for idx in (0..self.len()).rev() {
+            let parent = self.parent[idx];
+            if parent != NO_PARENT {
+                self.weight[parent as usize] += self.weight[idx];
+            }
+        }
+        self.weighed = self.len();
+    }
+
+    /// Like `recalculate_weight`, but only counting the nodes added since the weights were last
+    /// updated into their ancestors. Every node whose weight changed is pushed to `changed`,
+    /// the ancestors once for each new node below them
+    pub fn update_weight(&mut self, changed: &mut Vec<usize>) {
+        for idx in self.weighed..self.len() {
+            self.weight[idx] = 1;
+            changed.push(idx);
+        }
+        for idx in self.weighed..self.len() {
+            let mut parent = self.parent[idx];
+            while parent != NO_PARENT {
+                self.weight[parent as usize] += 1;
+                changed.push(parent as usize);
+                parent = self.parent[parent as usize];
+            }
+        }
+        self.weighed = self.len();
+    }
+
+    /// Sets the depth of every node from its parent's
+    pub fn recalculate_depth(&mut self) {
+        for idx in 0..self.len() {
+            let parent = self.parent[idx];
+            if parent != NO_PARENT {
+                self.depth[idx] = self.depth[parent as usize] + 1;
+            }
+        }
+    }
+}
+
+impl FromIterator<Node> for Nodes {
+    fn from_iter<I: IntoIterator<Item = Node>>(iter: I) -> Self {
+        let mut nodes = Self::default();
+        for node in iter {
+            nodes.push(node);
+        }
+        nodes
+    }
+}
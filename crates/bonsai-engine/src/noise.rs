@@ -0,0 +1,141 @@
+//! The noise the attraction point density is made of
+//!
+//! Several octaves of a noise are layered, each at twice the frequency of the one before.
+//! Simplex gives long veins of points and so open, branching crowns, value noise gives
+//! soft patches and worley noise round clumps, which grow cloud pruned canopies.
+
+use fuss::Simplex;
+
+/// Algorithm behind the noise
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NoiseKind {
+    Simplex,
+    Value,
+    Worley,
+}
+
+impl NoiseKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "simplex" => Some(NoiseKind::Simplex),
+            "value" => Some(NoiseKind::Value),
+            "worley" => Some(NoiseKind::Worley),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NoiseKind::Simplex => "simplex",
+            NoiseKind::Value => "value",
+            NoiseKind::Worley => "worley",
+        }
+    }
+}
+
+/// Which noise the density is made of and how fine it is
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Noise {
+    pub kind: NoiseKind,
+    /// Layers of detail on top of each other
+    pub octaves: usize,
+    /// How much each octave counts relative to the one before
+    pub persistence: f32,
+    /// Frequency of the coarsest octave, in cycles per world unit
+    pub frequency: f32,
+}
+
+impl Default for Noise {
+    fn default() -> Self {
+        Self {
+            kind: NoiseKind::Simplex,
+            octaves: 3,
+            persistence: 0.5,
+            frequency: 0.003,
+        }
+    }
+}
+
+/// A noise set up for one seed
+pub struct NoiseField {
+    noise: Noise,
+    simplex: Simplex,
+    seed: u64,
+}
+
+impl NoiseField {
+    pub fn new(noise: Noise, seed: u64) -> Self {
+        Self {
+            noise,
+            simplex: Simplex::from_seed(vec![(seed >> 32) as usize, seed as u32 as usize]),
+            seed,
+        }
+    }
+
+    /// Density at `x`, `y` between 0 and 1
+    pub fn at(&self, x: f32, y: f32) -> f32 {
+        let Noise {
+            kind,
+            octaves,
+            persistence,
+            frequency,
+        } = self.noise;
+        if kind == NoiseKind::Simplex {
+            return self
+                .simplex
+                .sum_octave_2d(octaves as isize, x, y, persistence, frequency)
+                .abs();
+        }
+        let (mut sum, mut max_amp, mut amp, mut freq) = (0.0, 0.0, 1.0, frequency);
+        for octave in 0..octaves.max(1) as u64 {
+            let seed = self.seed ^ octave.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+            let value = match kind {
+                NoiseKind::Value => value_noise(x * freq, y * freq, seed),
+                _ => worley_noise(x * freq, y * freq, seed),
+            };
+            sum += value * amp;
+            max_amp += amp;
+            amp *= persistence;
+            freq *= 2.0;
+        }
+        sum / max_amp
+    }
+}
+
+/// Random number between 0 and 1 for a lattice point
+fn hash(x: i64, y: i64, seed: u64) -> f32 {
+    let mut h = seed
+        ^ (x as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+        ^ (y as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f);
+    // splitmix64 finalizer
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^= h >> 31;
+    (h >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Random values on the integer lattice, smoothly interpolated in between
+fn value_noise(x: f32, y: f32, seed: u64) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (ix, iy) = (x0 as i64, y0 as i64);
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let (tx, ty) = (smooth(x - x0), smooth(y - y0));
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    let bottom = lerp(hash(ix, iy, seed), hash(ix + 1, iy, seed), tx);
+    let top = lerp(hash(ix, iy + 1, seed), hash(ix + 1, iy + 1, seed), tx);
+    lerp(bottom, top, ty)
+}
+
+/// High close to a random feature point in each lattice cell, fading out to 0 between them
+fn worley_noise(x: f32, y: f32, seed: u64) -> f32 {
+    let (ix, iy) = (x.floor() as i64, y.floor() as i64);
+    let mut nearest = f32::MAX;
+    for cx in ix - 1..=ix + 1 {
+        for cy in iy - 1..=iy + 1 {
+            let fx = cx as f32 + hash(cx, cy, seed);
+            let fy = cy as f32 + hash(cx, cy, seed ^ 0x5555_5555);
+            nearest = nearest.min((fx - x) * (fx - x) + (fy - y) * (fy - y));
+        }
+    }
+    (1.0 - nearest.sqrt()).max(0.0)
+}
@@ -0,0 +1,147 @@
+//! Growing the trees at the same pace however fast the frames are drawn
+//!
+//! The window draws as many frames as `target_fps` and vsync let it, but the trees grow in ticks
+//! of their own, `tick_rate` of them a second. A `Ticker` collects the time the frames took and
+//! hands out the ticks that fit into it, keeping the rest for the next frame, so a tree on a
+//! 144 Hz screen grows as fast as on a 60 Hz one.
+//!
+//! The growth clock can be held, stepped tick by tick while it is, and run faster than the ticks
+//! come in. With `fixed_step` every frame counts as one frame of `target_fps` however long it took,
+//! so a recording of a slow machine grows the trees frame by frame like one of a fast machine.
+
+/// Most ticks a single frame catches up on, at the usual speed. A frame that took longer, like one
+/// waiting for a dragged window, lets the rest of the time go instead of stalling the next ones
+pub const MAX_TICKS_PER_FRAME: u64 = 8;
+
+/// How many times faster the clock runs while fast-forwarding
+pub const FAST_FORWARD: f32 = 8.0;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ticker {
+    /// seconds a tick lasts
+    tick: f32,
+    /// time passed that didn't make up a whole tick yet
+    behind: f32,
+    /// ticks handed out so far
+    count: u64,
+    /// ticks per tick of real time, 1 at the usual pace
+    speed: f32,
+    paused: bool,
+    /// single ticks asked for with `step`, handed out even while paused
+    steps: u64,
+}
+
+impl Ticker {
+    /// Ticks `rate` times a second
+    pub fn new(rate: f32) -> Self {
+        Self {
+            tick: 1.0 / rate.max(1.0),
+            behind: 0.0,
+            count: 0,
+            speed: 1.0,
+            paused: false,
+            steps: 0,
+        }
+    }
+
+    /// Lets `seconds` pass and returns the numbers of the ticks that fit into them
+    pub fn advance(&mut self, seconds: f32) -> std::ops::Range<u64> {
+        let ticks = if self.paused {
+            0
+        } else {
+            let most = MAX_TICKS_PER_FRAME * self.speed.ceil() as u64;
+            self.behind += seconds.max(0.0) * self.speed;
+            let ticks = ((self.behind / self.tick) as u64).min(most);
+            self.behind = if ticks == most {
+                0.0
+            } else {
+                self.behind - ticks as f32 * self.tick
+            };
+            ticks
+        };
+        let from = self.count;
+        self.count += ticks + std::mem::take(&mut self.steps);
+        from..self.count
+    }
+
+    /// Holds the clock, or lets it run on, without catching up on the time it was held
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.behind = 0.0;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Hands out one more tick with the next frame, for stepping through the growth while paused
+    pub fn step(&mut self) {
+        self.steps += 1;
+    }
+
+    /// Runs the clock `speed` times as fast, 1 for the usual pace
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.0);
+    }
+}
+
+/// Frame times of a benchmark run, see `--benchmark`
+#[derive(Clone, Debug, Default)]
+pub struct FrameStats {
+    frames: u64,
+    total: f64,
+    slowest: f32,
+}
+
+impl FrameStats {
+    pub fn record(&mut self, seconds: f32) {
+        self.frames += 1;
+        self.total += seconds as f64;
+        self.slowest = self.slowest.max(seconds);
+    }
+
+    /// Frames, average frames per second and the slowest frame, for the log
+    pub fn summary(&self) -> String {
+        let fps = if self.total > 0.0 {
+            self.frames as f64 / self.total
+        } else {
+            0.0
+        };
+        format!(
+            "{} frames, {:.1} fps on average, slowest frame {:.1} ms",
+            self.frames,
+            fps,
+            self.slowest * 1000.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_dont_depend_on_the_frame_rate() {
+        let ticks = |fps: f32| {
+            let mut ticker = Ticker::new(60.0);
+            let frames = (fps * 10.0) as usize;
+            (0..frames)
+                .map(|_| ticker.advance(1.0 / fps).count())
+                .sum::<usize>()
+        };
+        for fps in [30.0, 60.0, 144.0, 1000.0] {
+            assert!(ticks(fps).abs_diff(600) <= 1, "{} fps", fps);
+        }
+        let mut ticker = Ticker::new(60.0);
+        assert_eq!(ticker.advance(5.0), 0..MAX_TICKS_PER_FRAME);
+        assert_eq!(ticker.advance(0.0).count(), 0);
+
+        ticker.toggle_pause();
+        assert_eq!(ticker.advance(1.0).count(), 0);
+        ticker.step();
+        assert_eq!(ticker.advance(1.0), 8..9);
+        ticker.toggle_pause();
+        ticker.set_speed(FAST_FORWARD);
+        assert_eq!(ticker.advance(0.5).count(), 64);
+    }
+}
@@ -0,0 +1,303 @@
+//! Built in color schemes for the tree and the sky, and palette files of your own
+//!
+//! The default sakura pinks are hard to tell apart from the sky and from each other with
+//! a color vision deficiency. The accessible schemes keep branches, foliage and blossoms
+//! apart by brightness as well as by hue, using colors from the Okabe-Ito set.
+//!
+//! The others set a mood, down to the glaze of the pot, the color the shadows fall into and,
+//! for the Game Boy, the four greens every pixel is dithered to. A palette file does the same
+//! with colors of your own, see `apply_file`.
+
+use crate::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Palette {
+    /// Orange blossoms and blue accents on green, readable with red-green deficiencies
+    OkabeIto,
+    /// Vermillion and teal, readable with blue-yellow deficiencies
+    Tritan,
+    /// Black branches and saturated foliage on white
+    HighContrast,
+    /// Pale and deep pink blossoms on mint, the colors trees grow in by default
+    Sakura,
+    /// Rust, amber and crimson leaves against a hazy cream sky
+    Autumn,
+    /// Dark needles on a cool overcast sky
+    Pine,
+    /// Cyan and magenta glowing against the night
+    Neon,
+    /// The four greens of the original Game Boy and nothing else
+    GameBoy,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 8] = [
+        Palette::OkabeIto,
+        Palette::Tritan,
+        Palette::HighContrast,
+        Palette::Sakura,
+        Palette::Autumn,
+        Palette::Pine,
+        Palette::Neon,
+        Palette::GameBoy,
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|palette| palette.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::OkabeIto => "okabe-ito",
+            Palette::Tritan => "tritan",
+            Palette::HighContrast => "high-contrast",
+            Palette::Sakura => "sakura",
+            Palette::Autumn => "autumn",
+            Palette::Pine => "pine",
+            Palette::Neon => "neon",
+            Palette::GameBoy => "gameboy",
+        }
+    }
+
+    pub fn colors(self) -> ColorPalette {
+        let (leaf, new_branch, old_branch) = match self {
+            Palette::OkabeIto => ("009e73", "009e73", "3b2a20"),
+            Palette::Tritan => ("1b7f7a", "1b7f7a", "3a2a28"),
+            Palette::HighContrast => ("0057b8", "000000", "000000"),
+            Palette::Sakura => ("ffe0e0", "7a5a4a", "8b6354"),
+            Palette::Autumn => ("d2691e", "7a4a30", "5a3a28"),
+            Palette::Pine => ("2f5d3a", "5e6b3a", "4a3a2c"),
+            Palette::Neon => ("00f0ff", "ff2bd6", "2a1a3a"),
+            Palette::GameBoy => ("8bac0f", "306230", "0f380f"),
+        };
+        ColorPalette {
+            leaf: Color::from_hex(leaf).unwrap(),
+            new_branch: Color::from_hex(new_branch).unwrap(),
+            old_branch: Color::from_hex(old_branch).unwrap(),
+        }
+    }
+
+    pub fn leaves(self) -> Vec<LeafType> {
+        let leaf = |hex: &str, probability: f32, size: f32| LeafType {
+            color: Color::from_hex(hex).unwrap(),
+            probability,
+            size,
+        };
+        match self {
+            Palette::OkabeIto => vec![leaf("e69f00", 0.7, 2.5), leaf("0072b2", 0.3, 1.0)],
+            Palette::Tritan => vec![leaf("d55e00", 0.7, 2.5), leaf("1b7f7a", 0.3, 1.0)],
+            Palette::HighContrast => vec![leaf("ffb000", 0.7, 2.5), leaf("0057b8", 0.3, 1.0)],
+            Palette::Sakura => vec![leaf("ffe0e0", 0.8, 2.5), leaf("ff5173", 0.2, 1.0)],
+            Palette::Autumn => vec![
+                leaf("d2691e", 0.5, 2.5),
+                leaf("e8a33c", 0.3, 2.0),
+                leaf("9c2f1c", 0.2, 1.5),
+            ],
+            Palette::Pine => vec![
+                leaf("2f5d3a", 0.6, 2.5),
+                leaf("3f7a4a", 0.3, 2.0),
+                leaf("6b8f5a", 0.1, 1.0),
+            ],
+            Palette::Neon => vec![
+                leaf("00f0ff", 0.6, 2.5),
+                leaf("ff2bd6", 0.3, 1.5),
+                leaf("fff200", 0.1, 1.0),
+            ],
+            Palette::GameBoy => vec![leaf("8bac0f", 0.8, 2.5), leaf("306230", 0.2, 1.0)],
+        }
+    }
+
+    pub fn sky(self) -> Color {
+        let hex = match self {
+            Palette::OkabeIto => "eef5f9",
+            Palette::Tritan => "f6efe9",
+            Palette::HighContrast => "ffffff",
+            Palette::Sakura => "cff7e5",
+            Palette::Autumn => "f6e7c8",
+            Palette::Pine => "dfe9ee",
+            Palette::Neon => "0d0221",
+            Palette::GameBoy => "9bbc0f",
+        };
+        Color::from_hex(hex).unwrap()
+    }
+
+    /// Glaze of the pot, `None` for the accessible schemes, whose pots match the bark instead
+    pub fn pot(self) -> Option<Color> {
+        let hex = match self {
+            Palette::OkabeIto | Palette::Tritan | Palette::HighContrast => return None,
+            Palette::Sakura => "5a6b7a",
+            Palette::Autumn => "7a4f3a",
+            Palette::Pine => "4d5560",
+            Palette::Neon => "261447",
+            Palette::GameBoy => "306230",
+        };
+        Some(Color::from_hex(hex).unwrap())
+    }
+
+    /// Color the shadows fall into, see `Config::shadow_tint`
+    pub fn shadow(self) -> Color {
+        let hex = match self {
+            Palette::OkabeIto | Palette::Tritan | Palette::HighContrast => "000000",
+            Palette::Sakura => "3a2a4a",
+            Palette::Autumn => "3a1f2a",
+            Palette::Pine => "1c2a33",
+            Palette::Neon => "2d0b5a",
+            Palette::GameBoy => "0f380f",
+        };
+        Color::from_hex(hex).unwrap()
+    }
+
+    /// Colors of the nearest hills, the grass and the soil of the scenery, see `scenery`
+    pub fn scenery(self) -> [Color; 3] {
+        let hexes = match self {
+            Palette::OkabeIto => ["56b4e9", "009e73", "3b2a20"],
+            Palette::Tritan => ["8fb8b4", "1b7f7a", "3a2a28"],
+            Palette::HighContrast => ["c8c8c8", "5a5a5a", "000000"],
+            Palette::Sakura => ["a6dcc3", "7fb77e", "6b4f44"],
+            Palette::Autumn => ["d9b98a", "a38a3c", "5a3a28"],
+            Palette::Pine => ["9fb3bd", "4a6b3a", "3a2e26"],
+            Palette::Neon => ["3a1a6a", "00f0ff", "1a0a2e"],
+            Palette::GameBoy => ["8bac0f", "306230", "0f380f"],
+        };
+        hexes.map(|hex| Color::from_hex(hex).unwrap())
+    }
+
+    /// Colors every pixel is dithered to, see `Config::ramp`
+    pub fn ramp(self) -> Vec<Color> {
+        match self {
+            Palette::GameBoy => ["0f380f", "306230", "8bac0f", "9bbc0f"]
+                .iter()
+                .map(|hex| Color::from_hex(hex).unwrap())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    pub fn apply(self, config: &mut Config) {
+        config.colors = self.colors();
+        config.leaves = self.leaves();
+        config.sky = self.sky();
+        config.shadow_tint = self.shadow();
+        config.ramp = self.ramp();
+        if let (Some(pot), Some(glaze)) = (&mut config.pot, self.pot()) {
+            pot.color = glaze;
+            pot.decorated = false;
+        }
+        if let Some(scenery) = &mut config.scenery {
+            [scenery.hill_color, scenery.grass, scenery.soil] = self.scenery();
+        }
+    }
+}
+
+/// Colors the config from the lines of a palette file, each a role followed by its colors in hex:
+///
+/// ```text
+/// # sakura at dusk
+/// sky     2b2340
+/// bark    4a3040
+/// twig    6a4050
+/// leaves  ffb7c5 ff8fab
+/// pot     3a3a5a
+/// shadow  20183a
+/// hills   5a4a7a
+/// grass   3a5a4a
+/// soil    2a2030
+/// ramp    1a1c2c 5d275d b13e53 ef7d57 ffcd75
+/// ```
+///
+/// Lines with only a color add it to the ramp, so a palette downloaded as a list of hex colors
+/// dithers the tree to its colors. Roles left out keep what the config has, as do the pot and the
+/// scenery roles without a pot or scenery in the config. The leaves share the canopy evenly and
+/// keep the sizes the kinds had. Lines starting with `#` that aren't a color are comments
+pub fn apply_file(text: &str, config: &mut Config) -> Result<(), String> {
+    // six digits, so a word like `bead` isn't taken for a color
+    let parse = |hex: &str| {
+        let digits = hex.trim_start_matches('#');
+        Some(digits)
+            .filter(|digits| digits.len() == 6)
+            .and_then(|digits| Color::from_hex(digits).ok())
+            .ok_or_else(|| format!("invalid color {}", hex))
+    };
+    let mut ramp = vec![];
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if let Ok(color) = parse(line) {
+            ramp.push(color);
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let role = words.next().unwrap_or_default();
+        let colors = words.map(parse).collect::<Result<Vec<_>, _>>()?;
+        let Some(&first) = colors.first() else {
+            return Err(format!("no colors for {}", role));
+        };
+        match role {
+            "sky" => config.sky = first,
+            "bark" => config.colors.old_branch = first,
+            "twig" => config.colors.new_branch = first,
+            "leaves" => {
+                let probability = 1.0 / colors.len() as f32;
+                config.colors.leaf = first;
+                config.leaves = colors
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, color)| LeafType {
+                        color: *color,
+                        probability,
+                        size: config.leaves.get(idx).map_or(1.0, |leaf| leaf.size),
+                    })
+                    .collect();
+            }
+            "pot" => {
+                if let Some(pot) = &mut config.pot {
+                    pot.color = first;
+                    pot.decorated = false;
+                }
+            }
+            "shadow" => config.shadow_tint = first,
+            "hills" | "grass" | "soil" => {
+                if let Some(scenery) = &mut config.scenery {
+                    match role {
+                        "hills" => scenery.hill_color = first,
+                        "grass" => scenery.grass = first,
+                        _ => scenery.soil = first,
+                    }
+                }
+            }
+            "ramp" => ramp.extend(colors),
+            _ => {
+                return Err(format!(
+                    "unknown role {}, expected sky, bark, twig, leaves, pot, shadow, hills, grass, soil or ramp",
+                    role
+                ))
+            }
+        }
+    }
+    if !ramp.is_empty() {
+        config.ramp = ramp;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palette_files_color_the_config() {
+        let mut config = Config::default();
+        let file =
+            "# dusk\nsky 2b2340\nleaves ffb7c5 #ff8fab\nshadow 20183a\n1a1c2c\nramp 5d275d\n";
+        apply_file(file, &mut config).unwrap();
+        assert_eq!(config.sky, Color::from_hex("2b2340").unwrap());
+        assert_eq!(config.leaves.len(), 2);
+        assert_eq!(config.leaves[1].color, Color::from_hex("ff8fab").unwrap());
+        assert_eq!(config.ramp.len(), 2);
+        assert!(apply_file("leafs 00ff00", &mut config).is_err());
+        for palette in Palette::ALL {
+            assert_eq!(Palette::parse(palette.name()), Some(palette));
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! Spreading the growth of large trees over all cores, with the `parallel` feature
+//!
+//! Only the part every tip works out on its own is spread: where it grows to. Everything
+//! depending on the order, like the randomness of the new nodes, stays on one thread.
+
+use std::thread;
+
+/// Fewer nodes than this are quicker on one thread than spread out
+pub(crate) const MIN_ITEMS: usize = 2048;
+
+/// `f` of every index below `count` in order, each thread handed a scratch buffer of its own
+pub(crate) fn map<T: Send>(count: usize, f: impl Fn(usize, &mut Vec<usize>) -> T + Sync) -> Vec<T> {
+    let threads = thread::available_parallelism()
+        .map_or(1, |threads| threads.get())
+        .clamp(1, count.max(1));
+    let chunk = count.div_ceil(threads);
+    let f = &f;
+    thread::scope(|scope| {
+        let chunks = (0..threads)
+            .map(|thread| {
+                let range = thread * chunk..((thread + 1) * chunk).min(count);
+                scope.spawn(move || {
+                    let mut scratch = vec![];
+                    range.map(|idx| f(idx, &mut scratch)).collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        chunks
+            .into_iter()
+            .flat_map(|chunk| chunk.join().unwrap())
+            .collect()
+    })
+}
@@ -0,0 +1,83 @@
+//! Positions the growth runs on, in `f64` with the `f64` feature
+//!
+//! Far from the origin `f32` positions get so coarse that the small growth steps round visibly.
+//! The renderer stays in `f32`, it only sees where the nodes ended up.
+
+use std::ops::{Add, Mul, Sub};
+
+use crate::math::Vector2;
+
+#[cfg(not(feature = "f64"))]
+pub type Real = f32;
+#[cfg(feature = "f64")]
+pub type Real = f64;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Point {
+    pub x: Real,
+    pub y: Real,
+}
+
+impl Point {
+    pub const fn new(x: Real, y: Real) -> Self {
+        Self { x, y }
+    }
+
+    pub const fn zero() -> Self {
+        Self::new(0.0, 0.0)
+    }
+
+    pub fn length_sqr(self) -> Real {
+        self.x * self.x + self.y * self.y
+    }
+
+    pub fn length(self) -> Real {
+        self.length_sqr().sqrt()
+    }
+
+    pub fn normalized(self) -> Self {
+        let length_sqr = self.length_sqr();
+        if length_sqr == 0.0 {
+            return self;
+        }
+        let length = length_sqr.sqrt();
+        Self::new(self.x / length, self.y / length)
+    }
+
+    pub fn lerp(self, other: Self, t: Real) -> Self {
+        self + (other - self) * t
+    }
+
+    /// The position for rendering
+    #[allow(clippy::unnecessary_cast)]
+    pub fn vec(self) -> Vector2 {
+        Vector2::new(self.x as f32, self.y as f32)
+    }
+}
+
+impl From<Vector2> for Point {
+    fn from(v: Vector2) -> Self {
+        Self::new(Real::from(v.x), Real::from(v.y))
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<Real> for Point {
+    type Output = Self;
+    fn mul(self, factor: Real) -> Self {
+        Self::new(self.x * factor, self.y * factor)
+    }
+}
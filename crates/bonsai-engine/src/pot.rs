@@ -0,0 +1,141 @@
+//! The pot the tree stands in, seen in a 3/4 view from slightly above
+//!
+//! Only its shape lives here, `bonsai_render::pot` draws it.
+
+use crate::*;
+
+/// Built in kinds of pot, like the ones cbonsai draws under its trees
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum Shape {
+    /// round and moderately deep, the all-rounder
+    #[default]
+    Oval,
+    /// straight sides and corners, for formal upright trees
+    Rectangle,
+    /// sides bulging out between rim and foot
+    Drum,
+    /// narrow and tall, so a cascading tree can hang below the rim
+    Cascade,
+    /// wide and flat, for forests and landscapes
+    Tray,
+}
+
+impl Shape {
+    pub const ALL: [Shape; 5] = [
+        Shape::Oval,
+        Shape::Rectangle,
+        Shape::Drum,
+        Shape::Cascade,
+        Shape::Tray,
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|shape| shape.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Shape::Oval => "oval",
+            Shape::Rectangle => "rectangle",
+            Shape::Drum => "drum",
+            Shape::Cascade => "cascade",
+            Shape::Tray => "tray",
+        }
+    }
+
+    /// Exponent of the superellipse outlining the rim, 2 is an ellipse, higher gets boxier
+    pub fn corners(self) -> f32 {
+        match self {
+            Shape::Rectangle | Shape::Tray => 8.0,
+            _ => 2.0,
+        }
+    }
+
+    /// Width of the side `down` from the rim (0) to the foot (1) relative to the rim,
+    /// for a pot whose foot is `taper` as wide as the rim
+    pub fn profile(self, taper: f32, down: f32) -> f32 {
+        let narrowing = 1.0 - (1.0 - taper) * down;
+        match self {
+            Shape::Drum => narrowing + 0.12 * (down * std::f32::consts::PI).sin(),
+            _ => narrowing,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Pot {
+    /// half the width of the rim, in world units
+    pub radius: f32,
+    /// height of the side, in world units
+    pub height: f32,
+    /// width of the bottom relative to the rim
+    pub taper: f32,
+    /// how far the camera looks down onto the pot, in degrees: 0 is side-on, 90 straight down
+    pub tilt: f32,
+    pub color: Color,
+    pub soil: Color,
+    /// glaze, pattern and stamp picked from the seed of the tree instead of the plain `color`
+    pub decorated: bool,
+    pub shape: Shape,
+}
+
+impl Default for Pot {
+    fn default() -> Self {
+        Self {
+            radius: 90.0,
+            height: 50.0,
+            taper: 0.8,
+            tilt: 25.0,
+            color: Color::from_hex("6f8a9e").unwrap(),
+            soil: Color::from_hex("4a3528").unwrap(),
+            decorated: true,
+            shape: Shape::Oval,
+        }
+    }
+}
+
+impl Pot {
+    /// A pot of `shape` in its usual proportions
+    pub fn of_shape(shape: Shape) -> Self {
+        let (radius, height, taper) = match shape {
+            Shape::Oval => (90.0, 50.0, 0.8),
+            Shape::Rectangle => (95.0, 45.0, 0.9),
+            Shape::Drum => (80.0, 60.0, 0.9),
+            Shape::Cascade => (55.0, 110.0, 0.85),
+            Shape::Tray => (115.0, 18.0, 0.92),
+        };
+        Self {
+            radius,
+            height,
+            taper,
+            shape,
+            ..Self::default()
+        }
+    }
+
+    /// A plain pot glazed in a muted shade of the bark, to match the colors of the tree
+    pub fn colored_from(mut self, colors: &ColorPalette) -> Self {
+        let clay = Color::from_hex("9a8f86").unwrap();
+        self.color = colors.old_branch.lerp(clay, 0.6);
+        self.decorated = false;
+        self
+    }
+
+    /// How far the pot reaches below the foot of the trunk, in world units
+    pub fn reach_below(&self) -> f32 {
+        let tilt = self.tilt.to_radians();
+        // the front of the side at each height, a bulging side may reach lowest above the foot
+        (0..=16)
+            .map(|step| {
+                let down = step as f32 / 16.0;
+                self.height * tilt.cos() * down
+                    + self.radius * self.shape.profile(self.taper, down) * tilt.sin()
+            })
+            .fold(0.0, f32::max)
+    }
+
+    /// Left and right end of the rim and its height, in world units
+    pub fn rim(&self, foot: Vector2) -> (f32, f32, f32) {
+        (foot.x - self.radius, foot.x + self.radius, foot.y)
+    }
+}
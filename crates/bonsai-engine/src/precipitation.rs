@@ -0,0 +1,28 @@
+//! Snow and rain falling over the tree in the window
+//!
+//! Only the kinds live here, `bonsai_render::precipitation` lets the drops fall, blown aslant by
+//! the wind and a passing storm, and the snow settle on whatever faces up, melting away again
+//! over `snow_melt` seconds. The seasons turn rain into snow in winter and back, see `Season::apply`.
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Precipitation {
+    Snow,
+    Rain,
+}
+
+impl Precipitation {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "snow" => Some(Self::Snow),
+            "rain" => Some(Self::Rain),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Snow => "snow",
+            Self::Rain => "rain",
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Roots spreading into the soil below the trunk, so the tree stands on the ground
+//! instead of floating above it
+//!
+//! The roots grow like a tree of their own, upside down: their world is the tree's mirrored
+//! at the origin, so "up" leads into the soil and `min_y_growth` keeps them from surfacing.
+//! Their attraction points fill a half ellipse below the trunk, `Tree::roots` holds them and
+//! `planted` turns them the right way up for drawing.
+
+use std::sync::Arc;
+
+use crate::*;
+use density::{DensityMap, DensitySource};
+
+/// World units per value of the mask shaping the root ball
+const MASK_CELL: usize = 4;
+/// Nodes the roots grow before they count as sprouted
+const SPROUT_STEPS: usize = 20;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Roots {
+    /// how far the roots reach below the origin, in world units
+    pub depth: f32,
+    /// width of the root ball, in world units
+    pub spread: f32,
+    /// bark of the roots, darker than the trunk when not set
+    pub color: Option<Color>,
+    /// the line of soil drawn over the root collar when the tree has no pot
+    pub soil: Color,
+}
+
+impl Default for Roots {
+    fn default() -> Self {
+        Self {
+            depth: 60.0,
+            spread: 220.0,
+            color: None,
+            soil: Color::from_hex("5a4636").unwrap(),
+        }
+    }
+}
+
+impl Roots {
+    /// Color of the roots under a tree with `colors`
+    pub fn bark(&self, colors: &ColorPalette) -> Color {
+        self.color
+            .unwrap_or_else(|| colors.old_branch.lerp(Color::BLACK, 0.35))
+    }
+
+    /// The config the roots of a tree grown with `config` grow with, in the mirrored world:
+    /// shorter steps, fewer points and no leaves
+    pub fn growth_config(&self, config: &Config) -> Config {
+        let bark = self.bark(&config.colors);
+        Config {
+            seed: config.seed ^ 0x726f_6f74,
+            num_points: (config.num_points / 8).max(1),
+            grow_dist: config.grow_dist * 0.6,
+            kill_dist: config.kill_dist * 0.8,
+            node_min_dist: config.node_min_dist * 0.6,
+            // roots wind sideways and back up, the points alone keep them in the soil
+            min_y_growth: -config.grow_dist,
+            // the mask alone decides where the points go
+            falloff: falloff::Falloff::Flat,
+            density: DensitySource::Mask(Arc::new(self.mask(config))),
+            leaf_max_width: 0.0,
+            foliage: 0.0,
+            colors: ColorPalette {
+                leaf: bark,
+                new_branch: bark,
+                old_branch: bark,
+            },
+            color_rules: vec![],
+            pot: None,
+            kusamono: None,
+            ivy: None,
+            nest_chance: 0.0,
+            tokonoma: None,
+            background: None,
+            roots: None,
+            volume: None,
+            ..config.clone()
+        }
+    }
+
+    /// The roots of a tree grown with `config`, a few steps into their growth.
+    /// Like a tree, they sometimes get stuck right away and start over from another seed
+    pub fn sprout(&self, config: &Config) -> Tree {
+        Tree::new_min_growth(self.growth_config(config), SPROUT_STEPS)
+    }
+
+    /// The half ellipse the roots fill, above the origin in the mirrored world
+    fn mask(&self, config: &Config) -> DensityMap {
+        let (width, height) = (
+            (config.width as usize / MASK_CELL).max(1),
+            (config.height as usize / MASK_CELL).max(1),
+        );
+        let origin = config.origin / MASK_CELL as f32;
+        let (reach_x, reach_y) = (
+            self.spread / 2.0 / MASK_CELL as f32,
+            self.depth / MASK_CELL as f32,
+        );
+        let values = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x as f32, y as f32)))
+            .map(|(x, y)| {
+                let (dx, dy) = ((x - origin.x) / reach_x, (y - origin.y) / reach_y);
+                // thinning out towards the tips, the ball is densest under the trunk
+                (1.0 - dx * dx - dy * dy).max(0.0) * (dy >= 0.0) as u8 as f32
+            })
+            .collect();
+        DensityMap::new(width, height, values).expect("one value per cell")
+    }
+}
+
+/// The roots the right way up below the tree's origin, for drawing
+pub fn planted(roots: &Tree) -> Tree {
+    let mirror = Real::from(roots.config.origin.y * 2.0);
+    let nodes = roots
+        .nodes()
+        .map(|mut node| {
+            node.pos.y = mirror - node.pos.y;
+            node
+        })
+        .collect();
+    Tree::from_parts(roots.config.clone(), nodes, None)
+}
@@ -0,0 +1,272 @@
+//! Several trees standing side by side in one world, a small grove like a forest planting
+//!
+//! Every tree grows in a world like that of a tree of its own, with the same parameters, and is
+//! scaled down into the scene's world where it stands: branches, twigs and all, so some trees
+//! of the grove stay smaller than others. The trees take turns growing a step. Whatever a tree's
+//! new branches reach is gone for the others too: the attraction points of every other tree
+//! within its `kill_dist` of them get removed, so crowns meet without growing through each
+//! other. Trees with a volume compete on the picture plane only.
+//!
+//! `Scene::merged` puts the grown trees together as one tree of the scene's world, so they get
+//! painted into one canvas and shade each other like the branches of a single tree.
+
+use crate::*;
+use grid::Grid;
+
+/// Smallest scale a tree of the grove grows at, relative to the largest
+const MIN_SCALE: f32 = 0.6;
+
+/// Width of a full scale tree's world, in the distances between the feet of the trees.
+/// The crown fills about its middle two thirds, so neighbouring crowns overlap
+const OVERLAP: f32 = 5.0;
+
+/// Share of the world's width the feet of the trees are spread over
+const SPREAD: f32 = 0.7;
+
+/// Where a tree's world lies in the scene's world
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Placement {
+    /// scene position of the bottom left corner of the tree's world
+    pub offset: Vector2,
+    /// scene units per unit of the tree's world
+    pub scale: f32,
+}
+
+impl Placement {
+    pub fn to_scene(&self, pos: Point) -> Point {
+        pos * Real::from(self.scale) + Point::from(self.offset)
+    }
+
+    pub fn to_tree(&self, pos: Point) -> Point {
+        (pos - Point::from(self.offset)) * Real::from(1.0 / self.scale)
+    }
+}
+
+#[derive(Clone)]
+pub struct Scene {
+    /// the world every tree stands in, with the pot, light and colors of the whole scene
+    pub config: Config,
+    pub trees: Vec<Tree>,
+    pub placements: Vec<Placement>,
+    /// nodes of each tree the others already gave up their points to,
+    /// `usize::MAX` once it stopped growing and all of them were
+    yielded: Vec<usize>,
+    /// positions of new nodes in the scene's world, reused by every step
+    reached: Vec<Point>,
+}
+
+impl Scene {
+    /// The configs of a grove of `count` trees standing in the world of `config`, with where
+    /// each of them is placed. Each tree grows from its own seed, counting up from the scene's
+    pub fn layout(config: &Config, count: usize) -> Vec<(Config, Placement)> {
+        let count = count.max(1);
+        let mut rng = ChaCha12Rng::seed_from_u64(config.seed ^ 0x666f_7265_7374);
+        let spacing = config.width * SPREAD / count as f32;
+        let largest = (spacing * OVERLAP / config.width.min(config.height)).min(1.0);
+        (0..count)
+            .map(|idx| {
+                let scale = largest * (MIN_SCALE + rng.gen::<f32>() * (1.0 - MIN_SCALE));
+                let foot = Vector2::new(
+                    config.width * (1.0 - SPREAD) / 2.0 + spacing * (idx as f32 + 0.5),
+                    config.origin.y,
+                );
+                let tree = Config {
+                    seed: config.seed.wrapping_add(idx as u64),
+                    // a merged grove grows the roots of the scene's config, below its middle
+                    roots: None,
+                    forest: None,
+                    ..config.clone()
+                };
+                let offset = foot - config.origin * scale;
+                (tree, Placement { offset, scale })
+            })
+            .collect()
+    }
+
+    /// Plants a grove of `count` trees in the world of `config`, nothing has grown yet
+    pub fn forest(config: &Config, count: usize) -> Self {
+        let (trees, placements) = Self::layout(config, count)
+            .into_iter()
+            .map(|(config, placement)| (Tree::new(config), placement))
+            .unzip();
+        Self::of(config.clone(), trees, placements)
+    }
+
+    /// Like `forest`, with every tree grown like `Tree::new_min_growth_with`, calling `step`
+    /// the same way. Gives up with `None` once it returns false
+    pub fn forest_with(
+        config: &Config,
+        count: usize,
+        iter: usize,
+        mut step: impl FnMut() -> bool,
+    ) -> Option<Self> {
+        let mut trees = vec![];
+        let mut placements = vec![];
+        for (config, placement) in Self::layout(config, count) {
+            trees.push(Tree::new_min_growth_with(config, iter, &mut step)?);
+            placements.push(placement);
+        }
+        Some(Self::of(config.clone(), trees, placements))
+    }
+
+    fn of(config: Config, trees: Vec<Tree>, placements: Vec<Placement>) -> Self {
+        let mut scene = Self {
+            config,
+            yielded: vec![0; trees.len()],
+            trees,
+            placements,
+            reached: vec![],
+        };
+        for idx in 0..scene.trees.len() {
+            scene.yield_points(idx);
+        }
+        scene
+    }
+
+    /// Whether any tree of the grove is still growing
+    pub fn is_growing(&self) -> bool {
+        self.trees.iter().any(Tree::is_growing)
+    }
+
+    /// Grows every tree a step, in turns
+    pub fn step(&mut self) {
+        for idx in 0..self.trees.len() {
+            self.trees[idx].step();
+            self.yield_points(idx);
+        }
+    }
+
+    pub fn grow_fully(&mut self, cancel: &cancel::CancelToken) {
+        for _ in 0..10_000 {
+            if !self.is_growing() || cancel.is_cancelled() {
+                break;
+            }
+            self.step();
+        }
+    }
+
+    /// Removes the points of the other trees within reach of the nodes tree `idx` added since
+    /// it last gave them up
+    fn yield_points(&mut self, idx: usize) {
+        let tree = &self.trees[idx];
+        let placement = self.placements[idx];
+        let nodes = tree.nodes.pos();
+        let from = match self.yielded[idx] {
+            usize::MAX => return,
+            // growth that stopped subdivides the segments, which renumbers the nodes
+            _ if !tree.growing => 0,
+            yielded => yielded,
+        };
+        self.yielded[idx] = if tree.growing {
+            nodes.len()
+        } else {
+            usize::MAX
+        };
+        self.reached.clear();
+        self.reached
+            .extend(nodes[from..].iter().map(|&pos| placement.to_scene(pos)));
+        if self.reached.is_empty() {
+            return;
+        }
+        for (other_idx, other) in self.trees.iter_mut().enumerate() {
+            if other_idx == idx {
+                continue;
+            }
+            let placement = self.placements[other_idx];
+            // compared in the scene's world, where the other tree is scaled too
+            let kill_dist = Real::from(other.config.kill_dist * placement.scale);
+            let mut grid = Grid::default();
+            grid.reset(kill_dist);
+            grid.extend(&self.reached);
+            let reached = &self.reached;
+            other.points.retain(|&p| {
+                let p = placement.to_scene(p);
+                !grid
+                    .near(p, kill_dist)
+                    .any(|idx| (p - reached[idx]).length_sqr() < kill_dist * kill_dist)
+            });
+        }
+    }
+
+    /// The grove as one tree of the scene's world, each tree's nodes after those of the one
+    /// before it. The roots of all trees have no parent, not just the first node
+    pub fn merged(&self) -> Tree {
+        let leaf_max_width = self.config.leaf_max_width;
+        let mut nodes = vec![];
+        for (tree, placement) in self.trees.iter().zip(&self.placements) {
+            let base = nodes.len();
+            nodes.extend(tree.nodes().map(|node| Node {
+                pos: placement.to_scene(node.pos),
+                parent: node.parent.map(|parent| parent + base),
+                // twigs stay as thick as the thinnest branch drawn as wood, so every
+                // tree keeps its leaves where it had them
+                radius: match node.radius * placement.scale {
+                    radius if node.radius < leaf_max_width => radius,
+                    radius => radius.max(leaf_max_width),
+                },
+                z: node.z * placement.scale,
+                ..node
+            }));
+        }
+        // the foliage shrinks with the trees, as far as one size fits them all
+        let scale = self
+            .placements
+            .iter()
+            .map(|placement| placement.scale)
+            .sum::<f32>()
+            / self.placements.len().max(1) as f32;
+        let mut config = self.config.clone();
+        config.leaf_size *= scale;
+        for leaf in &mut config.leaves {
+            leaf.size = (leaf.size * scale).max(1.0);
+        }
+        Tree::from_parts(config, nodes, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trees_dont_keep_points_the_others_reached() {
+        let config = Config {
+            width: 300.0,
+            height: 200.0,
+            origin: Vector2::new(150.0, 10.0),
+            num_points: 3000,
+            ..Config::default()
+        };
+        let mut scene = Scene::forest_with(&config, 3, 5, || true).unwrap();
+        scene.grow_fully(&cancel::CancelToken::new());
+        for (idx, tree) in scene.trees.iter().enumerate() {
+            let reached = tree
+                .nodes
+                .pos()
+                .iter()
+                .map(|&pos| scene.placements[idx].to_scene(pos))
+                .collect::<Vec<_>>();
+            for (other_idx, other) in scene.trees.iter().enumerate() {
+                if idx == other_idx {
+                    continue;
+                }
+                let placement = scene.placements[other_idx];
+                let kill_dist = Real::from(other.config.kill_dist * placement.scale);
+                for &p in &other.points {
+                    let p = placement.to_scene(p);
+                    assert!(reached
+                        .iter()
+                        .all(|&pos| (p - pos).length_sqr() >= kill_dist * kill_dist));
+                }
+            }
+        }
+        let merged = scene.merged();
+        let count = scene.trees.iter().map(Tree::node_count).sum::<usize>();
+        assert!(count > 3 * 5);
+        assert_eq!(merged.node_count(), count);
+        assert_eq!(
+            merged.nodes().filter(|node| node.parent.is_none()).count(),
+            3
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! A landscape behind the tree, so a wallpaper doesn't float on a flat sky
+//!
+//! The sky deepens towards the top and pales towards the horizon around the config's sky color,
+//! the sun, or the moon at night, stands where the light comes from, hills from the seed roll
+//! along the horizon, the farther ones fading into the sky, and the tree stands on a strip of
+//! grass over soil. Drawn behind everything by `bonsai_render::scenery`, in the colors of the
+//! palette, see `Palette::scenery`.
+
+use crate::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Scenery {
+    /// how much the sky darkens towards the top and pales towards the horizon, 0 keeps it flat
+    pub gradient: f32,
+    /// show the sun, or the moon at night, in the direction the light comes from
+    pub sun: bool,
+    /// ranges of hills behind each other, 0 for an open horizon
+    pub hills: usize,
+    /// height of the nearest hills above the ground, in world units
+    pub hill_height: f32,
+    /// depth of the ground in front of the tree's foot, in world units
+    pub ground: f32,
+    /// of the nearest hills, the farther ones fade into the sky
+    pub hill_color: Color,
+    pub grass: Color,
+    pub soil: Color,
+}
+
+impl Default for Scenery {
+    fn default() -> Self {
+        let [hill_color, grass, soil] = palette::Palette::Sakura.scenery();
+        Self {
+            gradient: 0.25,
+            sun: true,
+            hills: 3,
+            hill_height: 60.0,
+            ground: 30.0,
+            hill_color,
+            grass,
+            soil,
+        }
+    }
+}
+
+impl Scenery {
+    /// Sets the scenery up in the config, with the tree standing high enough for the ground
+    /// to show in front of it
+    pub fn apply(self, config: &mut Config) {
+        let below = config.pot.map_or(0.0, |pot| pot.reach_below());
+        config.origin.y = config.origin.y.max(below + self.ground);
+        config.scenery = Some(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_ground_shows_and_the_palette_colors_the_land() {
+        let mut config = Config {
+            pot: Some(pot::Pot::default()),
+            ..Config::default()
+        };
+        Scenery::default().apply(&mut config);
+        let scenery = config.scenery.unwrap();
+        assert!(floor(&config) >= scenery.ground - 0.01);
+        palette::Palette::GameBoy.apply(&mut config);
+        let scenery = config.scenery.unwrap();
+        assert!(config.ramp.contains(&scenery.grass) && config.ramp.contains(&scenery.soil));
+        palette::apply_file("hills 123456", &mut config).unwrap();
+        assert_eq!(
+            config.scenery.unwrap().hill_color,
+            Color::from_hex("123456").unwrap()
+        );
+    }
+}
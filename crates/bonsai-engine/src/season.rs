@@ -0,0 +1,211 @@
+//! Seasons, picking them from the calendar and letting them pass one after another
+//!
+//! A `SeasonCycle` moves through the year in the window: spring blossoms come out,
+//! summer turns them green, autumn colors them orange and sheds them and winter stays bare.
+//! Over the last quarter of each season its colors blend into the next one's.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::*;
+use precipitation::Precipitation;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+/// Today's date in UTC as (year, month, day), months and days starting at 1
+pub fn today() -> (i64, u32, u32) {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    civil_from_days((secs / 86_400) as i64)
+}
+
+/// Converts days since 1970-01-01 to a date in the proleptic gregorian calendar
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+impl Season {
+    /// Meteorological season of the month on the northern hemisphere
+    pub fn from_month(month: u32) -> Self {
+        match month {
+            3..=5 => Season::Spring,
+            6..=8 => Season::Summer,
+            9..=11 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    pub fn current() -> Self {
+        Self::from_month(today().1)
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "spring" => Some(Season::Spring),
+            "summer" => Some(Season::Summer),
+            "autumn" | "fall" => Some(Season::Autumn),
+            "winter" => Some(Season::Winter),
+            _ => None,
+        }
+    }
+
+    /// The season after this one
+    pub fn next(self) -> Self {
+        match self {
+            Season::Spring => Season::Summer,
+            Season::Summer => Season::Autumn,
+            Season::Autumn => Season::Winter,
+            Season::Winter => Season::Spring,
+        }
+    }
+
+    /// Foliage of the season: sakura in spring, green in summer, orange in autumn and snow in winter
+    pub fn leaves(self) -> Vec<LeafType> {
+        let leaf = |hex: &str, probability: f32, size: f32| LeafType {
+            color: Color::from_hex(hex).unwrap(),
+            probability,
+            size,
+        };
+        match self {
+            Season::Spring => vec![leaf("ffe0e0", 0.8, 2.5), leaf("FF5173", 0.2, 1.0)],
+            Season::Summer => vec![leaf("5d9e3f", 0.6, 2.5), leaf("3e7a35", 0.4, 2.0)],
+            Season::Autumn => vec![
+                leaf("e8872c", 0.5, 2.5),
+                leaf("c9482b", 0.3, 2.0),
+                leaf("f2c14e", 0.2, 1.5),
+            ],
+            Season::Winter => vec![leaf("f4f8fb", 0.9, 1.5), leaf("d6e2ea", 0.1, 1.0)],
+        }
+    }
+
+    pub fn sky(self) -> Color {
+        let hex = match self {
+            Season::Spring => "CFF7E5",
+            Season::Summer => "BDE8F5",
+            Season::Autumn => "F3E3C9",
+            Season::Winter => "DDE6EE",
+        };
+        Color::from_hex(hex).unwrap()
+    }
+
+    /// Leaf, sprout and bark colors, the bark greying over winter
+    pub fn colors(self) -> ColorPalette {
+        let (leaf, new_branch, old_branch) = match self {
+            Season::Spring => ("00e430", "00e430", "8b6354"),
+            Season::Summer => ("3e7a35", "5d9e3f", "7d5a48"),
+            Season::Autumn => ("c9482b", "a7783a", "8b6354"),
+            Season::Winter => ("8a8680", "8a8680", "6f6660"),
+        };
+        ColorPalette {
+            leaf: Color::from_hex(leaf).unwrap(),
+            new_branch: Color::from_hex(new_branch).unwrap(),
+            old_branch: Color::from_hex(old_branch).unwrap(),
+        }
+    }
+
+    /// Share of the leaves on the tree through the season, with `progress` going from 0 to 1:
+    /// blossoms come out over early spring and fall over autumn
+    pub fn foliage(self, progress: f32) -> f32 {
+        match self {
+            Season::Spring => (progress * 4.0).min(1.0),
+            Season::Summer => 1.0,
+            Season::Autumn => 1.0 - progress,
+            Season::Winter => 0.0,
+        }
+    }
+
+    /// Looks of the season at its height, with a full canopy except for the bare winter
+    pub fn apply(self, config: &mut Config) {
+        config.leaves = self.leaves();
+        config.sky = self.sky();
+        config.colors = self.colors();
+        config.foliage = if self == Season::Winter { 0.0 } else { 1.0 };
+        self.turn_weather(config);
+    }
+
+    /// Turns rain into snow in winter and snow into rain the rest of the year
+    fn turn_weather(self, config: &mut Config) {
+        if let Some(falling) = &mut config.precipitation {
+            *falling = match self {
+                Season::Winter => Precipitation::Snow,
+                _ => Precipitation::Rain,
+            };
+        }
+    }
+}
+
+/// Steps a season's looks change in, so a tree only gets repainted every so often
+const STAGES: f32 = 48.0;
+
+/// The seasons passing in a loop, each lasting the same time
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SeasonCycle {
+    season: Season,
+    /// how far the current season is, from 0 to 1
+    progress: f32,
+    /// seconds a season lasts
+    length: f32,
+}
+
+impl SeasonCycle {
+    pub fn new(start: Season, length: f32) -> Self {
+        Self {
+            season: start,
+            progress: 0.0,
+            length: length.max(1.0),
+        }
+    }
+
+    pub fn season(&self) -> Season {
+        self.season
+    }
+
+    /// Lets `seconds` pass, moving on to the next season when this one is over
+    pub fn advance(&mut self, seconds: f32) {
+        self.progress += seconds / self.length;
+        while self.progress >= 1.0 {
+            self.progress -= 1.0;
+            self.season = self.season.next();
+        }
+    }
+
+    /// Sets the colors, sky and foliage of this moment of the year
+    pub fn apply(&self, config: &mut Config) {
+        let progress = (self.progress * STAGES).floor() / STAGES;
+        // the colors move on to the next season's over the last quarter of this one
+        let blend = ((progress - 0.75) * 4.0).clamp(0.0, 1.0);
+        let (now, next) = (self.season, self.season.next());
+        let next_leaves = next.leaves();
+        config.leaves = now
+            .leaves()
+            .into_iter()
+            .enumerate()
+            .map(|(idx, leaf)| LeafType {
+                color: leaf
+                    .color
+                    .lerp(next_leaves[idx % next_leaves.len()].color, blend),
+                ..leaf
+            })
+            .collect();
+        config.sky = now.sky().lerp(next.sky(), blend);
+        config.colors = now.colors().lerp(next.colors(), blend);
+        config.foliage = now.foliage(progress);
+        now.turn_weather(config);
+    }
+}
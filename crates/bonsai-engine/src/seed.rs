@@ -0,0 +1,109 @@
+//! Human readable seeds
+//!
+//! Trees are seeded with a u64, which is hard to remember and share.
+//! Any phrase can be used instead and gets hashed to the number,
+//! random trees get a fresh three word phrase so their seed can be written down.
+
+use rand::Rng;
+
+const ADJECTIVES: &[&str] = &[
+    "quiet",
+    "misty",
+    "golden",
+    "silver",
+    "gentle",
+    "ancient",
+    "hidden",
+    "lonely",
+    "bright",
+    "hollow",
+    "crooked",
+    "patient",
+    "windy",
+    "sleepy",
+    "wild",
+    "tender",
+    "frosty",
+    "dusty",
+    "humble",
+    "restless",
+    "velvet",
+    "amber",
+    "pale",
+    "drifting",
+    "silent",
+    "weathered",
+    "early",
+    "sunny",
+    "stormy",
+    "faded",
+    "noble",
+    "little",
+];
+
+const NOUNS: &[&str] = &[
+    "maple", "pine", "cedar", "willow", "cherry", "juniper", "ginkgo", "oak", "plum", "birch",
+    "moss", "stone", "river", "lantern", "temple", "garden", "mountain", "crane", "heron", "fox",
+    "pebble", "bamboo", "cloud", "meadow", "harbor", "bridge", "valley", "feather", "acorn",
+    "orchard", "shrine", "ember",
+];
+
+const TIMES: &[&str] = &[
+    "morning",
+    "evening",
+    "dawn",
+    "dusk",
+    "noon",
+    "midnight",
+    "spring",
+    "summer",
+    "autumn",
+    "winter",
+    "rain",
+    "snow",
+    "frost",
+    "breeze",
+    "twilight",
+    "sunrise",
+    "sunset",
+    "harvest",
+    "bloom",
+    "tide",
+    "moonrise",
+    "solstice",
+    "equinox",
+    "thaw",
+    "drizzle",
+    "fog",
+    "daybreak",
+    "nightfall",
+    "monsoon",
+    "blossom",
+    "starlight",
+    "afterglow",
+];
+
+/// Turns a seed given on the command line into the number, plain numbers are taken as they are
+pub fn parse(seed: &str) -> u64 {
+    seed.trim().parse().unwrap_or_else(|_| from_phrase(seed))
+}
+
+/// FNV-1a hash of the phrase, ignoring case and extra whitespace
+pub fn from_phrase(phrase: &str) -> u64 {
+    let normalized = phrase
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ");
+    normalized
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+}
+
+/// A new random phrase like "quiet maple evening"
+pub fn random_phrase(rng: &mut impl Rng) -> String {
+    let mut pick = |words: &[&'static str]| words[rng.gen_range(0..words.len())];
+    format!("{} {} {}", pick(ADJECTIVES), pick(NOUNS), pick(TIMES))
+}
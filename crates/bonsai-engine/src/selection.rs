@@ -0,0 +1,117 @@
+//! Named sets of branches picked on a tree, like "front branch" or "apex", so the styling of a
+//! saved tree can be repeated by name: cutting a set off or painting it in a color of its own
+//!
+//! A set holds the nodes it was picked at, everything growing from them belongs to it as well.
+//! Sets follow their nodes when the tree renumbers them, a set whose nodes are all gone is empty.
+
+use crate::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Selection {
+    pub name: String,
+    /// the nodes the set was picked at, in the order of `Tree::nodes`
+    pub nodes: Vec<usize>,
+    /// color the branches and leaves of the set are painted in instead of the palette's
+    pub color: Option<Color>,
+}
+
+impl Tree {
+    /// The sets picked on the tree, in the order they were first named
+    pub fn selections(&self) -> &[Selection] {
+        &self.selections
+    }
+
+    /// The set called `name`
+    pub fn selection(&self, name: &str) -> Option<&Selection> {
+        self.selections.iter().find(|set| set.name == name)
+    }
+
+    /// Adds the nodes to the set called `name`, which is made when there is none yet
+    pub fn select(&mut self, name: &str, nodes: &[usize]) {
+        let set = match self.selections.iter().position(|set| set.name == name) {
+            Some(idx) => &mut self.selections[idx],
+            None => {
+                self.selections.push(Selection {
+                    name: name.to_string(),
+                    nodes: vec![],
+                    color: None,
+                });
+                self.selections.last_mut().unwrap()
+            }
+        };
+        for &idx in nodes {
+            if idx < self.nodes.len() && !set.nodes.contains(&idx) {
+                set.nodes.push(idx);
+            }
+        }
+        set.nodes.sort_unstable();
+        self.revision += 1;
+    }
+
+    /// Paints the set called `name` in `color`, or in the palette again with `None`.
+    /// False when there is no such set
+    pub fn recolor_selection(&mut self, name: &str, color: Option<Color>) -> bool {
+        let Some(set) = self.selections.iter_mut().find(|set| set.name == name) else {
+            return false;
+        };
+        set.color = color;
+        self.revision += 1;
+        true
+    }
+
+    /// Kills the branches of the set called `name`. False when there is no such set
+    pub fn prune_selection(&mut self, name: &str) -> bool {
+        let Some(set) = self.selection(name) else {
+            return false;
+        };
+        for idx in set.nodes.clone() {
+            self.kill_subtree(idx);
+        }
+        true
+    }
+
+    /// Whether each node belongs to `set`, indexed like `nodes`
+    pub fn members(&self, set: &Selection) -> Vec<bool> {
+        let mut members = vec![false; self.nodes.len()];
+        for &idx in &set.nodes {
+            members[idx] = true;
+        }
+        // children always come after their parents
+        for idx in 0..self.nodes.len() {
+            if let Some(parent_idx) = self.nodes.parent(idx) {
+                members[idx] |= members[parent_idx];
+            }
+        }
+        members
+    }
+
+    /// The color each node is painted in by the sets it belongs to, the set named last wins
+    pub fn selection_colors(&self) -> Vec<Option<Color>> {
+        let mut colors = vec![];
+        self.selection_colors_into(&mut colors);
+        colors
+    }
+    /// Like `selection_colors`, but reusing the buffer
+    pub fn selection_colors_into(&self, colors: &mut Vec<Option<Color>>) {
+        colors.clear();
+        colors.resize(self.nodes.len(), None);
+        for set in &self.selections {
+            let Some(color) = set.color else {
+                continue;
+            };
+            for (idx, member) in self.members(set).into_iter().enumerate() {
+                if member {
+                    colors[idx] = Some(color);
+                }
+            }
+        }
+    }
+
+    /// Moves the sets along with their nodes after the tree renumbered them,
+    /// `None` for nodes that are gone
+    pub(crate) fn renumber_selections(&mut self, new_idx: impl Fn(usize) -> Option<usize>) {
+        for set in &mut self.selections {
+            set.nodes = set.nodes.iter().filter_map(|&idx| new_idx(idx)).collect();
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! A canonical text form of the structure of a tree, for checking that a change to the growth
+//! grows the same trees as before, or which ones it changes on purpose
+//!
+//! Only what the growth decides is in it: one line per node with its parent, position, depth,
+//! weight, radius and whether it is alive, values rounded to two decimals. Nodes are numbered
+//! from the root outwards with the children of every node ordered by their position, so the
+//! order the simulation happened to add them in doesn't matter.
+//!
+//! ```text
+//! bonsai-snapshot 1
+//! nodes 3
+//! 0 - 250.00 20.00 0.00 3 2.11 1
+//! 1 0 250.00 30.00 0.42 2 1.80 1
+//! ```
+
+use crate::*;
+
+/// First line of every snapshot, counting up whenever the format changes
+pub const HEADER: &str = "bonsai-snapshot 1";
+
+/// The snapshot of the tree as it is grown so far
+pub fn write(tree: &Tree) -> String {
+    let nodes = tree.nodes().collect::<Vec<_>>();
+    let mut children = vec![vec![]; nodes.len()];
+    for (idx, node) in nodes.iter().enumerate() {
+        if let Some(parent) = node.parent {
+            children[parent].push(idx);
+        }
+    }
+    let key = |idx: usize| {
+        let pos = nodes[idx].pos.vec();
+        (hundredths(pos.x), hundredths(pos.y), idx)
+    };
+    for siblings in &mut children {
+        siblings.sort_by_key(|&idx| key(idx));
+    }
+
+    // numbered breadth first, so every parent is numbered before its children
+    let mut order = vec![0];
+    let mut next = 0;
+    while next < order.len() {
+        order.extend_from_slice(&children[order[next]]);
+        next += 1;
+    }
+    let mut number = vec![0; nodes.len()];
+    for (canonical, &idx) in order.iter().enumerate() {
+        number[idx] = canonical;
+    }
+
+    let mut out = format!("{}\nnodes {}\n", HEADER, order.len());
+    for (canonical, &idx) in order.iter().enumerate() {
+        let node = nodes[idx];
+        let pos = node.pos.vec();
+        let parent = node
+            .parent
+            .map_or("-".to_string(), |parent| number[parent].to_string());
+        out.push_str(&format!(
+            "{} {} {} {} {} {} {} {}\n",
+            canonical,
+            parent,
+            rounded(pos.x),
+            rounded(pos.y),
+            rounded(node.z),
+            node.weight,
+            rounded(node.radius),
+            node.alive as u8,
+        ));
+    }
+    out
+}
+
+/// The first line two snapshots differ in, numbered from 1, with the line of each.
+/// A line missing at the end of one of them is empty. Numbers `tolerance` apart or closer count
+/// as the same, so growth in `f64` can be held against the snapshots of `f32` growth, which
+/// round the odd position the other way
+pub fn first_difference(
+    expected: &str,
+    actual: &str,
+    tolerance: f32,
+) -> Option<(usize, String, String)> {
+    let close = |a: &str, b: &str| {
+        let (a, b) = (a.split_whitespace(), b.split_whitespace());
+        a.clone().count() == b.clone().count()
+            && a.zip(b).all(|(a, b)| {
+                a == b
+                    || matches!((a.parse::<f32>(), b.parse::<f32>()),
+                        (Ok(a), Ok(b)) if (a - b).abs() <= tolerance)
+            })
+    };
+    let (mut expected, mut actual) = (expected.lines(), actual.lines());
+    for line in 1.. {
+        match (expected.next(), actual.next()) {
+            (None, None) => return None,
+            (Some(a), Some(b)) if close(a, b) => {}
+            (a, b) => {
+                let text = |line: Option<&str>| line.unwrap_or_default().to_string();
+                return Some((line, text(a), text(b)));
+            }
+        }
+    }
+    unreachable!()
+}
+
+fn hundredths(value: f32) -> i64 {
+    (value * 100.0).round() as i64
+}
+
+/// Two decimals, without the sign of a value that rounds to zero
+fn rounded(value: f32) -> String {
+    let hundredths = hundredths(value);
+    let sign = if hundredths < 0 { "-" } else { "" };
+    format!(
+        "{}{}.{:02}",
+        sign,
+        hundredths.abs() / 100,
+        hundredths.abs() % 100
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbering_ignores_the_order_nodes_were_added_in() {
+        let node = |x: f32, y: f32, parent: Option<usize>| Node {
+            alive: true,
+            pos: Vector2::new(x, y).into(),
+            parent,
+            child_count: 0,
+            depth: parent.map_or(0, |_| 1),
+            weight: 1,
+            radius: 1.5,
+            z: -0.001,
+        };
+        let root = node(0.0, 0.0, None);
+        let (left, right) = (node(-5.0, 10.0, Some(0)), node(5.0, 10.0, Some(0)));
+        let a = Tree::from_parts(Config::default(), vec![root, left, right], None);
+        let b = Tree::from_parts(Config::default(), vec![root, right, left], None);
+        assert_eq!(write(&a), write(&b));
+        assert!(write(&a).contains("\n1 0 -5.00 10.00 0.00 1 1.50 1\n"));
+        assert_eq!(first_difference(&write(&a), &write(&b), 0.0), None);
+    }
+}
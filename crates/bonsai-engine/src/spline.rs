@@ -0,0 +1,44 @@
+use crate::math::Vector2;
+
+/// Uniform Catmull-Rom segment between the two middle control points
+#[derive(Copy, Clone, Debug)]
+pub struct Spline {
+    points: [Vector2; 4],
+}
+
+impl Spline {
+    pub fn new(p0: Vector2, p1: Vector2, p2: Vector2, p3: Vector2) -> Self {
+        Self {
+            points: [p0, p1, p2, p3],
+        }
+    }
+    /// Segment from `from` to `to` with the neighbours `before` and `after` shaping the tangents.
+    /// Missing neighbours are extrapolated, which makes the ends straight.
+    pub fn through(
+        before: Option<Vector2>,
+        from: Vector2,
+        to: Vector2,
+        after: Option<Vector2>,
+    ) -> Self {
+        let before = before.unwrap_or(from * 2.0 - to);
+        let after = after.unwrap_or(to * 2.0 - from);
+        Self::new(before, from, to, after)
+    }
+    /// Point on the spline, t = 0 is the start of the segment and t = 1 the end
+    pub fn at(&self, t: f32) -> Vector2 {
+        let [p0, p1, p2, p3] = self.points;
+        let t2 = t * t;
+        let t3 = t2 * t;
+        (p1 * 2.0
+            + (p2 - p0) * t
+            + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+            + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+            * 0.5
+    }
+    /// The same curve as a cubic Bézier from the start to the end of the segment,
+    /// with the two control points between
+    pub fn bezier(&self) -> [Vector2; 4] {
+        let [p0, p1, p2, p3] = self.points;
+        [p1, p1 + (p2 - p0) / 6.0, p2 - (p3 - p1) / 6.0, p2]
+    }
+}
@@ -0,0 +1,30 @@
+//! Leaf clusters drawn from pixel art sprites instead of plain spheres
+//!
+//! Only where the sprites come from lives here, `bonsai_render::sprite` loads and draws them.
+
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LeafSprites {
+    /// the few clusters that come with the renderer
+    Builtin,
+    /// every `*.png` in the directory, each with its `*.normal.png` next to it if there is one
+    Dir(PathBuf),
+}
+
+impl LeafSprites {
+    /// `builtin` or the directory the sprites are in
+    pub fn parse(name: &str) -> Self {
+        match name {
+            "builtin" => Self::Builtin,
+            dir => Self::Dir(dir.into()),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::Builtin => "builtin".to_string(),
+            Self::Dir(dir) => dir.display().to_string(),
+        }
+    }
+}
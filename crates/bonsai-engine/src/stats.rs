@@ -0,0 +1,83 @@
+//! Numbers describing the shape of a tree and how far it grew
+//!
+//! `Tree::stats` counts them up on demand, for the overlay in the window and for tests that
+//! check a change to the growth still grows trees of a sensible size and structure.
+
+use crate::*;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// every node, alive or dead
+    pub nodes: usize,
+    pub alive: usize,
+    /// nodes pruned by heavier branches or cut off
+    pub dead: usize,
+    /// attraction points not reached yet
+    pub points: usize,
+    /// simulation steps grown so far, see `History::steps`
+    pub steps: u32,
+    /// from the foot of the trunk up to the top of the canopy, in world units
+    pub height: f32,
+    /// from the leftmost to the rightmost living node, in world units
+    pub width: f32,
+    /// nodes between the root and the living node furthest from it
+    pub max_depth: usize,
+    /// living tips, every branch ends in one
+    pub branches: usize,
+}
+
+impl Tree {
+    /// Counts up the shape of the tree as it stands
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats {
+            nodes: self.node_count(),
+            points: self.points.len(),
+            steps: self.history.steps(),
+            height: self.top() - self.config.origin.y,
+            ..Stats::default()
+        };
+        // a living node is a tip unless something living grew from it
+        let mut tip = vec![false; stats.nodes];
+        let (mut left, mut right) = (f32::INFINITY, f32::NEG_INFINITY);
+        for (idx, node) in self.nodes().enumerate().filter(|(_, node)| node.alive) {
+            stats.alive += 1;
+            stats.max_depth = stats.max_depth.max(node.depth);
+            tip[idx] = true;
+            if let Some(parent) = node.parent {
+                tip[parent] = false;
+            }
+            let x = node.pos.vec().x;
+            (left, right) = (left.min(x), right.max(x));
+        }
+        stats.dead = stats.nodes - stats.alive;
+        stats.width = (right - left).max(0.0);
+        stats.branches = tip.iter().filter(|&&tip| tip).count();
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_describe_the_grown_tree() {
+        let mut tree = Tree::new_min_growth(
+            Config {
+                seed: 8,
+                num_points: 3000,
+                ..Config::default()
+            },
+            5,
+        );
+        let sapling = tree.stats();
+        assert!(sapling.alive > 0 && sapling.branches > 0);
+        tree.grow_fully(&cancel::CancelToken::new());
+        let stats = tree.stats();
+        assert_eq!(stats.alive + stats.dead, tree.node_count());
+        assert_eq!(stats.points, tree.points().len());
+        assert!(stats.steps > 0 && stats.height > 0.0 && stats.width > 0.0);
+        assert!(stats.branches > 1 && stats.branches < stats.alive);
+        assert!(stats.max_depth > 0);
+    }
+}
@@ -0,0 +1,112 @@
+//! Storms passing over the trees in the window now and then
+//!
+//! Every `storm_every` seconds on average a storm comes up, when exactly is drawn from the seed,
+//! so the same seed brings the same weather. Over `STORM_LENGTH` seconds the wind swells up to
+//! `storm_wind` and dies down again, and branches bent further than their wood holds snap off,
+//! see `wind::Wind::breaking`. A storm can also be called up right away with `Weather::brew`.
+
+use std::f32::consts::PI;
+
+use rand::Rng;
+use rand_chacha::{rand_core::SeedableRng, ChaCha12Rng};
+
+use crate::*;
+
+/// Seconds a storm lasts from the first gust to the calm after it
+const STORM_LENGTH: f32 = 20.0;
+
+/// The storms to come and the one passing
+pub struct Weather {
+    rng: ChaCha12Rng,
+    /// seconds until the next storm comes up, `None` without storms
+    calm_for: Option<f32>,
+    /// seconds the storm passing has been blowing for
+    storm: Option<f32>,
+}
+
+impl Weather {
+    /// The storms of `config`, drawn from `seed`
+    pub fn new(seed: u64, config: &Config) -> Self {
+        let mut weather = Self {
+            rng: ChaCha12Rng::seed_from_u64(seed ^ 0x5702_3a11),
+            calm_for: None,
+            storm: None,
+        };
+        weather.calm_for = weather.next_calm(config);
+        weather
+    }
+
+    fn next_calm(&mut self, config: &Config) -> Option<f32> {
+        // exponentially distributed, so storms come as unannounced as they do outside
+        (config.storm_every > 0.0).then(|| -config.storm_every * (1.0 - self.rng.gen::<f32>()).ln())
+    }
+
+    /// Lets `seconds` pass
+    pub fn advance(&mut self, seconds: f32, config: &Config) {
+        if let Some(storm) = &mut self.storm {
+            *storm += seconds;
+            if *storm >= STORM_LENGTH {
+                self.storm = None;
+                self.calm_for = self.next_calm(config);
+            }
+            return;
+        }
+        if config.storm_every <= 0.0 {
+            self.calm_for = None;
+            return;
+        }
+        let calm_for = match self.calm_for {
+            Some(calm_for) => calm_for - seconds,
+            None => self.next_calm(config).unwrap_or_default(),
+        };
+        if calm_for <= 0.0 {
+            self.brew();
+        } else {
+            self.calm_for = Some(calm_for);
+        }
+    }
+
+    /// Starts a storm now, unless one is passing already
+    pub fn brew(&mut self) {
+        self.storm.get_or_insert(0.0);
+        self.calm_for = None;
+    }
+
+    pub fn is_stormy(&self) -> bool {
+        self.storm.is_some()
+    }
+
+    /// How much stronger than `wind` of `config` the wind blows now, 0 between storms
+    pub fn extra_wind(&self, config: &Config) -> f32 {
+        let Some(storm) = self.storm else {
+            return 0.0;
+        };
+        let swell = (storm / STORM_LENGTH * PI).sin().powi(2);
+        (config.storm_wind - config.wind).max(0.0) * swell
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_storm_swells_and_passes() {
+        let config = Config {
+            storm_every: 60.0,
+            ..Config::default()
+        };
+        let mut weather = Weather::new(1, &config);
+        let mut strongest: f32 = 0.0;
+        let mut stormy = 0;
+        for _ in 0..60 * 60 {
+            weather.advance(1.0, &config);
+            strongest = strongest.max(weather.extra_wind(&config));
+            stormy += weather.is_stormy() as usize;
+        }
+        assert!(strongest > config.storm_wind * 0.9 - config.wind);
+        // a third of the time at most, for storms every minute lasting a third of one
+        assert!(stormy > 0 && stormy < 60 * 60 / 2);
+        assert_eq!(Weather::new(1, &Config::default()).extra_wind(&config), 0.0);
+    }
+}
@@ -0,0 +1,69 @@
+//! The display alcove a bonsai is traditionally shown in
+//!
+//! The tree stands on a wooden stand on the raised alcove floor, with a hanging scroll
+//! on the wall behind it and the accent plant on the floor beside it.
+//! The stand is painted with the tree, the alcove itself is drawn behind everything,
+//! both by `bonsai_render::tokonoma`.
+
+use crate::*;
+use kusamono::Kusamono;
+use pot::Pot;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tokonoma {
+    /// plaster of the alcove's back wall, at the bottom; it darkens towards the beam above
+    pub wall: Color,
+    /// wood of the stand, the posts and the alcove floor
+    pub wood: Color,
+    /// height of the stand the pot stands on, in world units
+    pub stand_height: f32,
+    /// hang a scroll with ink painting on the wall
+    pub scroll: bool,
+}
+
+impl Default for Tokonoma {
+    fn default() -> Self {
+        Self {
+            wall: Color::from_hex("d8ccb0").unwrap(),
+            wood: Color::from_hex("6e4a30").unwrap(),
+            stand_height: 30.0,
+            scroll: true,
+        }
+    }
+}
+
+/// How far the alcove floor reaches in front of and behind the line things stand on,
+/// in world units
+pub const FLOOR_FRONT: f32 = 12.0;
+pub const FLOOR_BACK: f32 = 35.0;
+/// Height of the dark beam along the front edge of the alcove floor
+pub const FRONT_BEAM: f32 = 12.0;
+/// Width of the posts on both sides of the alcove
+pub const POST: f32 = 18.0;
+
+impl Tokonoma {
+    /// Sets the scene up in the config: the tree gets a pot, an accent plant and a stand,
+    /// and the fog fades into the wall instead of the sky
+    pub fn apply(self, config: &mut Config) {
+        let pot = *config.pot.get_or_insert_with(Pot::default);
+        config.kusamono.get_or_insert_with(Kusamono::default);
+        let below = pot.reach_below() + self.stand_height + FLOOR_FRONT + FRONT_BEAM;
+        config.origin.y = config.origin.y.max(below + 10.0);
+        config.sky = self.wall;
+        config.tokonoma = Some(self);
+    }
+
+    /// Half the width and the depth of the stand, a bit larger than the pot
+    pub fn stand_size(pot: &Pot) -> (f32, f32) {
+        (pot.radius * 1.15, pot.radius * 0.9)
+    }
+
+    /// How far shadows move on the back wall, in canvas pixels,
+    /// and the canvas row the wall starts at above the floor
+    pub fn wall_shadow(&self, config: &Config) -> (i32, i32, i32) {
+        let ph = config.pixel_height() as f32;
+        let away = -Vector2::new(config.sun_angle.cos(), config.sun_angle.sin()) * 4.0;
+        let wall = ((floor(config) + FLOOR_BACK) / ph).ceil() as i32;
+        (away.x.round() as i32, away.y.round() as i32, wall)
+    }
+}
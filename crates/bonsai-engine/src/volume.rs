@@ -0,0 +1,89 @@
+//! Growing the crown in three dimensions instead of on the picture plane, so it can be turned
+//!
+//! Flat trees only use `Node::z` as a hint of how far back a branch is. With a volume it is a
+//! real coordinate: every attraction point lies somewhere in the depth of the crown as well,
+//! and the tips grow towards them in all three directions. The depth of a point follows from
+//! where it lies on the picture plane, so saved trees need nothing besides their points.
+//! `turned` is what gets drawn: the tree seen from `yaw`, its depth back in the range of the hint.
+
+use crate::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Volume {
+    /// thickness of the crown from front to back at its widest, in world units
+    pub depth: f32,
+    /// direction the tree is seen from, in radians turning it counterclockwise seen from above
+    pub yaw: f32,
+}
+
+impl Default for Volume {
+    fn default() -> Self {
+        Self {
+            depth: 300.0,
+            yaw: 0.0,
+        }
+    }
+}
+
+impl Volume {
+    /// Depth of an attraction point at `pos` of a tree grown with `config`, in front of the
+    /// trunk when negative. The crown is thickest above the origin and thins out to the sides
+    /// and down to the root
+    #[allow(clippy::unnecessary_cast)]
+    pub fn depth_of(&self, pos: Point, config: &Config) -> f32 {
+        let mut hash = config.seed
+            ^ (pos.x as f64).to_bits().rotate_left(17)
+            ^ (pos.y as f64).to_bits().rotate_left(41);
+        // splitmix64, spreading the bits of the position over the whole word
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        hash ^= hash >> 31;
+        let unit = (hash >> 40) as f32 / (1u64 << 24) as f32;
+        let pos = pos.vec();
+        let side = (pos.x - config.origin.x) / (config.width / 2.0).max(1.0);
+        // narrowing down to the trunk, which finds its first points straight above it
+        let rise = ((pos.y - config.origin.y) / (self.depth / 2.0).max(1.0)).clamp(0.0, 1.0);
+        let thickness = (1.0 - side * side).max(0.0).sqrt() * rise;
+        (unit - 0.5) * self.depth * thickness
+    }
+}
+
+/// A direction on the picture plane and its part in depth scaled to length 1.
+/// Without any depth it is the plain direction, exactly as flat trees grow
+pub(crate) fn unit(dir: Point, z: f32) -> (Point, f32) {
+    if z == 0.0 {
+        return (dir.normalized(), 0.0);
+    }
+    let flat = dir.vec();
+    let length = (flat.x * flat.x + flat.y * flat.y + z * z).sqrt();
+    (dir * Real::from(1.0 / length), z / length)
+}
+
+/// The tree seen from the yaw of its volume, with `Node::z` turned back into a depth hint
+/// between 0 and `Config::node_depth_max` so it draws like a flat tree. `None` for flat trees.
+/// The roots stay behind, they grow flat and are drawn as they are
+pub fn turned(tree: &Tree) -> Option<Tree> {
+    let volume = tree.config.volume?;
+    let (sin, cos) = volume.yaw.sin_cos();
+    let axis = tree.config.origin.x;
+    let depth_max = tree.config.node_depth_max as f32;
+    // turned sideways the width of the crown becomes its depth
+    let span = tree.config.width.max(volume.depth).max(1.0);
+    let nodes = tree
+        .nodes()
+        .map(|mut node| {
+            let side = node.pos.vec().x - axis;
+            node.pos.x = Real::from(axis + side * cos - node.z * sin);
+            let back = side * sin + node.z * cos;
+            node.z = ((back / span + 0.5) * depth_max).clamp(0.0, depth_max);
+            node
+        })
+        .collect();
+    let config = Config {
+        roots: None,
+        ..tree.config.clone()
+    };
+    let mut turned = Tree::from_parts(config, nodes, None);
+    turned.selections = tree.selections.clone();
+    Some(turned)
+}
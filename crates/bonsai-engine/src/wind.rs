@@ -0,0 +1,188 @@
+//! Wind swaying a grown tree, for showing it animated
+//!
+//! Every segment bends like a cantilever loaded at its end with the drag of everything it
+//! carries, which grows with its weight. How much it resists goes with the fourth power of its
+//! radius and stiffens as the wood ages, and a segment also turns everything above it by the
+//! angle it bent. So the trunk barely moves, while the twigs at the end of long thin branches
+//! whip around. The phase of each swing comes from simplex noise drifting over time, which keeps
+//! neighbouring twigs moving together and rolls gusts through the canopy, and lags further
+//! behind the further out along the branches a node is.
+
+use std::f32::consts::TAU;
+
+use fuss::Simplex;
+
+use crate::{Tree, Vector2};
+
+/// Swings per second of a twig
+const FREQUENCY: f32 = 0.4;
+/// Noise cycles per world unit, about one per branch
+const PHASE_SCALE: f32 = 0.01;
+/// How fast the phases drift, in noise cycles per second
+const DRIFT: f32 = 0.15;
+/// How fast gusts roll through the canopy, in world units per second
+const GUST_SPEED: f32 = 60.0;
+/// Radians a swing lags behind per world unit along the branches out from the root
+const PHASE_LAG: f32 = 0.004;
+/// How much stiffer the oldest wood is than a fresh twig, on top of being thicker
+const AGE_STIFFENING: f32 = 1.0;
+/// Noise cycles per world unit of the weak spots in the wood
+const FLAW_SCALE: f32 = 0.05;
+/// How much weaker the wood at a weak spot is, and stronger elsewhere
+const FLAW_SPREAD: f32 = 0.5;
+/// Nodes a branch needs to carry to snap off in a storm
+const MIN_SNAPPED: usize = 4;
+/// How far the wood gives per unit of `wind`, so the tips of a grown tree swing a few times
+/// `wind` while the trunk stays within a fraction of a world unit
+const COMPLIANCE: f32 = 0.035;
+
+/// The noise of the wind and the offsets it gave the nodes last
+pub struct Wind {
+    simplex: Simplex,
+    sway: Vec<Vector2>,
+    /// angle each node is turned by the bending below it, in the last sway
+    turn: Vec<f32>,
+    /// distance from the root along the branches
+    reach: Vec<f32>,
+    /// how far the surface of each segment got stretched by its bending, in the last sway
+    strain: Vec<f32>,
+}
+
+impl Wind {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            simplex: Simplex::from_seed(vec![seed as usize, 0x77_1d]),
+            sway: vec![],
+            turn: vec![],
+            reach: vec![],
+            strain: vec![],
+        }
+    }
+
+    /// Offsets of the nodes of `tree` at `time` seconds with the wind blowing `strength`,
+    /// usually `wind` of the tree's config, indexed like `Tree::nodes`. The twigs swing a few
+    /// times `strength`, stiffer wood by `wind_stiffness` of the config moves less
+    pub fn sway(&mut self, tree: &Tree, time: f32, strength: f32) -> &[Vector2] {
+        let config = &tree.config;
+        let stiffness = config.wind_stiffness.max(1e-3);
+        let twig_length = config.grow_dist.max(1e-3);
+        let twig_radius = tree
+            .nodes()
+            .filter(|node| node.alive)
+            .map(|node| node.radius)
+            .fold(f32::INFINITY, f32::min)
+            .max(1e-3);
+        let history = tree.history();
+        let steps = history.steps().max(1) as f32;
+        let pos = tree.nodes().map(|node| node.pos.vec()).collect::<Vec<_>>();
+        self.sway.clear();
+        self.turn.clear();
+        self.reach.clear();
+        self.strain.clear();
+        // parents always come before their children
+        for (idx, node) in tree.nodes().enumerate() {
+            // the root stands in the ground
+            let Some(parent) = node.parent else {
+                self.sway.push(Vector2::zero());
+                self.turn.push(0.0);
+                self.reach.push(0.0);
+                self.strain.push(0.0);
+                continue;
+            };
+            let length = (pos[idx] - pos[parent]).length();
+            let reach = self.reach[parent] + length;
+            let phase = self.simplex.noise_3d(
+                pos[idx].x * PHASE_SCALE,
+                pos[idx].y * PHASE_SCALE,
+                time * DRIFT,
+            ) * TAU
+                - reach * PHASE_LAG;
+            // a gust leans the canopy downwind while it passes
+            let gust = (self
+                .simplex
+                .noise_2d((pos[idx].x - time * GUST_SPEED) * PHASE_SCALE * 0.5, 17.0)
+                + 1.0)
+                * 0.5;
+            let swing = (time * FREQUENCY * TAU + phase).sin() * 0.5 + gust;
+
+            // a cantilever of length l and stiffness EI under a load F at its end is pushed
+            // F l³ / 3EI aside there and bends by F l² / 2EI, both relative to a fresh twig
+            let age = 1.0 - history.born(idx) as f32 / steps;
+            let rigidity =
+                stiffness * (1.0 + AGE_STIFFENING * age) * (node.radius / twig_radius).powi(4);
+            let relative_length = length / twig_length;
+            let push = COMPLIANCE * strength * swing * node.weight.max(1) as f32 / rigidity;
+            let shift = push * relative_length.powi(3);
+            let bend = push * relative_length.powi(2) * 1.5 / twig_length;
+            let turn = self.turn[parent];
+            let amount = turn * length + shift;
+            self.sway
+                .push(self.sway[parent] + Vector2::new(amount, -amount.abs() * 0.2));
+            self.turn.push(turn + bend);
+            self.reach.push(reach);
+            // the curvature times the distance of the surface from the middle, against the
+            // weak spots scattered through the wood
+            let flaw = self
+                .simplex
+                .noise_2d(pos[idx].x * FLAW_SCALE, pos[idx].y * FLAW_SCALE + 91.0);
+            let strain = (bend / length.max(1e-3)).abs() * node.radius;
+            self.strain.push(strain / (1.0 + FLAW_SPREAD * flaw));
+        }
+        &self.sway
+    }
+
+    /// The living nodes of `tree` whose segment got bent further than `limit` of strain in the
+    /// last sway, without those whose branch snaps further in already. Twigs carrying fewer
+    /// than `MIN_SNAPPED` nodes only lose leaves
+    pub fn breaking(&self, tree: &Tree, limit: f32) -> Vec<usize> {
+        let mut snapped = vec![false; self.strain.len()];
+        let mut breaking = vec![];
+        for (idx, node) in tree.nodes().enumerate().take(self.strain.len()) {
+            let Some(parent) = node.parent else {
+                continue;
+            };
+            snapped[idx] = snapped[parent];
+            if node.alive && !snapped[idx] && node.weight >= MIN_SNAPPED && self.strain[idx] > limit
+            {
+                snapped[idx] = true;
+                breaking.push(idx);
+            }
+        }
+        breaking
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cancel::CancelToken, Config};
+
+    #[test]
+    fn the_trunk_barely_moves_while_the_twigs_whip() {
+        let config = Config {
+            seed: 4,
+            wind: 3.0,
+            ..Config::default()
+        };
+        let mut tree = Tree::new(config.clone());
+        tree.grow_fully(&CancelToken::new());
+        let heaviest = tree.nodes().map(|node| node.weight).max().unwrap();
+        let sway = Wind::new(4).sway(&tree, 1.0, 3.0).to_vec();
+        let most = |trunk: bool| {
+            tree.nodes()
+                .zip(&sway)
+                .filter(|(node, _)| (node.weight * 2 > heaviest) == trunk)
+                .map(|(_, sway)| sway.x.abs())
+                .fold(0.0, f32::max)
+        };
+        assert!(most(true) < 0.5 && most(false) > 5.0);
+
+        // stiffer wood gives less
+        tree.config.wind_stiffness = 2.0;
+        let stiff = Wind::new(4).sway(&tree, 1.0, 3.0).to_vec();
+        let tip = (0..sway.len())
+            .max_by(|&a, &b| sway[a].x.abs().total_cmp(&sway[b].x.abs()))
+            .unwrap();
+        assert!((stiff[tip].x * 2.0 - sway[tip].x).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,100 @@
+//! Generating many trees at once, spread over threads
+//!
+//! Every job gets its seed from the master seed and its index, never from the thread running it,
+//! so a batch gives the same files no matter how many threads render it.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use crate::cancel::CancelToken;
+
+/// Seed of the job at `idx` in a batch started from `master`
+pub fn job_seed(master: u64, idx: usize) -> u64 {
+    // splitmix64, so neighbouring jobs get unrelated seeds
+    let mut z = master.wrapping_add((idx as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Output file of the job at `idx`, numbered next to `out`
+pub fn job_path(out: &Path, idx: usize) -> PathBuf {
+    let stem = out.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match out.extension() {
+        Some(ext) => format!("{}-{:04}.{}", stem, idx, ext.to_string_lossy()),
+        None => format!("{}-{:04}", stem, idx),
+    };
+    out.with_file_name(name)
+}
+
+/// Runs `count` jobs on up to `threads` threads, handing each its index and seed.
+/// Once cancelled no new jobs are started, returns how many finished without error
+pub fn run(
+    count: usize,
+    threads: usize,
+    master: u64,
+    cancel: &CancelToken,
+    job: impl Fn(usize, u64) -> io::Result<()> + Sync,
+) -> usize {
+    let next = AtomicUsize::new(0);
+    let finished = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, count.max(1)) {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                if idx >= count || cancel.is_cancelled() {
+                    break;
+                }
+                if job(idx, job_seed(master, idx)).is_ok() {
+                    finished.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    finished.into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::*;
+
+    /// Grows a small tree per job and saves it into `dir`
+    fn run_into(dir: &Path, threads: usize) {
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir).unwrap();
+        let finished = run(16, threads, 42, &CancelToken::new(), |idx, seed| {
+            let config = Config {
+                seed,
+                num_points: 1500,
+                ..Config::default()
+            };
+            let mut tree = Tree::new_min_growth(config, 5);
+            tree.grow_fully(&CancelToken::new());
+            let mut out = fs::File::create(job_path(&dir.join("tree.bonsai"), idx))?;
+            save::write(&tree, &mut out)
+        });
+        assert_eq!(finished, 16);
+    }
+
+    #[test]
+    fn thread_count_does_not_change_the_output() {
+        let base = std::env::temp_dir().join(format!("pixel-bonsai-batch-{}", std::process::id()));
+        let (single, many) = (base.join("single"), base.join("many"));
+        run_into(&single, 1);
+        run_into(&many, 16);
+        for idx in 0..16 {
+            let name = job_path(Path::new("tree.bonsai"), idx);
+            let a = fs::read(single.join(&name)).unwrap();
+            let b = fs::read(many.join(&name)).unwrap();
+            assert!(a == b, "{} differs", name.display());
+        }
+        let _ = fs::remove_dir_all(base);
+    }
+}
@@ -0,0 +1,175 @@
+//! Renders many trees straight to image files, without a window or raylib,
+//! for generating them by the thousand on machines without a display
+//!
+//! `pixel-bonsai-gen --count 100 --out renders/` writes `renders/bonsai-0000.png` and on.
+//! With `--cache dir/` trees rendered before by any batch are copied from there instead.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+use bonsai_engine::{cancel, seed, Config};
+use bonsai_io::{batch, cache::Cache, config_file, export, wallpaper};
+use wallpaper::{Target, Variant};
+
+const USAGE: &str = "usage: pixel-bonsai-gen [--count 100] [--out dir/] [--seed \"quiet maple evening\"] [--threads 8] [--target phone|phone-hd|tablet|desktop|ultrawide] [--config bonsai.toml] [--set key=value] [--cache dir/ [--cache-size 512]]";
+
+struct Args {
+    count: usize,
+    out: PathBuf,
+    seed: Option<u64>,
+    threads: Option<usize>,
+    target: Target,
+    config: Option<PathBuf>,
+    overrides: Vec<String>,
+    cache: Option<PathBuf>,
+    /// megabytes the cache may take up
+    cache_size: usize,
+}
+
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut args = Self {
+            count: 1,
+            out: PathBuf::from("."),
+            seed: None,
+            threads: None,
+            target: Target::Desktop,
+            config: None,
+            overrides: vec![],
+            cache: None,
+            cache_size: 512,
+        };
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            let mut value = |name: &str| {
+                iter.next()
+                    .ok_or_else(|| format!("{} needs a value\n{}", name, USAGE))
+            };
+            match arg.as_str() {
+                "--count" | "--threads" | "--cache-size" => {
+                    let value = value(&arg)?;
+                    let count = value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| format!("invalid {} {}\n{}", arg, value, USAGE))?;
+                    match arg.as_str() {
+                        "--count" => args.count = count,
+                        "--threads" => args.threads = Some(count),
+                        _ => args.cache_size = count,
+                    }
+                }
+                "--out" => args.out = value("--out")?.into(),
+                "--seed" => args.seed = Some(seed::parse(&value("--seed")?)),
+                "--target" => {
+                    let name = value("--target")?;
+                    args.target = Target::parse(&name)
+                        .ok_or_else(|| format!("unknown target {}\n{}", name, USAGE))?;
+                }
+                "--config" => args.config = Some(value("--config")?.into()),
+                "--set" => args.overrides.push(value("--set")?),
+                "--cache" => args.cache = Some(value("--cache")?.into()),
+                "--help" | "-h" => return Err(USAGE.to_string()),
+                _ => return Err(format!("unknown argument {}\n{}", arg, USAGE)),
+            }
+        }
+        Ok(args)
+    }
+}
+
+fn main() {
+    let args = Args::parse().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(2);
+    });
+
+    let mut config = Config::default();
+    if let Some(path) = &args.config {
+        if let Err(err) = config_file::load(path, &mut config) {
+            eprintln!("could not load the config: {}", err);
+            process::exit(2);
+        }
+    }
+    for assignment in &args.overrides {
+        if let Err(err) = config_file::apply_override(assignment, &mut config) {
+            eprintln!("invalid --set {}: {}", assignment, err);
+            process::exit(2);
+        }
+    }
+    let master = args.seed.unwrap_or_else(|| {
+        let phrase = seed::random_phrase(&mut rand::thread_rng());
+        println!("seed: {}", phrase);
+        seed::from_phrase(&phrase)
+    });
+    if let Err(err) = fs::create_dir_all(&args.out) {
+        eprintln!("could not create {}: {}", args.out.display(), err);
+        process::exit(1);
+    }
+
+    let cancel = cancel::CancelToken::new();
+    cancel::catch_interrupt();
+    let threads = args
+        .threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |threads| threads.get()));
+    let cache = args.cache.as_ref().map(|dir| {
+        Cache::open(dir, args.cache_size as u64 * 1024 * 1024).unwrap_or_else(|err| {
+            eprintln!("could not open the cache {}: {}", dir.display(), err);
+            process::exit(1);
+        })
+    });
+    let out = args.out.join("bonsai.png");
+    let finished = batch::run(args.count, threads, master, &cancel, |idx, seed| {
+        println!("tree {}: seed {}", idx, seed);
+        let config = Config {
+            seed,
+            ..config.clone()
+        };
+        let key = cache
+            .as_ref()
+            .and_then(|cache| Some((cache, Cache::key(&config, args.target)?)));
+        if let Some(png) = key.and_then(|(cache, key)| cache.get(key)) {
+            println!("tree {}: cached", idx);
+            return write_png(&batch::job_path(&out, idx), &png);
+        }
+        let rendered = wallpaper::render(&config, args.target, &[Variant::Light], &[], &cancel);
+        for warning in &rendered.report.warnings {
+            eprintln!("warning: {}", warning);
+        }
+        let Some((_, frame)) = rendered.frames.first() else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Interrupted,
+                "cancelled",
+            ));
+        };
+        let mut png = vec![];
+        let metadata = export::metadata::Metadata::of(&config);
+        export::metadata::write_png(&frame.to_image(), &metadata, &mut png)?;
+        if let Some((cache, key)) = key {
+            if let Err(err) = cache.put(key, &png) {
+                eprintln!("could not cache tree {}: {}", idx, err);
+            }
+        }
+        write_png(&batch::job_path(&out, idx), &png)
+    });
+    println!("rendered {} of {} trees", finished, args.count);
+    if cancel.is_cancelled() {
+        process::exit(130);
+    }
+    if finished < args.count {
+        process::exit(1);
+    }
+}
+
+/// Writes the encoded image, telling how it went
+fn write_png(path: &Path, png: &[u8]) -> io::Result<()> {
+    let written = export::save(path, |file| file.write_all(png));
+    match &written {
+        Ok(()) => println!("wrote {}", path.display()),
+        Err(err) => eprintln!("{}", err),
+    }
+    written
+}
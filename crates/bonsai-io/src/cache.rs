@@ -0,0 +1,155 @@
+//! Rendered images kept on disk by what they were rendered from, so the same tree is only
+//! rendered once
+//!
+//! The key hashes everything the image depends on: the config written out like a config file,
+//! which has the seed, the size of the world and the palette, along with the target and the
+//! version of the renderer. Configs drawing on files the key can't see, a density map, a
+//! background image or leaf sprites from a directory, aren't cached. Once the cache grows past its size, the images used the
+//! longest time ago are deleted first.
+
+use std::{fs, io, path::PathBuf, time::SystemTime};
+
+use crate::*;
+use wallpaper::Target;
+
+pub struct Cache {
+    dir: PathBuf,
+    /// bytes the images in the cache may take up together
+    max_bytes: u64,
+}
+
+impl Cache {
+    /// The cache in `dir`, created if it isn't there yet
+    pub fn open(dir: impl Into<PathBuf>, max_bytes: u64) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, max_bytes })
+    }
+
+    /// Key of the image of `config` rendered for `target`, `None` when it can't be cached
+    pub fn key(config: &Config, target: Target) -> Option<u64> {
+        if !matches!(config.density, bonsai_engine::density::DensitySource::Noise)
+            || config.background.is_some()
+            || matches!(config.leaf_sprites, Some(sprites::LeafSprites::Dir(_)))
+        {
+            return None;
+        }
+        let resolved = format!(
+            "{}\n{:?}\n{:?}\n{}",
+            config_file::write(config),
+            config.color_rules,
+            target,
+            env!("CARGO_PKG_VERSION"),
+        );
+        Some(fnv1a(resolved.as_bytes()))
+    }
+
+    fn path(&self, key: u64) -> PathBuf {
+        self.dir.join(format!("{:016x}.png", key))
+    }
+
+    /// The image stored under `key`, marking it as just used
+    pub fn get(&self, key: u64) -> Option<Vec<u8>> {
+        let path = self.path(key);
+        let bytes = fs::read(&path).ok()?;
+        if let Ok(file) = fs::File::options().append(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(bytes)
+    }
+
+    /// Stores the image under `key`, then deletes the least recently used ones past the size
+    pub fn put(&self, key: u64, bytes: &[u8]) -> io::Result<()> {
+        // written next to it first, so no other batch ever reads half an image
+        let partial = self
+            .dir
+            .join(format!("{:016x}.{}.part", key, std::process::id()));
+        fs::write(&partial, bytes)?;
+        fs::rename(&partial, self.path(key))?;
+        self.evict()
+    }
+
+    /// Deletes the images used the longest time ago until the rest fits into the size
+    fn evict(&self) -> io::Result<()> {
+        let mut images = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "png") {
+                continue;
+            }
+            // another process may have evicted it already
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            let used = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            images.push((used, meta.len(), path));
+        }
+        let mut total: u64 = images.iter().map(|(_, len, _)| len).sum();
+        images.sort();
+        // the rest is still evicted after one that can't be, which is told about then
+        let mut failed = Ok(());
+        for (_, len, path) in images {
+            if total <= self.max_bytes {
+                break;
+            }
+            match fs::remove_file(&path) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                    failed = failed.and(Err(io::Error::new(
+                        err.kind(),
+                        format!("could not evict {}: {}", path.display(), err),
+                    )));
+                }
+                _ => total -= len,
+            }
+        }
+        failed
+    }
+}
+
+/// 64 bit FNV-1a, which unlike the hasher of the standard library stays the same across builds
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_the_least_recently_used() {
+        let dir = std::env::temp_dir().join(format!("pixel-bonsai-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let cache = Cache::open(&dir, 25).unwrap();
+        let old = SystemTime::now() - std::time::Duration::from_secs(60);
+        for key in 0..2 {
+            cache.put(key, &[key as u8; 10]).unwrap();
+            fs::File::options()
+                .append(true)
+                .open(cache.path(key))
+                .unwrap()
+                .set_modified(old + std::time::Duration::from_secs(key))
+                .unwrap();
+        }
+        // 0 is used again, so 1 is the one used the longest time ago
+        assert_eq!(cache.get(0), Some(vec![0; 10]));
+        cache.put(2, &[2; 10]).unwrap();
+        assert!(cache.get(1).is_none());
+        assert!(cache.get(0).is_some() && cache.get(2).is_some());
+
+        let config = Config::default();
+        let key = Cache::key(&config, Target::Desktop);
+        assert_eq!(key, Cache::key(&config.clone(), Target::Desktop));
+        assert_ne!(key, Cache::key(&config, Target::Phone));
+        let reseeded = Config { seed: 1, ..config };
+        assert_ne!(key, Cache::key(&reseeded, Target::Desktop));
+        let sprites = Config {
+            leaf_sprites: Some(sprites::LeafSprites::Dir("sprites".into())),
+            ..Config::default()
+        };
+        assert_eq!(Cache::key(&sprites, Target::Desktop), None);
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,905 @@
+//! Reading configs from TOML files, so trees can be tuned without recompiling
+//!
+//! Only the part of TOML that configs need is understood: `key = value` lines with numbers, booleans
+//! and quoted strings, `[section]` tables and `[[leaf]]` entries, one per blossom kind.
+//! Colors are hex strings like `"8b6354"`, angles are in degrees. Keys that are missing keep
+//! the value of the config the file is applied to, unknown keys are an error.
+//! A program keeps keys of its own in the same files through `AppKeys`, like the window's `ui_scale`.
+//!
+//! ```toml
+//! seed = "quiet maple evening"
+//! num_points = 20000
+//! attraction_dist = 25
+//!
+//! [colors]
+//! old_branch = "4a3728"
+//!
+//! [[leaf]]
+//! color = "ffe0e0"
+//! probability = 1
+//! size = 2.5
+//! ```
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::*;
+use composition::{Anchor, Overflow};
+use falloff::Falloff;
+use noise::NoiseKind;
+use precipitation::Precipitation;
+
+/// Keys of the program the trees are grown in, kept at the top of the same files as the `Config`
+pub trait AppKeys {
+    /// Sets the key, `None` if the program has no key of that name
+    fn set(&mut self, key: &str, value: &Value) -> Option<Result<(), String>>;
+    /// Every key with its value as a file has it
+    fn write(&self) -> Vec<(&'static str, String)>;
+}
+
+/// No keys besides the ones of the `Config`
+impl AppKeys for () {
+    fn set(&mut self, _key: &str, _value: &Value) -> Option<Result<(), String>> {
+        None
+    }
+    fn write(&self) -> Vec<(&'static str, String)> {
+        vec![]
+    }
+}
+
+/// Reads the file and applies it over `config`, returning the keys it set,
+/// `section.key` for the ones in a section
+pub fn load(path: &Path, config: &mut Config) -> io::Result<Vec<String>> {
+    load_with(path, config, &mut ())
+}
+
+/// Like `load`, with the program's own keys going to `app`
+pub fn load_with(
+    path: &Path,
+    config: &mut Config,
+    app: &mut impl AppKeys,
+) -> io::Result<Vec<String>> {
+    let text = fs::read_to_string(path)?;
+    apply_with(&text, config, app).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), err),
+        )
+    })
+}
+
+/// Applies the lines of a config file over `config`, returning the keys it set
+pub fn apply(text: &str, config: &mut Config) -> Result<Vec<String>, String> {
+    apply_with(text, config, &mut ())
+}
+
+/// Like `apply`, with the program's own keys going to `app`
+pub fn apply_with(
+    text: &str,
+    config: &mut Config,
+    app: &mut impl AppKeys,
+) -> Result<Vec<String>, String> {
+    let mut section = String::new();
+    let mut keys = vec![];
+    // the file's blossom kinds replace the config's, not add to them
+    let mut leaves = vec![];
+    for (idx, line) in text.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        let at_line = |err: String| format!("line {}: {}", idx + 1, err);
+        if line.is_empty() {
+            continue;
+        }
+        if line == "[[leaf]]" {
+            section = "leaf".to_string();
+            leaves.push(LeafType {
+                color: config.colors.leaf,
+                size: 1.0,
+                probability: 1.0,
+            });
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| at_line(format!("expected `key = value`, found `{}`", line)))?;
+        let (key, value) = (key.trim(), Value::parse(value.trim()).map_err(at_line)?);
+        match section.as_str() {
+            "leaf" => set_leaf(leaves.last_mut().unwrap(), key, &value),
+            "" => app
+                .set(key, &value)
+                .unwrap_or_else(|| set(config, "", key, &value)),
+            _ => set(config, &section, key, &value),
+        }
+        .map_err(at_line)?;
+        keys.push(match section.as_str() {
+            "" => key.to_string(),
+            _ => format!("{}.{}", section, key),
+        });
+    }
+    if !leaves.is_empty() {
+        config.leaves = leaves;
+    }
+    Ok(keys)
+}
+
+/// The config as a file that `apply` reads back into it, everything a config file can set.
+/// Color rules and density maps have no keys and are left out
+pub fn write(config: &Config) -> String {
+    write_with(config, &())
+}
+
+/// Like `write`, with the program's own keys along
+pub fn write_with(config: &Config, app: &impl AppKeys) -> String {
+    let hex = |color: Color| format!("\"{:02x}{:02x}{:02x}\"", color.r, color.g, color.b);
+    let mut out = String::new();
+    let mut line = |key: &str, value: String| {
+        out.push_str(&match key.strip_prefix('[') {
+            Some(_) => format!("\n{}\n", key),
+            None => format!("{} = {}\n", key, value),
+        })
+    };
+    // quoted, a number this large would lose digits as a float
+    line("seed", format!("\"{}\"", config.seed));
+    line("width", config.width.to_string());
+    line("height", config.height.to_string());
+    line("origin_x", config.origin.x.to_string());
+    line("origin_y", config.origin.y.to_string());
+    line("num_points", config.num_points.to_string());
+    line("attraction_dist", config.attraction_dist.to_string());
+    line("kill_dist", config.kill_dist.to_string());
+    line("grow_dist", config.grow_dist.to_string());
+    line("node_min_dist", config.node_min_dist.to_string());
+    line(
+        "subdivide_max_length",
+        config.subdivide_max_length.to_string(),
+    );
+    line("max_children", config.max_children.to_string());
+    line("max_depth", config.max_depth.to_string());
+    line("min_y_growth", config.min_y_growth.to_string());
+    line("parent_dir_factor", config.parent_dir_factor.to_string());
+    line("tropism_x", config.tropism.x.to_string());
+    line("tropism_y", config.tropism.y.to_string());
+    line("weight_display_pow", config.weight_display_pow.to_string());
+    line("radius_taper_cap", config.radius_taper_cap.to_string());
+    line("canopy_memory", config.canopy_memory.to_string());
+    line("prune_pow", config.prune_pow.to_string());
+    line("prune_size_ratio", config.prune_size_ratio.to_string());
+    line("leaf_max_width", config.leaf_max_width.to_string());
+    line("sprout_max_width", config.sprout_max_width.to_string());
+    line("leaf_size", config.leaf_size.to_string());
+    line("leaf_depth_jitter", config.leaf_depth_jitter.to_string());
+    line("foliage", config.foliage.to_string());
+    line("node_depth_change", config.node_depth_change.to_string());
+    line("node_depth_max", config.node_depth_max.to_string());
+    line("pixel_size", config.pixel_size.to_string());
+    line("pixel_aspect", config.pixel_aspect.to_string());
+    line("sky", hex(config.sky));
+    line("sun_angle", config.sun_angle.to_degrees().to_string());
+    line(
+        "sun_elevation",
+        config.sun_elevation.to_degrees().to_string(),
+    );
+    line("ambient", config.ambient.to_string());
+    line("ambient_sky", hex(config.ambient_sky));
+    line("ambient_ground", hex(config.ambient_ground));
+    line("sun_color", hex(config.sun_color));
+    line("light_floor", config.light_floor.to_string());
+    line("growth_speed", config.growth_speed.to_string());
+    line("tick_rate", config.tick_rate.to_string());
+    line("target_fps", config.target_fps.to_string());
+    line("vsync", config.vsync.to_string());
+    line("fixed_step", config.fixed_step.to_string());
+    line("idle", config.idle.to_string());
+    line("season_length", config.season_length.to_string());
+    line("day_length", config.day_length.to_string());
+    line("regrow_after", config.regrow_after.to_string());
+    line("regrow_share", config.regrow_share.to_string());
+    line("high_contrast", config.high_contrast.to_string());
+    line("overflow", format!("\"{}\"", config.overflow.name()));
+    line("depth_fog", config.depth_fog.to_string());
+    line("canopy_occlusion", config.canopy_occlusion.to_string());
+    line("self_shadow", config.self_shadow.to_string());
+    line("ambient_occlusion", config.ambient_occlusion.to_string());
+    line(
+        "ambient_occlusion_radius",
+        config.ambient_occlusion_radius.to_string(),
+    );
+    line("grain", config.grain.to_string());
+    line("shades", config.shades.to_string());
+    line("shadow_tint", hex(config.shadow_tint));
+    if !config.ramp.is_empty() {
+        let ramp = config
+            .ramp
+            .iter()
+            .map(|color| format!("{:02x}{:02x}{:02x}", color.r, color.g, color.b));
+        line(
+            "ramp",
+            format!("\"{}\"", ramp.collect::<Vec<_>>().join(" ")),
+        );
+    }
+    line("fruit_density", config.fruit_density.to_string());
+    line("fruit_color", hex(config.fruit_color));
+    line("ground_shadow", config.ground_shadow.to_string());
+    line("wind", config.wind.to_string());
+    line("wind_stiffness", config.wind_stiffness.to_string());
+    line("branch_strength", config.branch_strength.to_string());
+    line("storm_every", config.storm_every.to_string());
+    line("storm_wind", config.storm_wind.to_string());
+    let falling = config
+        .precipitation
+        .map_or("none", |falling| falling.name());
+    line("precipitation", format!("\"{}\"", falling));
+    line(
+        "precipitation_intensity",
+        config.precipitation_intensity.to_string(),
+    );
+    line("snow_melt", config.snow_melt.to_string());
+    line("nest_chance", config.nest_chance.to_string());
+    line("hatch_after", config.hatch_after.to_string());
+    line("aging_every", config.aging_every.to_string());
+    line("aging_severity", config.aging_severity.to_string());
+    line("deadwood_color", hex(config.deadwood_color));
+    line("night_sky", config.night_sky.to_string());
+    line("nameplate", config.nameplate.to_string());
+    if let Some(sprites) = &config.leaf_sprites {
+        line("leaf_sprites", format!("\"{}\"", sprites.name()));
+    }
+    if let Some(background) = &config.background {
+        line("background", format!("\"{}\"", background.path.display()));
+        line("background_fit", format!("\"{}\"", background.fit.name()));
+        line("pixelate_background", background.pixelate.to_string());
+    }
+    if let Some(count) = config.forest {
+        line("forest", count.to_string());
+    }
+    for (key, value) in app.write() {
+        line(key, value);
+    }
+
+    line("[colors]", String::new());
+    line("leaf", hex(config.colors.leaf));
+    line("new_branch", hex(config.colors.new_branch));
+    line("old_branch", hex(config.colors.old_branch));
+
+    let noise = &config.noise;
+    line("[noise]", String::new());
+    line("kind", format!("\"{}\"", noise.kind.name()));
+    line("octaves", noise.octaves.to_string());
+    line("persistence", noise.persistence.to_string());
+    line("frequency", noise.frequency.to_string());
+
+    line("[falloff]", String::new());
+    match config.falloff {
+        Falloff::Ring {
+            edge_pow,
+            inner,
+            outer,
+        } => {
+            line("kind", "\"ring\"".to_string());
+            line("edge_pow", edge_pow.to_string());
+            line("inner", inner.to_string());
+            line("outer", outer.to_string());
+        }
+        Falloff::InverseDistance { radius, power } => {
+            line("kind", "\"inverse-distance\"".to_string());
+            line("radius", radius.to_string());
+            line("power", power.to_string());
+        }
+        Falloff::Gaussian { sigma } => {
+            line("kind", "\"gaussian\"".to_string());
+            line("sigma", sigma.to_string());
+        }
+        Falloff::Flat => line("kind", "\"flat\"".to_string()),
+    }
+
+    let composition = &config.composition;
+    line("[composition]", String::new());
+    line("output_width", composition.output_width.to_string());
+    line("output_height", composition.output_height.to_string());
+    line("margin", composition.margin.to_string());
+    line("top_clearance", composition.top_clearance.to_string());
+    line("trees", composition.trees.to_string());
+    line("anchor", format!("\"{}\"", composition.anchor.name()));
+    line("view_x", composition.view.pan.x.to_string());
+    line("view_y", composition.view.pan.y.to_string());
+    line("view_zoom", composition.view.zoom.to_string());
+    line("detail_zoom", composition.detail_zoom.to_string());
+
+    if let Some(pot) = &config.pot {
+        line("[pot]", String::new());
+        line("shape", format!("\"{}\"", pot.shape.name()));
+        line("radius", pot.radius.to_string());
+        line("height", pot.height.to_string());
+        line("taper", pot.taper.to_string());
+        line("tilt", pot.tilt.to_string());
+        line("color", hex(pot.color));
+        line("soil", hex(pot.soil));
+        line("decorated", pot.decorated.to_string());
+    }
+    if let Some(roots) = &config.roots {
+        line("[roots]", String::new());
+        line("depth", roots.depth.to_string());
+        line("spread", roots.spread.to_string());
+        if let Some(color) = roots.color {
+            line("color", hex(color));
+        }
+        line("soil", hex(roots.soil));
+    }
+    if let Some(ivy) = &config.ivy {
+        line("[ivy]", String::new());
+        line("reach", ivy.reach.to_string());
+        line("seed", ivy.seed.to_string());
+        line("leaf", hex(ivy.leaf));
+        line("stem", hex(ivy.stem));
+    }
+    if let Some(scenery) = &config.scenery {
+        line("[scenery]", String::new());
+        line("gradient", scenery.gradient.to_string());
+        line("sun", scenery.sun.to_string());
+        line("hills", scenery.hills.to_string());
+        line("hill_height", scenery.hill_height.to_string());
+        line("ground", scenery.ground.to_string());
+        line("hill_color", hex(scenery.hill_color));
+        line("grass", hex(scenery.grass));
+        line("soil", hex(scenery.soil));
+    }
+    if let Some(kusamono) = &config.kusamono {
+        line("[kusamono]", String::new());
+        line("height", kusamono.height.to_string());
+        if let Some(kind) = kusamono.kind {
+            line("kind", format!("\"{}\"", kind.name()));
+        }
+    }
+    if let Some(tokonoma) = &config.tokonoma {
+        line("[tokonoma]", String::new());
+        line("wall", hex(tokonoma.wall));
+        line("wood", hex(tokonoma.wood));
+        line("stand_height", tokonoma.stand_height.to_string());
+        line("scroll", tokonoma.scroll.to_string());
+    }
+    if let Some(volume) = &config.volume {
+        line("[volume]", String::new());
+        line("depth", volume.depth.to_string());
+        line("yaw", volume.yaw.to_degrees().to_string());
+    }
+
+    for leaf in &config.leaves {
+        line("[[leaf]]", String::new());
+        line("color", hex(leaf.color));
+        line("size", leaf.size.to_string());
+        line("probability", leaf.probability.to_string());
+    }
+    out
+}
+
+/// Writes the config and the program's keys as a new file in the save directory,
+/// next to the saved trees
+pub fn export_new(config: &Config, app: &impl AppKeys) -> io::Result<PathBuf> {
+    fs::create_dir_all(save::SAVE_DIR)?;
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let path = Path::new(save::SAVE_DIR).join(format!("bonsai-{}.toml", stamp));
+    fs::write(&path, write_with(config, app))?;
+    Ok(path)
+}
+
+/// Applies a single `key=value` override, `section.key` for keys in a section
+pub fn apply_override(assignment: &str, config: &mut Config) -> Result<(), String> {
+    apply_override_with(assignment, config, &mut ())
+}
+
+/// Like `apply_override`, with the program's own keys going to `app`
+pub fn apply_override_with(
+    assignment: &str,
+    config: &mut Config,
+    app: &mut impl AppKeys,
+) -> Result<(), String> {
+    let (path, value) = assignment
+        .split_once('=')
+        .ok_or_else(|| format!("expected key=value, found `{}`", assignment))?;
+    let (section, key) = path.trim().rsplit_once('.').unwrap_or(("", path.trim()));
+    // quotes are optional on the command line, so a value like 404040 may be meant as a color
+    let text = Value::Text(value.trim().trim_matches('"').to_string());
+    let mut assign = |value: &Value| match section {
+        "" => app
+            .set(key, value)
+            .unwrap_or_else(|| set(config, "", key, value)),
+        _ => set(config, section, key, value),
+    };
+    match Value::parse(value.trim()) {
+        Ok(parsed) => assign(&parsed).or_else(|err| assign(&text).map_err(|_| err)),
+        Err(_) => assign(&text),
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (idx, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..idx],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// A value of a config file
+pub enum Value {
+    Number(f64),
+    Bool(bool),
+    Text(String),
+}
+
+impl Value {
+    fn parse(value: &str) -> Result<Self, String> {
+        if let Some(text) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            return Ok(Value::Text(text.to_string()));
+        }
+        match value {
+            "true" => return Ok(Value::Bool(true)),
+            "false" => return Ok(Value::Bool(false)),
+            _ => {}
+        }
+        value
+            .replace('_', "")
+            .parse()
+            .map(Value::Number)
+            .map_err(|_| format!("invalid value `{}`", value))
+    }
+
+    pub fn number(&self) -> Result<f64, String> {
+        match self {
+            Value::Number(n) => Ok(*n),
+            _ => Err("expected a number".to_string()),
+        }
+    }
+    pub fn float(&self) -> Result<f32, String> {
+        self.number().map(|n| n as f32)
+    }
+    pub fn count(&self) -> Result<usize, String> {
+        let n = self.number()?;
+        if n < 0.0 || n.fract() != 0.0 {
+            return Err(format!("expected a whole number, found {}", n));
+        }
+        Ok(n as usize)
+    }
+    pub fn flag(&self) -> Result<bool, String> {
+        match self {
+            Value::Bool(b) => Ok(*b),
+            _ => Err("expected true or false".to_string()),
+        }
+    }
+    pub fn text(&self) -> Result<&str, String> {
+        match self {
+            Value::Text(text) => Ok(text),
+            _ => Err("expected a quoted string".to_string()),
+        }
+    }
+    pub fn color(&self) -> Result<Color, String> {
+        let hex = self.text()?;
+        Color::from_hex(hex.trim_start_matches('#'))
+            .map_err(|_| format!("invalid color `{}`, expected hex like \"8b6354\"", hex))
+    }
+    /// A number as it is, anything else as a seed phrase
+    pub fn seed(&self) -> Result<u64, String> {
+        match self {
+            Value::Number(n) if *n >= 0.0 && n.fract() == 0.0 => Ok(*n as u64),
+            Value::Text(phrase) => Ok(seed::parse(phrase)),
+            _ => Err("expected a whole number or a phrase".to_string()),
+        }
+    }
+}
+
+fn unknown(section: &str, key: &str) -> String {
+    match section {
+        "" => format!("unknown key `{}`", key),
+        _ => format!("unknown key `{}` in [{}]", key, section),
+    }
+}
+
+fn set(config: &mut Config, section: &str, key: &str, value: &Value) -> Result<(), String> {
+    match section {
+        "" => set_top(config, key, value),
+        "colors" => {
+            let color = value.color()?;
+            match key {
+                "leaf" => config.colors.leaf = color,
+                "new_branch" => config.colors.new_branch = color,
+                "old_branch" => config.colors.old_branch = color,
+                _ => return Err(unknown(section, key)),
+            }
+            Ok(())
+        }
+        "noise" => {
+            let noise = &mut config.noise;
+            match key {
+                "kind" => {
+                    noise.kind = NoiseKind::parse(value.text()?)
+                        .ok_or("expected \"simplex\", \"value\" or \"worley\"")?
+                }
+                "octaves" => noise.octaves = value.count()?,
+                "persistence" => noise.persistence = value.float()?,
+                "frequency" => noise.frequency = value.float()?,
+                _ => return Err(unknown(section, key)),
+            }
+            Ok(())
+        }
+        "falloff" => set_falloff(&mut config.falloff, key, value),
+        "composition" => {
+            let composition = &mut config.composition;
+            match key {
+                "output_width" => composition.output_width = value.float()?,
+                "output_height" => composition.output_height = value.float()?,
+                "margin" => composition.margin = value.float()?,
+                "top_clearance" => composition.top_clearance = value.float()?,
+                "trees" => composition.trees = value.count()?.max(1),
+                "view_x" => composition.view.pan.x = value.float()?,
+                "view_y" => composition.view.pan.y = value.float()?,
+                "view_zoom" => composition.view.zoom = value.float()?.max(0.01),
+                "detail_zoom" => composition.detail_zoom = value.float()?.max(0.0),
+                "anchor" => {
+                    composition.anchor = Anchor::parse(value.text()?)
+                        .ok_or("expected \"center\", \"left-third\" or \"right-third\"")?
+                }
+                _ => return Err(unknown(section, key)),
+            }
+            Ok(())
+        }
+        "pot" => {
+            // any pot key plants the tree in a pot
+            let pot = config.pot.get_or_insert_with(pot::Pot::default);
+            match key {
+                // a shape comes with its own proportions, keys after it can still change them
+                "shape" => {
+                    let shape = pot::Shape::parse(value.text()?).ok_or(
+                        "expected \"oval\", \"rectangle\", \"drum\", \"cascade\" or \"tray\"",
+                    )?;
+                    *pot = pot::Pot {
+                        tilt: pot.tilt,
+                        color: pot.color,
+                        soil: pot.soil,
+                        decorated: pot.decorated,
+                        ..pot::Pot::of_shape(shape)
+                    };
+                }
+                "radius" => pot.radius = value.float()?,
+                "height" => pot.height = value.float()?,
+                "taper" => pot.taper = value.float()?,
+                "tilt" => pot.tilt = value.float()?,
+                "color" => pot.color = value.color()?,
+                "soil" => pot.soil = value.color()?,
+                "decorated" => pot.decorated = value.flag()?,
+                _ => return Err(unknown(section, key)),
+            }
+            Ok(())
+        }
+        "roots" => {
+            // any roots key grows roots
+            let roots = config.roots.get_or_insert_with(roots::Roots::default);
+            match key {
+                "depth" => roots.depth = value.float()?,
+                "spread" => roots.spread = value.float()?,
+                "color" => roots.color = Some(value.color()?),
+                "soil" => roots.soil = value.color()?,
+                _ => return Err(unknown(section, key)),
+            }
+            Ok(())
+        }
+        "ivy" => {
+            // any ivy key lets the ivy climb
+            let ivy = config.ivy.get_or_insert_with(ivy::Ivy::default);
+            match key {
+                "reach" => ivy.reach = value.float()?,
+                "seed" => ivy.seed = value.seed()?,
+                "leaf" => ivy.leaf = value.color()?,
+                "stem" => ivy.stem = value.color()?,
+                _ => return Err(unknown(section, key)),
+            }
+            Ok(())
+        }
+        "kusamono" => {
+            // any kusamono key sets the accent plant beside the tree
+            let kusamono = config
+                .kusamono
+                .get_or_insert_with(kusamono::Kusamono::default);
+            match key {
+                "height" => kusamono.height = value.float()?,
+                "kind" => {
+                    kusamono.kind = Some(
+                        kusamono::Kind::parse(value.text()?)
+                            .ok_or("expected \"grass\" or \"flowering\"")?,
+                    )
+                }
+                _ => return Err(unknown(section, key)),
+            }
+            Ok(())
+        }
+        "tokonoma" => {
+            // any tokonoma key shows the tree in the alcove, the pot and the sky are keys of their own
+            let tokonoma = config
+                .tokonoma
+                .get_or_insert_with(tokonoma::Tokonoma::default);
+            match key {
+                "wall" => tokonoma.wall = value.color()?,
+                "wood" => tokonoma.wood = value.color()?,
+                "stand_height" => tokonoma.stand_height = value.float()?,
+                "scroll" => tokonoma.scroll = value.flag()?,
+                _ => return Err(unknown(section, key)),
+            }
+            Ok(())
+        }
+        "volume" => {
+            // any volume key grows the crown in depth
+            let volume = config.volume.get_or_insert_with(volume::Volume::default);
+            match key {
+                "depth" => volume.depth = value.float()?,
+                "yaw" => volume.yaw = value.float()?.to_radians(),
+                _ => return Err(unknown(section, key)),
+            }
+            Ok(())
+        }
+        "scenery" => {
+            // any scenery key draws the scenery
+            let scenery = config.scenery.get_or_insert_with(scenery::Scenery::default);
+            match key {
+                "gradient" => scenery.gradient = value.float()?,
+                "sun" => scenery.sun = value.flag()?,
+                "hills" => scenery.hills = value.count()?,
+                "hill_height" => scenery.hill_height = value.float()?,
+                "ground" => scenery.ground = value.float()?,
+                "hill_color" => scenery.hill_color = value.color()?,
+                "grass" => scenery.grass = value.color()?,
+                "soil" => scenery.soil = value.color()?,
+                _ => return Err(unknown(section, key)),
+            }
+            Ok(())
+        }
+        _ => Err(format!("unknown section [{}]", section)),
+    }
+}
+
+fn set_top(config: &mut Config, key: &str, value: &Value) -> Result<(), String> {
+    match key {
+        "seed" => config.seed = value.seed()?,
+        "width" => config.width = value.float()?,
+        "height" => config.height = value.float()?,
+        "origin_x" => config.origin.x = value.float()?,
+        "origin_y" => config.origin.y = value.float()?,
+        "num_points" => config.num_points = value.count()?,
+        "attraction_dist" => config.attraction_dist = value.float()?,
+        "kill_dist" => config.kill_dist = value.float()?,
+        "grow_dist" => config.grow_dist = value.float()?,
+        "node_min_dist" => config.node_min_dist = value.float()?,
+        "subdivide_max_length" => config.subdivide_max_length = value.float()?,
+        "max_children" => config.max_children = value.count()?,
+        "max_depth" => config.max_depth = value.count()?,
+        "min_y_growth" => config.min_y_growth = value.float()?,
+        "parent_dir_factor" => config.parent_dir_factor = value.float()?,
+        "tropism_x" => config.tropism.x = value.float()?,
+        "tropism_y" => config.tropism.y = value.float()?,
+        "weight_display_pow" => config.weight_display_pow = value.float()?,
+        "radius_taper_cap" => config.radius_taper_cap = value.float()?,
+        "canopy_memory" => config.canopy_memory = value.float()?,
+        "prune_pow" => config.prune_pow = value.float()?,
+        "prune_size_ratio" => config.prune_size_ratio = value.float()?,
+        "leaf_max_width" => config.leaf_max_width = value.float()?,
+        "sprout_max_width" => config.sprout_max_width = value.float()?,
+        "leaf_size" => config.leaf_size = value.float()?,
+        "leaf_depth_jitter" => config.leaf_depth_jitter = value.float()?,
+        "foliage" => config.foliage = value.float()?.clamp(0.0, 1.0),
+        "node_depth_change" => config.node_depth_change = value.float()?,
+        "node_depth_max" => config.node_depth_max = value.count()?,
+        "pixel_size" => config.pixel_size = value.count()?.max(1),
+        "pixel_aspect" => config.pixel_aspect = value.float()?,
+        "sky" => config.sky = value.color()?,
+        "sun_angle" => config.sun_angle = value.float()?.to_radians(),
+        "sun_elevation" => config.sun_elevation = value.float()?.clamp(0.0, 90.0).to_radians(),
+        "ambient" => config.ambient = value.float()?.clamp(0.0, 1.0),
+        "ambient_sky" => config.ambient_sky = value.color()?,
+        "ambient_ground" => config.ambient_ground = value.color()?,
+        "sun_color" => config.sun_color = value.color()?,
+        "light_floor" => config.light_floor = value.float()?.clamp(0.0, 1.0),
+        "growth_speed" => config.growth_speed = value.count()?,
+        "tick_rate" => config.tick_rate = value.float()?.max(1.0),
+        "target_fps" => config.target_fps = value.count()? as u32,
+        "vsync" => config.vsync = value.flag()?,
+        "fixed_step" => config.fixed_step = value.flag()?,
+        "idle" => config.idle = value.flag()?,
+        "season_length" => config.season_length = value.float()?,
+        "day_length" => config.day_length = value.float()?,
+        "regrow_after" => config.regrow_after = value.float()?,
+        "regrow_share" => config.regrow_share = value.float()?,
+        "high_contrast" => config.high_contrast = value.flag()?,
+        "palette" => {
+            let name = value.text()?;
+            match palette::Palette::parse(name) {
+                Some(preset) => preset.apply(config),
+                None => {
+                    let text = std::fs::read_to_string(name).map_err(|_| {
+                        let names = palette::Palette::ALL.map(|preset| preset.name());
+                        format!("expected a palette file or one of {}", names.join(", "))
+                    })?;
+                    palette::apply_file(&text, config)?
+                }
+            }
+        }
+        "overflow" => {
+            config.overflow =
+                Overflow::parse(value.text()?).ok_or("expected \"clip\", \"zoom\" or \"scroll\"")?
+        }
+        "depth_fog" => config.depth_fog = value.float()?,
+        "canopy_occlusion" => config.canopy_occlusion = value.float()?,
+        "self_shadow" => config.self_shadow = value.float()?,
+        "ambient_occlusion" => config.ambient_occlusion = value.float()?,
+        "ambient_occlusion_radius" => config.ambient_occlusion_radius = value.count()?,
+        "grain" => config.grain = value.float()?,
+        "shades" => config.shades = value.count()?,
+        "shadow_tint" => config.shadow_tint = value.color()?,
+        "ramp" => {
+            config.ramp = value
+                .text()?
+                .split_whitespace()
+                .map(|hex| Color::from_hex(hex.trim_start_matches('#')))
+                .collect::<Result<_, _>>()
+                .map_err(|_| "expected colors in hex like \"0f380f 306230\"")?
+        }
+        "fruit_density" => config.fruit_density = value.float()?,
+        "fruit_color" => config.fruit_color = value.color()?,
+        "ground_shadow" => config.ground_shadow = value.float()?,
+        "wind" => config.wind = value.float()?,
+        "wind_stiffness" => config.wind_stiffness = value.float()?,
+        "branch_strength" => config.branch_strength = value.float()?,
+        "storm_every" => config.storm_every = value.float()?,
+        "storm_wind" => config.storm_wind = value.float()?,
+        "precipitation" => {
+            config.precipitation = match value.text()? {
+                "none" => None,
+                name => Some(
+                    Precipitation::parse(name).ok_or("expected \"snow\", \"rain\" or \"none\"")?,
+                ),
+            }
+        }
+        "precipitation_intensity" => config.precipitation_intensity = value.float()?,
+        "snow_melt" => config.snow_melt = value.float()?,
+        "nest_chance" => config.nest_chance = value.float()?,
+        "hatch_after" => config.hatch_after = value.float()?,
+        "aging_every" => config.aging_every = value.float()?,
+        "aging_severity" => config.aging_severity = value.float()?.clamp(0.0, 1.0),
+        "deadwood_color" => config.deadwood_color = value.color()?,
+        "night_sky" => config.night_sky = value.flag()?,
+        "nameplate" => config.nameplate = value.flag()?,
+        "leaf_sprites" => config.leaf_sprites = Some(sprites::LeafSprites::parse(value.text()?)),
+        "background" => {
+            let path = value.text()?.into();
+            match &mut config.background {
+                Some(background) => background.path = path,
+                None => {
+                    config.background = Some(background::Background {
+                        path,
+                        fit: background::Fit::Contain,
+                        pixelate: false,
+                    })
+                }
+            }
+        }
+        // after `background`, which they change
+        "background_fit" => {
+            let fit =
+                background::Fit::parse(value.text()?).ok_or("expected \"contain\" or \"cover\"")?;
+            config
+                .background
+                .as_mut()
+                .ok_or("expected `background` first")?
+                .fit = fit;
+        }
+        "pixelate_background" => {
+            let pixelate = value.flag()?;
+            config
+                .background
+                .as_mut()
+                .ok_or("expected `background` first")?
+                .pixelate = pixelate;
+        }
+        "forest" => config.forest = Some(value.count()?.max(1)),
+        _ => return Err(unknown("", key)),
+    }
+    Ok(())
+}
+
+fn set_leaf(leaf: &mut LeafType, key: &str, value: &Value) -> Result<(), String> {
+    match key {
+        "color" => leaf.color = value.color()?,
+        "size" => leaf.size = value.float()?,
+        "probability" => {
+            leaf.probability = value.float()?;
+            if leaf.probability <= 0.0 {
+                return Err("the probability of a leaf has to be above 0".to_string());
+            }
+        }
+        _ => return Err(unknown("leaf", key)),
+    }
+    Ok(())
+}
+
+/// `kind` switches to another falloff with its default parameters, which the other keys
+/// then change, e.g. `kind = "gaussian"` followed by `sigma = 0.4`
+fn set_falloff(falloff: &mut Falloff, key: &str, value: &Value) -> Result<(), String> {
+    if key == "kind" {
+        *falloff = match value.text()? {
+            "ring" => Falloff::default(),
+            "inverse-distance" => Falloff::InverseDistance {
+                radius: 0.5,
+                power: 2.0,
+            },
+            "gaussian" => Falloff::Gaussian { sigma: 0.5 },
+            "flat" => Falloff::Flat,
+            _ => {
+                return Err(
+                    "expected \"ring\", \"inverse-distance\", \"gaussian\" or \"flat\"".to_string(),
+                )
+            }
+        };
+        return Ok(());
+    }
+    let param = match (falloff, key) {
+        (Falloff::Ring { edge_pow, .. }, "edge_pow") => edge_pow,
+        (Falloff::Ring { inner, .. }, "inner") => inner,
+        (Falloff::Ring { outer, .. }, "outer") => outer,
+        (Falloff::InverseDistance { radius, .. }, "radius") => radius,
+        (Falloff::InverseDistance { power, .. }, "power") => power,
+        (Falloff::Gaussian { sigma }, "sigma") => sigma,
+        _ => return Err(format!("`{}` doesn't apply to this falloff kind", key)),
+    };
+    *param = value.float()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn written_configs_read_back_the_same() {
+        let mut config = Config {
+            seed: u64::MAX - 7,
+            num_points: 1234,
+            falloff: Falloff::Gaussian { sigma: 0.3 },
+            pot: Some(pot::Pot::of_shape(pot::Shape::Cascade)),
+            roots: Some(roots::Roots {
+                color: Some(Color::BLACK),
+                ..roots::Roots::default()
+            }),
+            volume: Some(volume::Volume::default()),
+            ..Config::default()
+        };
+        config.composition.view.zoom = 1.5;
+        tokonoma::Tokonoma::default().apply(&mut config);
+        config.kusamono.as_mut().unwrap().kind = Some(kusamono::Kind::Flowering);
+        config.background = Some(background::Background {
+            path: "shore.png".into(),
+            fit: background::Fit::Cover,
+            pixelate: true,
+        });
+        let mut read = Config::default();
+        apply(&write(&config), &mut read).unwrap();
+        // angles go through degrees
+        assert!((read.sun_angle - config.sun_angle).abs() < 1e-5);
+        read.sun_angle = config.sun_angle;
+        read.sun_elevation = config.sun_elevation;
+        assert_eq!(write(&read), write(&config));
+        assert_eq!(read.seed, config.seed);
+        assert!(read.tokonoma.is_some() && read.kusamono.is_some_and(|k| k.kind.is_some()));
+        assert!(read
+            .background
+            .is_some_and(|b| b.pixelate && b.fit == background::Fit::Cover));
+    }
+}
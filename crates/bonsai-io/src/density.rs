@@ -0,0 +1,53 @@
+//! The density the attraction points are scattered by, as a grayscale PNG to touch up
+//! in an image editor and read back in
+//!
+//! One image pixel is one world unit with the top of the world at the top of the image.
+//! White is the densest part of the field, black gets no points at all.
+
+use std::{
+    io::{self, Seek, Write},
+    path::Path,
+};
+
+use crate::*;
+use bonsai_engine::density::DensityMap;
+
+/// Encodes the density field of `config` as a grayscale PNG, scaled so its densest unit is white
+pub fn write_png(config: &Config, out: &mut (impl Write + Seek)) -> io::Result<()> {
+    let density = SimplexDensityPRG::new(config);
+    let weights = density.weights();
+    let (width, height) = (weights.len(), weights.first().map_or(0, Vec::len));
+    let max = weights.iter().flatten().fold(0.0f32, |max, w| max.max(*w));
+    let scale = if max > 0.0 { 255.0 / max } else { 0.0 };
+    let image = image::GrayImage::from_fn(width as u32, height as u32, |x, y| {
+        let value = weights[x as usize][height - 1 - y as usize] * scale;
+        image::Luma([value.round() as u8])
+    });
+    image
+        .write_to(out, image::ImageOutputFormat::Png)
+        .map_err(io::Error::other)
+}
+
+/// Reads a density field written by `write_png`, or painted from scratch
+///
+/// Colors count by their brightness and transparent pixels as black.
+pub fn load(path: &Path) -> io::Result<DensityMap> {
+    let image = image::open(path)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
+        .to_luma_alpha8();
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let mut values = Vec::with_capacity(width * height);
+    for y in (0..height).rev() {
+        for x in 0..width {
+            let [luma, alpha] = image.get_pixel(x as u32, y as u32).0;
+            values.push(luma as f32 / 255.0 * alpha as f32 / 255.0);
+        }
+    }
+    if !values.iter().any(|value| *value > 0.0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is black all over, no point could grow", path.display()),
+        ));
+    }
+    Ok(DensityMap::new(width, height, values).expect("one value per pixel"))
+}
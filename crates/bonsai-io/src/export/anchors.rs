@@ -0,0 +1,209 @@
+//! Where things are on a rendered image, for placing captions or compositing without
+//! detecting the tree again. Written as JSON next to the image, in image pixels from the top left.
+
+use std::io::{self, Write};
+
+use crate::composition::Slot;
+use crate::*;
+use bonsai_render::caption;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    fn from_corners(left: f32, top: f32, right: f32, bottom: f32) -> Self {
+        let (left, top) = (left.floor() as i32, top.floor() as i32);
+        Self {
+            x: left,
+            y: top,
+            width: (right.ceil() as i32 - left).max(0),
+            height: (bottom.ceil() as i32 - top).max(0),
+        }
+    }
+
+    fn area(&self) -> i32 {
+        self.width * self.height
+    }
+
+    fn json(&self) -> String {
+        format!(
+            "{{\"x\": {}, \"y\": {}, \"width\": {}, \"height\": {}}}",
+            self.x, self.y, self.width, self.height
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Anchors {
+    pub width: usize,
+    pub height: usize,
+    /// foot of the trunk
+    pub root: (i32, i32),
+    /// everything with leaves on it
+    pub canopy: Rect,
+    /// the whole tree including the trunk
+    pub tree: Rect,
+    /// row the trunk stands on
+    pub ground_y: i32,
+    /// line the trunk leaves the pot at, trees without a pot have none
+    pub pot_rim: Option<(i32, i32, i32)>,
+    /// largest area beside or above the tree that is free for text
+    pub text_safe: Rect,
+    /// the tree's name, see `name`
+    pub name: String,
+    /// where the plate with the name goes below the tree, with `Config::nameplate` on
+    pub nameplate: Option<Rect>,
+}
+
+impl Anchors {
+    /// Anchors of the tree drawn into its slot of a `width` × `height` image
+    pub fn find(tree: &Tree, slot: &Slot, width: usize, height: usize) -> Self {
+        let config = &tree.config;
+        let root = slot.to_screen(config.origin);
+        // leaf blobs spread around their node and are drawn up to 1.5 times their size
+        let largest_leaf = config.leaves.iter().map(|l| l.size).fold(0.0, f32::max);
+        let leaf_reach = config.leaf_size + largest_leaf * 1.5 * config.pixel_size as f32;
+
+        let bounds = |leaves_only: bool| {
+            let mut min = Vector2::new(f32::INFINITY, f32::INFINITY);
+            let mut max = Vector2::new(f32::NEG_INFINITY, f32::NEG_INFINITY);
+            for node in tree.nodes().filter(|node| node.alive) {
+                let leaf = tree.radius_of(&node) < config.leaf_max_width;
+                if leaves_only && !leaf {
+                    continue;
+                }
+                let reach = if leaf {
+                    leaf_reach
+                } else {
+                    tree.radius_of(&node)
+                };
+                let pos = node.pos.vec();
+                for corner in [
+                    Vector2::new(pos.x - reach, pos.y + reach),
+                    Vector2::new(pos.x + reach, pos.y - reach),
+                ] {
+                    let corner = slot.to_screen(corner);
+                    min = Vector2::new(min.x.min(corner.x), min.y.min(corner.y));
+                    max = Vector2::new(max.x.max(corner.x), max.y.max(corner.y));
+                }
+            }
+            if min.x > max.x {
+                // nothing there, an empty box at the root
+                return Rect::from_corners(root.x, root.y, root.x, root.y);
+            }
+            Rect::from_corners(
+                min.x.max(0.0),
+                min.y.max(0.0),
+                max.x.min(width as f32),
+                max.y.min(height as f32),
+            )
+        };
+        let (canopy, whole) = (bounds(true), bounds(false));
+
+        // keep a few tree pixels of distance to the tree
+        let gap = (config.pixel_size as f32 * slot.zoom * 4.0) as i32;
+        let (w, h) = (width as i32, height as i32);
+        let text_safe = [
+            Rect {
+                x: 0,
+                y: 0,
+                width: w,
+                height: whole.y - gap,
+            },
+            Rect {
+                x: 0,
+                y: 0,
+                width: whole.x - gap,
+                height: h,
+            },
+            Rect {
+                x: whole.x + whole.width + gap,
+                y: 0,
+                width: w - (whole.x + whole.width + gap),
+                height: h,
+            },
+        ]
+        .into_iter()
+        .filter(|rect| rect.width > 0 && rect.height > 0)
+        .max_by_key(Rect::area)
+        .unwrap_or_default();
+
+        let name = name::of(tree);
+        // centered below the tree at the bottom of the image, in pixels as large as the tree's
+        // unless that makes it wider than three quarters of the image
+        let nameplate = config.nameplate.then(|| {
+            let widest = w * 3 / 4 / caption::plate_size(&name, 1).0;
+            let scale = ((config.pixel_size as f32 * slot.zoom) as i32)
+                .min(widest)
+                .max(1);
+            let (plate_width, plate_height) = caption::plate_size(&name, scale);
+            Rect {
+                x: (root.x.round() as i32 - plate_width / 2).clamp(0, (w - plate_width).max(0)),
+                y: h - plate_height - 2 * scale,
+                width: plate_width,
+                height: plate_height,
+            }
+        });
+
+        Self {
+            width,
+            height,
+            root: (root.x.round() as i32, root.y.round() as i32),
+            canopy,
+            tree: whole,
+            ground_y: root.y.round() as i32,
+            pot_rim: config.pot.map(|pot| {
+                let (left, right, y) = pot.rim(config.origin);
+                let (left, right) = (
+                    slot.to_screen(Vector2::new(left, y)),
+                    slot.to_screen(Vector2::new(right, y)),
+                );
+                (
+                    left.x.round() as i32,
+                    right.x.round() as i32,
+                    left.y.round() as i32,
+                )
+            }),
+            text_safe,
+            name,
+            nameplate,
+        }
+    }
+}
+
+pub fn write_json(anchors: &Anchors, out: &mut impl Write) -> io::Result<()> {
+    let pot_rim = match anchors.pot_rim {
+        Some((left, right, y)) => {
+            format!("{{\"left\": {}, \"right\": {}, \"y\": {}}}", left, right, y)
+        }
+        None => "null".to_string(),
+    };
+    writeln!(out, "{{")?;
+    writeln!(
+        out,
+        "  \"image\": {{\"width\": {}, \"height\": {}}},",
+        anchors.width, anchors.height
+    )?;
+    writeln!(
+        out,
+        "  \"root\": {{\"x\": {}, \"y\": {}}},",
+        anchors.root.0, anchors.root.1
+    )?;
+    writeln!(out, "  \"canopy\": {},", anchors.canopy.json())?;
+    writeln!(out, "  \"tree\": {},", anchors.tree.json())?;
+    writeln!(out, "  \"ground_y\": {},", anchors.ground_y)?;
+    writeln!(out, "  \"pot_rim\": {},", pot_rim)?;
+    let nameplate = anchors
+        .nameplate
+        .as_ref()
+        .map_or("null".to_string(), Rect::json);
+    writeln!(out, "  \"text_safe\": {},", anchors.text_safe.json())?;
+    writeln!(out, "  \"name\": \"{}\",", anchors.name)?;
+    writeln!(out, "  \"nameplate\": {}", nameplate)?;
+    writeln!(out, "}}")
+}
@@ -0,0 +1,361 @@
+//! Cross-stitch chart export: the pixel render reduced to DMC thread colors, as a PDF
+
+use std::io::{self, Write};
+
+use bonsai_engine::Color;
+
+/// DMC thread code, name and approximate color
+const DMC: &[(&str, &str, [u8; 3])] = &[
+    ("310", "Black", [0, 0, 0]),
+    ("B5200", "Snow White", [255, 255, 255]),
+    ("Ecru", "Ecru", [240, 234, 218]),
+    ("3799", "Very Dark Pewter Gray", [66, 66, 66]),
+    ("413", "Dark Pewter Gray", [86, 86, 86]),
+    ("317", "Pewter Gray", [108, 108, 108]),
+    ("414", "Dark Steel Gray", [140, 140, 140]),
+    ("318", "Light Steel Gray", [171, 171, 171]),
+    ("415", "Pearl Gray", [211, 211, 214]),
+    ("762", "Very Light Pearl Gray", [236, 236, 236]),
+    ("646", "Dark Beaver Gray", [135, 134, 123]),
+    ("648", "Light Beaver Gray", [188, 180, 172]),
+    ("815", "Medium Garnet", [135, 7, 31]),
+    ("498", "Dark Red", [167, 19, 43]),
+    ("321", "Red", [199, 43, 59]),
+    ("666", "Bright Red", [227, 29, 66]),
+    ("3350", "Ultra Dark Dusty Rose", [188, 67, 101]),
+    ("335", "Rose", [238, 84, 110]),
+    ("899", "Medium Rose", [242, 118, 136]),
+    ("3705", "Dark Melon", [255, 121, 140]),
+    ("3733", "Dusty Rose", [232, 135, 155]),
+    ("3706", "Medium Melon", [255, 173, 188]),
+    ("3326", "Light Rose", [251, 173, 180]),
+    ("776", "Medium Pink", [252, 176, 185]),
+    ("3354", "Light Dusty Rose", [228, 166, 172]),
+    ("3708", "Light Melon", [255, 203, 213]),
+    ("818", "Baby Pink", [255, 223, 217]),
+    ("819", "Light Baby Pink", [255, 238, 235]),
+    ("601", "Dark Cranberry", [209, 40, 106]),
+    ("603", "Cranberry", [255, 164, 190]),
+    ("605", "Very Light Cranberry", [255, 192, 205]),
+    ("3607", "Light Plum", [197, 73, 137]),
+    ("3609", "Ultra Light Plum", [244, 174, 213]),
+    ("208", "Very Dark Lavender", [131, 91, 139]),
+    ("210", "Medium Lavender", [195, 159, 195]),
+    ("211", "Light Lavender", [227, 203, 227]),
+    ("340", "Medium Blue Violet", [173, 167, 199]),
+    ("809", "Delft Blue", [148, 168, 198]),
+    ("800", "Pale Delft Blue", [192, 204, 222]),
+    ("517", "Dark Wedgwood", [59, 118, 143]),
+    ("3325", "Light Baby Blue", [184, 210, 230]),
+    ("3761", "Light Sky Blue", [172, 216, 226]),
+    ("747", "Very Light Sky Blue", [229, 252, 253]),
+    ("3813", "Light Blue Green", [178, 212, 189]),
+    ("890", "Ultra Dark Pistachio Green", [23, 73, 35]),
+    ("319", "Very Dark Pistachio Green", [32, 95, 46]),
+    ("367", "Dark Pistachio Green", [97, 122, 82]),
+    ("320", "Medium Pistachio Green", [105, 136, 90]),
+    ("368", "Light Pistachio Green", [166, 194, 152]),
+    ("699", "Green", [5, 101, 23]),
+    ("700", "Bright Green", [7, 115, 27]),
+    ("701", "Light Green", [63, 143, 41]),
+    ("702", "Kelly Green", [71, 167, 47]),
+    ("703", "Chartreuse", [123, 181, 71]),
+    ("704", "Bright Chartreuse", [158, 207, 52]),
+    ("904", "Very Dark Parrot Green", [85, 120, 34]),
+    ("905", "Dark Parrot Green", [98, 138, 40]),
+    ("906", "Medium Parrot Green", [127, 179, 53]),
+    ("907", "Light Parrot Green", [199, 230, 102]),
+    ("987", "Dark Forest Green", [88, 113, 65]),
+    ("988", "Medium Forest Green", [115, 139, 91]),
+    ("989", "Forest Green", [141, 166, 117]),
+    ("3347", "Medium Yellow Green", [113, 130, 80]),
+    ("3348", "Light Yellow Green", [204, 217, 177]),
+    ("470", "Light Avocado Green", [148, 171, 79]),
+    ("471", "Very Light Avocado Green", [174, 191, 121]),
+    ("472", "Ultra Light Avocado Green", [216, 228, 152]),
+    ("3819", "Light Moss Green", [224, 232, 104]),
+    ("727", "Very Light Topaz", [255, 241, 175]),
+    ("744", "Pale Yellow", [255, 231, 147]),
+    ("726", "Light Topaz", [253, 215, 85]),
+    ("725", "Medium Light Topaz", [255, 200, 64]),
+    ("743", "Medium Yellow", [254, 211, 118]),
+    ("742", "Light Tangerine", [255, 191, 87]),
+    ("741", "Medium Tangerine", [255, 163, 43]),
+    ("740", "Tangerine", [255, 139, 0]),
+    ("947", "Burnt Orange", [255, 123, 77]),
+    ("946", "Medium Burnt Orange", [235, 99, 7]),
+    ("900", "Dark Burnt Orange", [209, 88, 7]),
+    ("921", "Copper", [198, 98, 24]),
+    ("920", "Medium Copper", [172, 84, 20]),
+    ("919", "Red Copper", [166, 69, 16]),
+    ("918", "Dark Red Copper", [130, 52, 10]),
+    ("301", "Medium Mahogany", [179, 95, 43]),
+    ("400", "Dark Mahogany", [143, 67, 15]),
+    ("300", "Very Dark Mahogany", [111, 47, 0]),
+    ("3371", "Black Brown", [30, 17, 8]),
+    ("938", "Ultra Dark Coffee Brown", [54, 31, 14]),
+    ("898", "Very Dark Coffee Brown", [73, 42, 19]),
+    ("801", "Dark Coffee Brown", [101, 57, 25]),
+    ("433", "Medium Brown", [122, 69, 31]),
+    ("434", "Light Brown", [152, 94, 51]),
+    ("435", "Very Light Brown", [184, 119, 72]),
+    ("436", "Tan", [203, 144, 81]),
+    ("437", "Light Tan", [228, 187, 142]),
+    ("738", "Very Light Tan", [236, 204, 158]),
+    ("739", "Ultra Very Light Tan", [248, 228, 200]),
+    ("3772", "Very Dark Desert Sand", [160, 108, 80]),
+    ("839", "Dark Beige Brown", [103, 85, 65]),
+    ("840", "Medium Beige Brown", [154, 124, 92]),
+    ("841", "Light Beige Brown", [182, 155, 126]),
+    ("842", "Very Light Beige Brown", [209, 186, 161]),
+    ("3790", "Ultra Dark Beige Gray", [127, 106, 85]),
+    ("3862", "Dark Mocha Beige Brown", [138, 110, 78]),
+    ("3863", "Medium Mocha Beige Brown", [164, 131, 92]),
+];
+
+/// Chart symbols, all of them available in the standard PDF Helvetica font
+const SYMBOLS: &[&str] = &[
+    "X", "O", "+", "*", "#", "@", "%", "&", "=", "/", "<", ">", "^", "~", "?", "$", "S", "T", "V",
+    "W", "Z", "A", "H", "K", "M", "N", "E", "U", "Y", "L", "C", "D",
+];
+
+#[derive(Copy, Clone, Debug)]
+pub struct CrossStitchOptions {
+    /// maximum amount of thread colors used in the chart
+    pub threads: usize,
+    /// size of one stitch in the chart, in points
+    pub cell_size: f32,
+}
+
+impl Default for CrossStitchOptions {
+    fn default() -> Self {
+        Self {
+            threads: 16,
+            cell_size: 8.0,
+        }
+    }
+}
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> i32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+    d(a[0], b[0]) + d(a[1], b[1]) + d(a[2], b[2])
+}
+
+/// Index into `DMC` of the closest thread among `candidates`
+fn closest_thread(color: [u8; 3], candidates: impl Iterator<Item = usize>) -> usize {
+    candidates
+        .min_by_key(|idx| distance(color, DMC[*idx].2))
+        .unwrap()
+}
+
+/// Writes the chart for the pixels (indexed `[x][y]`, y going up) as a single page PDF.
+/// Transparent pixels are left unstitched.
+pub fn write(
+    pixels: &[Vec<Color>],
+    options: &CrossStitchOptions,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let rgb = |c: &Color| [c.r, c.g, c.b];
+    let stitched = |c: &Color| c.a > 127;
+
+    // crop to the stitched area
+    let mut bounds: Option<(usize, usize, usize, usize)> = None;
+    for (x, column) in pixels.iter().enumerate() {
+        for (y, color) in column.iter().enumerate() {
+            if stitched(color) {
+                let (x0, y0, x1, y1) = bounds.unwrap_or((x, y, x, y));
+                bounds = Some((x0.min(x), y0.min(y), x1.max(x), y1.max(y)));
+            }
+        }
+    }
+    let Some((x0, y0, x1, y1)) = bounds else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "nothing to stitch",
+        ));
+    };
+
+    // match every pixel to the full thread table, then keep the most used threads
+    let mut usage = vec![0usize; DMC.len()];
+    for column in &pixels[x0..=x1] {
+        for color in column[y0..=y1].iter().filter(|c| stitched(c)) {
+            usage[closest_thread(rgb(color), 0..DMC.len())] += 1;
+        }
+    }
+    let mut threads: Vec<usize> = (0..DMC.len()).filter(|idx| usage[*idx] > 0).collect();
+    threads.sort_by_key(|idx| std::cmp::Reverse(usage[*idx]));
+    threads.truncate(options.threads.clamp(1, SYMBOLS.len()));
+
+    let (cols, rows) = (x1 - x0 + 1, y1 - y0 + 1);
+    let mut chart = vec![vec![None; rows]; cols];
+    let mut counts = vec![0usize; threads.len()];
+    for (col, x) in (x0..=x1).enumerate() {
+        for (row, y) in (y0..=y1).rev().enumerate() {
+            let color = &pixels[x][y];
+            if stitched(color) {
+                let thread = closest_thread(rgb(color), threads.iter().copied());
+                let slot = threads.iter().position(|t| *t == thread).unwrap();
+                counts[slot] += 1;
+                chart[col][row] = Some(slot);
+            }
+        }
+    }
+
+    let cell = options.cell_size;
+    let margin = 36.0;
+    let legend_line = 14.0;
+    let chart_w = cols as f32 * cell;
+    let chart_h = rows as f32 * cell;
+    let page_w = (chart_w + 2.0 * margin).max(300.0);
+    let legend_h = threads.len() as f32 * legend_line + legend_line;
+    let page_h = chart_h + legend_h + 3.0 * margin;
+
+    let mut content = String::new();
+    let top = page_h - margin;
+    let color_op = |c: [u8; 3]| {
+        format!(
+            "{:.3} {:.3} {:.3}",
+            c[0] as f32 / 255.0,
+            c[1] as f32 / 255.0,
+            c[2] as f32 / 255.0
+        )
+    };
+    let symbol_color = |c: [u8; 3]| {
+        let luma = 0.299 * c[0] as f32 + 0.587 * c[1] as f32 + 0.114 * c[2] as f32;
+        if luma > 128.0 {
+            "0 0 0"
+        } else {
+            "1 1 1"
+        }
+    };
+    let text = |content: &mut String, x: f32, y: f32, size: f32, s: &str| {
+        let escaped = s
+            .replace('\\', "\\\\")
+            .replace('(', "\\(")
+            .replace(')', "\\)");
+        content.push_str(&format!(
+            "BT /F1 {} Tf {:.2} {:.2} Td ({}) Tj ET\n",
+            size, x, y, escaped
+        ));
+    };
+
+    for (col, column) in chart.iter().enumerate() {
+        for (row, slot) in column.iter().enumerate() {
+            if let Some(slot) = slot {
+                let c = DMC[threads[*slot]].2;
+                let (x, y) = (margin + col as f32 * cell, top - (row + 1) as f32 * cell);
+                content.push_str(&format!(
+                    "{} rg {:.2} {:.2} {:.2} {:.2} re f\n",
+                    color_op(c),
+                    x,
+                    y,
+                    cell,
+                    cell
+                ));
+                content.push_str(&format!("{} rg\n", symbol_color(c)));
+                text(
+                    &mut content,
+                    x + cell * 0.2,
+                    y + cell * 0.2,
+                    cell * 0.8,
+                    SYMBOLS[*slot],
+                );
+            }
+        }
+    }
+
+    // grid, with a thicker line every ten stitches
+    for (thick, width) in [(false, 0.2), (true, 0.8)] {
+        content.push_str(&format!("0.3 0.3 0.3 RG {} w\n", width));
+        for col in (0..=cols).filter(|c| (c % 10 == 0) == thick || (thick && *c == cols)) {
+            let x = margin + col as f32 * cell;
+            content.push_str(&format!(
+                "{:.2} {:.2} m {:.2} {:.2} l S\n",
+                x,
+                top,
+                x,
+                top - chart_h
+            ));
+        }
+        for row in (0..=rows).filter(|r| (r % 10 == 0) == thick || (thick && *r == rows)) {
+            let y = top - row as f32 * cell;
+            content.push_str(&format!(
+                "{:.2} {:.2} m {:.2} {:.2} l S\n",
+                margin,
+                y,
+                margin + chart_w,
+                y
+            ));
+        }
+    }
+
+    // legend
+    let mut y = top - chart_h - margin;
+    content.push_str("0 0 0 rg\n");
+    text(
+        &mut content,
+        margin,
+        y,
+        10.0,
+        &format!("{} x {} stitches, {} colors", cols, rows, threads.len()),
+    );
+    for (slot, thread) in threads.iter().enumerate() {
+        y -= legend_line;
+        let (code, name, c) = DMC[*thread];
+        content.push_str(&format!(
+            "{} rg {:.2} {:.2} {:.2} {:.2} re f\n",
+            color_op(c),
+            margin,
+            y - 2.0,
+            10.0,
+            10.0
+        ));
+        content.push_str(&format!("{} rg\n", symbol_color(c)));
+        text(&mut content, margin + 2.0, y, 8.0, SYMBOLS[slot]);
+        content.push_str("0 0 0 rg\n");
+        text(
+            &mut content,
+            margin + 16.0,
+            y,
+            9.0,
+            &format!("DMC {} {} ({} stitches)", code, name, counts[slot]),
+        );
+    }
+
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.0} {:.0}] /Contents 4 0 R \
+             /Resources << /Font << /F1 5 0 R >> >> >>",
+            page_w, page_h
+        ),
+        format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+    ];
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = vec![];
+    for (idx, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n{}\nendobj\n", idx + 1, object).bytes());
+    }
+    let xref = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes());
+    for offset in offsets {
+        pdf.extend(format!("{:010} 00000 n \n", offset).bytes());
+    }
+    pdf.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .bytes(),
+    );
+    out.write_all(&pdf)
+}
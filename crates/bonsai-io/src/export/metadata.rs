@@ -0,0 +1,135 @@
+//! What a rendered image was made from, kept in text chunks of the PNG itself
+//!
+//! Every exported PNG carries the seed of the tree, a hash of the config it was rendered with,
+//! the version of pixel-bonsai and when it was made, so a shared image tells how to grow it again.
+//! The whole config goes along compressed, `--inspect` reads it all back.
+
+use std::{
+    io::{self, Read, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::*;
+
+const SEED: &str = "bonsai:seed";
+const CONFIG_HASH: &str = "bonsai:config-hash";
+const GENERATED: &str = "bonsai:generated";
+const CONFIG: &str = "bonsai:config";
+/// Keyword PNG readers know for the program that made the image
+const SOFTWARE: &str = "Software";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Metadata {
+    pub seed: u64,
+    /// FNV-1a of the config written out like a config file, along with its color rules
+    pub config_hash: u64,
+    pub version: String,
+    /// seconds since the Unix epoch
+    pub generated: u64,
+    /// the config written out like a config file
+    pub config: String,
+}
+
+impl Metadata {
+    /// What an image rendered now with `config` was made from
+    pub fn of(config: &Config) -> Self {
+        let written = config_file::write(config);
+        let resolved = format!("{}\n{:?}", written, config.color_rules);
+        Self {
+            seed: config.seed,
+            config_hash: cache::fnv1a(resolved.as_bytes()),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            generated: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs()),
+            config: written,
+        }
+    }
+}
+
+/// Encodes the image as PNG with the metadata in text chunks ahead of the pixels
+pub fn write_png(
+    image: &image::RgbaImage,
+    metadata: &Metadata,
+    out: &mut (impl Write + ?Sized),
+) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(out, image.width(), image.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    write_chunks(&mut writer, metadata)?;
+    writer
+        .write_image_data(image.as_raw())
+        .map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)
+}
+
+/// Writes the metadata as text chunks, between the header and the first frame
+pub fn write_chunks<W: Write>(writer: &mut png::Writer<W>, metadata: &Metadata) -> io::Result<()> {
+    for (keyword, text) in [
+        (SOFTWARE, format!("pixel-bonsai {}", metadata.version)),
+        (SEED, metadata.seed.to_string()),
+        (CONFIG_HASH, format!("{:016x}", metadata.config_hash)),
+        (GENERATED, metadata.generated.to_string()),
+    ] {
+        let chunk = png::text_metadata::TEXtChunk::new(keyword, text);
+        writer.write_text_chunk(&chunk).map_err(io::Error::other)?;
+    }
+    // UTF-8 and compressed, seed phrases and paths in the config aren't always Latin-1
+    let mut config = png::text_metadata::ITXtChunk::new(CONFIG, metadata.config.clone());
+    config.compress_text().map_err(io::Error::other)?;
+    writer.write_text_chunk(&config).map_err(io::Error::other)
+}
+
+/// Every text chunk of a PNG as keyword and text, in the order they were written.
+/// Images from elsewhere give back their own chunks, or none
+pub fn read(input: impl Read) -> io::Result<Vec<(String, String)>> {
+    let reader = png::Decoder::new(input)
+        .read_info()
+        .map_err(io::Error::other)?;
+    let info = reader.info();
+    let mut chunks = info
+        .uncompressed_latin1_text
+        .iter()
+        .map(|chunk| (chunk.keyword.clone(), chunk.text.clone()))
+        .collect::<Vec<_>>();
+    for chunk in &info.compressed_latin1_text {
+        let text = chunk.get_text().map_err(io::Error::other)?;
+        chunks.push((chunk.keyword.clone(), text));
+    }
+    for chunk in &info.utf8_text {
+        let text = chunk.get_text().map_err(io::Error::other)?;
+        chunks.push((chunk.keyword.clone(), text));
+    }
+    Ok(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn images_describe_themselves() {
+        let config = Config {
+            seed: seed::from_phrase("quiet maple evening"),
+            ..Config::default()
+        };
+        let metadata = Metadata::of(&config);
+        let mut png = vec![];
+        write_png(&image::RgbaImage::new(4, 3), &metadata, &mut png).unwrap();
+        let chunks = read(png.as_slice()).unwrap();
+        let get = |keyword: &str| {
+            chunks
+                .iter()
+                .find(|(key, _)| key == keyword)
+                .map(|(_, text)| text.clone())
+        };
+        assert_eq!(get(SEED), Some(config.seed.to_string()));
+        assert_eq!(get(CONFIG), Some(metadata.config));
+        assert_eq!(
+            get(CONFIG_HASH),
+            Some(format!("{:016x}", Metadata::of(&config).config_hash))
+        );
+        assert_eq!(image::load_from_memory(&png).unwrap().width(), 4);
+    }
+}
@@ -0,0 +1,80 @@
+pub mod anchors;
+pub mod cross_stitch;
+pub mod metadata;
+pub mod palette;
+pub mod registry;
+pub mod schem;
+pub mod skeleton;
+pub mod stl;
+pub mod svg;
+pub mod vox;
+
+pub use registry::{Exporter, Registry};
+
+use crate::Tree;
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+/// Encodes the image as PNG
+pub fn write_png(image: &image::RgbaImage, out: &mut (impl Write + Seek)) -> io::Result<()> {
+    image
+        .write_to(out, image::ImageOutputFormat::Png)
+        .map_err(io::Error::other)
+}
+
+/// Creates the file and hands it to the exporter, a failure names the file
+pub fn save(
+    path: impl AsRef<Path>,
+    write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+) -> io::Result<()> {
+    let path = path.as_ref();
+    File::create(path)
+        .and_then(|file| {
+            let mut out = BufWriter::new(file);
+            write(&mut out)?;
+            out.flush()
+        })
+        .map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!("failed to write {}: {}", path.display(), err),
+            )
+        })
+}
+
+/// What an export did, for the program running it to tell
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    /// every file written, in the order they were
+    pub written: Vec<PathBuf>,
+    /// the wallpapers among them
+    pub wallpapers: Vec<PathBuf>,
+    /// nodes of the tree once it stopped growing, `None` if it got cancelled before
+    pub grown: Option<usize>,
+    /// what went wrong without stopping the export, like a cancel or a tree growing oddly
+    pub warnings: Vec<String>,
+}
+
+impl Report {
+    /// Writes the file like `save` and notes it down
+    pub fn save(
+        &mut self,
+        path: impl AsRef<Path>,
+        write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+    ) -> io::Result<()> {
+        save(&path, write)?;
+        self.written.push(path.as_ref().to_path_buf());
+        Ok(())
+    }
+
+    /// Notes down how the tree grew, and what it warns about
+    pub fn grown(&mut self, tree: &Tree) {
+        self.grown = Some(tree.node_count());
+        self.warnings
+            .extend(tree.warnings().iter().map(ToString::to_string));
+    }
+}
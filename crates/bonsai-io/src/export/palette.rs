@@ -0,0 +1,75 @@
+//! Palettes of exported images, as GIMP `.gpl` and Adobe `.ase` swatches
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use bonsai_engine::Color;
+
+/// Every opaque color of the image, the most used first
+pub fn used_colors(image: &image::RgbaImage) -> Vec<Color> {
+    let mut counts = HashMap::<[u8; 3], usize>::new();
+    for pixel in image.pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a == 255 {
+            *counts.entry([r, g, b]).or_default() += 1;
+        }
+    }
+    let mut colors = counts.into_iter().collect::<Vec<_>>();
+    colors.sort_by(|(a, count_a), (b, count_b)| count_b.cmp(count_a).then(a.cmp(b)));
+    colors
+        .into_iter()
+        .map(|([r, g, b], _)| Color::new(r, g, b, 255))
+        .collect()
+}
+
+fn hex(color: Color) -> String {
+    format!("{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+/// GIMP palette, colors are named by their hex code
+pub fn write_gpl(name: &str, colors: &[Color], out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "GIMP Palette")?;
+    writeln!(out, "Name: {}", name)?;
+    writeln!(out, "Columns: 8")?;
+    writeln!(out, "#")?;
+    for &color in colors {
+        writeln!(
+            out,
+            "{:3} {:3} {:3}\t{}",
+            color.r,
+            color.g,
+            color.b,
+            hex(color)
+        )?;
+    }
+    Ok(())
+}
+
+/// Adobe swatch exchange file with one global RGB swatch per color
+pub fn write_ase(colors: &[Color], out: &mut impl Write) -> io::Result<()> {
+    out.write_all(b"ASEF")?;
+    out.write_all(&1u16.to_be_bytes())?;
+    out.write_all(&0u16.to_be_bytes())?;
+    out.write_all(&(colors.len() as u32).to_be_bytes())?;
+
+    for &color in colors {
+        // names are null terminated UTF-16
+        let name = hex(color).encode_utf16().chain([0]).collect::<Vec<_>>();
+        let mut block = vec![];
+        block.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        for unit in name {
+            block.extend_from_slice(&unit.to_be_bytes());
+        }
+        block.extend_from_slice(b"RGB ");
+        for c in [color.r, color.g, color.b] {
+            block.extend_from_slice(&(c as f32 / 255.0).to_be_bytes());
+        }
+        // global color
+        block.extend_from_slice(&0u16.to_be_bytes());
+
+        out.write_all(&1u16.to_be_bytes())?;
+        out.write_all(&(block.len() as u32).to_be_bytes())?;
+        out.write_all(&block)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,235 @@
+//! Every format a tree can be exported to, behind one trait and looked up by file extension
+//!
+//! `Registry::builtin` knows the formats of this crate, a program embedding it can `register`
+//! its own next to them, or in place of one, and `--format` and the export keys of the window
+//! pick them by extension.
+
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use super::*;
+use crate::voxel::VoxelGrid;
+use crate::*;
+use render::{Framebuffer, PrettyRender};
+
+/// Writes a grown tree in one file format
+pub trait Exporter: Send + Sync {
+    /// Extension of the files, without the dot, which is also the name `--format` takes
+    fn extension(&self) -> &str;
+    /// What the format is for, for listing the formats
+    fn description(&self) -> &str;
+    fn write(&self, tree: &Tree, out: &mut dyn Write) -> io::Result<()>;
+    /// What keeps the tree from coming out well in the format, it is written anyway
+    fn warnings(&self, _tree: &Tree) -> Vec<String> {
+        vec![]
+    }
+}
+
+/// The exporters, one per extension
+pub struct Registry {
+    exporters: Vec<Box<dyn Exporter>>,
+}
+
+impl Registry {
+    /// No formats at all
+    pub fn empty() -> Self {
+        Self { exporters: vec![] }
+    }
+
+    /// The formats of this crate
+    pub fn builtin() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Png);
+        registry.register(Svg);
+        registry.register(Skeleton);
+        registry.register(Vox);
+        registry.register(Schem);
+        registry.register(Stl(stl::StlOptions::default()));
+        registry.register(CrossStitch(cross_stitch::CrossStitchOptions::default()));
+        registry
+    }
+
+    /// Adds the exporter, replacing the one registered for its extension before
+    pub fn register(&mut self, exporter: impl Exporter + 'static) {
+        self.exporters
+            .retain(|other| !other.extension().eq_ignore_ascii_case(exporter.extension()));
+        self.exporters.push(Box::new(exporter));
+    }
+
+    /// The exporter for files ending in `extension`
+    pub fn get(&self, extension: &str) -> Option<&dyn Exporter> {
+        self.exporters
+            .iter()
+            .find(|exporter| exporter.extension().eq_ignore_ascii_case(extension))
+            .map(Box::as_ref)
+    }
+
+    /// The exporter for the extension of `path`
+    pub fn for_path(&self, path: &Path) -> Option<&dyn Exporter> {
+        self.get(path.extension()?.to_str()?)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Exporter> {
+        self.exporters.iter().map(Box::as_ref)
+    }
+
+    /// Writes the tree to `path` in the format its extension names, returning what the format
+    /// warns about it
+    pub fn save(&self, tree: &Tree, path: &Path) -> io::Result<Vec<String>> {
+        let exporter = self.for_path(path).ok_or_else(|| {
+            let known = self.iter().map(|e| e.extension()).collect::<Vec<_>>();
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "no format for {}, known are {}",
+                    path.display(),
+                    known.join(", ")
+                ),
+            )
+        })?;
+        save(path, |out| exporter.write(tree, out))?;
+        Ok(exporter.warnings(tree))
+    }
+}
+
+/// The tree rendered as it is shown, the size of its world
+struct Png;
+
+impl Exporter for Png {
+    fn extension(&self) -> &str {
+        "png"
+    }
+    fn description(&self) -> &str {
+        "the pixel art render"
+    }
+    fn write(&self, tree: &Tree, out: &mut dyn Write) -> io::Result<()> {
+        let config = &tree.config;
+        let mut frame = Framebuffer::new(config.width as usize, config.height as usize, config.sky);
+        PrettyRender::new(tree.clone()).render(&mut frame);
+        metadata::write_png(&frame.to_image(), &metadata::Metadata::of(config), out)
+    }
+}
+
+struct Svg;
+
+impl Exporter for Svg {
+    fn extension(&self) -> &str {
+        "svg"
+    }
+    fn description(&self) -> &str {
+        "the branches and leaves as paths"
+    }
+    fn write(&self, tree: &Tree, mut out: &mut dyn Write) -> io::Result<()> {
+        svg::write(tree, &mut out)
+    }
+}
+
+struct Skeleton;
+
+impl Exporter for Skeleton {
+    fn extension(&self) -> &str {
+        "json"
+    }
+    fn description(&self) -> &str {
+        "the skeleton of the branches"
+    }
+    fn write(&self, tree: &Tree, mut out: &mut dyn Write) -> io::Result<()> {
+        skeleton::write(tree, &mut out)
+    }
+}
+
+struct Vox;
+
+impl Exporter for Vox {
+    fn extension(&self) -> &str {
+        "vox"
+    }
+    fn description(&self) -> &str {
+        "voxels for MagicaVoxel"
+    }
+    fn write(&self, tree: &Tree, mut out: &mut dyn Write) -> io::Result<()> {
+        vox::write(&VoxelGrid::from_tree(tree, 1.0, true), &mut out)
+    }
+}
+
+struct Schem;
+
+impl Exporter for Schem {
+    fn extension(&self) -> &str {
+        "schem"
+    }
+    fn description(&self) -> &str {
+        "blocks for Minecraft"
+    }
+    fn write(&self, tree: &Tree, mut out: &mut dyn Write) -> io::Result<()> {
+        schem::write(&VoxelGrid::from_tree(tree, 1.0, true), &mut out)
+    }
+}
+
+struct Stl(stl::StlOptions);
+
+impl Exporter for Stl {
+    fn extension(&self) -> &str {
+        "stl"
+    }
+    fn description(&self) -> &str {
+        "a solid for 3D printing"
+    }
+    fn write(&self, tree: &Tree, mut out: &mut dyn Write) -> io::Result<()> {
+        stl::write(tree, &self.0, &mut out)
+    }
+    fn warnings(&self, tree: &Tree) -> Vec<String> {
+        stl::thickness_warning(tree, &self.0).into_iter().collect()
+    }
+}
+
+struct CrossStitch(cross_stitch::CrossStitchOptions);
+
+impl Exporter for CrossStitch {
+    fn extension(&self) -> &str {
+        "pdf"
+    }
+    fn description(&self) -> &str {
+        "a cross-stitch chart"
+    }
+    fn write(&self, tree: &Tree, mut out: &mut dyn Write) -> io::Result<()> {
+        let pixels = PrettyRender::new(tree.clone()).pixels();
+        cross_stitch::write(&pixels, &self.0, &mut out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Count;
+
+    impl Exporter for Count {
+        fn extension(&self) -> &str {
+            "SVG"
+        }
+        fn description(&self) -> &str {
+            "the number of nodes"
+        }
+        fn write(&self, tree: &Tree, out: &mut dyn Write) -> io::Result<()> {
+            write!(out, "{}", tree.node_count())
+        }
+    }
+
+    #[test]
+    fn registered_exporters_replace_the_builtin_ones() {
+        let mut registry = Registry::builtin();
+        let builtin = registry.iter().count();
+        registry.register(Count);
+        assert_eq!(registry.iter().count(), builtin);
+
+        let tree = Tree::new_min_growth(Config::default(), 5);
+        let exporter = registry.for_path(Path::new("tree.svg")).unwrap();
+        let mut out = vec![];
+        exporter.write(&tree, &mut out).unwrap();
+        assert_eq!(out, tree.node_count().to_string().into_bytes());
+        assert!(registry.get("gif").is_none());
+    }
+}
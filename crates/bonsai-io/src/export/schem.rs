@@ -0,0 +1,126 @@
+//! Sponge schematic (`.schem`) export for Minecraft
+
+use std::io::{self, Write};
+
+use flate2::{write::GzEncoder, Compression};
+
+use crate::voxel::VoxelGrid;
+use bonsai_engine::Color;
+
+/// Blocks the tree colors get mapped to, with their approximate average color
+const BLOCKS: &[(&str, [u8; 3])] = &[
+    ("minecraft:oak_log", [109, 85, 50]),
+    ("minecraft:spruce_log", [58, 37, 16]),
+    ("minecraft:dark_oak_log", [60, 46, 26]),
+    ("minecraft:jungle_log", [85, 67, 25]),
+    ("minecraft:oak_leaves", [60, 120, 30]),
+    ("minecraft:azalea_leaves", [90, 115, 45]),
+    ("minecraft:white_wool", [234, 236, 237]),
+    ("minecraft:pink_wool", [238, 141, 172]),
+    ("minecraft:magenta_wool", [190, 69, 180]),
+    ("minecraft:red_wool", [161, 39, 35]),
+    ("minecraft:orange_wool", [241, 118, 20]),
+    ("minecraft:yellow_wool", [249, 198, 40]),
+    ("minecraft:lime_wool", [112, 185, 26]),
+    ("minecraft:green_wool", [85, 110, 28]),
+    ("minecraft:brown_wool", [114, 72, 41]),
+];
+
+fn closest_block(color: Color) -> &'static str {
+    let dist = |c: [u8; 3]| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(color.r, c[0]) + d(color.g, c[1]) + d(color.b, c[2])
+    };
+    BLOCKS.iter().min_by_key(|(_, c)| dist(*c)).unwrap().0
+}
+
+/// Minimal big endian NBT writer
+struct Nbt(Vec<u8>);
+
+impl Nbt {
+    fn tag(&mut self, id: u8, name: &str) {
+        self.0.push(id);
+        self.0.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        self.0.extend_from_slice(name.as_bytes());
+    }
+    fn begin_compound(&mut self, name: &str) {
+        self.tag(10, name);
+    }
+    fn end_compound(&mut self) {
+        self.0.push(0);
+    }
+    fn short(&mut self, name: &str, value: i16) {
+        self.tag(2, name);
+        self.0.extend_from_slice(&value.to_be_bytes());
+    }
+    fn int(&mut self, name: &str, value: i32) {
+        self.tag(3, name);
+        self.0.extend_from_slice(&value.to_be_bytes());
+    }
+    fn byte_array(&mut self, name: &str, value: &[u8]) {
+        self.tag(7, name);
+        self.0
+            .extend_from_slice(&(value.len() as i32).to_be_bytes());
+        self.0.extend_from_slice(value);
+    }
+}
+
+fn varint(mut value: u32, out: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Writes the grid as a gzipped Sponge schematic (version 2)
+pub fn write(grid: &VoxelGrid, out: &mut impl Write) -> io::Result<()> {
+    let mut palette = vec!["minecraft:air"];
+    let mut block_of = vec![0u32];
+    for color in grid.palette.iter() {
+        let block = closest_block(*color);
+        let idx = match palette.iter().position(|b| *b == block) {
+            Some(idx) => idx,
+            None => {
+                palette.push(block);
+                palette.len() - 1
+            }
+        };
+        block_of.push(idx as u32);
+    }
+
+    // minecraft orders blocks by y, then z, then x
+    let mut blocks = vec![];
+    for y in 0..grid.size_y {
+        for z in 0..grid.size_z {
+            for x in 0..grid.size_x {
+                varint(block_of[grid.get(x, y, z) as usize], &mut blocks);
+            }
+        }
+    }
+
+    let mut nbt = Nbt(vec![]);
+    nbt.begin_compound("Schematic");
+    nbt.int("Version", 2);
+    nbt.int("DataVersion", 2586);
+    nbt.short("Width", grid.size_x as i16);
+    nbt.short("Height", grid.size_y as i16);
+    nbt.short("Length", grid.size_z as i16);
+    nbt.int("PaletteMax", palette.len() as i32);
+    nbt.begin_compound("Palette");
+    for (idx, block) in palette.iter().enumerate() {
+        nbt.int(block, idx as i32);
+    }
+    nbt.end_compound();
+    nbt.byte_array("BlockData", &blocks);
+    nbt.end_compound();
+
+    let mut encoder = GzEncoder::new(out, Compression::default());
+    encoder.write_all(&nbt.0)?;
+    encoder.finish()?;
+    Ok(())
+}
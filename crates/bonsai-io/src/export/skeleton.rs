@@ -0,0 +1,47 @@
+//! The raw branch graph as JSON, for tooling of one's own
+//!
+//! Nodes are listed in the order the tree stores them, every parent before its children, in
+//! world units with y pointing up from the bottom of the world. Dead nodes are kept, so the
+//! indices match those of a save of the same tree.
+//!
+//! ```json
+//! {"seed": 42, "width": 500, "height": 500, "origin": {"x": 250, "y": 20}, "nodes": [
+//!   {"x": 250, "y": 20, "z": 0, "parent": null, "weight": 312, "radius": 4.2, "alive": true},
+//!   ...
+//! ]}
+//! ```
+
+use std::io::{self, Write};
+
+use crate::*;
+
+/// Writes the skeleton of the tree as it is grown so far
+pub fn write(tree: &Tree, out: &mut impl Write) -> io::Result<()> {
+    let config = &tree.config;
+    writeln!(
+        out,
+        "{{\"seed\": {}, \"width\": {}, \"height\": {}, \"origin\": {{\"x\": {}, \"y\": {}}}, \"nodes\": [",
+        config.seed, config.width, config.height, config.origin.x, config.origin.y
+    )?;
+    let count = tree.node_count();
+    for (idx, node) in tree.nodes().enumerate() {
+        let pos = node.pos.vec();
+        let parent = node
+            .parent
+            .map_or("null".to_string(), |parent| parent.to_string());
+        let comma = if idx + 1 < count { "," } else { "" };
+        writeln!(
+            out,
+            "  {{\"x\": {}, \"y\": {}, \"z\": {}, \"parent\": {}, \"weight\": {}, \"radius\": {}, \"alive\": {}}}{}",
+            pos.x,
+            pos.y,
+            node.z,
+            parent,
+            node.weight,
+            tree.radius_of(&node),
+            node.alive,
+            comma
+        )?;
+    }
+    writeln!(out, "]}}")
+}
@@ -0,0 +1,184 @@
+//! Binary STL export of the tree as a solid for 3D printing
+
+use std::io::{self, Write};
+
+use crate::{voxel::VoxelGrid, Tree};
+
+#[derive(Copy, Clone, Debug)]
+pub struct StlOptions {
+    /// voxels per canvas pixel, higher values give smoother surfaces
+    pub resolution: f32,
+    /// physical size of one canvas pixel
+    pub millimeters_per_pixel: f32,
+    /// whether the leaf blobs are fused onto the branches
+    pub leaves: bool,
+    /// thinnest branch diameter the printer can handle
+    pub min_thickness: f32,
+}
+
+impl Default for StlOptions {
+    fn default() -> Self {
+        Self {
+            resolution: 2.0,
+            millimeters_per_pixel: 1.0,
+            leaves: true,
+            min_thickness: 0.8,
+        }
+    }
+}
+
+/// Least share of the tree a branch carries to count as one of the main branches
+const MAIN_BRANCH_SHARE: f32 = 0.1;
+
+/// Warns if the trunk or one of the main branches is below the printable thickness,
+/// the twigs come out thinner than that on any tree of printable size
+pub fn thickness_warning(tree: &Tree, options: &StlOptions) -> Option<String> {
+    let mm_per_unit = options.millimeters_per_pixel / tree.config.pixel_size as f32;
+    let alive = || tree.nodes().filter(|n| n.alive);
+    let total = alive().map(|n| n.weight).max().unwrap_or(0);
+    let thinnest = alive()
+        .filter(|n| n.weight as f32 >= total as f32 * MAIN_BRANCH_SHARE)
+        .map(|n| 2.0 * tree.radius_of(&n) * mm_per_unit)
+        .fold(f32::INFINITY, f32::min);
+    (thinnest < options.min_thickness).then(|| {
+        format!(
+            "thinnest main branch is {:.2}mm, below the minimum of {:.2}mm",
+            thinnest, options.min_thickness
+        )
+    })
+}
+
+/// Writes the union of all branch capsules (and leaf blobs) as a closed surface.
+///
+/// The surface is made of the outer faces of the voxelized tree, so it is watertight by construction.
+pub fn write(tree: &Tree, options: &StlOptions, out: &mut impl Write) -> io::Result<()> {
+    let grid = VoxelGrid::from_tree(tree, options.resolution, options.leaves);
+    let scale = options.millimeters_per_pixel / options.resolution;
+
+    // each face: direction of the outside neighbour, and the two in-plane axes with u × v = normal
+    let faces: [([isize; 3], [f32; 3], [f32; 3]); 6] = [
+        ([1, 0, 0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+        ([-1, 0, 0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+        ([0, 1, 0], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0]),
+        ([0, -1, 0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+        ([0, 0, 1], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ([0, 0, -1], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]),
+    ];
+
+    let mut triangles = vec![];
+    for z in 0..grid.size_z {
+        for y in 0..grid.size_y {
+            for x in 0..grid.size_x {
+                if grid.get(x, y, z) == 0 {
+                    continue;
+                }
+                let (xi, yi, zi) = (x as isize, y as isize, z as isize);
+                for (dir, u, v) in faces.iter() {
+                    if grid.get_signed(xi + dir[0], yi + dir[1], zi + dir[2]) != 0 {
+                        continue;
+                    }
+                    // positive faces lie on the far side of the voxel
+                    let base = [
+                        x as f32 + dir[0].max(0) as f32,
+                        y as f32 + dir[1].max(0) as f32,
+                        z as f32 + dir[2].max(0) as f32,
+                    ];
+                    let corner = |a: f32, b: f32| {
+                        [
+                            base[0] + u[0] * a + v[0] * b,
+                            base[1] + u[1] * a + v[1] * b,
+                            base[2] + u[2] * a + v[2] * b,
+                        ]
+                    };
+                    let quad = [
+                        corner(0.0, 0.0),
+                        corner(1.0, 0.0),
+                        corner(1.0, 1.0),
+                        corner(0.0, 1.0),
+                    ];
+                    let normal = [dir[0] as f32, dir[1] as f32, dir[2] as f32];
+                    triangles.push((normal, [quad[0], quad[1], quad[2]]));
+                    triangles.push((normal, [quad[0], quad[2], quad[3]]));
+                }
+            }
+        }
+    }
+
+    // grid space has y up and z towards the camera, STL has z up
+    let to_stl = |p: [f32; 3]| [p[0] * scale, -p[2] * scale, p[1] * scale];
+
+    let mut header = [0u8; 80];
+    let title = b"pixel-bonsai";
+    header[..title.len()].copy_from_slice(title);
+    out.write_all(&header)?;
+    out.write_all(&(triangles.len() as u32).to_le_bytes())?;
+    for (normal, vertices) in triangles {
+        let normal = [normal[0], -normal[2], normal[1]];
+        for v in std::iter::once(normal).chain(vertices.into_iter().map(to_stl)) {
+            for c in v {
+                out.write_all(&c.to_le_bytes())?;
+            }
+        }
+        out.write_all(&[0, 0])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn grown() -> Tree {
+        let mut tree = Tree::new_min_growth(bonsai_engine::Config::default(), 5);
+        tree.grow_fully(&bonsai_engine::cancel::CancelToken::new());
+        tree
+    }
+
+    #[test]
+    fn only_thin_main_branches_warn() {
+        let tree = grown();
+        assert_eq!(thickness_warning(&tree, &StlOptions::default()), None);
+        let tiny = StlOptions {
+            millimeters_per_pixel: 0.05,
+            ..StlOptions::default()
+        };
+        assert!(thickness_warning(&tree, &tiny).is_some());
+    }
+
+    #[test]
+    fn surface_is_closed() {
+        let tree = grown();
+        let options = StlOptions {
+            resolution: 0.5,
+            ..StlOptions::default()
+        };
+        let mut out = vec![];
+        write(&tree, &options, &mut out).unwrap();
+
+        assert!(out.starts_with(b"pixel-bonsai"));
+        let count = u32::from_le_bytes(out[80..84].try_into().unwrap()) as usize;
+        assert!(count > 0 && count.is_multiple_of(2), "{} triangles", count);
+        assert_eq!(out.len(), 84 + count * 50);
+
+        // every edge is walked as often one way as the other, so there are no holes
+        let mut edges = HashMap::new();
+        for triangle in out[84..].chunks(50) {
+            let vertex = |i: usize| {
+                let at = 12 + i * 12;
+                let c = |j: usize| {
+                    let bytes = triangle[at + j * 4..at + j * 4 + 4].try_into().unwrap();
+                    (f32::from_le_bytes(bytes) * 1000.0).round() as i64
+                };
+                [c(0), c(1), c(2)]
+            };
+            let vertices = [vertex(0), vertex(1), vertex(2)];
+            for i in 0..3 {
+                let (a, b) = (vertices[i], vertices[(i + 1) % 3]);
+                *edges.entry((a, b)).or_insert(0) += 1;
+                *edges.entry((b, a)).or_insert(0) -= 1;
+            }
+        }
+        assert!(edges.values().all(|&balance| balance == 0));
+    }
+}
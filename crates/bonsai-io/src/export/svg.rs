@@ -0,0 +1,146 @@
+//! SVG export of the tree as paths, for editing it in Inkscape or Illustrator
+//!
+//! Every living segment is a path of its own, a cubic Bézier along the same curve the pixel render
+//! draws, stroked as wide as the branch with round caps. The leaf blobs are circles where the
+//! render puts them. Leaves behind the branches, the branches and the leaves in front are one
+//! layer each. Units are world units with y pointing down, the view covers the world.
+
+use std::io::{self, Write};
+
+use bonsai_engine::{
+    color_rules::{self, NodeFacts, Part},
+    volume,
+};
+use bonsai_render::render::blob_threshold;
+
+use crate::*;
+
+/// Writes the tree as it is grown so far, seen the way the pixel render sees it
+pub fn write(tree: &Tree, out: &mut impl Write) -> io::Result<()> {
+    let turned = volume::turned(tree);
+    let tree = turned.as_ref().unwrap_or(tree);
+    let config = &tree.config;
+    let (pixel_width, pixel_height) = (config.pixel_size as f32, config.pixel_height() as f32);
+    // the render leaves room for leaves over the top and right edge of the world
+    let (pad_x, pad_y) = (
+        CANVAS_PADDING as f32 * pixel_width,
+        CANVAS_PADDING as f32 * pixel_height,
+    );
+    let flip = |pos: Vector2| (pos.x, config.height - pos.y);
+    let selection_colors = tree.selection_colors();
+    let color_of = |node_idx: usize, part, color| {
+        let facts = NodeFacts::of(tree, node_idx);
+        let color = color_rules::apply(&config.color_rules, part, &facts, color);
+        selection_colors[node_idx].unwrap_or(color)
+    };
+
+    let mut continuations = vec![];
+    tree.continuations_into(&mut continuations);
+    let mut branches = vec![];
+    // front and back, like the two leaf canvases of the render
+    let mut leaves = [vec![], vec![]];
+    let mut rng = config.leaf_rng();
+    for (node_idx, node) in tree.nodes().enumerate() {
+        let pos = node.pos.vec();
+        let radius = tree.radius_of(&node);
+        let is_leaf = radius < config.leaf_max_width && node.alive;
+        // drawn for every node in the same order as the render, so the leaves land on its spots
+        for blob in 0..4 {
+            let mut offset = || (rng.gen::<f32>() * 2.0 - 1.0) * config.leaf_size;
+            let offset = Vector2::new(offset(), offset());
+            let leaf = config.get_leaf_type(&mut rng);
+            let depth = (rng.gen::<f32>() * 2.0 - 1.0) * config.leaf_depth_jitter;
+            let shed = blob_threshold(config.seed, node_idx as u64 * 4 + blob as u64);
+            if is_leaf && shed < config.foliage {
+                let (x, y) = flip(pos + offset);
+                let size = leaf.size * (1.0 + 0.5 * depth) * pixel_width;
+                let color = color_of(node_idx, Part::Leaves, leaf.color);
+                leaves[blob % 2].push(format!(
+                    "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.2}\" fill=\"#{}\"/>",
+                    x,
+                    y,
+                    size,
+                    hex(color)
+                ));
+            }
+        }
+
+        if is_leaf || !node.alive {
+            continue;
+        }
+        let Some(spline) = tree.segment_spline(node_idx, &continuations) else {
+            continue;
+        };
+        let [from, a, b, to] = spline.bezier().map(flip);
+        let color = color_of(node_idx, Part::Bark, config.colors.old_branch);
+        branches.push(format!(
+            "<path d=\"M {:.2} {:.2} C {:.2} {:.2} {:.2} {:.2} {:.2} {:.2}\" stroke=\"#{}\" stroke-width=\"{:.2}\"/>",
+            from.0, from.1, a.0, a.1, b.0, b.1, to.0, to.1,
+            hex(color),
+            2.0 * radius
+        ));
+    }
+
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:inkscape=\"http://www.inkscape.org/namespaces/inkscape\" viewBox=\"0 {:.2} {:.2} {:.2}\" width=\"{:.0}\" height=\"{:.0}\">",
+        -pad_y,
+        config.width + pad_x,
+        config.height + pad_y,
+        config.width + pad_x,
+        config.height + pad_y
+    )?;
+    writeln!(out, "<!-- pixel-bonsai, seed {} -->", config.seed)?;
+    let layers = [
+        ("leaves behind", "", &leaves[1]),
+        (
+            "branches",
+            " fill=\"none\" stroke-linecap=\"round\"",
+            &branches,
+        ),
+        ("leaves", "", &leaves[0]),
+    ];
+    for (label, style, shapes) in layers {
+        writeln!(
+            out,
+            "<g inkscape:groupmode=\"layer\" inkscape:label=\"{}\"{}>",
+            label, style
+        )?;
+        for shape in shapes {
+            writeln!(out, "  {}", shape)?;
+        }
+        writeln!(out, "</g>")?;
+    }
+    writeln!(out, "</svg>")
+}
+
+fn hex(color: Color) -> String {
+    format!("{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_living_segment_is_a_path() {
+        let mut tree = Tree::new_min_growth(Config::default(), 5);
+        tree.grow_fully(&bonsai_engine::cancel::CancelToken::new());
+        let mut out = vec![];
+        write(&tree, &mut out).unwrap();
+        let svg = String::from_utf8(out).unwrap();
+        let segments = tree
+            .nodes()
+            .filter(|node| {
+                node.alive
+                    && node.parent.is_some()
+                    && tree.radius_of(node) >= tree.config.leaf_max_width
+            })
+            .count();
+        assert!(segments > 0);
+        assert_eq!(svg.matches("<path ").count(), segments);
+        assert!(svg.contains("<circle "));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+}
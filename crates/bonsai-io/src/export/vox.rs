@@ -0,0 +1,121 @@
+//! MagicaVoxel `.vox` export
+
+use std::io::{self, Write};
+
+use crate::voxel::VoxelGrid;
+
+fn chunk(id: &[u8; 4], content: &[u8], children: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(12 + content.len() + children.len());
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(content.len() as u32).to_le_bytes());
+    out.extend_from_slice(&(children.len() as u32).to_le_bytes());
+    out.extend_from_slice(content);
+    out.extend_from_slice(children);
+    out
+}
+
+/// Writes the grid as a single model, MagicaVoxel uses z as the up axis
+pub fn write(grid: &VoxelGrid, out: &mut impl Write) -> io::Result<()> {
+    if grid.size_x > 256 || grid.size_y > 256 || grid.size_z > 256 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "vox models are limited to 256 voxels per axis",
+        ));
+    }
+    if grid.palette.len() > 255 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "vox palettes are limited to 255 colors",
+        ));
+    }
+
+    let mut size = vec![];
+    for dim in [grid.size_x, grid.size_z, grid.size_y] {
+        size.extend_from_slice(&(dim as u32).to_le_bytes());
+    }
+
+    let mut voxels = vec![];
+    let mut count = 0u32;
+    for z in 0..grid.size_z {
+        for y in 0..grid.size_y {
+            for x in 0..grid.size_x {
+                let material = grid.get(x, y, z);
+                if material != 0 {
+                    // flip depth so the camera side faces the default MagicaVoxel view
+                    let depth = grid.size_z - 1 - z;
+                    voxels.extend_from_slice(&[x as u8, depth as u8, y as u8, material]);
+                    count += 1;
+                }
+            }
+        }
+    }
+    let mut xyzi = count.to_le_bytes().to_vec();
+    xyzi.extend(voxels);
+
+    let mut rgba = vec![0u8; 256 * 4];
+    for (i, color) in grid.palette.iter().enumerate() {
+        rgba[i * 4..i * 4 + 4].copy_from_slice(&[color.r, color.g, color.b, color.a]);
+    }
+
+    let mut children = chunk(b"SIZE", &size, &[]);
+    children.extend(chunk(b"XYZI", &xyzi, &[]));
+    children.extend(chunk(b"RGBA", &rgba, &[]));
+
+    out.write_all(b"VOX ")?;
+    out.write_all(&150u32.to_le_bytes())?;
+    out.write_all(&chunk(b"MAIN", &[], &children))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, Tree};
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn header_and_sizes_match_the_grid() {
+        let mut tree = Tree::new_min_growth(bonsai_engine::Config::default(), 5);
+        tree.grow_fully(&bonsai_engine::cancel::CancelToken::new());
+        let grid = VoxelGrid::from_tree(&tree, 0.5, true);
+        let mut out = vec![];
+        write(&grid, &mut out).unwrap();
+
+        assert_eq!(&out[..4], b"VOX ");
+        assert_eq!(u32_at(&out, 4), 150);
+        assert_eq!(&out[8..12], b"MAIN");
+        assert_eq!(u32_at(&out, 12), 0);
+        assert_eq!(u32_at(&out, 16) as usize, out.len() - 20);
+
+        let size = &out[20..];
+        assert_eq!(&size[..4], b"SIZE");
+        let dims: Vec<_> = (0..3).map(|i| u32_at(size, 12 + i * 4) as usize).collect();
+        assert_eq!(dims, [grid.size_x, grid.size_z, grid.size_y]);
+
+        let xyzi = &size[24..];
+        assert_eq!(&xyzi[..4], b"XYZI");
+        let filled = (0..grid.size_z)
+            .flat_map(|z| (0..grid.size_y).map(move |y| (y, z)))
+            .flat_map(|(y, z)| (0..grid.size_x).map(move |x| (x, y, z)))
+            .filter(|&(x, y, z)| grid.get(x, y, z) != 0)
+            .count();
+        assert!(filled > 0);
+        assert_eq!(u32_at(xyzi, 12) as usize, filled);
+        assert_eq!(u32_at(xyzi, 4) as usize, 4 + filled * 4);
+
+        let rgba = &xyzi[16 + filled * 4..];
+        assert_eq!(&rgba[..4], b"RGBA");
+        assert_eq!(rgba.len(), 12 + 256 * 4);
+        let first = grid.palette[0];
+        assert_eq!(rgba[12..16], [first.r, first.g, first.b, first.a]);
+    }
+
+    #[test]
+    fn oversized_grids_are_refused() {
+        let mut grid = VoxelGrid::new(300, 1, 1);
+        grid.palette.push(Color::GREEN);
+        assert!(write(&grid, &mut vec![]).is_err());
+    }
+}
@@ -0,0 +1,21 @@
+//! Everything that leaves the program as a file: saves, wallpapers, growth timelapses, morphs,
+//! lineages of configs and the exports to voxel editors, 3D printers and cross stitch charts,
+//! and config files and hand edited density fields coming in
+
+pub mod batch;
+pub mod cache;
+pub mod config_file;
+pub mod density;
+pub mod export;
+pub mod morph;
+pub mod phylogeny;
+pub mod save;
+pub mod thumbnail;
+pub mod timelapse;
+pub mod voxel;
+pub mod wallpaper;
+
+use bonsai_engine::*;
+use bonsai_render::{night, render};
+use point::{Point, Real};
+use rand::Rng;
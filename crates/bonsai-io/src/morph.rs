@@ -0,0 +1,82 @@
+//! Recording one tree turning into another and back, written as a looping animated PNG
+
+use std::{io, path::Path};
+
+use crate::*;
+use bonsai_engine::morph::Morph;
+use cancel::CancelToken;
+use export::Report;
+use render::{Framebuffer, PrettyRender};
+use wallpaper::Target;
+
+/// Frames per second of the animation
+const FPS: u16 = 24;
+/// Frames of the way from one tree to the other
+const FRAMES: usize = 48;
+/// How long each tree stays on screen before turning into the other, in seconds
+const HOLD: u16 = 1;
+
+/// Grows the tree of `config` and the one of `other_seed` with the same parameters, then writes
+/// the first turning into the second and back to `out`. A cancelled recording writes nothing
+pub fn export(
+    config: &Config,
+    other_seed: u64,
+    target: Target,
+    out: &Path,
+    cancel: &CancelToken,
+) -> io::Result<Report> {
+    let (width, height) = target.size();
+    let slot = target.composition().layout(config).remove(0);
+    let grow = |seed: u64| {
+        let config = Config {
+            seed,
+            ..slot.config.clone()
+        };
+        let mut tree = Tree::new_min_growth_with(config, 5, || !cancel.is_cancelled())?;
+        tree.grow_fully(cancel);
+        (!cancel.is_cancelled()).then_some(tree)
+    };
+    let mut report = Report::default();
+    let (Some(a), Some(b)) = (grow(config.seed), grow(other_seed)) else {
+        report
+            .warnings
+            .push("cancelled, no morph written".to_string());
+        return Ok(report);
+    };
+    let morph = Morph::between(a, b);
+
+    // there and back again, easing in and out of each tree
+    let amounts = (0..FRAMES)
+        .chain((0..FRAMES).rev().map(|frame| frame + 1))
+        .map(|frame| {
+            let t = frame as f32 / FRAMES as f32;
+            t * t * (3.0 - 2.0 * t)
+        })
+        .collect::<Vec<_>>();
+    report.save(out, |file| {
+        let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .set_animated(amounts.len() as u32, 0)
+            .map_err(io::Error::other)?;
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        for (frame, &amount) in amounts.iter().enumerate() {
+            if cancel.is_cancelled() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+            }
+            let mut image = Framebuffer::new(width, height, config.sky);
+            image.camera = slot.camera();
+            PrettyRender::new(morph.at(amount)).render(&mut image);
+            let delay = if frame % FRAMES == 0 { HOLD * FPS } else { 1 };
+            writer
+                .set_frame_delay(delay, FPS)
+                .map_err(io::Error::other)?;
+            writer
+                .write_image_data(image.to_image().as_raw())
+                .map_err(io::Error::other)?;
+        }
+        writer.finish().map_err(io::Error::other)
+    })?;
+    Ok(report)
+}
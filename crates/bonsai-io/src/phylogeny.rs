@@ -0,0 +1,220 @@
+//! Recording a lineage of configs as one animated PNG, the tree turning from each generation
+//! into the next while captions name the parameters that changed
+//!
+//! A lineage is any list of configs in order, e.g. the files the window exports one after
+//! another while a tree is tuned. Every generation grows with its own parameters and seed,
+//! consecutive ones are morphed like `--morph` does, with the colors and the sky blended along.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::*;
+use bonsai_engine::morph::Morph;
+use bonsai_render::caption;
+use cancel::CancelToken;
+use composition::Camera;
+use export::Report;
+use render::{Framebuffer, PrettyRender, Surface};
+use wallpaper::Target;
+
+/// Frames per second of the animation
+const FPS: u16 = 24;
+/// Frames of the way from one generation to the next
+const FRAMES: usize = 36;
+/// How long each generation stays on screen before turning into the next, in seconds
+const HOLD: u16 = 1;
+/// Backdrop behind the captions
+const CAPTION_BACKDROP: Color = Color::new(0, 0, 0, 150);
+/// Pixel height of the output a caption pixel is drawn per
+const CAPTION_PIXELS: usize = 270;
+
+/// The generations of a lineage, each file applied over `base`. A directory stands for the
+/// config files in it, in the order of their names, which is the order the window exported them in
+pub fn lineage(paths: &[PathBuf], base: &Config) -> io::Result<Vec<Config>> {
+    let mut files = vec![];
+    for path in paths {
+        if path.is_dir() {
+            let mut entries = fs::read_dir(path)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<io::Result<Vec<_>>>()?;
+            entries.retain(|entry| entry.extension().is_some_and(|ext| ext == "toml"));
+            entries.sort();
+            files.extend(entries);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    files
+        .iter()
+        .map(|file| {
+            let mut config = base.clone();
+            config_file::load(file, &mut config)?;
+            Ok(config)
+        })
+        .collect()
+}
+
+/// Grows the tree of every generation and writes the first turning into each following one to
+/// `out`, holding the last before it loops. A cancelled recording writes nothing.
+/// `progress` is told the index of every generation before it grows, and how many there are
+pub fn export(
+    generations: &[Config],
+    target: Target,
+    out: &Path,
+    cancel: &CancelToken,
+    mut progress: impl FnMut(usize, usize),
+) -> io::Result<Report> {
+    if generations.len() < 2 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "a lineage needs at least two generations",
+        ));
+    }
+    let mut report = Report::default();
+    let (width, height) = target.size();
+    let slots = generations
+        .iter()
+        .map(|config| target.composition().layout(config).remove(0))
+        .collect::<Vec<_>>();
+    let mut trees = vec![];
+    for (idx, slot) in slots.iter().enumerate() {
+        progress(idx, slots.len());
+        let tree = Tree::new_min_growth_with(slot.config.clone(), 5, || !cancel.is_cancelled());
+        let cancelled = || "cancelled, no lineage written".to_string();
+        let Some(mut tree) = tree.filter(|_| !cancel.is_cancelled()) else {
+            report.warnings.push(cancelled());
+            return Ok(report);
+        };
+        tree.grow_fully(cancel);
+        if cancel.is_cancelled() {
+            report.warnings.push(cancelled());
+            return Ok(report);
+        }
+        trees.push(tree);
+    }
+    let morphs = trees
+        .windows(2)
+        .map(|pair| Morph::between(pair[0].clone(), pair[1].clone()))
+        .collect::<Vec<_>>();
+    let captions = (0..generations.len())
+        .map(|idx| {
+            let mut lines = vec![format!("generation {}", idx + 1)];
+            if idx > 0 {
+                lines.extend(changes(&generations[idx - 1], &generations[idx]));
+            }
+            lines
+        })
+        .collect::<Vec<_>>();
+
+    // (morph, amount, ticks on screen): each generation held, then eased into the next
+    let mut frames = vec![];
+    for idx in 0..morphs.len() {
+        frames.push((idx, 0.0, HOLD * FPS));
+        frames.extend((1..FRAMES).map(|frame| {
+            let t = frame as f32 / FRAMES as f32;
+            (idx, t * t * (3.0 - 2.0 * t), 1)
+        }));
+    }
+    frames.push((morphs.len() - 1, 1.0, 2 * HOLD * FPS));
+
+    let scale = (height / CAPTION_PIXELS).max(1) as i32;
+    report.save(out, |file| {
+        let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .set_animated(frames.len() as u32, 0)
+            .map_err(io::Error::other)?;
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        for &(idx, amount, delay) in &frames {
+            if cancel.is_cancelled() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+            }
+            let (from, to) = (&trees[idx].config, &trees[idx + 1].config);
+            let mut tree = morphs[idx].at(amount);
+            // the look switches halfway, with the colors blending over the whole way
+            tree.config = Config {
+                colors: from.colors.lerp(to.colors, amount),
+                sky: from.sky.lerp(to.sky, amount),
+                roots: None,
+                ..if amount < 0.5 { from } else { to }.clone()
+            };
+            let mut image = Framebuffer::new(width, height, tree.config.sky);
+            image.camera = slots[idx].camera();
+            PrettyRender::new(tree).render(&mut image);
+            image.camera = Camera::default();
+            let generation = if amount > 0.0 { idx + 1 } else { idx };
+            draw_caption(&mut image, &captions[generation], scale);
+            writer
+                .set_frame_delay(delay, FPS)
+                .map_err(io::Error::other)?;
+            writer
+                .write_image_data(image.to_image().as_raw())
+                .map_err(io::Error::other)?;
+        }
+        writer.finish().map_err(io::Error::other)
+    })?;
+    Ok(report)
+}
+
+/// The settings `to` changed from `from`, as `key: old > new` in the keys of config files
+fn changes(from: &Config, to: &Config) -> Vec<String> {
+    let (from, to) = (settings(from), settings(to));
+    let mut lines = vec![];
+    for (key, new) in &to {
+        match from.iter().find(|(other, _)| other == key) {
+            Some((_, old)) if old == new => {}
+            Some((_, old)) => lines.push(format!("{}: {} > {}", key, old, new)),
+            None => lines.push(format!("{}: {}", key, new)),
+        }
+    }
+    for (key, old) in &from {
+        if !to.iter().any(|(other, _)| other == key) {
+            lines.push(format!("{}: {} > -", key, old));
+        }
+    }
+    lines
+}
+
+/// The `section.key` and value of every line `config_file::write` writes for the config,
+/// blossom kinds numbered from 1
+fn settings(config: &Config) -> Vec<(String, String)> {
+    let mut section = String::new();
+    let mut leaves = 0;
+    let mut settings = vec![];
+    for line in config_file::write(config).lines() {
+        if line == "[[leaf]]" {
+            leaves += 1;
+            section = format!("leaf{}.", leaves);
+        } else if let Some(name) = line.strip_prefix('[') {
+            section = format!("{}.", name.trim_end_matches(']'));
+        } else if let Some((key, value)) = line.split_once(" = ") {
+            settings.push((section.clone() + key, value.trim_matches('"').to_string()));
+        }
+    }
+    settings
+}
+
+/// The lines of a caption in the top left corner, on a dark backdrop
+fn draw_caption(image: &mut Framebuffer, lines: &[String], scale: i32) {
+    let margin = 4 * scale;
+    let width = lines
+        .iter()
+        .map(|line| caption::width(line, scale))
+        .max()
+        .unwrap_or(0);
+    let height = lines.len() as i32 * caption::line_height(scale) - scale;
+    image.fill_rect(
+        margin - 2 * scale,
+        margin - 2 * scale,
+        width + 4 * scale,
+        height + 4 * scale,
+        CAPTION_BACKDROP,
+    );
+    for (idx, line) in lines.iter().enumerate() {
+        let y = margin + idx as i32 * caption::line_height(scale);
+        caption::draw(image, line, margin, y, scale, Color::WHITE);
+    }
+}
@@ -0,0 +1,716 @@
+//! Saving grown trees as plain text `.bonsai` files
+//!
+//! A save holds the nodes of the tree, the parts of the config that change how it looks and the
+//! parameters it grows with, so a shared save grows on the same under anyone's config.
+//! Everything else comes from the config it is loaded with. Trees saved while growing also keep
+//! their attraction points, the ones already reached, queued nodes and random state,
+//! so they grow on exactly as before.
+//!
+//! The format is written out by hand rather than derived with serde: the engine stays free of
+//! serialization dependencies, and old saves keep loading as the config gains fields.
+
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::*;
+
+const HEADER: &str = "pixel-bonsai save 1";
+
+/// Where saves are written to and the gallery looks for them
+pub const SAVE_DIR: &str = "saves";
+
+/// File in the save directory the window keeps its tree in, in case it crashes or loses power
+pub const AUTOSAVE: &str = "autosave.bonsai";
+
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Parameters of the growth, saved as `param` lines under their config file keys
+fn growth_params(config: &Config) -> [(&'static str, String); 19] {
+    [
+        ("attraction_dist", config.attraction_dist.to_string()),
+        ("kill_dist", config.kill_dist.to_string()),
+        ("grow_dist", config.grow_dist.to_string()),
+        ("node_min_dist", config.node_min_dist.to_string()),
+        (
+            "subdivide_max_length",
+            config.subdivide_max_length.to_string(),
+        ),
+        ("max_children", config.max_children.to_string()),
+        ("max_depth", config.max_depth.to_string()),
+        ("min_y_growth", config.min_y_growth.to_string()),
+        ("parent_dir_factor", config.parent_dir_factor.to_string()),
+        ("tropism_x", config.tropism.x.to_string()),
+        ("tropism_y", config.tropism.y.to_string()),
+        ("weight_display_pow", config.weight_display_pow.to_string()),
+        ("radius_taper_cap", config.radius_taper_cap.to_string()),
+        ("canopy_memory", config.canopy_memory.to_string()),
+        ("prune_pow", config.prune_pow.to_string()),
+        ("prune_size_ratio", config.prune_size_ratio.to_string()),
+        ("leaf_max_width", config.leaf_max_width.to_string()),
+        ("sprout_max_width", config.sprout_max_width.to_string()),
+        ("leaf_size", config.leaf_size.to_string()),
+    ]
+}
+
+fn hex(color: Color) -> String {
+    format!("{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+pub fn write(tree: &Tree, out: &mut impl Write) -> io::Result<()> {
+    let config = &tree.config;
+    writeln!(out, "{}", HEADER)?;
+    writeln!(out, "seed {}", config.seed)?;
+    writeln!(out, "size {} {}", config.width, config.height)?;
+    writeln!(out, "origin {} {}", config.origin.x, config.origin.y)?;
+    writeln!(out, "sun_angle {}", config.sun_angle)?;
+    writeln!(out, "sun_elevation {}", config.sun_elevation)?;
+    writeln!(
+        out,
+        "ambient {} {} {}",
+        config.ambient,
+        hex(config.ambient_sky),
+        hex(config.ambient_ground)
+    )?;
+    writeln!(
+        out,
+        "light {} {}",
+        hex(config.sun_color),
+        config.light_floor
+    )?;
+    writeln!(out, "sky {}", hex(config.sky))?;
+    writeln!(
+        out,
+        "fruit {} {}",
+        config.fruit_density,
+        hex(config.fruit_color)
+    )?;
+    for (key, value) in growth_params(config) {
+        writeln!(out, "param {} {}", key, value)?;
+    }
+    writeln!(
+        out,
+        "colors {} {} {}",
+        hex(config.colors.leaf),
+        hex(config.colors.new_branch),
+        hex(config.colors.old_branch)
+    )?;
+    if let Some(pot) = &config.pot {
+        writeln!(
+            out,
+            "pot {} {} {} {} {} {} {} {}",
+            pot.radius,
+            pot.height,
+            pot.taper,
+            pot.tilt,
+            hex(pot.color),
+            hex(pot.soil),
+            pot.decorated as u8,
+            pot.shape.name()
+        )?;
+    }
+    if let Some(roots) = &config.roots {
+        let color = roots.color.map_or("-".to_string(), hex);
+        writeln!(
+            out,
+            "roots {} {} {} {}",
+            roots.depth,
+            roots.spread,
+            color,
+            hex(roots.soil)
+        )?;
+    }
+    if let Some(volume) = &config.volume {
+        writeln!(out, "volume {} {}", volume.depth, volume.yaw)?;
+    }
+    if let Some(kusamono) = &config.kusamono {
+        writeln!(
+            out,
+            "kusamono {} {}",
+            kusamono.height,
+            kusamono.kind.map_or("-", |kind| kind.name())
+        )?;
+    }
+    if let Some(ivy) = &config.ivy {
+        writeln!(
+            out,
+            "ivy {} {} {} {}",
+            ivy.reach,
+            ivy.seed,
+            hex(ivy.leaf),
+            hex(ivy.stem)
+        )?;
+    }
+    if let Some(tokonoma) = &config.tokonoma {
+        writeln!(
+            out,
+            "tokonoma {} {} {} {}",
+            hex(tokonoma.wall),
+            hex(tokonoma.wood),
+            tokonoma.stand_height,
+            tokonoma.scroll as u8
+        )?;
+    }
+    if let Some(scenery) = &config.scenery {
+        writeln!(
+            out,
+            "scenery {} {} {} {} {} {} {} {}",
+            scenery.gradient,
+            scenery.sun as u8,
+            scenery.hills,
+            scenery.hill_height,
+            scenery.ground,
+            hex(scenery.hill_color),
+            hex(scenery.grass),
+            hex(scenery.soil)
+        )?;
+    }
+    if let Some(background) = &config.background {
+        writeln!(
+            out,
+            "background {} {} {}",
+            background.fit.name(),
+            background.pixelate as u8,
+            background.path.display()
+        )?;
+    }
+    writeln!(out, "shadow_tint {}", hex(config.shadow_tint))?;
+    if !config.ramp.is_empty() {
+        let ramp = config.ramp.iter().map(|color| hex(*color));
+        writeln!(out, "ramp {}", ramp.collect::<Vec<_>>().join(" "))?;
+    }
+    if let Some(sprites) = &config.leaf_sprites {
+        writeln!(out, "leaf_sprites {}", sprites.name())?;
+    }
+    for leaf in &config.leaves {
+        writeln!(
+            out,
+            "leaf {} {} {}",
+            hex(leaf.color),
+            leaf.probability,
+            leaf.size
+        )?;
+    }
+    for node in tree.nodes() {
+        write_node(out, "node", &node)?;
+    }
+    for set in tree.selections() {
+        let nodes = set
+            .nodes
+            .iter()
+            .map(|idx| idx.to_string())
+            .collect::<Vec<_>>();
+        writeln!(
+            out,
+            "selection {} {} {}",
+            set.color.map_or("-".to_string(), hex),
+            if nodes.is_empty() {
+                "-".to_string()
+            } else {
+                nodes.join(",")
+            },
+            set.name
+        )?;
+    }
+    if tree.tended() > 0.0 {
+        writeln!(out, "tended {}", tree.tended())?;
+    }
+    if tree.aged() > 0 {
+        writeln!(out, "aged {}", tree.aged())?;
+    }
+    if let Some(growth) = tree.growth() {
+        writeln!(out, "growing {}", growth.word_pos)?;
+        for point in &growth.points {
+            writeln!(out, "point {} {}", point.x, point.y)?;
+        }
+        for node in &growth.pending {
+            write_node(out, "pending", node)?;
+        }
+        for point in &growth.consumed {
+            writeln!(out, "consumed {} {}", point.x, point.y)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_node(out: &mut impl Write, key: &str, node: &Node) -> io::Result<()> {
+    let parent = node.parent.map_or("-".to_string(), |p| p.to_string());
+    writeln!(
+        out,
+        "{} {} {} {} {} {} {} {} {} {}",
+        key,
+        node.pos.x,
+        node.pos.y,
+        node.z,
+        parent,
+        node.depth,
+        node.weight,
+        node.child_count,
+        node.radius,
+        node.alive as u8
+    )
+}
+
+fn invalid(line: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid line in save: {}", line),
+    )
+}
+
+/// Reads a saved tree, using `base` for everything the save doesn't contain
+pub fn read(input: impl BufRead, base: &Config) -> io::Result<Tree> {
+    let mut lines = input.lines();
+    if lines.next().transpose()?.as_deref() != Some(HEADER) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a pixel-bonsai save",
+        ));
+    }
+
+    let mut config = base.clone();
+    let mut leaves = vec![];
+    let mut nodes = vec![];
+    let mut pending = vec![];
+    let mut points = vec![];
+    let mut consumed = vec![];
+    // name, color and nodes of every selection set
+    let mut selections = vec![];
+    // word position of the random generator, only saved while growing
+    let mut growing = None;
+    // seconds the tree was looked after, for its nest
+    let mut tended = 0.0;
+    // times it aged, its deadwood is one of the selections
+    let mut aged = 0;
+    for line in lines {
+        let line = line?;
+        let mut words = line.split_whitespace();
+        let key = words.next();
+        let values = words.collect::<Vec<_>>();
+        let num = |i: usize| -> io::Result<f32> {
+            values
+                .get(i)
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(|| invalid(&line))
+        };
+        // positions keep the precision the growth runs in
+        let real = |i: usize| -> io::Result<Real> {
+            values
+                .get(i)
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(|| invalid(&line))
+        };
+        let color = |i: usize| {
+            values
+                .get(i)
+                .and_then(|v| Color::from_hex(v).ok())
+                .ok_or_else(|| invalid(&line))
+        };
+        match key {
+            Some("seed") => {
+                config.seed = values
+                    .first()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(|| invalid(&line))?
+            }
+            Some("size") => (config.width, config.height) = (num(0)?, num(1)?),
+            Some("origin") => config.origin = Vector2::new(num(0)?, num(1)?),
+            Some("sun_angle") => config.sun_angle = num(0)?,
+            Some("sun_elevation") => config.sun_elevation = num(0)?,
+            Some("ambient") => {
+                (config.ambient, config.ambient_sky, config.ambient_ground) =
+                    (num(0)?, color(1)?, color(2)?)
+            }
+            Some("light") => (config.sun_color, config.light_floor) = (color(0)?, num(1)?),
+            Some("sky") => config.sky = color(0)?,
+            Some("shadow_tint") => config.shadow_tint = color(0)?,
+            Some("ramp") => config.ramp = (0..values.len()).map(color).collect::<Result<_, _>>()?,
+            Some("fruit") => (config.fruit_density, config.fruit_color) = (num(0)?, color(1)?),
+            Some("param") => match values.as_slice() {
+                [key, value] => {
+                    config_file::apply_override(&format!("{}={}", key, value), &mut config)
+                        .map_err(|_| invalid(&line))?
+                }
+                _ => return Err(invalid(&line)),
+            },
+            Some("colors") => {
+                config.colors = ColorPalette {
+                    leaf: color(0)?,
+                    new_branch: color(1)?,
+                    old_branch: color(2)?,
+                }
+            }
+            Some("pot") => {
+                config.pot = Some(pot::Pot {
+                    radius: num(0)?,
+                    height: num(1)?,
+                    taper: num(2)?,
+                    tilt: num(3)?,
+                    color: color(4)?,
+                    soil: color(5)?,
+                    decorated: num(6)? != 0.0,
+                    // saves from before pots had shapes hold ovals
+                    shape: match values.get(7) {
+                        Some(name) => pot::Shape::parse(name).ok_or_else(|| invalid(&line))?,
+                        None => pot::Shape::Oval,
+                    },
+                })
+            }
+            Some("roots") => {
+                config.roots = Some(roots::Roots {
+                    depth: num(0)?,
+                    spread: num(1)?,
+                    color: match values.get(2) {
+                        Some(&"-") => None,
+                        _ => Some(color(2)?),
+                    },
+                    soil: color(3)?,
+                })
+            }
+            Some("volume") => {
+                config.volume = Some(volume::Volume {
+                    depth: num(0)?,
+                    yaw: num(1)?,
+                })
+            }
+            Some("kusamono") => {
+                let kind = match values.get(1) {
+                    Some(&"-") => None,
+                    Some(name) => Some(kusamono::Kind::parse(name).ok_or_else(|| invalid(&line))?),
+                    None => return Err(invalid(&line)),
+                };
+                config.kusamono = Some(kusamono::Kusamono {
+                    height: num(0)?,
+                    kind,
+                })
+            }
+            Some("ivy") => {
+                config.ivy = Some(ivy::Ivy {
+                    reach: num(0)?,
+                    seed: values
+                        .get(1)
+                        .and_then(|seed| seed.parse().ok())
+                        .ok_or_else(|| invalid(&line))?,
+                    leaf: color(2)?,
+                    stem: color(3)?,
+                })
+            }
+            Some("tokonoma") => {
+                config.tokonoma = Some(tokonoma::Tokonoma {
+                    wall: color(0)?,
+                    wood: color(1)?,
+                    stand_height: num(2)?,
+                    scroll: num(3)? != 0.0,
+                })
+            }
+            Some("scenery") => {
+                config.scenery = Some(scenery::Scenery {
+                    gradient: num(0)?,
+                    sun: num(1)? != 0.0,
+                    hills: num(2)? as usize,
+                    hill_height: num(3)?,
+                    ground: num(4)?,
+                    hill_color: color(5)?,
+                    grass: color(6)?,
+                    soil: color(7)?,
+                })
+            }
+            Some("background") => {
+                let fit = values.first().and_then(|name| background::Fit::parse(name));
+                // the path is the rest of the line, it may contain spaces
+                let path = line.splitn(4, ' ').nth(3).filter(|path| !path.is_empty());
+                let (Some(fit), Some(path)) = (fit, path) else {
+                    return Err(invalid(&line));
+                };
+                config.background = Some(background::Background {
+                    path: path.into(),
+                    fit,
+                    pixelate: num(1)? != 0.0,
+                })
+            }
+            Some("leaf_sprites") => {
+                // a directory is the rest of the line, it may contain spaces
+                let name = line.split_once(' ').map(|(_, name)| name);
+                let name = name
+                    .filter(|name| !name.is_empty())
+                    .ok_or_else(|| invalid(&line))?;
+                config.leaf_sprites = Some(sprites::LeafSprites::parse(name));
+            }
+            Some("leaf") => leaves.push(LeafType {
+                color: color(0)?,
+                probability: num(1)?,
+                size: num(2)?,
+            }),
+            Some(key @ ("node" | "pending")) => {
+                let parent = match values.get(3) {
+                    Some(&"-") => None,
+                    Some(v) => Some(v.parse().map_err(|_| invalid(&line))?),
+                    None => return Err(invalid(&line)),
+                };
+                let node = Node {
+                    pos: Point::new(real(0)?, real(1)?),
+                    z: num(2)?,
+                    parent,
+                    depth: num(4)? as usize,
+                    weight: num(5)? as usize,
+                    child_count: num(6)? as usize,
+                    radius: num(7)?,
+                    alive: num(8)? != 0.0,
+                };
+                match key {
+                    "node" => nodes.push(node),
+                    _ => pending.push(node),
+                }
+            }
+            Some("selection") => {
+                // the name is the rest of the line, it may contain spaces
+                let name = line.splitn(4, ' ').nth(3).filter(|name| !name.is_empty());
+                let (Some(name), Some(nodes)) = (name, values.get(1)) else {
+                    return Err(invalid(&line));
+                };
+                let nodes = match *nodes {
+                    "-" => vec![],
+                    nodes => nodes
+                        .split(',')
+                        .map(|idx| idx.parse::<usize>().map_err(|_| invalid(&line)))
+                        .collect::<io::Result<_>>()?,
+                };
+                let color = match values.first() {
+                    Some(&"-") => None,
+                    _ => Some(color(0)?),
+                };
+                selections.push((name.to_string(), color, nodes));
+            }
+            Some("tended") => tended = num(0)?,
+            Some("aged") => aged = num(0)? as u32,
+            Some("growing") => {
+                growing = Some(
+                    values
+                        .first()
+                        .and_then(|v| v.parse::<u128>().ok())
+                        .ok_or_else(|| invalid(&line))?,
+                )
+            }
+            Some("point") => points.push(Point::new(real(0)?, real(1)?)),
+            Some("consumed") => consumed.push(Point::new(real(0)?, real(1)?)),
+            None => {}
+            Some(_) => return Err(invalid(&line)),
+        }
+    }
+    if nodes
+        .iter()
+        .chain(&pending)
+        .any(|n| n.parent.is_some_and(|p| p >= nodes.len()))
+        || nodes.is_empty()
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "save has no tree or broken parent links",
+        ));
+    }
+    if !leaves.is_empty() {
+        config.leaves = leaves;
+    }
+
+    let growth = growing.map(|word_pos| Growth {
+        points,
+        pending,
+        consumed,
+        word_pos,
+    });
+    let mut tree = Tree::from_parts(config, nodes, growth);
+    for (name, color, nodes) in selections {
+        tree.select(&name, &nodes);
+        tree.recolor_selection(&name, color);
+    }
+    tree.tend(tended);
+    tree.set_aged(aged);
+    Ok(tree)
+}
+
+pub fn load(path: &Path, base: &Config) -> io::Result<Tree> {
+    read(io::BufReader::new(fs::File::open(path)?), base)
+}
+
+/// `tree.save(path)` and `Tree::load(path, &config)` for programs embedding the crates
+pub trait SaveFile: Sized {
+    /// Writes the tree to `path` as a `.bonsai` save
+    fn save(&self, path: &Path) -> io::Result<()>;
+    /// Reads the save at `path`, with everything it doesn't hold from `base`
+    fn load(path: &Path, base: &Config) -> io::Result<Self>;
+}
+
+impl SaveFile for Tree {
+    fn save(&self, path: &Path) -> io::Result<()> {
+        let mut out = io::BufWriter::new(fs::File::create(path)?);
+        write(self, &mut out)?;
+        out.flush()
+    }
+
+    fn load(path: &Path, base: &Config) -> io::Result<Self> {
+        load(path, base)
+    }
+}
+
+/// Writes the tree to a new file in the save directory
+pub fn save_new(tree: &Tree) -> io::Result<PathBuf> {
+    fs::create_dir_all(SAVE_DIR)?;
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let path = Path::new(SAVE_DIR).join(format!("bonsai-{}.bonsai", stamp));
+    export::save(&path, |out| write(tree, out))?;
+    Ok(path)
+}
+
+/// Replaces the autosave with the tree, writing to a temporary file first so a crash
+/// or power cut while writing doesn't lose the previous one
+pub fn autosave(tree: &Tree) -> io::Result<PathBuf> {
+    fs::create_dir_all(SAVE_DIR)?;
+    let path = Path::new(SAVE_DIR).join(AUTOSAVE);
+    let temp = path.with_extension("tmp");
+    let mut out = io::BufWriter::new(fs::File::create(&temp)?);
+    write(tree, &mut out)?;
+    out.into_inner()?.sync_all()?;
+    fs::rename(temp, &path)?;
+    Ok(path)
+}
+
+/// The autosaved tree from last time, `None` if there is none
+pub fn load_autosave(base: &Config) -> io::Result<Option<Tree>> {
+    let path = Path::new(SAVE_DIR).join(AUTOSAVE);
+    match load(&path, base) {
+        Ok(tree) => Ok(Some(tree)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(io::Error::new(
+            err.kind(),
+            format!("could not read {}: {}", path.display(), err),
+        )),
+    }
+}
+
+/// Autosaves the shown tree every minute while it changes
+pub struct Autosaver {
+    last: Instant,
+    /// seed, revision and minutes looked after of the tree last written
+    saved: Option<(u64, u64, u64)>,
+}
+
+impl Default for Autosaver {
+    fn default() -> Self {
+        Self {
+            last: Instant::now(),
+            saved: None,
+        }
+    }
+}
+
+impl Autosaver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes the tree if it changed and the last autosave is old enough, or right away with `force`,
+    /// returning where it went or why it didn't, `None` if it wasn't time to write
+    pub fn tick(&mut self, tree: &Tree, force: bool) -> Option<io::Result<PathBuf>> {
+        let state = (
+            tree.config.seed,
+            tree.revision(),
+            (tree.tended() / 60.0) as u64,
+        );
+        if self.saved == Some(state) || !(force || self.last.elapsed() >= AUTOSAVE_INTERVAL) {
+            return None;
+        }
+        self.last = Instant::now();
+        self.saved = Some(state);
+        Some(autosave(tree))
+    }
+}
+
+/// All saves in the directory, oldest first
+pub fn list(dir: &Path) -> Vec<PathBuf> {
+    let mut paths = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "bonsai"))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_saves_grow_on_with_their_own_parameters() {
+        let config = Config {
+            seed: 11,
+            num_points: 1500,
+            grow_dist: 3.0,
+            max_children: 2,
+            ..Config::default()
+        };
+        let mut tree = Tree::new(config);
+        for _ in 0..20 {
+            tree.step();
+        }
+        let mut saved = vec![];
+        write(&tree, &mut saved).unwrap();
+        // loaded by someone with the default parameters
+        let mut loaded = read(saved.as_slice(), &Config::default()).unwrap();
+        for _ in 0..20 {
+            tree.step();
+            loaded.step();
+        }
+        let (mut a, mut b) = (vec![], vec![]);
+        write(&tree, &mut a).unwrap();
+        write(&loaded, &mut b).unwrap();
+        assert!(a == b);
+    }
+
+    #[test]
+    fn selections_keep_their_names_and_colors() {
+        let mut tree = Tree::new(Config {
+            seed: 5,
+            ..Config::default()
+        });
+        for _ in 0..30 {
+            tree.step();
+        }
+        tree.select("front branch", &[3, 1]);
+        tree.select("apex", &[]);
+        tree.recolor_selection("front branch", Some(Color::from_hex("c04030").unwrap()));
+        let mut saved = vec![];
+        write(&tree, &mut saved).unwrap();
+        let loaded = read(saved.as_slice(), &Config::default()).unwrap();
+        assert_eq!(loaded.selections(), tree.selections());
+    }
+
+    #[test]
+    fn trees_load_as_they_were_saved() {
+        let mut tree = Tree::new(Config {
+            seed: 8,
+            ..Config::default()
+        });
+        for _ in 0..30 {
+            tree.step();
+        }
+        let path =
+            std::env::temp_dir().join(format!("pixel-bonsai-save-{}.bonsai", std::process::id()));
+        tree.save(&path).unwrap();
+        let loaded = Tree::load(&path, &Config::default()).unwrap();
+        fs::remove_file(&path).unwrap();
+        let (mut a, mut b) = (vec![], vec![]);
+        write(&tree, &mut a).unwrap();
+        write(&loaded, &mut b).unwrap();
+        assert!(a == b);
+    }
+}
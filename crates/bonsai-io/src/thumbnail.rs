@@ -0,0 +1,49 @@
+//! Small previews of saved trees, quick enough for a file manager to ask for one per file
+//!
+//! The tree is drawn with pixels as coarse as the thumbnail can show anyway, so the canvases stay
+//! small, and without the passes nobody would see at that size: the self shadows, the occlusion
+//! of the canopy, the grain and the dithering. The same save always gives the same thumbnail.
+//!
+//! A freedesktop thumbnailer hands it the size, the save and where the PNG goes:
+//!
+//! ```text
+//! [Thumbnailer Entry]
+//! TryExec=pixeltrees
+//! Exec=pixeltrees --thumbnail %s %i --out %o
+//! MimeType=application/x-bonsai;
+//! ```
+
+use std::io::{self, Seek, Write};
+
+use crate::*;
+use render::{Framebuffer, PrettyRender};
+
+/// Renders the tree to fit into a square of `size` pixels, keeping its aspect
+pub fn render(mut tree: Tree, size: u32) -> image::RgbaImage {
+    let size = size.max(1);
+    let config = &mut tree.config;
+    let (width, height) = (config.width.max(1.0), config.height.max(1.0));
+    // a canvas pixel per thumbnail pixel at most
+    let coarsest = (width.max(height) / size as f32).ceil() as usize;
+    config.pixel_size = config.pixel_size.max(coarsest);
+    (config.self_shadow, config.canopy_occlusion) = (0.0, 0.0);
+    config.ambient_occlusion = 0.0;
+    (config.grain, config.shades) = (0.0, 0);
+    (config.night_sky, config.nameplate) = (false, false);
+    let mut frame = Framebuffer::new(width as usize, height as usize, config.sky);
+    PrettyRender::new(tree).render(&mut frame);
+
+    let scale = size as f32 / width.max(height);
+    let fit = |side: f32| ((side * scale).round() as u32).clamp(1, size);
+    image::imageops::resize(
+        &frame.to_image(),
+        fit(width),
+        fit(height),
+        image::imageops::FilterType::Nearest,
+    )
+}
+
+/// Renders the thumbnail of the tree and encodes it as PNG
+pub fn write_png(tree: Tree, size: u32, out: &mut (impl Write + Seek)) -> io::Result<()> {
+    export::write_png(&render(tree, size), out)
+}
@@ -0,0 +1,106 @@
+//! Recording a tree while it grows, written as an animated PNG
+
+use std::{io, path::Path};
+
+use crate::*;
+use cancel::CancelToken;
+use export::Report;
+use render::{Framebuffer, PrettyRender};
+use wallpaper::Target;
+
+/// Frames per second of the animation
+const FPS: u16 = 24;
+/// How long the finished tree stays on screen before the animation loops, in seconds
+const HOLD: u16 = 2;
+
+/// Grows a tree for the target and writes `frames` evenly spaced moments of its growth
+/// to `out` as an animated PNG, the last frame showing the finished tree
+///
+/// Each frame stays on screen for `hold` ticks of the frame rate. Holding for more than one
+/// makes it stop-motion: the growth advances in visible jumps and the leaves boil a little
+/// from frame to frame, like hand animated pixel art.
+///
+/// The growth is deterministic, so it runs once to count the steps and again while
+/// rendering, which keeps only one frame in memory at a time.
+/// A cancelled recording writes nothing.
+pub fn export(
+    config: &Config,
+    target: Target,
+    frames: usize,
+    hold: u16,
+    out: &Path,
+    cancel: &CancelToken,
+) -> io::Result<Report> {
+    let (width, height) = target.size();
+    let slot = target.composition().layout(config).remove(0);
+
+    let mut report = Report::default();
+    let Some(sprout) = Tree::new_min_growth_with(slot.config.clone(), 5, || !cancel.is_cancelled())
+    else {
+        report
+            .warnings
+            .push("cancelled before the tree sprouted".to_string());
+        return Ok(report);
+    };
+    let mut tree = sprout.clone();
+    let mut steps = 0;
+    while tree.is_growing() && steps < 10_000 && !cancel.is_cancelled() {
+        tree.step();
+        steps += 1;
+    }
+    if cancel.is_cancelled() {
+        report
+            .warnings
+            .push("cancelled, no timelapse written".to_string());
+        return Ok(report);
+    }
+    report.grown(&tree);
+
+    // never more frames than there are steps to show
+    let frames = frames.clamp(1, steps + 1);
+    let step_of = |frame: usize| match frames {
+        1 => steps,
+        _ => (frame * steps + (frames - 1) / 2) / (frames - 1),
+    };
+    let mut pretty = PrettyRender::new(sprout);
+    let mut done = 0;
+    report.save(out, |file| {
+        let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .set_animated(frames as u32, 0)
+            .map_err(io::Error::other)?;
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        export::metadata::write_chunks(&mut writer, &export::metadata::Metadata::of(config))?;
+        for frame in 0..frames {
+            if cancel.is_cancelled() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+            }
+            while done < step_of(frame) {
+                pretty.tree_mut().step();
+                done += 1;
+            }
+            if hold > 1 {
+                pretty.set_jitter(Some(frame as u64));
+            }
+            let mut image = Framebuffer::new(width, height, config.sky);
+            image.camera = slot.camera();
+            pretty.render(&mut image);
+            let delay = if frame + 1 == frames {
+                HOLD * FPS
+            } else {
+                hold.max(1)
+            };
+            writer
+                .set_frame_delay(delay, FPS)
+                .map_err(io::Error::other)?;
+            writer
+                .write_image_data(image.to_image().as_raw())
+                .map_err(io::Error::other)?;
+        }
+        writer.finish().map_err(io::Error::other)
+    })?;
+    report.warnings.extend(pretty.take_sprite_error());
+    Ok(report)
+}
@@ -0,0 +1,257 @@
+use crate::*;
+
+/// Most colors a grid holds, every cell keeps its palette index + 1 in a byte
+pub const MAX_COLORS: usize = 255;
+
+/// Where the nodes of a tree go in a grid
+struct Placement {
+    /// voxels per world unit
+    scaling: f32,
+    /// voxels around the tree, for the leaves sticking out
+    margin: usize,
+    /// world units per unit of node depth
+    depth_unit: f32,
+    /// depth in world units of the frontmost node
+    front: f32,
+    /// world units between the frontmost and the backmost node
+    depth: f32,
+}
+
+impl Placement {
+    fn of(tree: &Tree, resolution: f32) -> Self {
+        let config = &tree.config;
+        // a tree grown with a volume keeps its depth in world units, in front of the trunk too
+        let depth_unit = match config.volume {
+            Some(_) => 1.0,
+            None => config.pixel_size as f32,
+        };
+        let (front, back) = match config.volume {
+            Some(_) => tree
+                .nodes()
+                .filter(|n| n.alive)
+                .fold((0.0f32, 0.0f32), |(lo, hi), n| (lo.min(n.z), hi.max(n.z))),
+            None => (0.0, config.node_depth_max as f32),
+        };
+        let scaling = resolution / config.pixel_size as f32;
+        let max_leaf = config.leaves.iter().map(|l| l.size).fold(0.0, f32::max);
+        Self {
+            scaling,
+            margin: ((config.leaf_size * scaling + max_leaf * resolution).ceil() as usize) + 1,
+            depth_unit,
+            front: front * depth_unit,
+            depth: (back - front) * depth_unit,
+        }
+    }
+
+    /// Node depth in world units
+    fn depth_of(&self, z: f32) -> f32 {
+        z * self.depth_unit
+    }
+
+    /// Grid position of a point `depth` world units back
+    fn at(&self, pos: Vector2, depth: f32) -> Vector3 {
+        let m = self.margin as f32;
+        Vector3::new(pos.x, pos.y, depth - self.front) * self.scaling + Vector3::new(m, m, m)
+    }
+
+    /// Voxels a grid needs along an axis `extent` world units long
+    fn size(&self, extent: f32) -> usize {
+        (extent * self.scaling).ceil() as usize + 2 * self.margin
+    }
+}
+
+/// A tree extruded into a 3D grid
+///
+/// x goes right, y goes up and z goes towards the camera
+pub struct VoxelGrid {
+    pub size_x: usize,
+    pub size_y: usize,
+    pub size_z: usize,
+    /// 0 = empty, otherwise index into `palette` + 1
+    cells: Vec<u8>,
+    pub palette: Vec<Color>,
+}
+
+impl VoxelGrid {
+    pub fn new(size_x: usize, size_y: usize, size_z: usize) -> Self {
+        Self {
+            size_x,
+            size_y,
+            size_z,
+            cells: vec![0; size_x * size_y * size_z],
+            palette: vec![],
+        }
+    }
+
+    /// Voxelizes the alive branches as tapered capsules and optionally the leaves as blobs
+    ///
+    /// resolution: voxels per canvas pixel
+    pub fn from_tree(tree: &Tree, resolution: f32, leaves: bool) -> Self {
+        let config = &tree.config;
+        let place = Placement::of(tree, resolution);
+        let scaling = place.scaling;
+        let mut grid = Self::new(
+            place.size(config.width),
+            place.size(config.height),
+            place.size(place.depth),
+        );
+
+        let wood = grid.add_color(config.colors.old_branch);
+        for node in tree.nodes().filter(|n| n.alive) {
+            if let Some(parent_idx) = node.parent {
+                let parent = tree.node(parent_idx);
+                grid.fill_capsule(
+                    place.at(parent.pos.vec(), place.depth_of(parent.z)),
+                    tree.radius_of(&parent) * scaling,
+                    place.at(node.pos.vec(), place.depth_of(node.z)),
+                    tree.radius_of(&node) * scaling,
+                    wood,
+                );
+            }
+        }
+
+        if !leaves {
+            return grid;
+        }
+        let mut rng = config.leaf_rng();
+        for node in tree.nodes().filter(|n| n.alive) {
+            if tree.radius_of(&node) >= config.leaf_max_width {
+                continue;
+            }
+            for _ in 0..2 {
+                let mut offset = || (rng.gen::<f32>() * 2.0 - 1.0) * config.leaf_size;
+                let o = Vector2::new(offset(), offset());
+                let oz = offset();
+                let leaf = config.get_leaf_type(&mut rng);
+                let material = grid.add_color(leaf.color);
+                let center = place.at(node.pos.vec() + o, place.depth_of(node.z) + oz);
+                let size = leaf.size * resolution;
+                grid.fill_capsule(center, size, center, size, material);
+            }
+        }
+        grid
+    }
+
+    /// Returns the palette entry for the color, adding it if necessary,
+    /// or the closest one once all `MAX_COLORS` are taken
+    fn add_color(&mut self, color: Color) -> u8 {
+        let idx = match self.palette.iter().position(|c| *c == color) {
+            Some(idx) => idx,
+            None if self.palette.len() < MAX_COLORS => {
+                self.palette.push(color);
+                self.palette.len() - 1
+            }
+            None => {
+                let distance = |c: &Color| {
+                    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+                    d(c.r, color.r) + d(c.g, color.g) + d(c.b, color.b)
+                };
+                (0..self.palette.len())
+                    .min_by_key(|&idx| distance(&self.palette[idx]))
+                    .unwrap()
+            }
+        };
+        u8::try_from(idx + 1).expect("the palette is capped below 256 entries")
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        x + self.size_x * (y + self.size_y * z)
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.cells[self.index(x, y, z)]
+    }
+
+    /// Like `get`, but everything outside of the grid is empty
+    pub fn get_signed(&self, x: isize, y: isize, z: isize) -> u8 {
+        let inside = |v: isize, size: usize| v >= 0 && (v as usize) < size;
+        if inside(x, self.size_x) && inside(y, self.size_y) && inside(z, self.size_z) {
+            self.get(x as usize, y as usize, z as usize)
+        } else {
+            0
+        }
+    }
+
+    pub fn color(&self, x: usize, y: usize, z: usize) -> Option<Color> {
+        match self.get(x, y, z) {
+            0 => None,
+            material => Some(self.palette[material as usize - 1]),
+        }
+    }
+
+    /// Fills empty voxels inside the capsule from `a` to `b` whose radius is interpolated along the way
+    fn fill_capsule(&mut self, a: Vector3, ra: f32, b: Vector3, rb: f32, material: u8) {
+        let r = ra.max(rb);
+        let lo = |a: f32, b: f32| (a.min(b) - r).floor().max(0.0) as usize;
+        let hi = |a: f32, b: f32, bound: usize| ((a.max(b) + r).ceil() as usize).min(bound - 1);
+
+        let ab = b - a;
+        let len_sqr = ab.dot(ab);
+        for z in lo(a.z, b.z)..=hi(a.z, b.z, self.size_z) {
+            for y in lo(a.y, b.y)..=hi(a.y, b.y, self.size_y) {
+                for x in lo(a.x, b.x)..=hi(a.x, b.x, self.size_x) {
+                    let p = Vector3::new(x as f32, y as f32, z as f32);
+                    let t = if len_sqr > 0.0 {
+                        ((p - a).dot(ab) / len_sqr).clamp(0.0, 1.0)
+                    } else {
+                        0.0
+                    };
+                    let closest = a + ab * t;
+                    let radius = ra + (rb - ra) * t;
+                    let idx = self.index(x, y, z);
+                    if (p - closest).length() <= radius && self.cells[idx] == 0 {
+                        self.cells[idx] = material;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volume_trees_stay_inside_the_grid() {
+        let mut tree = Tree::new(Config {
+            seed: 4,
+            volume: Some(bonsai_engine::volume::Volume::default()),
+            ..Config::default()
+        });
+        for _ in 0..400 {
+            tree.step();
+        }
+        assert!(tree.nodes().any(|node| node.alive && node.z < 0.0));
+
+        let grid = VoxelGrid::from_tree(&tree, 1.0, false);
+        let place = Placement::of(&tree, 1.0);
+        for node in tree.nodes().filter(|n| n.alive) {
+            let p = place.at(node.pos.vec(), place.depth_of(node.z));
+            let inside = |v: f32, size: usize| v >= 0.0 && v < size as f32;
+            assert!(
+                inside(p.x, grid.size_x) && inside(p.y, grid.size_y) && inside(p.z, grid.size_z),
+                "node at {:?} outside of {}x{}x{}",
+                p,
+                grid.size_x,
+                grid.size_y,
+                grid.size_z
+            );
+        }
+        let root = tree.node(0);
+        let p = place.at(root.pos.vec(), place.depth_of(root.z));
+        let cell = |v: f32| v.round() as usize;
+        assert_ne!(grid.get(cell(p.x), cell(p.y), cell(p.z)), 0);
+    }
+
+    #[test]
+    fn palette_is_capped() {
+        let mut grid = VoxelGrid::new(1, 1, 1);
+        for i in 0..300 {
+            let material = grid.add_color(Color::new(i as u8, (i / 256) as u8, 0, 255));
+            assert_ne!(material, 0);
+        }
+        assert_eq!(grid.palette.len(), MAX_COLORS);
+        assert_eq!(grid.add_color(Color::new(10, 1, 0, 255)), 11);
+    }
+}
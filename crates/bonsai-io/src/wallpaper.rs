@@ -0,0 +1,315 @@
+//! Rendering finished trees straight to wallpaper images for common screens
+
+use std::{io, path::Path};
+
+use crate::*;
+use bonsai_render::caption;
+use cancel::CancelToken;
+use composition::{Anchor, Camera, Composition, View};
+use export::{anchors::Anchors, Report};
+use night::NightSky;
+use render::{Framebuffer, Pass, PrettyRender};
+use scene::Scene;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+    Phone,
+    PhoneHd,
+    Tablet,
+    Desktop,
+    Ultrawide,
+}
+
+impl Target {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "phone" => Some(Target::Phone),
+            "phone-hd" => Some(Target::PhoneHd),
+            "tablet" => Some(Target::Tablet),
+            "desktop" => Some(Target::Desktop),
+            "ultrawide" => Some(Target::Ultrawide),
+            _ => None,
+        }
+    }
+
+    /// Output resolution in pixels
+    pub fn size(self) -> (usize, usize) {
+        match self {
+            Target::Phone => (1080, 2400),
+            Target::PhoneHd => (1440, 3200),
+            Target::Tablet => (1620, 2160),
+            Target::Desktop => (1920, 1080),
+            Target::Ultrawide => (3440, 1440),
+        }
+    }
+
+    pub fn composition(self) -> Composition {
+        let (width, height) = self.size();
+        let portrait = height > width;
+        Composition {
+            output_width: width as f32,
+            output_height: height as f32,
+            margin: width.min(height) as f32 * 0.08,
+            // phones and tablets show a clock in the upper part of the lock screen
+            top_clearance: if portrait { 1.0 / 3.0 } else { 0.0 },
+            trees: 1,
+            anchor: if portrait {
+                Anchor::Center
+            } else {
+                Anchor::RightThird
+            },
+            view: View::default(),
+            detail_zoom: 0.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Variant {
+    Light,
+    Dark,
+}
+
+impl Variant {
+    fn background(self, config: &Config) -> Color {
+        match self {
+            Variant::Light => config.sky,
+            Variant::Dark => Color::from_hex("1d2433").unwrap(),
+        }
+    }
+    /// How much the tree colors are dimmed
+    fn brightness(self) -> f32 {
+        match self {
+            Variant::Light => 1.0,
+            Variant::Dark => 0.55,
+        }
+    }
+}
+
+/// A tree rendered for a target
+#[derive(Default)]
+pub struct Rendered {
+    /// one image per variant
+    pub frames: Vec<(Variant, Framebuffer)>,
+    /// the tree alone in the separate render passes, on a transparent background
+    pub passes: Vec<(Pass, Framebuffer)>,
+    /// where the tree ended up
+    pub anchors: Option<Anchors>,
+    /// the tree as it was grown, unless it was cancelled before it sprouted
+    pub tree: Option<Tree>,
+    /// how the growth went and what went wrong, nothing is written yet
+    pub report: Report,
+}
+
+/// Grows a tree until it stops and renders it for the target, along with where it ended up
+///
+/// A cancelled growth renders the tree as far as it got, nothing at all if it never sprouted
+pub fn render(
+    config: &Config,
+    target: Target,
+    variants: &[Variant],
+    passes: &[Pass],
+    cancel: &CancelToken,
+) -> Rendered {
+    let (width, height) = target.size();
+    let slot = target.composition().layout(config).remove(0);
+
+    let keep_going = || !cancel.is_cancelled();
+    let grown = match slot.config.forest {
+        // the grove is grown up front, the merged tree has nothing left to grow
+        Some(count) => Scene::forest_with(&slot.config, count, 5, keep_going).map(|mut scene| {
+            scene.grow_fully(cancel);
+            scene.merged()
+        }),
+        None => Tree::new_min_growth_with(slot.config.clone(), 5, keep_going),
+    };
+    let mut report = Report::default();
+    let Some(mut tree) = grown else {
+        report
+            .warnings
+            .push("cancelled before the tree sprouted".to_string());
+        return Rendered {
+            report,
+            ..Rendered::default()
+        };
+    };
+    tree.grow_fully(cancel);
+    if cancel.is_cancelled() {
+        report.warnings.push(format!(
+            "cancelled after {} nodes, rendering the tree as far as it grew",
+            tree.node_count()
+        ));
+    } else {
+        report.grown(&tree);
+    }
+
+    let block = (slot.config.pixel_size as f32 * slot.zoom) as i32;
+    let mut layer = Framebuffer::new(width, height, Color::new(0, 0, 0, 0));
+    layer.camera = slot.camera();
+    let anchors = Anchors::find(&tree, &slot, width, height);
+    let mut pretty = PrettyRender::new(tree);
+    pretty.render(&mut layer);
+    report.warnings.extend(pretty.take_sprite_error());
+    if let Some(plate) = &anchors.nameplate {
+        // the plate is placed on the image, not in the world
+        layer.camera = Camera::default();
+        let scale = plate.height / caption::plate_size(&anchors.name, 1).1;
+        caption::draw_plate(&mut layer, &anchors.name, plate.x, plate.y, scale);
+    }
+    let passes = passes
+        .iter()
+        .map(|pass| {
+            let mut frame = Framebuffer::new(width, height, Color::new(0, 0, 0, 0));
+            frame.camera = slot.camera();
+            pretty.render_pass(&mut frame, *pass);
+            (*pass, frame)
+        })
+        .collect();
+
+    let backdrop = config.background.as_ref().and_then(|background| {
+        match bonsai_render::background::render(
+            background,
+            width,
+            height,
+            block as usize,
+            slot.offset,
+            config.sky,
+        ) {
+            Ok(frame) => Some(frame),
+            Err(err) => {
+                report.warnings.push(format!(
+                    "could not load {}: {}",
+                    background.path.display(),
+                    err
+                ));
+                None
+            }
+        }
+    });
+
+    let frames = variants
+        .iter()
+        .map(|variant| {
+            let mut frame = Framebuffer::new(width, height, variant.background(config));
+            let f = |c: u8| (c as f32 * variant.brightness()) as u8;
+            if let Some(backdrop) = &backdrop {
+                // dimmed like the tree, the ground shadow in the layer darkens it further
+                for y in 0..height {
+                    for x in 0..width {
+                        let c = backdrop.get(x, y);
+                        frame.blend_pixel(x, y, Color::new(f(c.r), f(c.g), f(c.b), c.a));
+                    }
+                }
+            } else if *variant == Variant::Dark && config.night_sky {
+                NightSky::now(block).draw(&mut frame, width, height);
+            }
+            for y in 0..height {
+                for x in 0..width {
+                    let c = layer.get(x, y);
+                    frame.blend_pixel(x, y, Color::new(f(c.r), f(c.g), f(c.b), c.a));
+                }
+            }
+            (*variant, frame)
+        })
+        .collect();
+    Rendered {
+        frames,
+        passes,
+        anchors: Some(anchors),
+        tree: Some(pretty.tree().clone()),
+        report,
+    }
+}
+
+/// Renders the wallpaper(s) and writes them as PNG next to `out`,
+/// with their palettes and anchors for compositing alongside.
+/// `with_passes` also writes the flat colors and the light of the tree as `-albedo` and `-lightmap`,
+/// `with_vectors` its branches as `.svg` and its skeleton as `-skeleton.json`
+///
+/// Once cancelled the first image is still written, the remaining ones are skipped
+pub fn export(
+    config: &Config,
+    target: Target,
+    with_variants: bool,
+    with_passes: bool,
+    with_vectors: bool,
+    out: &Path,
+    cancel: &CancelToken,
+) -> io::Result<Report> {
+    let variants: &[Variant] = if with_variants {
+        &[Variant::Light, Variant::Dark]
+    } else {
+        &[Variant::Light]
+    };
+    let passes: &[Pass] = if with_passes {
+        &[Pass::Albedo, Pass::Light]
+    } else {
+        &[]
+    };
+    let Rendered {
+        frames,
+        passes,
+        anchors,
+        tree,
+        mut report,
+    } = render(config, target, variants, passes, cancel);
+    let stem = out.file_stem().unwrap_or_default().to_string_lossy();
+    if !cancel.is_cancelled() {
+        for (pass, frame) in passes {
+            let suffix = match pass {
+                Pass::Lit => "lit",
+                Pass::Albedo => "albedo",
+                Pass::Light => "lightmap",
+            };
+            let path = out.with_file_name(format!("{}-{}.png", stem, suffix));
+            report.save(&path, |file| export::write_png(&frame.to_image(), file))?;
+        }
+    }
+    if let Some(tree) = tree.as_ref().filter(|_| with_vectors) {
+        report.save(out.with_extension("svg"), |file| {
+            export::svg::write(tree, file)
+        })?;
+        let path = out.with_file_name(format!("{}-skeleton.json", stem));
+        report.save(&path, |file| export::skeleton::write(tree, file))?;
+    }
+    let rendered = frames.len();
+    for (written, (variant, frame)) in frames.into_iter().enumerate() {
+        if written > 0 && cancel.is_cancelled() {
+            report.warnings.push(format!(
+                "cancelled, wrote {} of {} images",
+                written, rendered
+            ));
+            break;
+        }
+        let path = if with_variants {
+            let suffix = match variant {
+                Variant::Light => "light",
+                Variant::Dark => "dark",
+            };
+            out.with_file_name(format!("{}-{}.png", stem, suffix))
+        } else {
+            out.to_path_buf()
+        };
+        let image = frame.to_image();
+        let metadata = export::metadata::Metadata::of(config);
+        report.save(&path, |file| {
+            export::metadata::write_png(&image, &metadata, file)
+        })?;
+
+        let colors = export::palette::used_colors(&image);
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        report.save(path.with_extension("gpl"), |file| {
+            export::palette::write_gpl(&name, &colors, file)
+        })?;
+        report.save(path.with_extension("ase"), |file| {
+            export::palette::write_ase(&colors, file)
+        })?;
+        if let Some(anchors) = &anchors {
+            report.save(path.with_extension("json"), |file| {
+                export::anchors::write_json(anchors, file)
+            })?;
+        }
+        report.wallpapers.push(path);
+    }
+    Ok(report)
+}
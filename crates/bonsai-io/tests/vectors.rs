@@ -0,0 +1,63 @@
+//! Every config in `tests/vectors` grows the tree its `.snap` file next to it describes.
+//!
+//! A change that grows different trees on purpose updates them with
+//! `BLESS=1 cargo test -p bonsai-io --test vectors`, and the diff of the snapshots shows what
+//! changed. A new vector is a new config file, its snapshot is written the first time it is blessed.
+//! The snapshots are grown in `f32`, with the `f64` feature positions may be off by a hundredth.
+
+use std::{env, fs, path::Path};
+
+use bonsai_engine::{cancel::CancelToken, point::Real, snapshot, Config, Tree};
+use bonsai_io::config_file;
+
+#[test]
+fn configs_grow_their_checked_in_trees() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/vectors");
+    let bless = env::var_os("BLESS").is_some();
+    let mut configs = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect::<Vec<_>>();
+    configs.sort();
+    assert!(!configs.is_empty());
+
+    let mut failures = vec![];
+    for path in configs {
+        let mut config = Config::default();
+        config_file::load(&path, &mut config).unwrap();
+        // grown the way the app grows them, reseeding trees that stall right away
+        let mut tree = Tree::new_min_growth(config, 5);
+        tree.grow_fully(&CancelToken::new());
+        let actual = snapshot::write(&tree);
+
+        let snap = path.with_extension("snap");
+        if bless {
+            fs::write(&snap, &actual).unwrap();
+            continue;
+        }
+        let expected = fs::read_to_string(&snap).unwrap_or_default();
+        // growth in f64 rounds a few positions the other way, by a hundredth
+        let tolerance = if std::mem::size_of::<Real>() > 4 {
+            0.011
+        } else {
+            0.0
+        };
+        if let Some((line, expected, actual)) =
+            snapshot::first_difference(&expected, &actual, tolerance)
+        {
+            failures.push(format!(
+                "{}, line {}:\n  expected {}\n  grown    {}",
+                snap.display(),
+                line,
+                expected,
+                actual
+            ));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "trees changed, bless them with BLESS=1 if that was intended\n{}",
+        failures.join("\n")
+    );
+}
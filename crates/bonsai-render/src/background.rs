@@ -0,0 +1,84 @@
+//! Loading the background image and laying it out behind the tree
+
+use image::imageops::{self, FilterType};
+
+use crate::*;
+use bonsai_engine::background::{Background, Fit};
+use render::Framebuffer;
+
+/// Loads the image and lays it out on an output `width` × `height` large.
+///
+/// Pixelated images are averaged in blocks of `block` output pixels,
+/// on the grid the tree's pixels start at `grid`.
+pub fn render(
+    background: &Background,
+    width: usize,
+    height: usize,
+    block: usize,
+    grid: Vector2,
+    letterbox: Color,
+) -> image::ImageResult<Framebuffer> {
+    let source = image::open(&background.path)?.to_rgba8();
+    let (source_width, source_height) = (source.width() as f32, source.height() as f32);
+    let (scale_x, scale_y) = (width as f32 / source_width, height as f32 / source_height);
+    let scale = match background.fit {
+        Fit::Contain => scale_x.min(scale_y),
+        Fit::Cover => scale_x.max(scale_y),
+    };
+    let (scaled_width, scaled_height) = (
+        ((source_width * scale).round() as u32).max(1),
+        ((source_height * scale).round() as u32).max(1),
+    );
+    let scaled = imageops::resize(&source, scaled_width, scaled_height, FilterType::Triangle);
+    // centered, a covering image sticks out on both sides
+    let left = (width as i64 - scaled_width as i64) / 2;
+    let top = (height as i64 - scaled_height as i64) / 2;
+
+    let mut frame = Framebuffer::new(width, height, letterbox);
+    for y in 0..height {
+        for x in 0..width {
+            let (sx, sy) = (x as i64 - left, y as i64 - top);
+            if (0..scaled_width as i64).contains(&sx) && (0..scaled_height as i64).contains(&sy) {
+                let [r, g, b, a] = scaled.get_pixel(sx as u32, sy as u32).0;
+                frame.blend_pixel(x, y, Color::new(r, g, b, a));
+            }
+        }
+    }
+    if background.pixelate && block > 1 {
+        pixelate(&mut frame, block, grid);
+    }
+    Ok(frame)
+}
+
+/// Averages the frame in blocks of `block` × `block` pixels aligned to `grid`
+fn pixelate(frame: &mut Framebuffer, block: usize, grid: Vector2) {
+    let (width, height) = (frame.width(), frame.height());
+    let start = |offset: f32| -((block - offset.round().rem_euclid(block as f32) as usize) as i64);
+    let (start_x, start_y) = (start(grid.x), start(grid.y));
+    for block_y in (start_y..height as i64).step_by(block) {
+        for block_x in (start_x..width as i64).step_by(block) {
+            let xs = block_x.max(0) as usize..((block_x + block as i64) as usize).min(width);
+            let ys = block_y.max(0) as usize..((block_y + block as i64) as usize).min(height);
+            let mut sum = [0u32; 4];
+            let mut count = 0;
+            for y in ys.clone() {
+                for x in xs.clone() {
+                    let c = frame.get(x, y);
+                    for (total, channel) in sum.iter_mut().zip([c.r, c.g, c.b, c.a]) {
+                        *total += channel as u32;
+                    }
+                    count += 1;
+                }
+            }
+            if count == 0 {
+                continue;
+            }
+            let [r, g, b, a] = sum.map(|total| (total / count) as u8);
+            for y in ys.clone() {
+                for x in xs.clone() {
+                    frame.blend_pixel(x, y, Color::new(r, g, b, a));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! A tiny pixel font for captions burnt into exported images, where there is no window to
+//! draw text with
+//!
+//! Glyphs are 3×5 pixels, one row per entry with the left pixel in the high bit. Letters have one
+//! shape for both cases, anything without a glyph is drawn as `?`.
+
+use crate::*;
+use render::Surface;
+
+/// Rows of a glyph, top to bottom
+type Glyph = [u8; 5];
+
+const UNKNOWN: Glyph = [6, 1, 2, 0, 2];
+
+fn glyph(c: char) -> Glyph {
+    match c.to_ascii_lowercase() {
+        'a' => [2, 5, 7, 5, 5],
+        'b' => [6, 5, 6, 5, 6],
+        'c' => [3, 4, 4, 4, 3],
+        'd' => [6, 5, 5, 5, 6],
+        'e' => [7, 4, 6, 4, 7],
+        'f' => [7, 4, 6, 4, 4],
+        'g' => [3, 4, 5, 5, 3],
+        'h' => [5, 5, 7, 5, 5],
+        'i' => [7, 2, 2, 2, 7],
+        'j' => [1, 1, 1, 5, 2],
+        'k' => [5, 5, 6, 5, 5],
+        'l' => [4, 4, 4, 4, 7],
+        'm' => [5, 7, 7, 5, 5],
+        'n' => [6, 5, 5, 5, 5],
+        'o' => [2, 5, 5, 5, 2],
+        'p' => [6, 5, 6, 4, 4],
+        'q' => [2, 5, 5, 6, 3],
+        'r' => [6, 5, 6, 5, 5],
+        's' => [3, 4, 2, 1, 6],
+        't' => [7, 2, 2, 2, 2],
+        'u' => [5, 5, 5, 5, 7],
+        'v' => [5, 5, 5, 5, 2],
+        'w' => [5, 5, 7, 7, 5],
+        'x' => [5, 5, 2, 5, 5],
+        'y' => [5, 5, 2, 2, 2],
+        'z' => [7, 1, 2, 4, 7],
+        '0' => [7, 5, 5, 5, 7],
+        '1' => [2, 6, 2, 2, 7],
+        '2' => [6, 1, 2, 4, 7],
+        '3' => [6, 1, 2, 1, 6],
+        '4' => [5, 5, 7, 1, 1],
+        '5' => [7, 4, 6, 1, 6],
+        '6' => [3, 4, 6, 5, 2],
+        '7' => [7, 1, 2, 2, 2],
+        '8' => [2, 5, 2, 5, 2],
+        '9' => [2, 5, 3, 1, 6],
+        ' ' => [0; 5],
+        '.' => [0, 0, 0, 0, 2],
+        ',' => [0, 0, 0, 2, 4],
+        ':' => [0, 2, 0, 2, 0],
+        '-' => [0, 0, 7, 0, 0],
+        '+' => [0, 2, 7, 2, 0],
+        '_' => [0, 0, 0, 0, 7],
+        '=' => [0, 7, 0, 7, 0],
+        '>' => [4, 2, 1, 2, 4],
+        '<' => [1, 2, 4, 2, 1],
+        '/' => [1, 1, 2, 4, 4],
+        '(' => [1, 2, 2, 2, 1],
+        ')' => [4, 2, 2, 2, 4],
+        '"' => [5, 5, 0, 0, 0],
+        _ => UNKNOWN,
+    }
+}
+
+/// Height of a line of text in pixels at `scale`, with the gap to the next line
+pub fn line_height(scale: i32) -> i32 {
+    6 * scale
+}
+
+/// Width of `text` in pixels at `scale`
+pub fn width(text: &str, scale: i32) -> i32 {
+    (text.chars().count() as i32 * 4 - 1).max(0) * scale
+}
+
+/// Draws `text` with its top left corner at `x`, `y`, every pixel of the font `scale` pixels wide
+pub fn draw(surface: &mut impl Surface, text: &str, x: i32, y: i32, scale: i32, color: Color) {
+    for (idx, c) in text.chars().enumerate() {
+        let left = x + idx as i32 * 4 * scale;
+        for (row, bits) in glyph(c).into_iter().enumerate() {
+            for column in 0..3 {
+                if bits & (4 >> column) != 0 {
+                    let (px, py) = (left + column * scale, y + row as i32 * scale);
+                    surface.fill_rect(px, py, scale, scale, color);
+                }
+            }
+        }
+    }
+}
+
+/// Width and height of a nameplate for `text` at `scale`, see `draw_plate`
+pub fn plate_size(text: &str, scale: i32) -> (i32, i32) {
+    (width(text, scale) + 6 * scale, 11 * scale)
+}
+
+/// Draws `text` on a small wooden plate with its top left corner at `x`, `y`,
+/// framed by a darker border with two pixels of room around the letters
+pub fn draw_plate(surface: &mut impl Surface, text: &str, x: i32, y: i32, scale: i32) {
+    let (width, height) = plate_size(text, scale);
+    let (border, wood, letters) = (
+        Color::new(0x3b, 0x2a, 0x1e, 255),
+        Color::new(0x6b, 0x4a, 0x30, 255),
+        Color::new(0xe8, 0xd8, 0xb0, 255),
+    );
+    surface.fill_rect(x, y, width, height, border);
+    surface.fill_rect(
+        x + scale,
+        y + scale,
+        width - 2 * scale,
+        height - 2 * scale,
+        wood,
+    );
+    draw(surface, text, x + 3 * scale, y + 3 * scale, scale, letters);
+}
@@ -0,0 +1,64 @@
+//! The density the attraction points of a tree were scattered with, drawn over it
+//! to see how the noise and falloff shaped the crown
+
+use crate::*;
+use bonsai_engine::density::DensitySource;
+use render::Surface;
+
+/// Density field of a tree's config, kept until the config changes
+pub struct DensityOverlay {
+    seed: u64,
+    size: (f32, f32),
+    noise: noise::Noise,
+    falloff: falloff::Falloff,
+    source: DensitySource,
+    density: SimplexDensityPRG,
+}
+
+impl DensityOverlay {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            seed: config.seed,
+            size: (config.width, config.height),
+            noise: config.noise,
+            falloff: config.falloff,
+            source: config.density.clone(),
+            density: SimplexDensityPRG::new(config),
+        }
+    }
+
+    /// Whether this is the field the attraction points of `config` are scattered with
+    pub fn is_for(&self, config: &Config) -> bool {
+        self.seed == config.seed
+            && self.size == (config.width, config.height)
+            && self.noise == config.noise
+            && self.falloff == config.falloff
+            && self.source.same_as(&config.density)
+    }
+
+    /// Draws the field in the tree's pixels, placed like `PrettyRender` places its canvases,
+    /// the densest parts most opaque. In high contrast the field is a stronger black
+    pub fn draw(&self, d: &mut impl Surface, config: &Config) {
+        let weights = self.density.weights();
+        let max = weights.iter().flatten().fold(0.0f32, |max, w| max.max(*w));
+        if max <= 0.0 {
+            return;
+        }
+        let (pw, ph) = (config.pixel_size, config.pixel_height());
+        let rows = config.height as usize / ph + CANVAS_PADDING;
+        let height = weights.first().map_or(0, Vec::len);
+        let (tint, opacity) = if config.high_contrast {
+            (Color::BLACK, 0.85)
+        } else {
+            (Color::new(200, 40, 160, 255), 0.6)
+        };
+        for x in (0..weights.len()).step_by(pw) {
+            for y in (0..height).step_by(ph) {
+                let alpha = opacity * weights[x][y] / max;
+                let color = tint.fade(alpha);
+                let row = (rows - y / ph + 1) as i32;
+                d.fill_rect(x as i32, row * ph as i32, pw as i32, ph as i32, color);
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Grain in the flat colors of the bark and the leaves
+//!
+//! Bark gets striations running along the branch, leaves get mottled in clusters. Both come from
+//! a value noise over the canvas pixels, seeded by the tree, and only ever darken the color of
+//! the palette by a few fixed steps, so the bark is still a handful of shades and reads as pixel
+//! art. `grain` in the config sets how much darker the darkest step is.
+
+use crate::*;
+
+/// What a sphere is drawn as, which decides its grain
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Grain {
+    /// a single flat color
+    Flat,
+    /// bark, striated along the branch going in this direction on the canvas
+    Bark(Vector2),
+    /// leaves, mottled in clusters
+    Leaves,
+}
+
+/// Darker shades the grain has besides the color itself
+const STEPS: f32 = 2.0;
+/// Share of the noise that keeps the color as it is
+const CLEAR: f32 = 0.4;
+/// Canvas pixels a striation runs along the branch before it changes
+const BARK_ALONG: f32 = 6.0;
+/// Canvas pixels across a striation
+const BARK_ACROSS: f32 = 1.2;
+/// Canvas pixels across a cluster of leaves
+const LEAF_CLUSTER: f32 = 2.5;
+
+impl Grain {
+    /// How much darker the color gets at canvas pixel `x`, `y`, from 0 to `strength`
+    pub fn darkening(self, x: usize, y: usize, seed: u64, strength: f32) -> f32 {
+        if strength <= 0.0 {
+            return 0.0;
+        }
+        let pos = Vector2::new(x as f32, y as f32);
+        let noise = match self {
+            Grain::Flat => return 0.0,
+            Grain::Bark(along) => {
+                let along = along.normalized();
+                let across = Vector2::new(-along.y, along.x);
+                value_noise(
+                    pos.dot(along) / BARK_ALONG,
+                    pos.dot(across) / BARK_ACROSS,
+                    seed,
+                )
+            }
+            Grain::Leaves => value_noise(pos.x / LEAF_CLUSTER, pos.y / LEAF_CLUSTER, !seed),
+        };
+        let step = ((noise - CLEAR) / (1.0 - CLEAR) * STEPS).ceil();
+        step.clamp(0.0, STEPS) / STEPS * strength
+    }
+}
+
+/// `color` with its channels darkened by `amount`, keeping its alpha
+pub fn darken(color: Color, amount: f32) -> Color {
+    let channel = |c: u8| (c as f32 * (1.0 - amount)).round() as u8;
+    Color::new(
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        color.a,
+    )
+}
+
+/// Smooth noise between 0 and 1, changing over about one unit
+fn value_noise(x: f32, y: f32, seed: u64) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let (tx, ty) = (smooth(x - x0), smooth(y - y0));
+    let corner = |dx: f32, dy: f32| {
+        let lattice = ((x0 + dx) as i64 as u64) ^ ((y0 + dy) as i64 as u64).rotate_left(32);
+        render::blob_threshold(seed, lattice)
+    };
+    let top = corner(0.0, 0.0) + (corner(1.0, 0.0) - corner(0.0, 0.0)) * tx;
+    let bottom = corner(0.0, 1.0) + (corner(1.0, 1.0) - corner(0.0, 1.0)) * tx;
+    top + (bottom - top) * ty
+}
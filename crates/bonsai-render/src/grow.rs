@@ -0,0 +1,84 @@
+//! Growing a tree straight into an image, for programs embedding the bonsai without a window
+//!
+//! ```
+//! let config = bonsai_engine::Config {
+//!     num_points: 500,
+//!     ..Default::default()
+//! };
+//! let image = bonsai_render::grow_to_image(&config, 7);
+//! assert_eq!(image.dimensions(), (500, 500));
+//! ```
+//!
+//! The image is as big as the world of the config, with the tree on its sky. `grow_frames`
+//! hands out the tree as it grows instead, for showing the growth while it happens.
+
+use crate::*;
+use render::{Framebuffer, PrettyRender};
+
+/// Most steps a tree grows for, like `Tree::grow_fully`
+const MAX_STEPS: usize = 10_000;
+
+/// Grows the tree of `config` from `seed` until it stops and renders it
+pub fn grow_to_image(config: &Config, seed: u64) -> image::RgbaImage {
+    let mut frames = grow_frames(config, seed, MAX_STEPS);
+    let mut last = frames.next().unwrap();
+    for frame in frames {
+        last = frame;
+    }
+    last
+}
+
+/// The tree of `config` from `seed` rendered every `steps` steps of its growth,
+/// the last frame is the tree grown fully
+pub fn grow_frames(config: &Config, seed: u64, steps: usize) -> Frames {
+    let config = Config {
+        seed,
+        ..config.clone()
+    };
+    Frames {
+        pretty: PrettyRender::new(Tree::new_min_growth(config, 5)),
+        steps: steps.max(1),
+        grown: 0,
+        done: false,
+    }
+}
+
+/// Frames of a growing tree, see `grow_frames`
+pub struct Frames {
+    pretty: PrettyRender,
+    /// steps between two frames
+    steps: usize,
+    /// steps grown so far
+    grown: usize,
+    done: bool,
+}
+
+impl Frames {
+    /// The tree as far as it grew
+    pub fn tree(&self) -> &Tree {
+        self.pretty.tree()
+    }
+}
+
+impl Iterator for Frames {
+    type Item = image::RgbaImage;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let tree = self.pretty.tree_mut();
+        for _ in 0..self.steps {
+            if !tree.is_growing() || self.grown == MAX_STEPS {
+                break;
+            }
+            tree.step();
+            self.grown += 1;
+        }
+        self.done = !tree.is_growing() || self.grown == MAX_STEPS;
+        let config = &tree.config;
+        let mut frame = Framebuffer::new(config.width as usize, config.height as usize, config.sky);
+        self.pretty.render(&mut frame);
+        Some(frame.to_image())
+    }
+}
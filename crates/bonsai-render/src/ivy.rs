@@ -0,0 +1,49 @@
+//! Drawing the ivy climbing the tree, see `bonsai_engine::ivy`
+
+use crate::*;
+use bonsai_engine::ivy::{self, Ivy, VinePoint};
+use grain::Grain;
+use render::Canvas;
+
+/// Radius of the stem in canvas pixels, just enough to show as a line of single pixels
+const STEM_SIZE: f32 = 0.7;
+/// Radius of a leaf in canvas pixels
+const LEAF_SIZE: f32 = 1.3;
+
+/// Draws the vine onto the canvas of the branches, its parts round the back of the wood behind
+/// it. `to_canvas` takes world positions to the canvas, `sway` gives the offset the wind moved
+/// each node by
+pub fn draw(
+    ivy: &Ivy,
+    tree: &Tree,
+    canvas: &mut Canvas,
+    to_canvas: impl Fn(Vector2) -> Vector2,
+    sway: impl Fn(usize) -> Vector2,
+) {
+    let vine = ivy::climb(tree, ivy);
+    let parents = tree.nodes().map(|node| node.parent).collect::<Vec<_>>();
+    // the vine moves with the segment it holds on to
+    let place = |point: &VinePoint| {
+        let parent = parents[point.node].map_or(Vector2::zero(), &sway);
+        point.pos + parent.lerp(sway(point.node), point.t)
+    };
+    for stem in &vine.stems {
+        for point in stem {
+            // the wood is a bulge of height 1 at its middle
+            let depth = point.front;
+            let at = to_canvas(place(point));
+            canvas.draw_sphere(at, STEM_SIZE, ivy.stem, depth, 0.2, Grain::Flat);
+        }
+    }
+    for (point, out) in &vine.leaves {
+        let at = to_canvas(place(point) + *out);
+        canvas.draw_sphere(
+            at,
+            LEAF_SIZE,
+            ivy.leaf,
+            point.front + 0.3,
+            0.5,
+            Grain::Leaves,
+        );
+    }
+}
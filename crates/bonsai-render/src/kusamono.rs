@@ -0,0 +1,108 @@
+//! Drawing the kusamono, grass blades or flowering stems in the accent's pot
+
+use rand::SeedableRng;
+
+use crate::*;
+use bonsai_engine::kusamono::{Kind, Kusamono};
+use render::Canvas;
+
+/// Depth of the accent, behind the tree like its pot
+const DEPTH: f32 = -2.0;
+
+/// Draws the plant and its pot next to the tree,
+/// onto a canvas with pixels `pixel_width` × `pixel_height` large
+pub fn draw(
+    kusamono: &Kusamono,
+    canvas: &mut Canvas,
+    tree: &Tree,
+    pixel_width: f32,
+    pixel_height: f32,
+) {
+    let pot = kusamono.pot(tree);
+    let foot = kusamono.foot(tree);
+    pot::draw(
+        &pot,
+        canvas,
+        foot,
+        pixel_width,
+        pixel_height,
+        tree.config.seed,
+    );
+
+    let mut rng = ChaCha12Rng::seed_from_u64(tree.config.seed ^ 0x6b75_7361);
+    let kind = kusamono.kind.unwrap_or(if rng.gen_bool(0.5) {
+        Kind::Grass
+    } else {
+        Kind::Flowering
+    });
+    let mut plot = |pos: Vector2, color: Color, normal: Vector2| {
+        let (x, y) = (pos.x / pixel_width, pos.y / pixel_height);
+        if x >= 0.0 && y >= 0.0 && (x as i32) < canvas.width() && (y as i32) < canvas.height() {
+            canvas.draw_surface(x as usize, y as usize, color, normal, DEPTH);
+        }
+    };
+    // one sample per pixel row along a stem is enough
+    let steps = (kusamono.height / pixel_height).ceil().max(1.0) as usize * 2;
+    let soil = pot.radius * 0.6;
+    match kind {
+        Kind::Grass => {
+            let (dark, light) = (
+                Color::from_hex("5e8c3a").unwrap(),
+                Color::from_hex("c4dc78").unwrap(),
+            );
+            for _ in 0..rng.gen_range(5..9) {
+                let base = foot + Vector2::new(rng.gen_range(-soil..soil), 0.0);
+                // blades arch outwards, further the further out they start
+                let arch = (base.x - foot.x) / soil * kusamono.height * 0.35
+                    + rng.gen_range(-0.15..0.15) * kusamono.height;
+                let length = kusamono.height * rng.gen_range(0.6..1.0);
+                for step in 0..=steps {
+                    let t = step as f32 / steps as f32;
+                    let pos = base + Vector2::new(arch * t * t, length * t * (1.0 - 0.3 * t));
+                    let normal = Vector2::new(arch.signum() * 0.3, 0.5);
+                    plot(pos, mix(dark, light, t), normal);
+                }
+            }
+        }
+        Kind::Flowering => {
+            const BLOSSOMS: [&str; 4] = ["f4f1e8", "f2c84b", "e88fb0", "9b7fd1"];
+            let stem = Color::from_hex("6a9a44").unwrap();
+            let blossom = Color::from_hex(BLOSSOMS[rng.gen_range(0..BLOSSOMS.len())]).unwrap();
+            let heart = Color::from_hex("e0a020").unwrap();
+            for _ in 0..rng.gen_range(2..5) {
+                let base = foot + Vector2::new(rng.gen_range(-soil..soil) * 0.6, 0.0);
+                let sway = rng.gen_range(-0.25..0.25) * kusamono.height;
+                let length = kusamono.height * rng.gen_range(0.5..1.0);
+                let at = |t: f32| base + Vector2::new(sway * t, length * t);
+                for step in 0..=steps {
+                    plot(at(step as f32 / steps as f32), stem, Vector2::new(0.3, 0.3));
+                }
+                // a pair of leaves low on the stem
+                let node = at(rng.gen_range(0.2..0.5));
+                for side in [-1.0, 1.0] {
+                    for reach in 1..=2 {
+                        let leaf = node
+                            + Vector2::new(
+                                side * reach as f32 * pixel_width,
+                                reach as f32 * pixel_height * 0.5,
+                            );
+                        plot(leaf, stem, Vector2::new(side * 0.3, 0.5));
+                    }
+                }
+                // petals around a heart, one pixel each
+                let top = at(1.0);
+                for (dx, dy) in [(-1.0, 0.0), (1.0, 0.0), (0.0, -1.0), (0.0, 1.0)] {
+                    let petal = top + Vector2::new(dx * pixel_width, dy * pixel_height);
+                    plot(petal, blossom, Vector2::new(dx, dy) * 0.7);
+                }
+                plot(top, heart, Vector2::new(0.0, 0.5));
+            }
+        }
+    }
+}
+
+/// Blends from `a` at 0 to `b` at 1
+pub(crate) fn mix(a: Color, b: Color, t: f32) -> Color {
+    let f = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t) as u8;
+    Color::new(f(a.r, b.r), f(a.g, b.g), f(a.b, b.b), 255)
+}
@@ -0,0 +1,114 @@
+//! Draws the trees of `bonsai-engine` as shaded pixel art
+//!
+//! Everything is drawn onto a `render::Surface`, which is either a raylib window
+//! or a `render::Framebuffer` in memory for rendering without one, like on the web page
+//! of `bonsai-web`. The window side is only built with the `gui` feature, so the rest
+//! builds without raylib.
+//! `terminal` prints a framebuffer with colored half blocks instead, and `grow_to_image`
+//! grows and renders a tree in one call.
+
+pub mod background;
+pub mod caption;
+pub mod density;
+pub mod grain;
+pub mod grow;
+pub mod ivy;
+pub mod kusamono;
+pub mod nest;
+pub mod night;
+pub mod pot;
+pub mod precipitation;
+pub mod render;
+pub mod scenery;
+pub mod sprite;
+pub mod terminal;
+pub mod tokonoma;
+#[cfg(feature = "gui")]
+pub mod window;
+
+pub use grow::{grow_frames, grow_to_image};
+
+use bonsai_engine::*;
+use rand::Rng;
+use rand_chacha::ChaCha12Rng;
+use spline::Spline;
+
+#[cfg(test)]
+mod tests {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    use super::*;
+
+    /// Passes everything to the system allocator, counting allocations per thread
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    fn count_allocation() {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            count_allocation();
+            System.alloc(layout)
+        }
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            count_allocation();
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn allocations() -> usize {
+        ALLOCATIONS.with(Cell::get)
+    }
+
+    #[test]
+    fn growing_frames_only_allocate_as_the_tree_outgrows_its_buffers() {
+        let config = Config {
+            seed: 1,
+            ..Config::default()
+        };
+        // partway grown, so every step still adds to the tree and every frame repaints it
+        let mut pretty = render::PrettyRender::new(Tree::new_min_growth(config, 5));
+        for _ in 0..40 {
+            pretty.tree_mut().step();
+        }
+        let mut frame = render::Framebuffer::new(600, 600, Color::WHITE);
+        // the first frame sets up the canvases
+        pretty.render(&mut frame);
+
+        let nodes = pretty.tree().node_count();
+        let (mut stepping, mut rendering) = (0, 0);
+        for _ in 0..100 {
+            let before = allocations();
+            pretty.tree_mut().step();
+            let stepped = allocations();
+            pretty.render(&mut frame);
+            stepping += stepped - before;
+            rendering += allocations() - stepped;
+        }
+        let added = pretty.tree().node_count() - nodes;
+        assert!(pretty.tree().is_growing() && added >= 50);
+        // growth can't be free of allocations: the nodes, their history and the buckets of the
+        // grids grow with the tree. The scratch buffers of a step are reused though, rebuilding
+        // them would allocate for every cell of the grids on every step
+        assert!(
+            stepping < added,
+            "{} allocations for {} nodes",
+            stepping,
+            added
+        );
+        // the canvases are reused, only the lists with an entry per node grow now and then
+        assert!(rendering <= 8, "{} allocations in 100 frames", rendering);
+    }
+}
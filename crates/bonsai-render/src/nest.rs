@@ -0,0 +1,67 @@
+//! Drawing the bird's nest, see `bonsai_engine::nest`
+
+use crate::*;
+use bonsai_engine::nest::Nest;
+use grain::Grain;
+use render::{blob_threshold, Canvas};
+
+/// Frames a second the chicks beg in, like the frames of an animated sprite
+pub const CHIRP_FPS: f32 = 3.0;
+/// Twigs woven into the rim of the nest
+const TWIGS: usize = 9;
+/// Radius of an egg in canvas pixels
+const EGG_SIZE: f32 = 1.0;
+/// Radius of the head of a chick in canvas pixels
+const HEAD_SIZE: f32 = 1.0;
+/// Depth of the bowl, in front of the wood of the fork it sits in
+const DEPTH: f32 = 1.5;
+
+/// Draws the nest in its fork onto the canvas of the branches, with its eggs or chicks in it.
+/// `to_canvas` takes world positions to the canvas, `sway` gives the offset the wind moved each
+/// node by, `chirp` is the frame the chicks beg in once they hatched
+pub fn draw(
+    nest: &Nest,
+    canvas: &mut Canvas,
+    pixel_width: f32,
+    to_canvas: impl Fn(Vector2) -> Vector2,
+    sway: impl Fn(usize) -> Vector2,
+    chirp: u64,
+) {
+    let (straw, twig) = (
+        Color::from_hex("a08050").unwrap(),
+        Color::from_hex("6e5234").unwrap(),
+    );
+    let center = to_canvas(nest.pos + sway(nest.node));
+    // a few pixels wide at least, so the eggs have room in it
+    let half = (nest.width / pixel_width * 0.5).max(2.5);
+    let size = (half * 0.3).max(1.0);
+    // the eggs or chicks sit in the bowl, peeking out over its rim
+    let rim = center + Vector2::new(0.0, size * 1.5);
+    for idx in 0..nest.eggs {
+        let x = (idx as f32 + 0.5) / nest.eggs as f32 * 2.0 - 1.0;
+        let at = rim + Vector2::new(x * (half - size), 0.0);
+        if !nest.hatched {
+            let egg = Color::from_hex("b8d8e0").unwrap();
+            canvas.draw_sphere(at, EGG_SIZE, egg, DEPTH - 0.2, 0.1, Grain::Flat);
+            continue;
+        }
+        // every chick stretches its neck up and gapes now and then, each at its own moments
+        let begging = blob_threshold(chirp, idx as u64) < 0.5;
+        let head = at + Vector2::new(0.0, if begging { HEAD_SIZE * 1.5 } else { 0.0 });
+        let down = Color::from_hex("8a7a68").unwrap();
+        canvas.draw_sphere(head, HEAD_SIZE, down, DEPTH - 0.2, 0.2, Grain::Flat);
+        if begging {
+            let beak = Color::from_hex("f0b030").unwrap();
+            let gape = head + Vector2::new(0.0, HEAD_SIZE);
+            canvas.draw_sphere(gape, HEAD_SIZE * 0.6, beak, DEPTH - 0.1, 0.2, Grain::Flat);
+        }
+    }
+    // the bowl, twigs laid round a half ellipse below the rim
+    for idx in 0..=TWIGS {
+        let angle = std::f32::consts::PI * (1.0 + idx as f32 / TWIGS as f32);
+        let at = center + Vector2::new(angle.cos() * half, angle.sin() * size);
+        let color = if idx % 3 == 0 { twig } else { straw };
+        let along = Vector2::new(-angle.sin(), angle.cos());
+        canvas.draw_sphere(at, size, color, DEPTH, 0.3, Grain::Bark(along));
+    }
+}
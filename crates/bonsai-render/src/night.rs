@@ -0,0 +1,181 @@
+//! Night sky with the real moon phase and star positions for the current time
+
+use rand::SeedableRng;
+use std::f64::consts::{PI, TAU};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::*;
+use render::Surface;
+
+/// Length of a lunar cycle in days
+const SYNODIC_MONTH: f64 = 29.530_588_853;
+/// A new moon, on 2000-01-06 18:14 UTC, in days since the unix epoch
+const REFERENCE_NEW_MOON: f64 = 10_962.76;
+/// Latitude the sky is seen from, in degrees
+const LATITUDE: f64 = 50.0;
+const BACKGROUND_STARS: usize = 400;
+
+/// Right ascension (hours) and declination (degrees) of the constellation stars
+const CONSTELLATIONS: &[&[(f64, f64)]] = &[
+    // big dipper
+    &[
+        (11.062, 61.75),
+        (11.031, 56.38),
+        (11.897, 53.69),
+        (12.257, 57.03),
+        (12.900, 55.96),
+        (13.399, 54.93),
+        (13.792, 49.31),
+    ],
+    // cassiopeia
+    &[
+        (0.153, 59.15),
+        (0.675, 56.54),
+        (0.945, 60.72),
+        (1.430, 60.24),
+        (1.907, 63.67),
+    ],
+    // little dipper, from polaris to pherkad
+    &[
+        (2.530, 89.26),
+        (17.537, 86.59),
+        (16.766, 82.04),
+        (15.734, 77.79),
+        (14.845, 74.16),
+        (15.345, 71.83),
+    ],
+];
+
+/// Days since the unix epoch
+pub fn now_days() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+        / 86_400.0
+}
+
+/// Position in the lunar cycle: 0 is new moon, 0.5 full moon
+pub fn moon_phase(days: f64) -> f64 {
+    ((days - REFERENCE_NEW_MOON) / SYNODIC_MONTH).rem_euclid(1.0)
+}
+
+pub struct NightSky {
+    /// Days since the unix epoch
+    pub days: f64,
+    /// Size of one sky pixel in output pixels
+    pub block: i32,
+}
+
+impl NightSky {
+    pub fn now(block: i32) -> Self {
+        Self {
+            days: now_days(),
+            block,
+        }
+    }
+
+    /// Projects a star to the screen, looking north with a 200° wide view and the horizon at the bottom
+    fn project(&self, ra: f64, dec: f64, width: f64, height: f64) -> Option<(i32, i32)> {
+        // greenwich sidereal time, the sky is seen from longitude 0
+        let gmst = 18.697_374_558 + 24.065_709_824_419_08 * (self.days - 10_957.5);
+        let hour_angle = (gmst - ra) / 24.0 * TAU;
+        let (dec, lat) = (dec.to_radians(), LATITUDE.to_radians());
+
+        let altitude = (dec.sin() * lat.sin() + dec.cos() * lat.cos() * hour_angle.cos()).asin();
+        if altitude <= 0.0 {
+            return None;
+        }
+        let azimuth = (-hour_angle.sin() * dec.cos())
+            .atan2(dec.sin() * lat.cos() - dec.cos() * lat.sin() * hour_angle.cos());
+
+        let fov = 200f64.to_radians();
+        let x = 0.5 + azimuth / fov;
+        if !(0.0..1.0).contains(&x) {
+            return None;
+        }
+        let y = 1.0 - altitude / (PI / 2.0);
+        Some(((x * width) as i32, (y * height) as i32))
+    }
+
+    pub fn draw(&self, d: &mut impl Surface, width: usize, height: usize) {
+        let (w, h) = (width as f64, height as f64);
+        let b = self.block;
+        let snap = |v: i32| v - v.rem_euclid(b);
+
+        let mut rng = ChaCha12Rng::seed_from_u64(0x5747);
+        for _ in 0..BACKGROUND_STARS {
+            let ra = rng.gen::<f64>() * 24.0;
+            let dec = rng.gen_range(-1.0f64..1.0).asin().to_degrees();
+            let alpha = rng.gen_range(40..140);
+            if let Some((x, y)) = self.project(ra, dec, w, h) {
+                d.fill_rect(snap(x), snap(y), b, b, Color::new(255, 255, 240, alpha));
+            }
+        }
+
+        for stars in CONSTELLATIONS {
+            let points = stars
+                .iter()
+                .map(|&(ra, dec)| self.project(ra, dec, w, h))
+                .collect::<Vec<_>>();
+            for pair in points.windows(2) {
+                if let [Some(from), Some(to)] = pair {
+                    self.draw_line(d, *from, *to);
+                }
+            }
+            for &(x, y) in points.iter().flatten() {
+                d.fill_rect(snap(x), snap(y), b, b, Color::new(255, 250, 225, 255));
+            }
+        }
+
+        self.draw_moon(d, width, height);
+    }
+
+    /// Faint dotted line between two constellation stars
+    fn draw_line(&self, d: &mut impl Surface, from: (i32, i32), to: (i32, i32)) {
+        let b = self.block;
+        let (dx, dy) = ((to.0 - from.0) as f32, (to.1 - from.1) as f32);
+        let steps = (dx.abs().max(dy.abs()) / b as f32) as i32;
+        for i in (1..steps).step_by(2) {
+            let t = i as f32 / steps as f32;
+            let x = from.0 + (dx * t) as i32;
+            let y = from.1 + (dy * t) as i32;
+            d.fill_rect(
+                x - x.rem_euclid(b),
+                y - y.rem_euclid(b),
+                b,
+                b,
+                Color::new(200, 210, 255, 35),
+            );
+        }
+    }
+
+    /// Moon in the upper left, with the terminator at the real phase
+    fn draw_moon(&self, d: &mut impl Surface, width: usize, height: usize) {
+        let b = self.block;
+        let radius = 6;
+        let cx = (width as i32 / 5) / b;
+        let cy = (height as i32 / 8) / b;
+        let lit = Color::from_hex("f4efd8").unwrap();
+        let dark = Color::from_hex("2b3344").unwrap();
+
+        let phase = moon_phase(self.days);
+        let terminator = (phase * TAU).cos() as f32;
+        for j in -radius..=radius {
+            for i in -radius..=radius {
+                let (x, y) = (i as f32 / radius as f32, j as f32 / radius as f32);
+                if x * x + y * y > 1.0 {
+                    continue;
+                }
+                let half_width = (1.0 - y * y).sqrt();
+                let is_lit = if phase < 0.5 {
+                    x > half_width * terminator
+                } else {
+                    x < -half_width * terminator
+                };
+                let color = if is_lit { lit } else { dark };
+                d.fill_rect((cx + i) * b, (cy + j) * b, b, b, color);
+            }
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! The pot the tree stands in, seen in a 3/4 view from slightly above
+//!
+//! The rim and the soil are ellipses flattened by the camera tilt, or rounded rectangles for
+//! the boxy shapes, the side is swept from the rim down to the bottom along the profile of the
+//! shape. Everything is drawn behind the tree.
+//!
+//! A decorated pot gets its glaze, pattern and seal stamp from the seed of its tree,
+//! so every tree comes with its own pot.
+
+use rand::SeedableRng;
+
+use crate::*;
+use bonsai_engine::pot::Pot;
+use render::Canvas;
+
+/// Depth of the pot, so the tree is always drawn over it
+const DEPTH: f32 = -2.0;
+
+/// Draws the pot around the foot of the trunk, in world units,
+/// onto a canvas with pixels `pixel_width` × `pixel_height` large
+pub fn draw(
+    pot: &Pot,
+    canvas: &mut Canvas,
+    foot: Vector2,
+    pixel_width: f32,
+    pixel_height: f32,
+    seed: u64,
+) {
+    let decoration = if pot.decorated {
+        Decoration::from_seed(seed)
+    } else {
+        Decoration::plain(pot.color)
+    };
+    let tilt = pot.tilt.clamp(0.0, 90.0).to_radians();
+    // the rim ellipse and how far the side drops below it on screen
+    let (a, b) = (pot.radius, (pot.radius * tilt.sin()).max(1.0));
+    let drop = pot.height * tilt.cos();
+    // seen from above a level surface faces partly up
+    let up = Vector2::new(0.0, tilt.cos());
+    let rim = lighten(decoration.glaze, 0.45);
+
+    let corners = pot.shape.corners();
+    let inside = |dx: f32, dy: f32, a: f32, b: f32| {
+        (dx / a).abs().powf(corners) + (dy / b).abs().powf(corners) <= 1.0
+    };
+    // a bulging side reaches out past the rim
+    let widest = (0..=16)
+        .map(|step| pot.shape.profile(pot.taper, step as f32 / 16.0))
+        .fold(1.0, f32::max);
+    let from_x = ((foot.x - a * widest) / pixel_width).floor().max(0.0) as usize;
+    let to_x = ((foot.x + a * widest) / pixel_width).ceil().max(0.0) as usize;
+    let from_y = ((foot.y - pot.reach_below()) / pixel_height)
+        .floor()
+        .max(0.0) as usize;
+    let to_y = ((foot.y + b) / pixel_height).ceil().max(0.0) as usize;
+    for x in from_x..=to_x.min(canvas.width() as usize - 1) {
+        for y in from_y..=to_y.min(canvas.height() as usize - 1) {
+            let (dx, dy) = (
+                x as f32 * pixel_width - foot.x,
+                y as f32 * pixel_height - foot.y,
+            );
+            if inside(dx, dy, a, b) {
+                if inside(dx, dy, a * 0.88, b * 0.88) {
+                    canvas.draw_surface(x, y, soil_at(pot.soil, x, y), up, DEPTH);
+                } else {
+                    canvas.draw_surface(x, y, rim, up, DEPTH);
+                }
+                continue;
+            }
+            // the side is the rim ellipse swept down while it narrows
+            const STEPS: usize = 32;
+            let side = (0..=STEPS).find_map(|step| {
+                let t = step as f32 / STEPS as f32;
+                let narrowing = pot.shape.profile(pot.taper, t);
+                let (a, b) = (a * narrowing, b * narrowing);
+                inside(dx, dy + drop * t, a, b).then_some((dx / a, t))
+            });
+            if let Some((across, down)) = side {
+                // round towards the sides like a cylinder, tilted down a bit
+                let normal = Vector2::new(facing(across, corners) * 0.95, -tilt.sin() * 0.3);
+                let color = decoration.side(across, down);
+                canvas.draw_surface(x, y, color, normal, DEPTH);
+            }
+        }
+    }
+
+    // the seal sits on the front of the side, a bit right of the middle
+    let size = GLYPH as i32 + 2;
+    let stamp_height = size as f32 * pixel_height;
+    // centered on the side below the front of the rim, left out where it doesn't fit
+    let stamp = decoration.stamp.filter(|_| stamp_height < drop);
+    if let Some(glyph) = &stamp {
+        let left = ((foot.x + a * 0.3) / pixel_width) as i32;
+        let top = ((foot.y - b * 0.95 - (drop - stamp_height) / 2.0) / pixel_height) as i32;
+        for sx in 0..size {
+            for sy in 0..size {
+                let (x, y) = (left + sx, top - sy);
+                if x < 0 || y < 0 || x >= canvas.width() || y >= canvas.height() {
+                    continue;
+                }
+                let border = sx == 0 || sy == 0 || sx == size - 1 || sy == size - 1;
+                let ink = !border && glyph[(sy - 1) as usize][(sx - 1) as usize];
+                let color = if border || ink {
+                    Color::from_hex("b0302a").unwrap()
+                } else {
+                    lighten(decoration.glaze, 0.6)
+                };
+                let across = (x as f32 * pixel_width - foot.x) / a;
+                let normal = Vector2::new(facing(across, corners) * 0.95, -tilt.sin() * 0.3);
+                canvas.draw_surface(x as usize, y as usize, color, normal, DEPTH);
+            }
+        }
+    }
+}
+
+/// How far the side `across` from the left (-1) to the right (1) edge faces sideways,
+/// boxy pots have a flat front and turn only at their corners
+fn facing(across: f32, corners: f32) -> f32 {
+    across.signum() * across.abs().powf(corners - 1.0)
+}
+
+/// Speckled soil, the same speckles for the same pixel every time
+fn soil_at(soil: Color, x: usize, y: usize) -> Color {
+    let mut hash = (x as u32).wrapping_mul(0x9e37_79b9) ^ (y as u32).wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2c1b_3c6d);
+    hash ^= hash >> 12;
+    match hash % 7 {
+        0 => lighten(soil, 0.2),
+        1 | 2 => lighten(soil, -0.2),
+        _ => soil,
+    }
+}
+
+/// Cells of a seal glyph per side
+const GLYPH: usize = 5;
+
+type Glyph = [[bool; GLYPH]; GLYPH];
+
+/// Surface pattern on the side of a pot
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Pattern {
+    Plain,
+    /// horizontal stripes around the pot
+    Bands,
+    Checks,
+    Waves,
+    Dots,
+}
+
+/// What a decorated pot looks like, all picked from a seed
+struct Decoration {
+    /// color at the rim
+    glaze: Color,
+    /// color the glaze runs into towards the bottom
+    drip: Color,
+    pattern: Pattern,
+    accent: Color,
+    /// how many times the pattern repeats around the pot
+    repeats: f32,
+    /// kanji-like strokes of a seal stamp
+    stamp: Option<Glyph>,
+}
+
+impl Decoration {
+    fn plain(color: Color) -> Self {
+        Self {
+            glaze: color,
+            drip: color,
+            pattern: Pattern::Plain,
+            accent: color,
+            repeats: 0.0,
+            stamp: None,
+        }
+    }
+
+    fn from_seed(seed: u64) -> Self {
+        // glazes of classic bonsai pots: celadon, cobalt, ochre, unglazed clay, cream and oxblood
+        const GLAZES: [&str; 6] = ["8fb3a0", "3e5f8a", "c08a3e", "8a5a44", "d8cfb4", "7a2e2e"];
+        let mut rng = ChaCha12Rng::seed_from_u64(seed ^ 0x706f_7400);
+        let mut pick = || Color::from_hex(GLAZES[rng.gen_range(0..GLAZES.len())]).unwrap();
+        let (glaze, accent) = (pick(), pick());
+        let drip = lighten(glaze, -0.35);
+        let pattern = [
+            Pattern::Plain,
+            Pattern::Bands,
+            Pattern::Checks,
+            Pattern::Waves,
+            Pattern::Dots,
+        ][rng.gen_range(0..5)];
+        let repeats = rng.gen_range(4..9) as f32;
+
+        let stamp = rng.gen_bool(0.5).then(|| {
+            // a few straight strokes, like a character carved into a seal
+            let mut glyph = [[false; GLYPH]; GLYPH];
+            for _ in 0..rng.gen_range(3..6) {
+                let (row, from) = (rng.gen_range(0..GLYPH), rng.gen_range(0..GLYPH - 1));
+                let to = rng.gen_range(from + 2..=GLYPH);
+                let across = rng.gen_bool(0.5);
+                if across {
+                    glyph[row][from..to].fill(true);
+                } else {
+                    glyph[from..to]
+                        .iter_mut()
+                        .for_each(|cells| cells[row] = true);
+                }
+            }
+            glyph
+        });
+
+        Self {
+            glaze,
+            drip,
+            pattern,
+            accent,
+            repeats,
+            stamp,
+        }
+    }
+
+    /// Color of the side `across` from the left (-1) to the right (1) edge
+    /// and `down` from the rim (0) to the bottom (1)
+    fn side(&self, across: f32, down: f32) -> Color {
+        // the pattern wraps around, so it gets narrower towards the edges
+        let around = across.clamp(-1.0, 1.0).asin() / std::f32::consts::PI + 0.5;
+        let (u, v) = (around * self.repeats, down);
+        let accent = match self.pattern {
+            Pattern::Plain => false,
+            Pattern::Bands => (0.2..0.3).contains(&v) || (0.65..0.72).contains(&v),
+            Pattern::Checks => {
+                (0.25..0.75).contains(&v) && (u.floor() as i32 + (v * 4.0).floor() as i32) % 2 == 0
+            }
+            Pattern::Waves => {
+                let wave = 0.45 + 0.1 * (u * std::f32::consts::TAU).sin();
+                (v - wave).abs() < 0.07
+            }
+            Pattern::Dots => {
+                let (du, dv) = (u.fract() - 0.5, (v - 0.45) * 2.0);
+                du * du + dv * dv < 0.06
+            }
+        };
+        if accent {
+            return self.accent;
+        }
+        // the glaze runs darker towards the bottom, in a few flat steps like pixel art
+        let step = (down * 3.0).floor().min(2.0) / 2.0;
+        let mix = |g: u8, d: u8| (g as f32 + (d as f32 - g as f32) * step) as u8;
+        Color::new(
+            mix(self.glaze.r, self.drip.r),
+            mix(self.glaze.g, self.drip.g),
+            mix(self.glaze.b, self.drip.b),
+            255,
+        )
+    }
+}
+
+/// Moves the color towards white, or towards black for a negative amount
+pub(crate) fn lighten(color: Color, amount: f32) -> Color {
+    let target = if amount < 0.0 { 0.0 } else { 255.0 };
+    let f = |c: u8| (c as f32 + (target - c as f32) * amount.abs()) as u8;
+    Color::new(f(color.r), f(color.g), f(color.b), color.a)
+}
@@ -0,0 +1,238 @@
+//! Snow and rain falling over the tree, and the snow settling on it
+//!
+//! Drops come in above the canvases, as many as `precipitation_intensity` says, and fall blown
+//! aslant by the wind until they hit the tree or the ground. A flake landing on a pixel whose
+//! normal faces up stays there as snow, which melts away over `snow_melt` seconds once it stops
+//! snowing, and quicker in the rain. Snow left on a pixel that no longer faces up slides off.
+
+use rand::SeedableRng;
+
+use crate::*;
+use bonsai_engine::precipitation::Precipitation;
+use render::{Canvas, Surface, BAYER_4X4};
+
+/// Canvas pixels per second a flake or a drop falls
+const SNOW_SPEED: f32 = 8.0;
+const RAIN_SPEED: f32 = 60.0;
+/// Drops coming in per second above every canvas column, at full intensity
+const SNOW_RATE: f32 = 0.08;
+const RAIN_RATE: f32 = 1.2;
+/// Canvas pixels per second the wind blows a drop sideways, for every world unit of wind
+const DRIFT: f32 = 1.5;
+/// Snow a single flake leaves on a pixel, which is white all over at 1
+const FLAKE: f32 = 0.35;
+/// Least upward part of a pixel's normal for snow to stay on it
+const FLAT: f32 = 0.3;
+/// Most drops in the air at once
+const MOST_DROPS: usize = 4000;
+
+#[derive(Copy, Clone, Debug)]
+struct Drop {
+    /// position on the canvases
+    pos: Vector2,
+    /// where in its swaying a flake starts, so they don't all sway in step
+    phase: f32,
+}
+
+/// The drops in the air and the snow lying on the tree
+pub struct Drops {
+    rng: ChaCha12Rng,
+    drops: Vec<Drop>,
+    /// snow on every canvas pixel, indexed `[x][y]` with y going up
+    cover: Vec<Vec<f32>>,
+    /// drops to come in that didn't make up a whole one yet
+    due: f32,
+    /// seconds passed, for the swaying of the flakes
+    time: f32,
+}
+
+impl Drops {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: ChaCha12Rng::seed_from_u64(seed ^ 0x7261_696e),
+            drops: vec![],
+            cover: vec![],
+            due: 0.0,
+            time: 0.0,
+        }
+    }
+
+    /// Whether anything is falling or melting
+    pub fn is_active(&self, config: &Config) -> bool {
+        config.precipitation.is_some()
+            || !self.drops.is_empty()
+            || self.cover.iter().flatten().any(|&snow| snow > 0.0)
+    }
+
+    /// Lets `seconds` pass with `wind` blowing, onto the canvases of the tree from the front one
+    /// to the back one
+    pub fn advance(&mut self, seconds: f32, config: &Config, wind: f32, layers: [&Canvas; 3]) {
+        let (width, height) = (layers[0].width() as usize, layers[0].height() as usize);
+        if self.cover.len() != width || self.cover.first().map(Vec::len) != Some(height) {
+            self.cover = vec![vec![0.0; height]; width];
+        }
+        self.time += seconds;
+        let falling = config.precipitation;
+        let (speed, rate) = match falling {
+            Some(Precipitation::Snow) => (SNOW_SPEED, SNOW_RATE),
+            _ => (RAIN_SPEED, RAIN_RATE),
+        };
+        let drift = wind * DRIFT;
+
+        // the drops blown in from the side come in beyond the edge they are blown from
+        if falling.is_some() {
+            let intensity = config.precipitation_intensity.max(0.0);
+            self.due += seconds * rate * intensity * width as f32;
+            let reach = (drift * height as f32 / speed).abs();
+            let (from, to) = if drift > 0.0 {
+                (-reach, width as f32)
+            } else {
+                (0.0, width as f32 + reach)
+            };
+            while self.due >= 1.0 {
+                self.due -= 1.0;
+                if self.drops.len() < MOST_DROPS {
+                    self.drops.push(Drop {
+                        pos: Vector2::new(self.rng.gen_range(from..to), height as f32),
+                        phase: self.rng.gen::<f32>() * std::f32::consts::TAU,
+                    });
+                }
+            }
+        }
+
+        let ground = floor(config) / config.pixel_height() as f32;
+        let snowing = falling == Some(Precipitation::Snow);
+        let (cover, time) = (&mut self.cover, self.time);
+        self.drops.retain_mut(|drop| {
+            let from = drop.pos.y;
+            let sway = if snowing {
+                (time + drop.phase).sin() * 2.0
+            } else {
+                0.0
+            };
+            drop.pos.x += (drift + sway) * seconds;
+            drop.pos.y -= speed * seconds;
+            // every row it passed, so a fast drop doesn't fall through a thin twig
+            let x = drop.pos.x.floor();
+            if x < 0.0 || x >= width as f32 {
+                return drop.pos.y > ground;
+            }
+            let x = x as usize;
+            let rows =
+                (drop.pos.y.max(0.0).floor() as usize)..=(from.floor() as usize).min(height - 1);
+            for y in rows.rev() {
+                let Some(normal) = layers.iter().find_map(|layer| layer.facing(x, y)) else {
+                    continue;
+                };
+                if snowing && normal.y >= FLAT {
+                    cover[x][y] = (cover[x][y] + FLAKE).min(1.0);
+                }
+                return false;
+            }
+            drop.pos.y > ground
+        });
+
+        let melt = match falling {
+            _ if config.snow_melt <= 0.0 => 1.0,
+            Some(Precipitation::Snow) => 0.0,
+            Some(Precipitation::Rain) => seconds * 4.0 / config.snow_melt,
+            None => seconds / config.snow_melt,
+        };
+        for (x, column) in self.cover.iter_mut().enumerate() {
+            for (y, snow) in column
+                .iter_mut()
+                .enumerate()
+                .filter(|(_, snow)| **snow > 0.0)
+            {
+                let faces_up = layers
+                    .iter()
+                    .find_map(|layer| layer.facing(x, y))
+                    .is_some_and(|normal| normal.y >= FLAT);
+                *snow = if faces_up {
+                    (*snow - melt).max(0.0)
+                } else {
+                    0.0
+                };
+            }
+        }
+    }
+
+    /// Draws the snow lying on the tree and the drops in the air, on canvases `height` pixels high
+    pub fn render_to(&self, d: &mut impl Surface, config: &Config, height: i32) {
+        let (pw, ph) = (config.pixel_size as i32, config.pixel_height() as i32);
+        let paint = |color: Color| {
+            scenery::on_ramp(render::multiply(color, config.sun_color), &config.ramp)
+        };
+        let snow = paint(Color::from_hex("f4f8fb").unwrap());
+        // dithered, so the snow thins out as it melts
+        for (x, column) in self.cover.iter().enumerate() {
+            for (y, cover) in column.iter().enumerate() {
+                if *cover > (BAYER_4X4[y % 4][x % 4] + 0.5) / 16.0 {
+                    let (x, y) = (x as i32, y as i32);
+                    d.fill_rect(x * pw, (height - y + 1) * ph, pw, ph, snow);
+                }
+            }
+        }
+        let (color, length) = match config.precipitation {
+            Some(Precipitation::Snow) | None => (snow.fade(0.9), 1),
+            Some(Precipitation::Rain) => (paint(Color::from_hex("6d86a8").unwrap()).fade(0.6), 2),
+        };
+        for drop in &self.drops {
+            let (x, y) = (drop.pos.x.floor() as i32, drop.pos.y.floor() as i32);
+            d.fill_rect(x * pw, (height - y + 1) * ph, pw, ph * length, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grain::Grain;
+
+    #[test]
+    fn snow_settles_on_top_and_melts() {
+        let mut config = Config {
+            precipitation: Some(Precipitation::Snow),
+            precipitation_intensity: 1.0,
+            snow_melt: 10.0,
+            ..Config::default()
+        };
+        config.origin.y = 0.0;
+        let sun = render::Normal::sun(&config);
+        let mut blob = Canvas::new(40, 40, sun, 1, 1);
+        blob.draw_sphere(
+            Vector2::new(20.0, 10.0),
+            8.0,
+            Color::GREEN,
+            0.0,
+            0.0,
+            Grain::Flat,
+        );
+        let empty = Canvas::new(40, 40, sun, 1, 1);
+        let mut drops = Drops::new(1);
+        for _ in 0..600 {
+            drops.advance(0.1, &config, 0.0, [&empty, &blob, &empty]);
+        }
+        let lying = |drops: &Drops| {
+            let mut lying = vec![];
+            for (x, column) in drops.cover.iter().enumerate() {
+                lying.extend(
+                    (0..column.len())
+                        .filter(|&y| column[y] > 0.0)
+                        .map(|y| (x, y)),
+                );
+            }
+            lying
+        };
+        let settled = lying(&drops);
+        assert!(!settled.is_empty());
+        // only on the top of the blob
+        assert!(settled.iter().all(|&(_, y)| y >= 10));
+
+        config.precipitation = None;
+        for _ in 0..200 {
+            drops.advance(0.1, &config, 0.0, [&empty, &blob, &empty]);
+        }
+        assert!(lying(&drops).is_empty() && !drops.is_active(&config));
+    }
+}
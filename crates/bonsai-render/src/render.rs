@@ -0,0 +1,1763 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
+use crate::*;
+use std::sync::Arc;
+
+use bonsai_engine::{composition::Camera, sprites::LeafSprites, volume, wind::Wind};
+use color_rules::{ColorRule, NodeFacts, Part};
+use grain::{darken, Grain};
+
+/// Draws a tree as shaded pixel art, repainting only when the tree or its look changed
+pub struct PrettyRender {
+    /// the tree we render
+    tree: Tree,
+    /// canvases of the last paint, cleared and reused by the next one
+    layers: Option<Layers>,
+    /// what the canvases were last painted from, they stay as they are while it matches
+    painted: Option<PaintKey>,
+    continuations: Vec<Option<usize>>,
+    selection_colors: Vec<Option<Color>>,
+    ground: GroundShadow,
+    /// held frame the leaves are jittered for, see `set_jitter`
+    jitter: Option<u64>,
+    /// moment the wind swayed the tree to, see `set_wind`
+    wind_time: Option<f32>,
+    /// wind blowing on top of the config's while a storm passes, see `set_storm`
+    storm: f32,
+    wind: Option<Wind>,
+    /// foliage the canvases were last painted with, leaves between it and a lower one fall off
+    painted_foliage: Option<f32>,
+    /// leaves that dropped off, on their way to the ground
+    falling: Vec<FallingLeaf>,
+    falling_canvas: Option<Canvas>,
+    /// snow or rain in the air and the snow lying on the tree, see `precipitation`
+    drops: precipitation::Drops,
+    /// the roots the right way up and the revision of the roots they were planted from
+    roots: Option<(u64, Box<PrettyRender>)>,
+    /// the leaf sprites of the config and where they were loaded from, see `leaf_sprites`
+    sprites: Option<(LeafSprites, Arc<[Sprite]>)>,
+    /// why the leaf sprites didn't load, until `take_sprite_error` hands it on
+    sprite_error: Option<String>,
+}
+
+/// Canvas pixels per second a dropped leaf falls
+const FALL_SPEED: f32 = 5.0;
+/// Canvas pixels per second a falling leaf drifts from side to side at most
+const FALL_DRIFT: f32 = 4.0;
+
+/// A leaf that dropped off the tree
+#[derive(Copy, Clone, Debug)]
+struct FallingLeaf {
+    /// position on the canvases
+    pos: Vector2,
+    color: Color,
+    depth: f32,
+    /// seconds since it dropped, with a random head start to not all drift in step
+    age: f32,
+}
+
+/// Everything the painted canvases depend on
+#[derive(Clone, Debug, PartialEq)]
+struct PaintKey {
+    /// set by `PrettyRender::invalidate`, the key is kept for its lists
+    stale: bool,
+    revision: u64,
+    width: f32,
+    height: f32,
+    origin: Vector2,
+    pixel_size: usize,
+    pixel_aspect: f32,
+    sky: Color,
+    colors: ColorPalette,
+    leaves: Vec<LeafType>,
+    color_rules: Vec<ColorRule>,
+    depth_fog: f32,
+    foliage: f32,
+    canopy_occlusion: f32,
+    self_shadow: f32,
+    ambient_occlusion: (f32, usize),
+    ground_shadow: f32,
+    grain: f32,
+    /// whether the tree still grew, the fruit only comes once it stopped
+    growing: bool,
+    fruit_density: f32,
+    fruit_color: Color,
+    pot: Option<bonsai_engine::pot::Pot>,
+    kusamono: Option<bonsai_engine::kusamono::Kusamono>,
+    ivy: Option<bonsai_engine::ivy::Ivy>,
+    nest_chance: f32,
+    /// frame the chicks of the nest beg in, once they hatched
+    chirp: Option<u64>,
+    tokonoma: Option<bonsai_engine::tokonoma::Tokonoma>,
+    volume: Option<bonsai_engine::volume::Volume>,
+    leaf_sprites: Option<LeafSprites>,
+}
+
+impl PaintKey {
+    /// How `tree` looks now, filling in the lists of the `old` key so repainting doesn't allocate
+    fn of(tree: &Tree, old: Option<Self>) -> Self {
+        let config = &tree.config;
+        let (mut leaves, mut color_rules) =
+            old.map_or_else(Default::default, |old| (old.leaves, old.color_rules));
+        leaves.clone_from(&config.leaves);
+        color_rules.clone_from(&config.color_rules);
+        Self {
+            stale: false,
+            revision: tree.revision(),
+            width: config.width,
+            height: config.height,
+            origin: config.origin,
+            pixel_size: config.pixel_size,
+            pixel_aspect: config.pixel_aspect,
+            sky: config.sky,
+            colors: config.colors,
+            leaves,
+            color_rules,
+            depth_fog: config.depth_fog,
+            foliage: config.foliage,
+            canopy_occlusion: config.canopy_occlusion,
+            self_shadow: config.self_shadow,
+            ambient_occlusion: (config.ambient_occlusion, config.ambient_occlusion_radius),
+            ground_shadow: config.ground_shadow,
+            grain: config.grain,
+            growing: tree.is_growing(),
+            fruit_density: config.fruit_density,
+            fruit_color: config.fruit_color,
+            pot: config.pot,
+            kusamono: config.kusamono,
+            ivy: config.ivy,
+            nest_chance: config.nest_chance,
+            chirp: chirp(tree),
+            tokonoma: config.tokonoma,
+            volume: config.volume,
+            leaf_sprites: config.leaf_sprites.clone(),
+        }
+    }
+
+    fn matches(&self, tree: &Tree) -> bool {
+        let config = &tree.config;
+        !self.stale
+            && self.revision == tree.revision()
+            && self.width == config.width
+            && self.height == config.height
+            && self.origin == config.origin
+            && self.pixel_size == config.pixel_size
+            && self.pixel_aspect == config.pixel_aspect
+            && self.sky == config.sky
+            && self.colors == config.colors
+            && self.leaves == config.leaves
+            && self.color_rules == config.color_rules
+            && self.depth_fog == config.depth_fog
+            && self.foliage == config.foliage
+            && self.canopy_occlusion == config.canopy_occlusion
+            && self.self_shadow == config.self_shadow
+            && self.ambient_occlusion == (config.ambient_occlusion, config.ambient_occlusion_radius)
+            && self.ground_shadow == config.ground_shadow
+            && self.grain == config.grain
+            && self.growing == tree.is_growing()
+            && self.fruit_density == config.fruit_density
+            && self.fruit_color == config.fruit_color
+            && self.pot == config.pot
+            && self.kusamono == config.kusamono
+            && self.ivy == config.ivy
+            && self.nest_chance == config.nest_chance
+            && self.chirp == chirp(tree)
+            && self.tokonoma == config.tokonoma
+            && self.volume == config.volume
+            && self.leaf_sprites == config.leaf_sprites
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Normal(Vector2);
+
+/// Soft light from the sky dome above and bounced off the ground below,
+/// blended by how much a surface faces up
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ambient {
+    /// share of the light coming from the dome instead of the sun, 0 for only the sun
+    pub strength: f32,
+    pub sky: Color,
+    pub ground: Color,
+    /// color of the sunlight
+    pub sun: Color,
+    /// least light a surface gets from the sun alone
+    pub floor: f32,
+    /// color the light missing in the shade is filled in with, black for plain darkness
+    pub shadow: Color,
+}
+
+impl Default for Ambient {
+    fn default() -> Self {
+        Self {
+            strength: 0.0,
+            sky: Color::WHITE,
+            ground: Color::WHITE,
+            sun: Color::WHITE,
+            floor: 0.2,
+            shadow: Color::BLACK,
+        }
+    }
+}
+
+impl Ambient {
+    pub fn of(config: &Config) -> Self {
+        Self {
+            strength: config.ambient.clamp(0.0, 1.0),
+            sky: config.ambient_sky,
+            ground: config.ambient_ground,
+            sun: config.sun_color,
+            floor: config.light_floor,
+            shadow: config.shadow_tint,
+        }
+    }
+    /// Light reaching a surface facing `normal`, per color channel
+    fn light(&self, normal: Vector3) -> Vector3 {
+        let up = 0.5 + 0.5 * normal.y;
+        let channel =
+            |sky: u8, ground: u8| (ground as f32 + (sky as f32 - ground as f32) * up) / 255.0;
+        Vector3::new(
+            channel(self.sky.r, self.ground.r),
+            channel(self.sky.g, self.ground.g),
+            channel(self.sky.b, self.ground.b),
+        )
+    }
+}
+
+impl Normal {
+    pub fn implied_z_sqr(&self) -> f32 {
+        1.0 - self.0.length_sqr()
+    }
+    pub fn implied_z(&self) -> f32 {
+        self.implied_z_sqr().max(0.0).sqrt()
+    }
+    pub fn to_vec3(self) -> Vector3 {
+        Vector3::new(self.0.x, self.0.y, self.implied_z())
+    }
+    /// Direction the sun of the config shines from
+    pub fn sun(config: &Config) -> Self {
+        let dir = Vector2::new(config.sun_angle.cos(), config.sun_angle.sin());
+        Normal(dir * config.sun_elevation.cos())
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Pixel {
+    /// (unlit) color of the pixel
+    /// maybe just index into color palette?
+    color: Color,
+    /// Normal direction the drawn geometry points to
+    normal: Normal,
+    /// Depth offset of the drawn geometry towards the camera
+    depth: f32,
+    /// How much light shines through, bending the normal towards the sun when it gets lit
+    translucency: f32,
+}
+
+impl Default for Pixel {
+    fn default() -> Self {
+        Self {
+            // transparent
+            color: Color::new(0, 0, 0, 0),
+            // s.t. implied z is zero and this pixel gets overdrawn always
+            normal: Normal(Vector2::new(0.0, 1.0)),
+            depth: f32::NEG_INFINITY,
+            translucency: 0.0,
+        }
+    }
+}
+
+impl Pixel {
+    /// returns whether this pixel should be drawn in front of the other pixel
+    fn covers(&self, other: &Self) -> bool {
+        self.height() > other.height()
+    }
+    /// how far the surface sticks out towards the camera
+    fn height(&self) -> f32 {
+        self.depth + self.normal.implied_z()
+    }
+}
+
+/// What gets drawn of the canvases
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Pass {
+    /// the shaded colors
+    Lit,
+    /// the flat colors, without any light or shadow
+    Albedo,
+    /// only the light, white where fully lit
+    Light,
+}
+
+/// A small picture drawn onto a canvas as it is, with a normal for each of its pixels
+#[derive(Clone, Debug)]
+pub struct Sprite {
+    pixels: Vec<(usize, usize, Pixel)>,
+    width: usize,
+    height: usize,
+}
+
+impl Sprite {
+    /// A sprite `width` × `height` pixels large, from its opaque pixels with their color and normal.
+    /// `y` counts up like on the canvas
+    pub fn new(
+        width: usize,
+        height: usize,
+        pixels: impl IntoIterator<Item = (usize, usize, Color, Vector2)>,
+    ) -> Self {
+        let pixels = pixels
+            .into_iter()
+            .map(|(x, y, color, normal)| {
+                let pixel = Pixel {
+                    color,
+                    normal: Normal(normal),
+                    depth: 0.0,
+                    translucency: 0.0,
+                };
+                (x, y, pixel)
+            })
+            .collect();
+        Self {
+            pixels,
+            width,
+            height,
+        }
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+/// How much in shade a pixel is
+///
+/// 0 = exposed to light
+/// 1 = shadow
+/// 2 = double shadow??
+pub struct ShadowSample(f32);
+
+/// Shadow the spheres drawn onto a canvas throw onto a pixel
+#[derive(Copy, Clone, Debug)]
+struct CastShadow {
+    /// opacity of everything casting onto the pixel, adding up
+    amount: f32,
+    /// height of the highest caster, only surfaces below it get darkened
+    height: f32,
+}
+
+impl Default for CastShadow {
+    fn default() -> Self {
+        Self {
+            amount: 0.0,
+            height: f32::NEG_INFINITY,
+        }
+    }
+}
+
+/// How far a sphere throws its shadow away from the sun, in pixel widths when the sun grazes the tree
+const SHADOW_REACH: f32 = 2.0;
+/// How much further towards the camera a neighbor sticks out before it darkens a pixel,
+/// in the heights of `Pixel::height`
+const AO_BIAS: f32 = 0.3;
+/// How much further than that it sticks out to darken it fully
+const AO_RANGE: f32 = 0.7;
+
+#[derive(Clone)]
+pub struct Canvas {
+    pixel_size: i32,
+    pixel_height: i32,
+    sun: Normal,
+    ambient: Ambient,
+    pixels: Vec<Vec<Pixel>>,
+    light: Vec<Vec<ShadowSample>>,
+    cast: Vec<Vec<CastShadow>>,
+    /// how much darker the grain of the spheres gets and the seed of its noise, see `grain`
+    grain: (f32, u64),
+    /// shades of each color the light is dithered to, 0 for smooth shading
+    shades: usize,
+    /// colors the shaded pixels are dithered to with their luma, darkest first, see `set_ramp`
+    ramp: Vec<(f32, Color)>,
+}
+
+impl Canvas {
+    pub fn new(
+        width: usize,
+        height: usize,
+        sun: Normal,
+        pixel_size: i32,
+        pixel_height: i32,
+    ) -> Self {
+        Self {
+            pixels: vec![vec![Pixel::default(); height]; width],
+            light: vec![vec![ShadowSample::default(); height]; width],
+            cast: vec![vec![CastShadow::default(); height]; width],
+            sun,
+            ambient: Ambient::default(),
+            pixel_size,
+            pixel_height,
+            grain: (0.0, 0),
+            shades: 0,
+            ramp: vec![],
+        }
+    }
+    /// Clears the canvas for a new drawing, keeping the buffers if the size didn't change
+    pub fn reset(
+        &mut self,
+        width: usize,
+        height: usize,
+        sun: Normal,
+        pixel_size: i32,
+        pixel_height: i32,
+    ) {
+        if self.pixels.len() == width && self.pixels.first().map(Vec::len) == Some(height) {
+            for column in self.pixels.iter_mut() {
+                column.fill(Pixel::default());
+            }
+            for column in self.light.iter_mut() {
+                column.fill(ShadowSample::default());
+            }
+            for column in self.cast.iter_mut() {
+                column.fill(CastShadow::default());
+            }
+        } else {
+            self.pixels = vec![vec![Pixel::default(); height]; width];
+            self.light = vec![vec![ShadowSample::default(); height]; width];
+            self.cast = vec![vec![CastShadow::default(); height]; width];
+        }
+        self.sun = sun;
+        self.pixel_size = pixel_size;
+        self.pixel_height = pixel_height;
+    }
+    /// Gives the spheres drawn from now on a grain up to `strength` darker, from the noise of `seed`
+    pub fn set_grain(&mut self, strength: f32, seed: u64) {
+        self.grain = (strength.clamp(0.0, 1.0), seed);
+    }
+    /// Height of a pixel relative to its width
+    pub fn aspect(&self) -> f32 {
+        self.pixel_height as f32 / self.pixel_size as f32
+    }
+    /// Draws a single opaque pixel of a surface facing `normal`
+    pub fn draw_surface(&mut self, x: usize, y: usize, color: Color, normal: Vector2, depth: f32) {
+        let pixel = Pixel {
+            color,
+            normal: Normal(normal),
+            depth,
+            translucency: 0.0,
+        };
+        self.draw_pixel(x, y, pixel, 0.0);
+    }
+    pub fn draw_pixel(&mut self, x: usize, y: usize, mut pixel: Pixel, translucency: f32) {
+        pixel.translucency = translucency;
+        if pixel.covers(&self.pixels[x][y]) {
+            self.pixels[x][y] = pixel;
+        }
+    }
+    /// Draws a sphere onto the canvas
+    ///
+    /// center: the center of the sphere
+    ///
+    /// radius: the radius of the sphere, in pixel widths
+    ///
+    /// color: the color of the Sphere
+    ///
+    /// depth: how far the sphere is pushed towards the camera
+    ///
+    /// translucency: how much light the sphere lets through (0 = no light, 1 = full light)
+    ///
+    /// grain: the texture darkening the color in places, see `set_grain`
+    ///
+    /// The sphere also throws its shadow away from the sun, see `receive_shadows`.
+    pub fn draw_sphere(
+        &mut self,
+        center: Vector2,
+        radius: f32,
+        color: Color,
+        depth: f32,
+        translucency: f32,
+        grain: Grain,
+    ) {
+        // non-square pixels squash the sphere vertically on the canvas
+        let aspect = self.aspect();
+        let from = |x: f32, radius: f32| (x.max(radius) - radius) as usize;
+        let to = |x: f32, radius: f32, bound: usize| ((x + radius) as usize).min(bound - 1);
+        let (from_x, from_y) = (from(center.x, radius), from(center.y, radius / aspect));
+        let (to_x, to_y) = (
+            to(center.x, radius, self.pixels.len()),
+            to(center.y, radius / aspect, self.pixels[0].len()),
+        );
+
+        let inv_radius = 1.0 / radius;
+        for y in from_y..=to_y {
+            for x in from_x..=to_x {
+                let (xf, yf) = (x as f32, y as f32);
+                let offset = Vector2::new(xf - center.x, (yf - center.y) * aspect);
+                if offset.length_sqr() > radius * radius {
+                    continue;
+                }
+                let normal = Normal(offset * inv_radius);
+                let (strength, seed) = self.grain;
+                let pixel = Pixel {
+                    color: darken(color, grain.darkening(x, y, seed, strength)),
+                    normal,
+                    depth,
+                    translucency,
+                };
+                self.draw_pixel(x, y, pixel, translucency);
+                self.cast_shadow(x, y, pixel.height(), translucency);
+            }
+        }
+    }
+    /// Throws the shadow of a pixel `height` high onto the pixel the sun casts it to,
+    /// see `receive_shadows`
+    fn cast_shadow(&mut self, x: usize, y: usize, height: f32, translucency: f32) {
+        let aspect = self.aspect();
+        // the shadow lands away from the sun, less so the more the sun shines from the front
+        let (cast_x, cast_y) = (
+            (-self.sun.0.x * SHADOW_REACH).round() as isize,
+            (-self.sun.0.y * SHADOW_REACH / aspect).round() as isize,
+        );
+        let (sx, sy) = (x as isize + cast_x, y as isize + cast_y);
+        if (0..self.width() as isize).contains(&sx) && (0..self.height() as isize).contains(&sy) {
+            let cast = &mut self.cast[sx as usize][sy as usize];
+            cast.amount += 1.0 - translucency;
+            cast.height = cast.height.max(height);
+        }
+    }
+    /// Draws the sprite centered on `center`, stretched so its longer side is `radius` pixel
+    /// widths from the middle like a sphere of that radius, its colors multiplied by `tint`.
+    /// Its pixels are pushed `depth` towards the camera and throw their shadows like spheres
+    pub fn draw_sprite_at(
+        &mut self,
+        center: Vector2,
+        radius: f32,
+        sprite: &Sprite,
+        tint: Color,
+        depth: f32,
+        translucency: f32,
+    ) {
+        let scale = radius * 2.0 / sprite.width.max(sprite.height).max(1) as f32;
+        let (scale_x, scale_y) = (scale, scale / self.aspect());
+        let left = center.x - sprite.width as f32 * 0.5 * scale_x;
+        let bottom = center.y - sprite.height as f32 * 0.5 * scale_y;
+        let (width, height) = (self.width() as f32, self.height() as f32);
+        // every pixel of the sprite covers the canvas pixels its corners span, at least one
+        let span = |from: f32, scale: f32, bound: f32| {
+            let start = from.max(0.0);
+            let end = (from + scale).min(bound);
+            start as usize
+                ..(end.ceil() as usize)
+                    .max(start as usize + 1)
+                    .min(bound as usize)
+        };
+        for (x, y, pixel) in &sprite.pixels {
+            let from_x = left + *x as f32 * scale_x;
+            let from_y = bottom + *y as f32 * scale_y;
+            if from_x + scale_x < 0.0 || from_y + scale_y < 0.0 {
+                continue;
+            }
+            let pixel = Pixel {
+                color: multiply(pixel.color, tint),
+                depth: pixel.depth + depth,
+                ..*pixel
+            };
+            for cx in span(from_x, scale_x, width) {
+                for cy in span(from_y, scale_y, height) {
+                    self.draw_pixel(cx, cy, pixel, translucency);
+                    self.cast_shadow(cx, cy, pixel.height(), translucency);
+                }
+            }
+        }
+    }
+    pub fn draw_sprite(&mut self, ox: usize, oy: usize, sprite: &Sprite, translucency: f32) {
+        for (x, y, pixel) in &sprite.pixels {
+            self.draw_pixel(x + ox, y + oy, *pixel, translucency);
+        }
+    }
+    pub fn width(&self) -> i32 {
+        self.pixels.len() as _
+    }
+    pub fn height(&self) -> i32 {
+        self.pixels[0].len() as _
+    }
+    /// Direction the surface drawn on the pixel faces, `None` where nothing is drawn
+    pub(crate) fn facing(&self, x: usize, y: usize) -> Option<Vector2> {
+        let pixel = &self.pixels[x][y];
+        (pixel.color.a > 0).then_some(pixel.normal.0)
+    }
+    /// Darkens pixels below dense parts of the occluding canvases
+    ///
+    /// Coverage is accumulated from the top down per column, `strength` is how much
+    /// a fully covered column above darkens a pixel.
+    pub fn occlude_from_above(&mut self, occluders: &[&Canvas], strength: f32) {
+        // how much shadow one fully covered pixel above adds
+        let step = 0.05;
+        for (x, column) in self.light.iter_mut().enumerate() {
+            let mut shadow = 0.0f32;
+            for y in (0..column.len()).rev() {
+                column[y].0 += shadow * strength;
+                let cover = occluders
+                    .iter()
+                    .map(|canvas| canvas.pixels[x][y].color.a as f32 / 255.0)
+                    .fold(0.0, f32::max);
+                shadow = (shadow + cover * step).min(1.0);
+            }
+        }
+    }
+    /// Darkens the pixels the spheres of this canvas and of the ones in front of it
+    /// throw their shadows onto, `strength` is how dark a fully opaque caster makes them
+    ///
+    /// Casters on this canvas only shade surfaces lower than themselves,
+    /// the ones in front of it shade everything.
+    pub fn receive_shadows(&mut self, in_front: &[&Canvas], strength: f32) {
+        if strength <= 0.0 {
+            return;
+        }
+        for (x, column) in self.light.iter_mut().enumerate() {
+            for (y, light) in column.iter_mut().enumerate() {
+                let pixel = &self.pixels[x][y];
+                if pixel.color.a == 0 {
+                    continue;
+                }
+                let own = self.cast[x][y];
+                let mut amount = if own.height > pixel.height() {
+                    own.amount
+                } else {
+                    0.0
+                };
+                amount += in_front
+                    .iter()
+                    .map(|canvas| canvas.cast[x][y].amount)
+                    .sum::<f32>();
+                light.0 += amount.min(1.0) * strength;
+            }
+        }
+    }
+    /// Darkens the pixels the surfaces around them stick out over, within `radius` pixels:
+    /// the ones of this canvas that are closer to the camera, and everything drawn on the
+    /// canvases in front of it. `strength` is how dark a pixel gets that is surrounded by them
+    ///
+    /// Closer neighbors weigh more, so the creases where blobs overlap darken most.
+    pub fn occlude_ambient(&mut self, in_front: &[&Canvas], strength: f32, radius: usize) {
+        if strength <= 0.0 || radius == 0 {
+            return;
+        }
+        let (width, height) = (self.width() as isize, self.height() as isize);
+        let reach = radius as isize;
+        for x in 0..width {
+            for y in 0..height {
+                let pixel = &self.pixels[x as usize][y as usize];
+                if pixel.color.a == 0 {
+                    continue;
+                }
+                let own = pixel.height();
+                let (mut occluded, mut total) = (0.0, 0.0);
+                for dx in -reach..=reach {
+                    for dy in -reach..=reach {
+                        let (nx, ny) = (x + dx, y + dy);
+                        let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                        if (dx, dy) == (0, 0) || distance > radius as f32 {
+                            continue;
+                        }
+                        let weight = 1.0 - distance / (radius as f32 + 1.0);
+                        total += weight;
+                        if !(0..width).contains(&nx) || !(0..height).contains(&ny) {
+                            continue;
+                        }
+                        let (nx, ny) = (nx as usize, ny as usize);
+                        let neighbor = &self.pixels[nx][ny];
+                        // sticking out a little further doesn't count, or every sphere
+                        // would darken towards its rim
+                        let closer = if neighbor.color.a == 0 {
+                            0.0
+                        } else {
+                            ((neighbor.height() - own - AO_BIAS) / AO_RANGE).clamp(0.0, 1.0)
+                        };
+                        let covered = in_front
+                            .iter()
+                            .any(|canvas| canvas.pixels[nx][ny].color.a > 0);
+                        occluded += weight * if covered { 1.0 } else { closer };
+                    }
+                }
+                if total > 0.0 {
+                    self.light[x as usize][y as usize].0 += occluded / total * strength;
+                }
+            }
+        }
+    }
+    /// Changes the light the canvas gets shaded with and the shades it is dithered to,
+    /// the painted geometry stays
+    pub fn relight(&mut self, sun: Normal, ambient: Ambient, shades: usize) {
+        self.sun = sun;
+        self.ambient = ambient;
+        self.shades = shades;
+    }
+    /// Dithers the shaded colors to the colors of the ramp from now on, by their brightness.
+    /// An empty ramp keeps the shaded colors
+    pub fn set_ramp(&mut self, ramp: &[Color]) {
+        self.ramp.clear();
+        self.ramp
+            .extend(ramp.iter().map(|color| (luma(*color), *color)));
+        self.ramp.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+    }
+    /// The color of the ramp the shaded color of pixel `x`, `y` is dithered to: of the two whose
+    /// brightness it lies between, ordered dithering picks the closer one more often
+    fn snap(&self, color: Color, x: usize, y: usize) -> Color {
+        let (Some(darkest), Some(brightest)) = (self.ramp.first(), self.ramp.last()) else {
+            return color;
+        };
+        let brightness = luma(color);
+        let Some(above) = self.ramp.iter().position(|(luma, _)| *luma >= brightness) else {
+            return Color {
+                a: color.a,
+                ..brightest.1
+            };
+        };
+        if above == 0 {
+            return Color {
+                a: color.a,
+                ..darkest.1
+            };
+        }
+        let (low, high) = (self.ramp[above - 1], self.ramp[above]);
+        let t = (brightness - low.0) / (high.0 - low.0).max(f32::EPSILON);
+        let threshold = (BAYER_4X4[y % 4][x % 4] + 0.5) / 16.0;
+        let (_, picked) = if t > threshold { high } else { low };
+        Color {
+            a: color.a,
+            ..picked
+        }
+    }
+    /// Snaps the light of pixel `x`, `y` to one of the shades, keeping its tint.
+    /// Ordered dithering picks the shade above or below, so smooth light turns into a pattern
+    fn dither(&self, light: Vector3, x: usize, y: usize) -> Vector3 {
+        let brightest = light.x.max(light.y).max(light.z);
+        if self.shades < 2 || brightest <= 0.0 {
+            return light;
+        }
+        let steps = (self.shades - 1) as f32;
+        let threshold = (BAYER_4X4[y % 4][x % 4] + 0.5) / 16.0;
+        let shade = (brightest * steps + threshold).floor().min(steps) / steps;
+        light * (shade / brightest)
+    }
+    /// How much light reaches a pixel per color channel, 1 for fully lit
+    pub fn light_at(&self, x: usize, y: usize) -> Vector3 {
+        // todo probably needs other light calculation because not smort enough
+        let sun = self.sun.to_vec3();
+        let pixel = &self.pixels[x][y];
+        let normal = Normal(pixel.normal.0.lerp(self.sun.0, pixel.translucency)).to_vec3();
+        let direct = sun.dot(normal).max(0.0);
+        let unshaded = 1.0 - self.light[x][y].0.min(1.0);
+
+        let strength = self.ambient.strength;
+        let tint = self.ambient.sun;
+        let [r, g, b] = [tint.r, tint.g, tint.b].map(|c| c as f32 / 255.0);
+        if strength <= 0.0 {
+            let light = direct.max(self.ambient.floor) * unshaded;
+            return self.tint_shadow(Vector3::new(light * r, light * g, light * b));
+        }
+        let dome = self.ambient.light(normal);
+        let direct = direct * (1.0 - strength);
+        self.tint_shadow(Vector3::new(
+            (direct * r + dome.x * strength) * unshaded,
+            (direct * g + dome.y * strength) * unshaded,
+            (direct * b + dome.z * strength) * unshaded,
+        ))
+    }
+    /// Fills the light missing from full brightness with the shadow tint of the ambient
+    fn tint_shadow(&self, light: Vector3) -> Vector3 {
+        let tint = self.ambient.shadow;
+        let fill = |light: f32, tint: u8| light + (1.0 - light).max(0.0) * tint as f32 / 255.0;
+        Vector3::new(
+            fill(light.x, tint.r),
+            fill(light.y, tint.g),
+            fill(light.z, tint.b),
+        )
+    }
+    /// Lit color of a pixel
+    pub fn shade(&self, x: usize, y: usize) -> Color {
+        self.shade_pass(x, y, Pass::Lit)
+    }
+    /// Color of a pixel in the pass
+    pub fn shade_pass(&self, x: usize, y: usize, pass: Pass) -> Color {
+        let c = self.pixels[x][y].color;
+        match pass {
+            Pass::Lit => {
+                let light = self.dither(self.light_at(x, y), x, y);
+                // TODO parametrize
+                let f = |c: u8, light: f32| ((c as f32) * light) as u8;
+                let lit = Color::new(f(c.r, light.x), f(c.g, light.y), f(c.b, light.z), c.a);
+                self.snap(lit, x, y)
+            }
+            Pass::Albedo => c,
+            Pass::Light => {
+                let light = self.dither(self.light_at(x, y), x, y);
+                let f = |light: f32| (light * 255.0) as u8;
+                Color::new(f(light.x), f(light.y), f(light.z), c.a)
+            }
+        }
+    }
+    pub fn render_to(&self, d: &mut impl Surface) {
+        self.render_pass_to(d, Pass::Lit);
+    }
+    pub fn render_pass_to(&self, d: &mut impl Surface, pass: Pass) {
+        for x in 0..self.width() {
+            for y in 0..self.height() {
+                let color = self.shade_pass(x as usize, y as usize, pass);
+                d.fill_rect(
+                    x * self.pixel_size,
+                    (self.height() - y + 1) * self.pixel_height,
+                    self.pixel_size,
+                    self.pixel_height,
+                    color,
+                );
+            }
+        }
+    }
+    /// Draws the silhouette moved by `offset_x`, `offset_y` canvas pixels as a shadow
+    /// on a wall behind it, which starts at canvas row `from_y`
+    pub fn render_cast_to(
+        &self,
+        d: &mut impl Surface,
+        offset_x: i32,
+        offset_y: i32,
+        from_y: i32,
+        alpha: f32,
+    ) {
+        for x in 0..self.width() {
+            for y in 0..self.height() {
+                let shadow = self.pixels[x as usize][y as usize].color.a as f32 / 255.0;
+                let (x, y) = (x + offset_x, y + offset_y);
+                if shadow <= 0.0 || y < from_y {
+                    continue;
+                }
+                d.fill_rect(
+                    x * self.pixel_size,
+                    (self.height() - y + 1) * self.pixel_height,
+                    self.pixel_size,
+                    self.pixel_height,
+                    Color::BLACK.fade(shadow * alpha),
+                );
+            }
+        }
+    }
+    pub fn render_shadows_to(
+        &self,
+        d: &mut impl Surface,
+        origin_x: i32,
+        origin_y: i32,
+        alpha: f32,
+    ) {
+        // rotate in square units so tall pixels don't stretch the shadow,
+        // each canvas row then covers `columns` shadow columns and more pixels overlap per shadow row
+        let aspect = self.aspect();
+        let columns = aspect.round().max(1.0) as i32;
+        let alpha = alpha / aspect.max(1.0);
+        for x in 0..self.width() {
+            for y in 0..self.height() {
+                let shadow = self.pixels[x as usize][y as usize].color.a as f32 / 255.0;
+                let color = Color::BLACK.fade(shadow * alpha);
+                let (ox, oy) = (origin_x / self.pixel_size, origin_y / self.pixel_height);
+                let (x, y) = (x - ox, y - oy);
+
+                let (x, y) = (
+                    (y as f32 * aspect) as i32,
+                    (-x as f32 / (2.0 * aspect)) as i32,
+                );
+                let (x, y) = (x + ox, y + oy);
+
+                d.fill_rect(
+                    x * self.pixel_size,
+                    (self.height() - y + 1) * self.pixel_height,
+                    self.pixel_size * columns,
+                    self.pixel_height,
+                    color,
+                );
+            }
+        }
+    }
+}
+
+/// Something the canvases can be drawn onto
+pub trait Surface {
+    fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: Color);
+}
+
+/// Software surface for rendering without a window
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+    /// placement of everything drawn
+    pub camera: Camera,
+}
+
+impl Framebuffer {
+    pub fn new(width: usize, height: usize, background: Color) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![background; width * height],
+            camera: Camera::default(),
+        }
+    }
+    /// Fills every pixel with `background` again, for drawing the next frame
+    pub fn clear(&mut self, background: Color) {
+        self.pixels.fill(background);
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    pub fn get(&self, x: usize, y: usize) -> Color {
+        self.pixels[x + y * self.width]
+    }
+    /// Blends the color over the pixel, ignoring the camera
+    pub fn blend_pixel(&mut self, x: usize, y: usize, color: Color) {
+        let idx = x + y * self.width;
+        self.pixels[idx] = blend(self.pixels[idx], color);
+    }
+    pub fn to_image(&self) -> image::RgbaImage {
+        image::RgbaImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let c = self.get(x as usize, y as usize);
+            image::Rgba([c.r, c.g, c.b, c.a])
+        })
+    }
+}
+
+impl Surface for Framebuffer {
+    fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: Color) {
+        let Camera { offset, zoom } = self.camera;
+        let map = |v: i32, offset: f32, bound: usize| {
+            ((v as f32 * zoom + offset).round().max(0.0) as usize).min(bound)
+        };
+        let (x0, x1) = (
+            map(x, offset.x, self.width),
+            map(x + width, offset.x, self.width),
+        );
+        let (y0, y1) = (
+            map(y, offset.y, self.height),
+            map(y + height, offset.y, self.height),
+        );
+        for y in y0..y1 {
+            for x in x0..x1 {
+                self.blend_pixel(x, y, color);
+            }
+        }
+    }
+}
+
+/// 4x4 ordered dithering thresholds
+pub(crate) const BAYER_4X4: [[f32; 4]; 4] = [
+    [0.0, 8.0, 2.0, 10.0],
+    [12.0, 4.0, 14.0, 6.0],
+    [3.0, 11.0, 1.0, 9.0],
+    [15.0, 7.0, 13.0, 5.0],
+];
+
+/// Shadow of the canopy on the ground, at canvas resolution
+///
+/// Alive nodes are projected along the sun direction onto the ground plane,
+/// their depth spreads them over a few rows around the foot of the trunk.
+pub struct GroundShadow {
+    /// sun the shadow was cast by
+    sun_angle: f32,
+    pixel_size: i32,
+    pixel_height: i32,
+    strength: f32,
+    /// soft occupancy between 0 and 1, indexed `[x][y]` with y going up
+    occupancy: Vec<Vec<f32>>,
+}
+
+impl GroundShadow {
+    pub fn from_tree(tree: &Tree, width: usize, height: usize) -> Self {
+        let mut shadow = Self {
+            sun_angle: 0.0,
+            pixel_size: 1,
+            pixel_height: 1,
+            strength: 0.0,
+            occupancy: vec![],
+        };
+        shadow.update(tree, width, height);
+        shadow
+    }
+
+    /// Recomputes the shadow for the tree, reusing the grid
+    pub fn update(&mut self, tree: &Tree, width: usize, height: usize) {
+        let config = &tree.config;
+        let (pw, ph) = (config.pixel_size as f32, config.pixel_height() as f32);
+        let counts = &mut self.occupancy;
+        if counts.len() != width || counts.first().map(Vec::len) != Some(height) {
+            *counts = vec![vec![0f32; height]; width];
+        }
+        for column in counts.iter_mut() {
+            column.fill(0.0);
+        }
+
+        // horizontal distance the shadow travels per unit of height,
+        // capped so low suns keep the patch under the tree instead of far off the canvas
+        let sun = Vector2::new(config.sun_angle.cos(), config.sun_angle.sin());
+        let run = (-sun.x / sun.y.max(0.1)).clamp(-0.6, 0.6);
+        let floor = floor(config);
+        let ground = floor / ph;
+        let mid_z = config.node_depth_max as f32 / 2.0;
+
+        for node in tree.nodes().filter(|node| node.alive) {
+            let radius = tree.radius_of(&node);
+            // thinned out foliage lets more light through, a bare twig shades like a branch
+            let (reach, weight) = if radius < config.leaf_max_width && config.foliage > 0.0 {
+                (config.leaf_size, config.foliage)
+            } else {
+                (radius, 0.5)
+            };
+            let pos = node.pos.vec();
+            let elevation = (pos.y - floor).max(0.0);
+            let cx = (pos.x + elevation * run) / pw;
+            // further back is higher up on the ground plane
+            let cy = ground + (node.z - mid_z) * 0.8;
+            let (rx, ry) = (reach / pw, (reach / ph * 0.4).max(1.0));
+
+            let x0 = (cx - rx).floor().max(0.0) as usize;
+            let y0 = (cy - ry).floor().max(0.0) as usize;
+            let x1 = (cx + rx).ceil().max(0.0) as usize;
+            let y1 = (cy + ry).ceil().max(0.0) as usize;
+            for (x, column) in counts.iter_mut().enumerate().take(x1).skip(x0) {
+                for (y, count) in column.iter_mut().enumerate().take(y1).skip(y0) {
+                    let (dx, dy) = ((x as f32 - cx) / rx, (y as f32 - cy) / ry);
+                    let falloff = 1.0 - (dx * dx + dy * dy);
+                    if falloff > 0.0 {
+                        *count += weight * falloff;
+                    }
+                }
+            }
+        }
+
+        for count in counts.iter_mut().flatten() {
+            *count = 1.0 - (-0.8 * *count).exp();
+        }
+        self.sun_angle = config.sun_angle;
+        self.pixel_size = config.pixel_size as _;
+        self.pixel_height = config.pixel_height() as _;
+        self.strength = config.ground_shadow;
+    }
+
+    /// Draws the dithered shadow, `top` is where the upper edge of the grid ends up
+    pub fn render_to(&self, d: &mut impl Surface, top: i32) {
+        let height = self.occupancy.first().map_or(0, Vec::len) as i32;
+        for (x, column) in self.occupancy.iter().enumerate() {
+            for (y, occupancy) in column.iter().enumerate() {
+                let threshold = (BAYER_4X4[y % 4][x % 4] + 0.5) / 16.0;
+                if occupancy * self.strength > threshold {
+                    d.fill_rect(
+                        x as i32 * self.pixel_size,
+                        top + (height - y as i32) * self.pixel_height,
+                        self.pixel_size,
+                        self.pixel_height,
+                        Color::BLACK.fade(0.35),
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl PrettyRender {
+    /// Creates a new renderer
+    /// Expensive shading computations
+    pub fn new(tree: Tree) -> Self {
+        Self {
+            drops: precipitation::Drops::new(tree.config.seed),
+            tree,
+            layers: None,
+            painted: None,
+            continuations: vec![],
+            selection_colors: vec![],
+            ground: GroundShadow {
+                sun_angle: 0.0,
+                pixel_size: 1,
+                pixel_height: 1,
+                strength: 0.0,
+                occupancy: vec![],
+            },
+            jitter: None,
+            wind_time: None,
+            storm: 0.0,
+            wind: None,
+            painted_foliage: None,
+            falling: vec![],
+            falling_canvas: None,
+            roots: None,
+            sprites: None,
+            sprite_error: None,
+        }
+    }
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+    /// Why the leaf sprites of the config didn't load and the built in ones are drawn instead,
+    /// once after they were tried
+    pub fn take_sprite_error(&mut self) -> Option<String> {
+        self.sprite_error.take()
+    }
+    pub fn tree_mut(&mut self) -> &mut Tree {
+        &mut self.tree
+    }
+    /// Forces the next render to repaint, for changes the tree's revision doesn't track
+    pub fn invalidate(&mut self) {
+        if let Some(key) = &mut self.painted {
+            key.stale = true;
+        }
+    }
+    /// Nudges every leaf blob by up to a pixel, differently for every `frame`,
+    /// so held frames of a stop-motion animation boil like hand drawn ones. `None` keeps them still
+    pub fn set_jitter(&mut self, frame: Option<u64>) {
+        if self.jitter != frame {
+            self.jitter = frame;
+            self.invalidate();
+        }
+    }
+    /// Whether the drawing changes on its own: the tree grows, leaves or snow fall, the wind sways
+    /// it or chicks beg in its nest
+    pub fn is_animating(&self) -> bool {
+        self.tree.is_growing()
+            || !self.falling.is_empty()
+            || self.drops.is_active(&self.tree.config)
+            || self.tree.config.wind + self.storm > 0.0
+            || chirp(&self.tree).is_some()
+    }
+    /// Lets the leaves that fell off when `foliage` of the config dropped fall for `seconds`,
+    /// they vanish when they reach the ground. Snow and rain fall onto the tree of the last render
+    pub fn advance(&mut self, seconds: f32) {
+        if let Some(layers) = &self.layers {
+            let wind = self.tree.config.wind + self.storm;
+            let front_to_back = [&layers.leaves_front, &layers.branches, &layers.leaves_back];
+            self.drops
+                .advance(seconds, &self.tree.config, wind, front_to_back);
+        }
+        let ground = floor(&self.tree.config) / self.tree.config.pixel_height() as f32;
+        for leaf in &mut self.falling {
+            leaf.age += seconds;
+            leaf.pos.y -= FALL_SPEED * seconds;
+            leaf.pos.x += (leaf.age * 2.0).sin() * FALL_DRIFT * seconds;
+        }
+        self.falling.retain(|leaf| leaf.pos.y > ground);
+    }
+    /// Sways the branches and leaves the way the wind blows at `time` seconds,
+    /// as strong as `wind` of the tree's config says. `None` keeps them still
+    pub fn set_wind(&mut self, time: Option<f32>) {
+        if self.wind_time != time {
+            self.wind_time = time;
+            self.invalidate();
+        }
+    }
+    /// Lets the wind blow `wind` world units stronger than the config says, while a storm passes
+    pub fn set_storm(&mut self, wind: f32) {
+        if self.storm != wind {
+            self.storm = wind;
+            self.invalidate();
+        }
+    }
+    /// Snaps the branches the wind bent further than `branch_strength` of the config holds in the
+    /// last render, their wood and leaves tumble to the ground. Gives back how many snapped
+    pub fn snap(&mut self) -> usize {
+        let Some(wind) = self.wind.as_ref().filter(|_| self.wind_time.is_some()) else {
+            return 0;
+        };
+        let tree = &mut self.tree;
+        let breaking = wind.breaking(tree, tree.config.branch_strength);
+        let alive = tree.nodes().map(|node| node.alive).collect::<Vec<_>>();
+        for &idx in &breaking {
+            tree.kill_subtree(idx);
+        }
+        self.shed(&alive);
+        breaking.len()
+    }
+    /// Ages the grown tree when it is due, see `aging`. The twigs of a cracked branch drop their
+    /// leaves, a snapped one tumbles to the ground in one piece
+    pub fn age(&mut self) -> Option<bonsai_engine::aging::Aging> {
+        let alive = self.tree.nodes().map(|node| node.alive).collect::<Vec<_>>();
+        let aging = self.tree.age()?;
+        self.shed(&alive);
+        Some(aging)
+    }
+    /// Lets the nodes that died since they were `alive` fall: the twigs drop their leaves one by
+    /// one, the wood of a snapped branch falls in one piece, a pixel for every pixel along it
+    fn shed(&mut self, alive: &[bool]) {
+        let tree = &self.tree;
+        let (pixel_width, pixel_height) = (tree.config.pixel_size, tree.config.pixel_height());
+        let to_canvas =
+            |pos: Vector2| Vector2::new(pos.x / pixel_width as f32, pos.y / pixel_height as f32);
+        // the wood of every snapped branch drifts along with where it broke off
+        let mut broke_at = vec![0; alive.len()];
+        for (idx, node) in tree.nodes().enumerate() {
+            if node.alive || !alive[idx] {
+                continue;
+            }
+            // parents always come before their children
+            let piece = match node.parent {
+                Some(parent) if alive[parent] && !tree.node(parent).alive => broke_at[parent],
+                _ => idx,
+            };
+            broke_at[idx] = piece;
+            if tree.radius_of(&node) < tree.config.leaf_max_width {
+                self.falling.push(FallingLeaf {
+                    pos: to_canvas(node.pos.vec()),
+                    color: tree.config.colors.leaf,
+                    depth: 0.3,
+                    age: blob_threshold(tree.config.seed, idx as u64) * 10.0,
+                });
+                continue;
+            }
+            let age = blob_threshold(tree.config.seed, piece as u64) * 10.0;
+            let to = to_canvas(node.pos.vec());
+            let from = node
+                .parent
+                .map_or(to, |parent| to_canvas(tree.node(parent).pos.vec()));
+            let steps = (to - from).length().ceil().max(1.0) as usize;
+            for step in 1..=steps {
+                self.falling.push(FallingLeaf {
+                    pos: from.lerp(to, step as f32 / steps as f32),
+                    color: tree.config.colors.old_branch,
+                    depth: 0.0,
+                    age,
+                });
+            }
+        }
+    }
+}
+
+/// Perceived brightness of the color, between 0 and 1
+pub(crate) fn luma(color: Color) -> f32 {
+    (0.299 * color.r as f32 + 0.587 * color.g as f32 + 0.114 * color.b as f32) / 255.0
+}
+
+/// Atmospheric perspective: desaturates the color and fades it towards the sky
+pub fn fog(color: Color, sky: Color, amount: f32) -> Color {
+    let amount = amount.clamp(0.0, 1.0);
+    let luma = 0.299 * color.r as f32 + 0.587 * color.g as f32 + 0.114 * color.b as f32;
+    let mix = |c: u8, s: u8| {
+        let desaturated = c as f32 + (luma - c as f32) * amount * 0.5;
+        (desaturated + (s as f32 - desaturated) * amount) as u8
+    };
+    Color::new(
+        mix(color.r, sky.r),
+        mix(color.g, sky.g),
+        mix(color.b, sky.b),
+        color.a,
+    )
+}
+
+/// Multiplies the colors channel by channel, white leaves the other as it is
+pub fn multiply(a: Color, b: Color) -> Color {
+    let mix = |a: u8, b: u8| (a as u16 * b as u16 / 255) as u8;
+    Color::new(mix(a.r, b.r), mix(a.g, b.g), mix(a.b, b.b), mix(a.a, b.a))
+}
+
+/// Blends `src` over `dst`
+pub fn blend(dst: Color, src: Color) -> Color {
+    let sa = src.a as f32 / 255.0;
+    let da = dst.a as f32 / 255.0;
+    let a = sa + da * (1.0 - sa);
+    if a <= 0.0 {
+        return Color::new(0, 0, 0, 0);
+    }
+    let mix = |s: u8, d: u8| ((s as f32 * sa + d as f32 * da * (1.0 - sa)) / a) as u8;
+    Color::new(
+        mix(src.r, dst.r),
+        mix(src.g, dst.g),
+        mix(src.b, dst.b),
+        (a * 255.0) as u8,
+    )
+}
+
+/// The separately drawn parts of the tree, from back to front
+struct Layers {
+    leaves_back: Canvas,
+    branches: Canvas,
+    leaves_front: Canvas,
+}
+
+impl PrettyRender {
+    pub fn render(&mut self, d: &mut impl Surface) {
+        self.render_roots(d);
+        let stale = !self.is_painted();
+        self.paint();
+        let tree = &self.tree;
+        let Some(Layers {
+            leaves_back,
+            branches,
+            leaves_front,
+        }) = &self.layers
+        else {
+            return;
+        };
+        let (ox, oy) = (tree.config.origin.x as _, floor(&tree.config) as _);
+        // same placement as the canvases, which have one row more above their top
+        let (width, height) = (branches.width() as usize, branches.height() as usize);
+        if stale || self.ground.sun_angle != tree.config.sun_angle {
+            let turned = volume::turned(tree);
+            self.ground
+                .update(turned.as_ref().unwrap_or(tree), width, height);
+        }
+        if let Some(tokonoma) = &tree.config.tokonoma {
+            tokonoma::render_backdrop(tokonoma, d, tree, width as i32, height as i32);
+        } else if let Some(scenery) = &tree.config.scenery {
+            scenery::render_backdrop(scenery, d, tree, width as i32, height as i32);
+        }
+        self.ground.render_to(d, tree.config.pixel_height() as i32);
+        if let Some(tokonoma) = &tree.config.tokonoma {
+            // in the alcove the shadows fall on the wall behind the tree instead of the ground
+            let (dx, dy, wall) = tokonoma.wall_shadow(&tree.config);
+            branches.render_cast_to(d, dx, dy, wall, 0.3);
+            leaves_back.render_cast_to(d, dx, dy, wall, 0.1);
+        } else {
+            branches.render_shadows_to(d, ox, oy, 0.3);
+            leaves_back.render_shadows_to(d, ox, oy, 0.1);
+        }
+        leaves_back.render_to(d);
+        branches.render_to(d);
+        leaves_front.render_to(d);
+        if !self.falling.is_empty() {
+            let (pixel_width, pixel_height) = (tree.config.pixel_size, tree.config.pixel_height());
+            let sun = Normal::sun(&tree.config);
+            let canvas = self.falling_canvas.get_or_insert_with(|| {
+                Canvas::new(width, height, sun, pixel_width as _, pixel_height as _)
+            });
+            canvas.reset(width, height, sun, pixel_width as _, pixel_height as _);
+            canvas.set_grain(tree.config.grain, tree.config.seed);
+            for leaf in &self.falling {
+                canvas.draw_sphere(leaf.pos, 0.9, leaf.color, leaf.depth, 0.3, Grain::Leaves);
+            }
+            canvas.relight(sun, Ambient::of(&tree.config), tree.config.shades);
+            canvas.set_ramp(&tree.config.ramp);
+            canvas.render_to(d);
+        }
+        self.drops.render_to(d, &tree.config, height as i32);
+    }
+
+    /// Draws the roots below the soil line, behind everything else so a pot hides them
+    fn render_roots(&mut self, d: &mut impl Surface) {
+        let Some(growing) = self.tree.roots() else {
+            self.roots = None;
+            return;
+        };
+        if self
+            .roots
+            .as_ref()
+            .is_none_or(|(revision, _)| *revision != growing.revision())
+        {
+            let planted = PrettyRender::new(roots::planted(growing));
+            self.roots = Some((growing.revision(), Box::new(planted)));
+        }
+        let config = &self.tree.config;
+        let (_, roots) = self.roots.as_mut().unwrap();
+        // the roots are lit by the same sun as the tree, which may have moved since they were planted
+        let lit = &mut roots.tree.config;
+        lit.sun_angle = config.sun_angle;
+        lit.sun_elevation = config.sun_elevation;
+        lit.ambient = config.ambient;
+        lit.ambient_sky = config.ambient_sky;
+        lit.ambient_ground = config.ambient_ground;
+        lit.sun_color = config.sun_color;
+        lit.light_floor = config.light_floor;
+        lit.shades = config.shades;
+        lit.shadow_tint = config.shadow_tint;
+        lit.ramp.clone_from(&config.ramp);
+        roots.render_pass(d, Pass::Lit);
+
+        let (Some(settings), None) = (config.roots, config.pot) else {
+            return;
+        };
+        let (pw, ph) = (config.pixel_size as i32, config.pixel_height() as i32);
+        let rows = config.height as i32 / ph + CANVAS_PADDING as i32;
+        let row = (config.origin.y / ph as f32).round() as i32;
+        let (left, right) = (
+            ((config.origin.x - settings.spread / 2.0) / pw as f32).floor() as i32,
+            ((config.origin.x + settings.spread / 2.0) / pw as f32).ceil() as i32,
+        );
+        d.fill_rect(
+            left * pw,
+            (rows - row + 1) * ph,
+            (right - left) * pw,
+            ph,
+            settings.soil,
+        );
+    }
+
+    /// Renders only the tree itself in the pass, without any shadows around it
+    pub fn render_pass(&mut self, d: &mut impl Surface, pass: Pass) {
+        let layers = self.paint();
+        for layer in [&layers.leaves_back, &layers.branches, &layers.leaves_front] {
+            layer.render_pass_to(d, pass);
+        }
+    }
+
+    /// Lit colors of the tree at canvas resolution, indexed `[x][y]` with y going up.
+    /// Pixels without any geometry are transparent.
+    pub fn pixels(&mut self) -> Vec<Vec<Color>> {
+        let layers = self.paint();
+        let (width, height) = (layers.branches.width(), layers.branches.height());
+        (0..width as usize)
+            .map(|x| {
+                (0..height as usize)
+                    .map(|y| {
+                        [&layers.leaves_back, &layers.branches, &layers.leaves_front]
+                            .iter()
+                            .fold(Color::new(0, 0, 0, 0), |dst, layer| {
+                                blend(dst, layer.shade(x, y))
+                            })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Whether the canvases still show the tree as it is
+    fn is_painted(&self) -> bool {
+        self.layers.is_some()
+            && self
+                .painted
+                .as_ref()
+                .is_some_and(|key| key.matches(&self.tree))
+    }
+
+    /// Paints the tree onto the canvases unless they already show it, then lights them.
+    /// A different light only relights them, the painted geometry stays
+    fn paint(&mut self) -> &Layers {
+        if !self.is_painted() {
+            self.repaint();
+        }
+        let config = &self.tree.config;
+        let (sun, ambient) = (Normal::sun(config), Ambient::of(config));
+        let layers = self.layers.as_mut().unwrap();
+        for layer in [
+            &mut layers.leaves_back,
+            &mut layers.branches,
+            &mut layers.leaves_front,
+        ] {
+            layer.relight(sun, ambient, config.shades);
+            layer.set_ramp(&config.ramp);
+        }
+        layers
+    }
+
+    /// The sprites the leaves are drawn with, loaded again only once the config names others.
+    /// Sprites that don't load give way to the built in ones
+    fn leaf_sprites(&mut self) -> Option<Arc<[Sprite]>> {
+        let source = self.tree.config.leaf_sprites.as_ref()?;
+        if self.sprites.as_ref().map(|(loaded, _)| loaded) != Some(source) {
+            let sprites = sprite::load(source).unwrap_or_else(|err| {
+                self.sprite_error = Some(format!(
+                    "could not load the leaf sprites {}: {}",
+                    source.name(),
+                    err
+                ));
+                sprite::builtin()
+            });
+            self.sprites = Some((source.clone(), sprites.into()));
+        }
+        self.sprites.as_ref().map(|(_, sprites)| sprites.clone())
+    }
+    fn repaint(&mut self) {
+        let sprites = self.leaf_sprites();
+        let sun = Normal::sun(&self.tree.config);
+        // a turned tree is put together anew, the time it was looked after stays with this one
+        let chirp = chirp(&self.tree);
+        // a tree grown with a volume is drawn as seen from its yaw
+        let turned = volume::turned(&self.tree);
+        let tree = turned.as_ref().unwrap_or(&self.tree);
+        let (pixel_width, pixel_height) = (tree.config.pixel_size, tree.config.pixel_height());
+        let (width, height) = (
+            tree.config.width as usize / pixel_width + CANVAS_PADDING,
+            tree.config.height as usize / pixel_height + CANVAS_PADDING,
+        );
+        let new_canvas = || Canvas::new(width, height, sun, pixel_width as _, pixel_height as _);
+        let Layers {
+            leaves_back: leaf_canvas_back,
+            branches: canvas,
+            leaves_front: leaf_canvas_front,
+        } = self.layers.get_or_insert_with(|| Layers {
+            leaves_back: new_canvas(),
+            branches: new_canvas(),
+            leaves_front: new_canvas(),
+        });
+        for layer in [
+            &mut *leaf_canvas_back,
+            &mut *canvas,
+            &mut *leaf_canvas_front,
+        ] {
+            layer.reset(width, height, sun, pixel_width as _, pixel_height as _);
+            layer.set_grain(tree.config.grain, tree.config.seed);
+        }
+        let scaling = 1.0 / pixel_width as f32;
+        // world position to canvas pixel
+        let to_canvas =
+            |pos: Vector2| Vector2::new(pos.x / pixel_width as f32, pos.y / pixel_height as f32);
+
+        if let Some(tokonoma) = &tree.config.tokonoma {
+            tokonoma::draw_stand(
+                tokonoma,
+                canvas,
+                &tree.config,
+                pixel_width as f32,
+                pixel_height as f32,
+            );
+        }
+        if let Some(pot) = &tree.config.pot {
+            pot::draw(
+                pot,
+                canvas,
+                tree.config.origin,
+                pixel_width as f32,
+                pixel_height as f32,
+                tree.config.seed,
+            );
+        }
+        if let Some(kusamono) = &tree.config.kusamono {
+            kusamono::draw(
+                kusamono,
+                canvas,
+                tree,
+                pixel_width as f32,
+                pixel_height as f32,
+            );
+        }
+
+        let mut rng = tree.config.leaf_rng();
+        // a stream of its own, so the leaves keep their places apart from the jitter
+        let mut jitter = self.jitter.map(|frame| {
+            ChaCha12Rng::seed_from_u64(tree.config.seed ^ frame.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+        });
+        tree.continuations_into(&mut self.continuations);
+        let continuations = &self.continuations;
+        let (painted_foliage, falling) = (self.painted_foliage, &mut self.falling);
+        let sway = match self.wind_time {
+            Some(time) => self
+                .wind
+                .get_or_insert_with(|| Wind::new(tree.config.seed))
+                .sway(tree, time, tree.config.wind + self.storm),
+            None => &[],
+        };
+        let sway_of = |idx: usize| sway.get(idx).copied().unwrap_or_default();
+        tree.selection_colors_into(&mut self.selection_colors);
+        let selection_colors = &self.selection_colors;
+
+        for (node_idx, node) in tree.nodes().enumerate() {
+            let pos = node.pos.vec() + sway_of(node_idx);
+            // nodes further back fade into the sky
+            let distance = node.z / tree.config.node_depth_max.max(1) as f32;
+            let facts = NodeFacts::of(tree, node_idx);
+            let fogged = |part, color| {
+                let color = color_rules::apply(&tree.config.color_rules, part, &facts, color);
+                let color = selection_colors[node_idx].unwrap_or(color);
+                fog(color, tree.config.sky, distance * tree.config.depth_fog)
+            };
+            let need_leaf_drawing =
+                tree.radius_of(&node) < tree.config.leaf_max_width && node.alive;
+            // rendering a leaf
+
+            let offset =
+                |rng: &mut ChaCha12Rng| (rng.gen::<f32>() * 2.0 - 1.0) * tree.config.leaf_size;
+            let offset = |rng: &mut ChaCha12Rng| Vector2::new(offset(rng), offset(rng));
+
+            let mut draw_leaf = |canvas: &mut Canvas, blob: u64| {
+                let mut o = offset(&mut rng);
+                if let Some(jitter) = &mut jitter {
+                    let nudge = |jitter: &mut ChaCha12Rng| (jitter.gen::<f32>() * 2.0 - 1.0) * 0.75;
+                    o.x += nudge(jitter) * pixel_width as f32;
+                    o.y += nudge(jitter) * pixel_height as f32;
+                }
+                let leaf = tree.config.get_leaf_type(&mut rng);
+                let depth = (rng.gen::<f32>() * 2.0 - 1.0) * tree.config.leaf_depth_jitter;
+                // the blobs still on the tree as the foliage thins out, the same ones every time
+                let shed = blob_threshold(tree.config.seed, node_idx as u64 * 4 + blob);
+                if need_leaf_drawing && shed < tree.config.foliage {
+                    // only check aliveness here to make the same number of calls to rng to have it consistent even when branches die
+                    // blobs closer to the camera appear bigger
+                    let size = leaf.size * (1.0 + 0.5 * depth);
+                    let color = fogged(Part::Leaves, leaf.color);
+                    let at = to_canvas(pos + o);
+                    match sprites.as_deref() {
+                        Some(sprites) if !sprites.is_empty() => {
+                            // picked apart from `rng`, so the leaves keep their places either way
+                            let roll = blob_threshold(
+                                tree.config.seed ^ SPRITE_SALT,
+                                node_idx as u64 * 4 + blob,
+                            );
+                            let sprite = &sprites[(roll * sprites.len() as f32) as usize];
+                            canvas.draw_sprite_at(at, size, sprite, color, depth, 0.65);
+                        }
+                        _ => canvas.draw_sphere(at, size, color, depth, 0.65, Grain::Leaves),
+                    }
+                } else if need_leaf_drawing && painted_foliage.is_some_and(|before| shed < before) {
+                    falling.push(FallingLeaf {
+                        pos: to_canvas(pos + o),
+                        color: fogged(Part::Leaves, leaf.color),
+                        depth,
+                        age: shed * 10.0,
+                    });
+                }
+            };
+
+            for blob in 0..2 {
+                draw_leaf(leaf_canvas_front, blob * 2);
+                draw_leaf(leaf_canvas_back, blob * 2 + 1);
+            }
+
+            if !need_leaf_drawing && node.alive {
+                // rendering a branch
+                let spline = tree
+                    .segment_spline(node_idx, continuations)
+                    .unwrap_or_else(|| {
+                        let parent_pos = pos - Vector2::new(0.0, tree.config.grow_dist);
+                        Spline::through(None, parent_pos, pos, None)
+                    });
+                // the segment bends from the sway of its parent to its own
+                let parent_sway = node.parent.map_or(Vector2::zero(), sway_of);
+                // the striations of the bark run along the segment, as it lies on the canvas
+                let along = to_canvas(spline.at(1.0)) - to_canvas(spline.at(0.0));
+                let along = if along.length_sqr() > 0.0 {
+                    along
+                } else {
+                    Vector2::new(0.0, 1.0)
+                };
+                for i in 0..10 {
+                    let t = 1.0 - i as f32 * 0.1;
+                    let interp_pos = spline.at(t) + parent_sway.lerp(sway_of(node_idx), t);
+                    canvas.draw_sphere(
+                        to_canvas(interp_pos),
+                        tree.radius_of(&node) * scaling,
+                        fogged(Part::Bark, tree.config.colors.old_branch),
+                        0.0,
+                        0.3,
+                        Grain::Bark(along),
+                    );
+                }
+            }
+        }
+        if let Some(ivy) = &tree.config.ivy {
+            ivy::draw(ivy, tree, canvas, to_canvas, sway_of);
+        }
+        if let Some(found) = bonsai_engine::nest::find(tree) {
+            let found = bonsai_engine::nest::Nest {
+                hatched: chirp.is_some(),
+                ..found
+            };
+            let frame = chirp.unwrap_or_default();
+            nest::draw(
+                &found,
+                leaf_canvas_front,
+                pixel_width as f32,
+                to_canvas,
+                sway_of,
+                frame,
+            );
+        }
+        if !tree.is_growing() && tree.config.fruit_density > 0.0 {
+            // blossoms and fruit on a share of the twigs with leaves, each from its own hash
+            // so they don't move the leaves
+            let seed = tree.config.seed ^ FRUIT_SALT;
+            for (node_idx, node) in tree.nodes().enumerate() {
+                let idx = node_idx as u64 * 3;
+                if !node.alive
+                    || tree.radius_of(&node) >= tree.config.leaf_max_width
+                    || blob_threshold(seed, idx) >= tree.config.fruit_density
+                {
+                    continue;
+                }
+                let offset =
+                    |blob| (blob_threshold(seed, blob) * 2.0 - 1.0) * tree.config.leaf_size;
+                let o = Vector2::new(offset(idx + 1), offset(idx + 2));
+                let pos = to_canvas(node.pos.vec() + sway_of(node_idx) + o);
+                let distance = node.z / tree.config.node_depth_max.max(1) as f32;
+                let color = fog(
+                    tree.config.fruit_color,
+                    tree.config.sky,
+                    distance * tree.config.depth_fog,
+                );
+                leaf_canvas_front.draw_sphere(pos, FRUIT_SIZE, color, 0.5, 0.8, Grain::Flat);
+            }
+        }
+        let self_shadow = tree.config.self_shadow;
+        leaf_canvas_back.receive_shadows(&[canvas, leaf_canvas_front], self_shadow);
+        canvas.receive_shadows(&[leaf_canvas_front], self_shadow);
+        leaf_canvas_front.receive_shadows(&[], self_shadow);
+        canvas.occlude_from_above(
+            &[leaf_canvas_front, leaf_canvas_back],
+            tree.config.canopy_occlusion,
+        );
+        let (strength, radius) = (
+            tree.config.ambient_occlusion,
+            tree.config.ambient_occlusion_radius,
+        );
+        leaf_canvas_back.occlude_ambient(&[canvas, leaf_canvas_front], strength, radius);
+        canvas.occlude_ambient(&[leaf_canvas_front], strength, radius);
+        leaf_canvas_front.occlude_ambient(&[], strength, radius);
+        self.painted = Some(PaintKey::of(&self.tree, self.painted.take()));
+        self.painted_foliage = Some(self.tree.config.foliage);
+    }
+}
+
+/// The frame the chicks in the tree's nest beg in, `None` before they hatched
+fn chirp(tree: &Tree) -> Option<u64> {
+    bonsai_engine::nest::hatched(tree).then(|| (tree.tended() * nest::CHIRP_FPS) as u64)
+}
+
+/// Keeps the hashes placing the fruit apart from the ones shedding the leaves
+const FRUIT_SALT: u64 = 0x6672_7569_7421;
+/// Radius of a blossom or fruit, in canvas pixels
+const FRUIT_SIZE: f32 = 1.2;
+/// Keeps the hashes picking the leaf sprites apart from the ones shedding the leaves
+const SPRITE_SALT: u64 = 0x7370_7269_7465;
+
+/// Random number between 0 and 1 for a leaf blob, independent of the leaf placement
+pub fn blob_threshold(seed: u64, blob: u64) -> f32 {
+    // splitmix64
+    let mut h = seed ^ blob.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^= h >> 31;
+    (h >> 40) as f32 / (1u64 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapped_branches_fall_in_one_piece() {
+        let config = Config {
+            seed: 8,
+            num_points: 3000,
+            aging_every: 60.0,
+            aging_severity: 1.0,
+            ..Config::default()
+        };
+        let mut pretty = PrettyRender::new(Tree::new_min_growth(config, 5));
+        pretty.tree_mut().grow_fully(&cancel::CancelToken::new());
+        pretty.tree_mut().tend(1e9);
+        let Some(bonsai_engine::aging::Aging::Snapped(idx)) = pretty.age() else {
+            panic!("the tree didn't snap");
+        };
+        let config = &pretty.tree().config;
+        let wood = pretty
+            .falling
+            .iter()
+            .filter(|piece| piece.color == config.colors.old_branch)
+            .collect::<Vec<_>>();
+        let dead_wood = pretty
+            .tree()
+            .nodes()
+            .filter(|node| !node.alive && node.radius >= config.leaf_max_width)
+            .count();
+        // every pixel along the branch, not only where its nodes are
+        assert!(wood.len() > dead_wood && dead_wood > 0);
+        assert!(wood.iter().all(|piece| piece.age == wood[0].age));
+        assert!(!pretty.tree().node(idx).alive);
+    }
+}
@@ -0,0 +1,185 @@
+//! Drawing the scenery behind everything: the sky, the sun or the moon, the hills and the ground
+
+use crate::*;
+use bonsai_engine::scenery::Scenery;
+use kusamono::mix;
+use pot::lighten;
+use render::Surface;
+
+/// Steps the sky deepens in from the horizon to the top
+const BANDS: i32 = 8;
+/// Rows of grass on top of the soil
+const GRASS: i32 = 2;
+
+/// Draws the scenery around the drawing of a canvas `width` × `height` pixels large
+pub fn render_backdrop(
+    scenery: &Scenery,
+    d: &mut impl Surface,
+    tree: &Tree,
+    width: i32,
+    height: i32,
+) {
+    let config = &tree.config;
+    let (pw, ph) = (config.pixel_size as i32, config.pixel_height() as i32);
+    // drawing position of a world height, like the canvases place their rows
+    let row = |world_y: f32| (height - (world_y / ph as f32).floor() as i32 + 1) * ph;
+    let ground = floor(config);
+    // far enough out to fill whatever the tree's drawing is shown on
+    let (far_left, far_right) = (-width * pw * 4, width * pw * 5);
+    let (far_top, far_bottom) = (-height * ph * 4, height * ph * 5);
+    // the ramp of the palette has the last word on every color, like on the tree
+    let paint = |color: Color| on_ramp(color, &config.ramp);
+    // the land is lit by the sun, or the moon, the sky glows on its own
+    let lit = |color: Color| render::multiply(color, config.sun_color);
+
+    // the config's sky halfway up, paler towards the horizon and deeper towards the top
+    let sky_at = |t: f32| lighten(config.sky, scenery.gradient * (0.5 - t.clamp(0.0, 1.0)));
+    let up = |world_y: f32| (world_y - ground) / (config.height - ground).max(1.0);
+    d.fill_rect(
+        far_left,
+        far_top,
+        far_right - far_left,
+        row(config.height) - far_top,
+        paint(sky_at(1.0)),
+    );
+    for band in 0..BANDS {
+        let t = band as f32 / BANDS as f32;
+        let from = ground + (config.height - ground) * t;
+        let to = ground + (config.height - ground) * (t + 1.0 / BANDS as f32);
+        let color = paint(sky_at(t + 0.5 / BANDS as f32));
+        d.fill_rect(
+            far_left,
+            row(to),
+            far_right - far_left,
+            row(from) - row(to),
+            color,
+        );
+    }
+
+    if scenery.sun {
+        // out from the middle of the sky towards where the light comes from, a low sun sets
+        // behind the hills. At night the light, and so the disc, is the moon's
+        let middle = Vector2::new(config.width / 2.0, (ground + config.height) / 2.0);
+        let reach = config.width.min(config.height - ground) * 0.4;
+        let toward = Vector2::new(config.sun_angle.cos(), config.sun_angle.sin());
+        let center = middle + toward * reach;
+        let radius = config.width.min(config.height) * 0.05;
+        let disc = lighten(config.sun_color, 0.5);
+        let halo = mix(sky_at(up(center.y)), disc, 0.3);
+        for (radius, color) in [(radius * 1.6, halo), (radius, disc)] {
+            fill_disc(d, center, radius, paint(color), (pw, ph), &row);
+        }
+    }
+
+    // the farther a range, the higher it rises behind the nearer ones and the paler it is
+    let noise = fuss::Simplex::from_seed(vec![config.seed as usize, 0x6869_6c6c]);
+    for range in 0..scenery.hills {
+        let near = (range + 1) as f32 / scenery.hills as f32;
+        let color = paint(mix(
+            sky_at(0.0),
+            lit(scenery.hill_color),
+            0.25 + 0.75 * near,
+        ));
+        let rise = scenery.hill_height * (1.6 - 0.6 * near);
+        let frequency = 1.0 / (120.0 + 180.0 * (1.0 - near));
+        let top_at = |column: i32| {
+            let x = (column * pw) as f32;
+            let height = noise.sum_octave_2d(3, x, range as f32 * 97.0, 0.5, frequency);
+            row(ground + rise * (0.5 + 0.5 * height).clamp(0.0, 1.0))
+        };
+        // one rect for every run of columns the same height
+        let (first, last) = (far_left / pw, far_right / pw);
+        let mut start = first;
+        let mut top = top_at(first);
+        for column in first + 1..=last {
+            let next = if column == last {
+                i32::MIN
+            } else {
+                top_at(column)
+            };
+            if next != top {
+                d.fill_rect(
+                    start * pw,
+                    top,
+                    (column - start) * pw,
+                    row(ground) - top,
+                    color,
+                );
+                (start, top) = (column, next);
+            }
+        }
+    }
+
+    // grass over the soil, with a tuft sticking up here and there
+    let (grass, soil) = (paint(lit(scenery.grass)), paint(lit(scenery.soil)));
+    let surface = row(ground);
+    d.fill_rect(far_left, surface, far_right - far_left, GRASS * ph, grass);
+    let below = surface + GRASS * ph;
+    d.fill_rect(
+        far_left,
+        below,
+        far_right - far_left,
+        far_bottom - below,
+        soil,
+    );
+    let furrow = paint(lighten(lit(scenery.soil), -0.12));
+    for y in (below + 2 * ph..below + height * ph).step_by(3 * ph as usize) {
+        d.fill_rect(far_left, y, far_right - far_left, ph, furrow);
+    }
+    for column in far_left / pw..far_right / pw {
+        if (column.wrapping_mul(7) ^ column >> 2).rem_euclid(5) == 0 {
+            d.fill_rect(column * pw, surface - ph, pw, ph, grass);
+        }
+    }
+}
+
+/// Fills the pixels whose centers lie within `radius` of `center`, both in world units,
+/// on pixels `pw` × `ph` large
+fn fill_disc(
+    d: &mut impl Surface,
+    center: Vector2,
+    radius: f32,
+    color: Color,
+    (pw, ph): (i32, i32),
+    row: &impl Fn(f32) -> i32,
+) {
+    let (pw, ph) = (pw as f32, ph as f32);
+    let (from, to) = (
+        ((center.y - radius) / ph).floor() as i32,
+        ((center.y + radius) / ph).ceil() as i32,
+    );
+    for y in from..=to {
+        let world_y = (y as f32 + 0.5) * ph;
+        let dy = world_y - center.y;
+        if dy.abs() > radius {
+            continue;
+        }
+        let half = (radius * radius - dy * dy).sqrt();
+        let (left, right) = (
+            ((center.x - half) / pw).round() as i32,
+            ((center.x + half) / pw).round() as i32,
+        );
+        if right > left {
+            let width = ((right - left) as f32 * pw) as i32;
+            d.fill_rect(
+                (left as f32 * pw) as i32,
+                row(world_y),
+                width,
+                ph as i32,
+                color,
+            );
+        }
+    }
+}
+
+/// The color of the ramp nearest in brightness, the color itself without a ramp
+pub(crate) fn on_ramp(color: Color, ramp: &[Color]) -> Color {
+    let brightness = render::luma(color);
+    ramp.iter()
+        .copied()
+        .min_by(|a, b| {
+            let off = |ramp: &Color| (render::luma(*ramp) - brightness).abs();
+            off(a).total_cmp(&off(b))
+        })
+        .unwrap_or(color)
+}
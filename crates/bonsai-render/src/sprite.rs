@@ -0,0 +1,238 @@
+//! Leaf cluster sprites, loaded from PNGs or drawn in here, see `bonsai_engine::sprites`
+//!
+//! A sprite is a color image and a normal map of the same size. The normal map is stored like
+//! the usual tangent space maps, red for right and green for up, and where there is none the
+//! normals are made up from the silhouette, domed up from its edges. The sprites are drawn
+//! tinted with the color of their leaves, so gray ones take on the palette and the seasons.
+
+use std::{fs, io, path::Path};
+
+use crate::*;
+use bonsai_engine::sprites::LeafSprites;
+use render::Sprite;
+
+/// The clusters that come with the renderer, rows from the top: `#` is a lit leaf,
+/// `+` one a little darker and `-` the gap between leaves
+const BUILTIN: &[&[&str]] = &[
+    &[
+        "...##....",
+        "..####.#.",
+        ".##+####.",
+        "####-##+#",
+        ".#+##-###",
+        "###-##+#.",
+        ".##+###..",
+        "..#.##...",
+    ],
+    &[
+        "....#....",
+        "..#####..",
+        ".##-+###.",
+        "##+###-##",
+        "#-##+####",
+        ".###-##+.",
+        "..##+##..",
+        "...#.#...",
+    ],
+    &[
+        ".##...##.",
+        "####.####",
+        "#+##-##+#",
+        ".#-###-#.",
+        "..##+##..",
+        ".#+###+#.",
+        "..##.##..",
+    ],
+];
+
+/// Shades of the leaves of the built in clusters, tinted with the leaf color when drawn
+fn shade(c: char) -> Option<Color> {
+    match c {
+        '#' => Some(Color::new(255, 255, 255, 255)),
+        '+' => Some(Color::new(220, 220, 220, 255)),
+        '-' => Some(Color::new(170, 170, 170, 255)),
+        _ => None,
+    }
+}
+
+/// The sprites `source` names
+pub fn load(source: &LeafSprites) -> image::ImageResult<Vec<Sprite>> {
+    match source {
+        LeafSprites::Builtin => Ok(builtin()),
+        LeafSprites::Dir(dir) => load_dir(dir),
+    }
+}
+
+/// The clusters that come with the renderer
+pub fn builtin() -> Vec<Sprite> {
+    BUILTIN
+        .iter()
+        .map(|rows| {
+            let (width, height) = (rows[0].len(), rows.len());
+            let mut colors = vec![None; width * height];
+            for (row, line) in rows.iter().enumerate() {
+                for (x, c) in line.chars().enumerate() {
+                    // the rows are written from the top, the canvas counts up
+                    colors[x + (height - 1 - row) * width] = shade(c);
+                }
+            }
+            from_colors(width, height, &colors, None)
+        })
+        .collect()
+}
+
+/// Every `*.png` in the directory in the order of their names, with the `*.normal.png` of the
+/// same name as their normal map, if there is one
+fn load_dir(dir: &Path) -> image::ImageResult<Vec<Sprite>> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    paths.retain(|path| {
+        let name = path.file_name().map(|name| name.to_string_lossy());
+        name.is_some_and(|name| name.ends_with(".png") && !name.ends_with(".normal.png"))
+    });
+    paths.sort();
+    if paths.is_empty() {
+        let message = format!("no sprites in {}", dir.display());
+        return Err(io::Error::new(io::ErrorKind::NotFound, message).into());
+    }
+    paths
+        .iter()
+        .map(|path| {
+            let color = image::open(path)?.to_rgba8();
+            let normals = path.with_extension("normal.png");
+            let normals = if normals.exists() {
+                Some(image::open(normals)?.to_rgba8())
+            } else {
+                None
+            };
+            Ok(from_images(&color, normals.as_ref()))
+        })
+        .collect()
+}
+
+/// A sprite from its color image and normal map, normals made up from the silhouette without one.
+/// Pixels less than half opaque are left out
+pub fn from_images(color: &image::RgbaImage, normals: Option<&image::RgbaImage>) -> Sprite {
+    let (width, height) = (color.width() as usize, color.height() as usize);
+    let mut colors = vec![None; width * height];
+    for (x, row, pixel) in color.enumerate_pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a >= 128 {
+            colors[x as usize + (height - 1 - row as usize) * width] =
+                Some(Color::new(r, g, b, 255));
+        }
+    }
+    let normals = normals
+        .filter(|normals| normals.dimensions() == color.dimensions())
+        .map(|normals| {
+            let mut flipped = vec![Vector2::zero(); width * height];
+            for (x, row, pixel) in normals.enumerate_pixels() {
+                let [r, g, ..] = pixel.0;
+                let channel = |c: u8| c as f32 / 127.5 - 1.0;
+                flipped[x as usize + (height - 1 - row as usize) * width] =
+                    Vector2::new(channel(r), channel(g));
+            }
+            flipped
+        });
+    from_colors(width, height, &colors, normals)
+}
+
+/// A sprite from the colors of its pixels, `None` where it is transparent, `y` counting up
+fn from_colors(
+    width: usize,
+    height: usize,
+    colors: &[Option<Color>],
+    normals: Option<Vec<Vector2>>,
+) -> Sprite {
+    let normals = normals.unwrap_or_else(|| domed(width, height, colors));
+    let pixels = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+    Sprite::new(
+        width,
+        height,
+        pixels.filter_map(|(x, y)| {
+            let idx = x + y * width;
+            colors[idx].map(|color| (x, y, color, normals[idx]))
+        }),
+    )
+}
+
+/// Normals of a surface domed up from the edges of the silhouette, like a cushion,
+/// rising the steeper the closer to the edge
+fn domed(width: usize, height: usize, colors: &[Option<Color>]) -> Vec<Vector2> {
+    let opaque = |x: isize, y: isize| {
+        (0..width as isize).contains(&x)
+            && (0..height as isize).contains(&y)
+            && colors[x as usize + y as usize * width].is_some()
+    };
+    // distance of every opaque pixel to the closest transparent one or the border
+    let reach = width.max(height) as isize;
+    let mut distance = vec![0.0f32; width * height];
+    for y in 0..height as isize {
+        for x in 0..width as isize {
+            if !opaque(x, y) {
+                continue;
+            }
+            let mut closest = reach as f32;
+            for dy in -reach..=reach {
+                for dx in -reach..=reach {
+                    if !opaque(x + dx, y + dy) {
+                        closest = closest.min(((dx * dx + dy * dy) as f32).sqrt());
+                    }
+                }
+            }
+            distance[x as usize + y as usize * width] = closest;
+        }
+    }
+    let top = distance.iter().copied().fold(1.0, f32::max);
+    // a quarter circle over the distance, flat on top and steep at the edge
+    let lift = |x: isize, y: isize| {
+        if !opaque(x, y) {
+            return 0.0;
+        }
+        let t = 1.0 - distance[x as usize + y as usize * width] / top;
+        (1.0 - t * t).max(0.0).sqrt()
+    };
+    let mut normals = vec![Vector2::zero(); width * height];
+    for y in 0..height as isize {
+        for x in 0..width as isize {
+            let slope = Vector2::new(
+                lift(x - 1, y) - lift(x + 1, y),
+                lift(x, y - 1) - lift(x, y + 1),
+            );
+            let normal = slope * 0.75;
+            // never quite on edge, or nothing could be drawn in front of it
+            let length = normal.length();
+            normals[x as usize + y as usize * width] = if length > 0.9 {
+                normal * (0.9 / length)
+            } else {
+                normal
+            };
+        }
+    }
+    normals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn silhouettes_dome_up_from_their_edges() {
+        let disc = (0..81)
+            .map(|idx| {
+                let (dx, dy) = ((idx % 9) as f32 - 4.0, (idx / 9) as f32 - 4.0);
+                (dx * dx + dy * dy <= 16.0).then_some(Color::WHITE)
+            })
+            .collect::<Vec<_>>();
+        let normals = domed(9, 9, &disc);
+        let at = |x: usize, y: usize| normals[x + y * 9];
+        assert!(at(0, 4).x < 0.0 && at(8, 4).x > 0.0);
+        assert!(at(4, 0).y < 0.0 && at(4, 8).y > 0.0);
+        assert_eq!(at(4, 4), Vector2::zero());
+        assert!(normals.iter().all(|normal| normal.length() <= 0.9 + 1e-6));
+        for (sprite, rows) in builtin().iter().zip(BUILTIN) {
+            assert!(rows.iter().all(|row| row.len() == sprite.width()));
+        }
+    }
+}
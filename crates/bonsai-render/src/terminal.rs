@@ -0,0 +1,122 @@
+//! Drawing a `Framebuffer` into a terminal, for growing trees over SSH like cbonsai
+//!
+//! Every character cell shows two pixels stacked on top of each other: the upper half block
+//! `▀` in the color of the top pixel on a background in the color of the bottom one.
+//! Transparent pixels keep the terminal's own background.
+
+use std::io::{self, Write};
+
+use crate::*;
+use render::Framebuffer;
+
+/// Pixels with less alpha than this are left to the terminal's background
+const OPAQUE: u8 = 128;
+
+/// How many colors the terminal can show
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorMode {
+    /// 24 bit colors, as most terminals today
+    TrueColor,
+    /// the 6×6×6 color cube and gray ramp of xterm
+    Ansi256,
+}
+
+impl ColorMode {
+    /// True color if the terminal announces it in `COLORTERM`, else the 256 colors every terminal has
+    pub fn detect() -> Self {
+        match std::env::var("COLORTERM").as_deref() {
+            Ok("truecolor" | "24bit") => ColorMode::TrueColor,
+            _ => ColorMode::Ansi256,
+        }
+    }
+
+    /// Escape sequence selecting the color, as foreground or background
+    fn escape(self, color: Color, background: bool) -> String {
+        let layer = if background { 48 } else { 38 };
+        match self {
+            ColorMode::TrueColor => {
+                format!("\x1b[{};2;{};{};{}m", layer, color.r, color.g, color.b)
+            }
+            ColorMode::Ansi256 => format!("\x1b[{};5;{}m", layer, ansi256(color)),
+        }
+    }
+}
+
+/// The closest of the xterm colors 16 to 255
+fn ansi256(color: Color) -> u8 {
+    // levels of the color cube
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let level = |c: u8| {
+        (0..6)
+            .min_by_key(|&idx| (LEVELS[idx] as i32 - c as i32).abs())
+            .unwrap()
+    };
+    let (r, g, b) = (level(color.r), level(color.g), level(color.b));
+    let cube = (LEVELS[r], LEVELS[g], LEVELS[b]);
+    // the gray ramp goes from 8 to 238 in steps of 10
+    let average = (color.r as u32 + color.g as u32 + color.b as u32) / 3;
+    let step = ((average.saturating_sub(3)) / 10).min(23) as u8;
+    let gray = 8 + step * 10;
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(color.r, r2) + d(color.g, g2) + d(color.b, b2)
+    };
+    if distance((gray, gray, gray)) < distance(cube) {
+        232 + step
+    } else {
+        16 + 36 * r as u8 + 6 * g as u8 + b as u8
+    }
+}
+
+/// Columns and rows of the terminal on stdout, `COLUMNS` and `LINES` or 80×24 where it can't tell
+pub fn size() -> (usize, usize) {
+    #[cfg(unix)]
+    {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        let ok = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+        if ok && size.ws_col > 0 && size.ws_row > 0 {
+            return (size.ws_col as usize, size.ws_row as usize);
+        }
+    }
+    let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse().ok());
+    (var("COLUMNS").unwrap_or(80), var("LINES").unwrap_or(24))
+}
+
+/// Draws the frame from the top left corner of the terminal, two pixel rows per line
+pub fn draw(frame: &Framebuffer, mode: ColorMode, out: &mut impl Write) -> io::Result<()> {
+    let mut text = String::from("\x1b[H");
+    let visible = |color: Color| (color.a >= OPAQUE).then_some(color);
+    for row in (0..frame.height()).step_by(2) {
+        // colors set last, so runs of the same color don't repeat their escapes
+        let (mut fg, mut bg) = (None, None);
+        for x in 0..frame.width() {
+            let top = visible(frame.get(x, row));
+            let bottom = (row + 1 < frame.height())
+                .then(|| visible(frame.get(x, row + 1)))
+                .flatten();
+            // the glyph takes the foreground, so a lone bottom pixel uses the lower half block
+            let (glyph, upper, lower) = match (top, bottom) {
+                (Some(top), bottom) => ('▀', Some(top), bottom),
+                (None, Some(bottom)) => ('▄', Some(bottom), None),
+                (None, None) => (' ', fg, None),
+            };
+            if upper != fg {
+                if let Some(color) = upper {
+                    text.push_str(&mode.escape(color, false));
+                }
+                fg = upper;
+            }
+            if lower != bg {
+                match lower {
+                    Some(color) => text.push_str(&mode.escape(color, true)),
+                    None => text.push_str("\x1b[49m"),
+                }
+                bg = lower;
+            }
+            text.push(glyph);
+        }
+        text.push_str("\x1b[0m\x1b[K\r\n");
+    }
+    out.write_all(text.as_bytes())?;
+    out.flush()
+}
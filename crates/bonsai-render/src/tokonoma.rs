@@ -0,0 +1,291 @@
+//! Drawing the display alcove: the stand gets painted with the tree,
+//! the alcove itself is drawn behind everything
+
+use crate::*;
+use bonsai_engine::tokonoma::{Tokonoma, FLOOR_BACK, FLOOR_FRONT, FRONT_BEAM, POST};
+use kusamono::mix;
+use pot::lighten;
+use render::{Canvas, Surface};
+
+/// Depth of the stand, behind the pot standing on it
+const DEPTH: f32 = -3.0;
+
+/// Draws the stand below the pot, onto a canvas with pixels `pixel_width` × `pixel_height` large
+pub fn draw_stand(
+    tokonoma: &Tokonoma,
+    canvas: &mut Canvas,
+    config: &Config,
+    pixel_width: f32,
+    pixel_height: f32,
+) {
+    let Some(pot) = &config.pot else {
+        return;
+    };
+    let tilt = pot.tilt.clamp(0.0, 90.0).to_radians();
+    let (half, depth) = Tokonoma::stand_size(pot);
+    let top = config.origin.y - pot.reach_below();
+    // the top seen from above, then the front of the slab and the legs below it
+    let top_face = depth * tilt.sin() / 2.0;
+    let slab = 6.0;
+    let foot = top - tokonoma.stand_height - top_face;
+    let leg = half * 0.22;
+    let up = Vector2::new(0.0, tilt.cos());
+    let front = Vector2::new(0.0, -tilt.sin() * 0.3);
+
+    let from_x = ((config.origin.x - half) / pixel_width).floor().max(0.0) as usize;
+    let to_x = ((config.origin.x + half) / pixel_width).ceil().max(0.0) as usize;
+    let from_y = (foot / pixel_height).floor().max(0.0) as usize;
+    let to_y = ((top + top_face) / pixel_height).ceil().max(0.0) as usize;
+    for x in from_x..=to_x.min(canvas.width() as usize - 1) {
+        for y in from_y..=to_y.min(canvas.height() as usize - 1) {
+            let (wx, wy) = (x as f32 * pixel_width, y as f32 * pixel_height);
+            let across = (wx - config.origin.x).abs();
+            if across > half {
+                continue;
+            }
+            let grain = wood_grain(tokonoma.wood, y);
+            if wy >= top - top_face {
+                canvas.draw_surface(x, y, lighten(grain, 0.15), up, DEPTH);
+            } else if wy >= top - top_face - slab {
+                canvas.draw_surface(x, y, grain, front, DEPTH);
+            } else if across > half - leg || wy >= top - top_face - slab - 3.0 {
+                // legs at both ends, joined by a narrow apron under the slab
+                canvas.draw_surface(x, y, lighten(grain, -0.2), front, DEPTH);
+            }
+        }
+    }
+}
+
+/// Draws the alcove behind the tree, filling everything around the drawing
+/// of a canvas `width` × `height` pixels large
+pub fn render_backdrop(
+    tokonoma: &Tokonoma,
+    d: &mut impl Surface,
+    tree: &Tree,
+    width: i32,
+    height: i32,
+) {
+    let config = &tree.config;
+    let (pw, ph) = (config.pixel_size as i32, config.pixel_height() as i32);
+    // drawing position of a world height, like the canvases place their rows
+    let row = |world_y: f32| (height - (world_y / ph as f32).floor() as i32 + 1) * ph;
+    let ground = floor(config);
+    // wide enough for the canvas, whose padding on the right catches the shadows there
+    let padding = (width * pw - config.width as i32).max(0);
+    let (left, right) = (-padding - POST as i32, width * pw + POST as i32);
+    // far enough out to fill whatever the tree's drawing is shown on
+    let (far_left, far_right) = (-width * pw * 4, width * pw * 5);
+    let (far_top, far_bottom) = (-height * ph * 4, height * ph * 5);
+
+    // plaster outside the alcove, then the back wall inside it, darker under the beam above
+    let room = lighten(tokonoma.wall, 0.4);
+    d.fill_rect(
+        far_left,
+        far_top,
+        far_right - far_left,
+        far_bottom - far_top,
+        room,
+    );
+    let (wall_top, wall_bottom) = (config.height, ground + FLOOR_BACK);
+    const BANDS: i32 = 8;
+    for band in 0..BANDS {
+        let t = band as f32 / BANDS as f32;
+        let from = wall_bottom + (wall_top - wall_bottom) * t;
+        let to = wall_bottom + (wall_top - wall_bottom) * (t + 1.0 / BANDS as f32);
+        let color = lighten(tokonoma.wall, -0.25 * t);
+        d.fill_rect(left, row(to), right - left, row(from) - row(to), color);
+    }
+
+    // the floor of the alcove with its dark front edge, tatami in front of it
+    for y in (ground - FLOOR_FRONT) as i32 / ph..(wall_bottom as i32 / ph) {
+        let color = lighten(wood_grain(tokonoma.wood, y as usize), 0.25);
+        d.fill_rect(left, (height - y + 1) * ph, right - left, ph, color);
+    }
+    let beam = Color::from_hex("2a2220").unwrap();
+    let edge = ground - FLOOR_FRONT;
+    d.fill_rect(
+        left,
+        row(edge),
+        right - left,
+        row(edge - FRONT_BEAM) - row(edge),
+        beam,
+    );
+    let tatami = Color::from_hex("b7b27e").unwrap();
+    let below = row(edge - FRONT_BEAM);
+    d.fill_rect(
+        far_left,
+        below,
+        far_right - far_left,
+        far_bottom - below,
+        tatami,
+    );
+    for y in (0..height).step_by(2) {
+        let weave = lighten(tatami, -0.08);
+        d.fill_rect(far_left, below + y * ph, far_right - far_left, ph, weave);
+    }
+
+    // posts on both sides and the beam above
+    let (lintel, post_bottom) = (row(wall_top + POST), row(edge - FRONT_BEAM));
+    for x in [left, right - POST as i32] {
+        d.fill_rect(x, lintel, POST as i32, post_bottom - lintel, tokonoma.wood);
+        d.fill_rect(
+            x,
+            lintel,
+            pw,
+            post_bottom - lintel,
+            lighten(tokonoma.wood, 0.2),
+        );
+    }
+    d.fill_rect(
+        left,
+        lintel,
+        right - left,
+        row(wall_top) - lintel,
+        tokonoma.wood,
+    );
+
+    // the sun throws everything's shadow to the other side
+    let sun = Vector2::new(config.sun_angle.cos(), config.sun_angle.sin());
+    let run = (-sun.x / sun.y.max(0.1)).clamp(-0.6, 0.6);
+
+    if let Some(pot) = &config.pot {
+        // the stand's shadow on the floor behind it
+        let (half, _) = Tokonoma::stand_size(pot);
+        let x = (config.origin.x - half + tokonoma.stand_height * run) as i32;
+        let back = row(ground + FLOOR_BACK * 0.5);
+        let color = Color::BLACK.fade(0.2);
+        d.fill_rect(x, back, (half * 2.0) as i32, row(ground) - back, color);
+    }
+
+    if tokonoma.scroll {
+        render_scroll(d, tree, run, row);
+    }
+}
+
+/// The hanging scroll, on the side of the tree away from the accent plant
+fn render_scroll(d: &mut impl Surface, tree: &Tree, run: f32, row: impl Fn(f32) -> i32) {
+    let config = &tree.config;
+    let (pw, ph) = (config.pixel_size as f32, config.pixel_height() as f32);
+    let side = match &config.kusamono {
+        Some(kusamono) if kusamono.foot(tree).x < config.origin.x => 1.0,
+        _ => -1.0,
+    };
+    let ground = floor(config);
+    // in whole pixels, so the mounting keeps its proportions
+    let columns = (config.width * 0.24 / pw).round().max(7.0) as i32;
+    let center = config.origin.x + side * config.width * 0.3;
+    let left = ((center / pw) as i32 - columns / 2).max(1);
+    let (top, bottom) = (
+        (config.height * 0.92 / ph) as i32,
+        ((ground + FLOOR_BACK + 40.0) / ph) as i32,
+    );
+    if top - bottom < 12 {
+        return;
+    }
+    let x = |column: i32| column * pw as i32;
+    let y = |pixel_row: i32| row(pixel_row as f32 * ph);
+    let mut pixel = |column, pixel_row, color| {
+        d.fill_rect(x(column), y(pixel_row), pw as i32, ph as i32, color)
+    };
+
+    let mounting = Color::from_hex("5d6b5a").unwrap();
+    let strip = Color::from_hex("c8a860").unwrap();
+    let paper = Color::from_hex("efe6d0").unwrap();
+    let rod = Color::from_hex("2a2220").unwrap();
+    let shift = ((run * 3.0).round() as i32).signum();
+
+    // shadow on the wall, the cord up to the beam and the mounting
+    for pixel_row in bottom - 1..=top {
+        for column in left + shift..left + columns + shift {
+            pixel(column, pixel_row, Color::BLACK.fade(0.15));
+        }
+    }
+    let hook = (config.height / ph) as i32;
+    for pixel_row in top + 1..=hook {
+        let offset = (pixel_row - top) * columns / 2 / (hook - top + 1).max(1);
+        pixel(left + offset, pixel_row, rod);
+        pixel(left + columns - 1 - offset, pixel_row, rod);
+    }
+    for pixel_row in bottom..=top {
+        for column in left..left + columns {
+            pixel(column, pixel_row, mounting);
+        }
+    }
+    // the painting, with thin gold strips above and below it
+    let (paper_left, paper_right) = (left + 1, left + columns - 1);
+    let (paper_top, paper_bottom) = (top - 4, bottom + 3);
+    for column in paper_left..paper_right {
+        pixel(column, paper_top + 1, strip);
+        pixel(column, paper_bottom - 1, strip);
+    }
+    let painting = InkPainting::new(config.seed);
+    let aspect = (paper_top - paper_bottom) as f32 / (paper_right - paper_left) as f32;
+    for column in paper_left..paper_right {
+        for pixel_row in paper_bottom..=paper_top {
+            let u = (column - paper_left) as f32 / (paper_right - paper_left) as f32;
+            let v = (pixel_row - paper_bottom) as f32 / (paper_top - paper_bottom) as f32;
+            pixel(column, pixel_row, painting.at(u, v, aspect, paper));
+        }
+    }
+    // rods at the top and bottom, the bottom one with knobs sticking out
+    for column in left..left + columns {
+        pixel(column, top + 1, rod);
+    }
+    for column in left - 1..=left + columns {
+        pixel(column, bottom - 1, rod);
+    }
+}
+
+/// Ink washed mountains, the further ones paler, on the paper of a scroll
+struct InkPainting {
+    noise: fuss::Simplex,
+    /// how many ridges are stacked, each nearer and darker
+    ridges: usize,
+}
+
+impl InkPainting {
+    fn new(seed: u64) -> Self {
+        Self {
+            noise: fuss::Simplex::from_seed(vec![seed as usize, 0x746f_6b6f]),
+            ridges: 2 + (seed % 2) as usize,
+        }
+    }
+
+    /// Color at `u` across and `v` up the painting, both from 0 to 1,
+    /// on a painting `aspect` times as tall as wide
+    fn at(&self, u: f32, v: f32, aspect: f32, paper: Color) -> Color {
+        let ink = Color::from_hex("1e1e22").unwrap();
+        // the nearest ridge is the lowest and covers the ones behind it
+        for ridge in 0..self.ridges {
+            let offset = ridge as f32 * 10.0;
+            let height = 0.2
+                + 0.18 * ridge as f32
+                + 0.22 * self.noise.sum_octave_2d(3, u * 20.0, offset, 0.5, 0.04);
+            if v < height {
+                // the bleed of the ink leaves a ragged edge, mist fades the foot of the mountain
+                let bleed = self.noise.noise_2d(u * 6.0, v * 6.0 * aspect + offset) * 0.03;
+                let mist = ((height - v) * 4.0).min(1.0);
+                if height - v + bleed < 0.0 {
+                    continue;
+                }
+                let tone = 0.25 + 0.5 * ridge as f32 / (self.ridges - 1) as f32;
+                return mix(ink, paper, tone + (1.0 - tone) * (1.0 - mist) * 0.8);
+            }
+        }
+        // the sun, or the moon, in a paler ink
+        let (du, dv) = (u - 0.7, (v - 0.8) * aspect);
+        if du * du + dv * dv < 0.015 {
+            return mix(Color::from_hex("b0302a").unwrap(), paper, 0.4);
+        }
+        paper
+    }
+}
+
+/// Wood with its grain running along the rows
+fn wood_grain(wood: Color, row: usize) -> Color {
+    match (row * 7 + row / 3) % 5 {
+        0 => lighten(wood, 0.1),
+        3 => lighten(wood, -0.1),
+        _ => wood,
+    }
+}
@@ -0,0 +1,151 @@
+//! The raylib side of drawing: canvases onto the window, conversions of the engine's types
+//! and a quick debug view of the growth
+
+use raylib::prelude::{self as rl, RaylibDraw, RaylibHandle, RaylibThread, Texture2D};
+
+use crate::*;
+use bonsai_engine::composition::Camera;
+use render::{Framebuffer, Surface};
+
+/// Converts the engine's plain types into raylib's
+pub trait ToRaylib {
+    type Raylib;
+    fn to_raylib(self) -> Self::Raylib;
+}
+
+impl ToRaylib for Color {
+    type Raylib = rl::Color;
+    fn to_raylib(self) -> rl::Color {
+        rl::Color::new(self.r, self.g, self.b, self.a)
+    }
+}
+
+impl ToRaylib for Vector2 {
+    type Raylib = rl::Vector2;
+    fn to_raylib(self) -> rl::Vector2 {
+        rl::Vector2::new(self.x, self.y)
+    }
+}
+
+impl ToRaylib for Camera {
+    type Raylib = rl::Camera2D;
+    fn to_raylib(self) -> rl::Camera2D {
+        rl::Camera2D {
+            offset: self.offset.to_raylib(),
+            target: rl::Vector2::zero(),
+            rotation: 0.0,
+            zoom: self.zoom,
+        }
+    }
+}
+
+impl<T: RaylibDraw> Surface for T {
+    fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: Color) {
+        self.draw_rectangle(x, y, width, height, color.to_raylib());
+    }
+}
+
+/// Uploads the frame for drawing it in the window
+pub fn texture(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    frame: &Framebuffer,
+) -> Result<Texture2D, String> {
+    let mut image =
+        rl::Image::gen_image_color(frame.width() as _, frame.height() as _, rl::Color::BLANK);
+    for y in 0..frame.height() {
+        for x in 0..frame.width() {
+            image.draw_pixel(x as _, y as _, frame.get(x, y).to_raylib());
+        }
+    }
+    rl.load_texture_from_image(thread, &image)
+}
+
+/// How `draw_tree` draws the tree
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum DrawMode {
+    /// attraction points and the node graph, dead nodes in red,
+    /// or in colorblind-safe orange against blue with `high_contrast`
+    Debug,
+    /// flat colored blobs, without any shading
+    Pretty,
+}
+
+/// Draws the tree straight to the window, without the canvases of `PrettyRender`,
+/// its nodes moved by `sway` from `Wind::sway`, which is empty for a still tree
+pub fn draw_tree(tree: &Tree, d: &mut impl RaylibDraw, mode: DrawMode, sway: &[Vector2]) {
+    let map_pos = |pos: &Vector2| rl::Vector2::new(pos.x, tree.config.height - pos.y);
+    let sway_of = |idx: usize| sway.get(idx).copied().unwrap_or_default();
+    // spline points bend from the sway of the segment's parent to the sway of its node
+    let swayed = |node: &Node, node_idx: usize, pos: Vector2, t: f32| {
+        let parent = node.parent.map_or(Vector2::zero(), sway_of);
+        pos + parent.lerp(sway_of(node_idx), t)
+    };
+    let continuations = tree.continuations();
+
+    // a grid in world pixels whose top row lands at the top of the world
+    let (width, height) = (
+        tree.config.width as usize / tree.config.pixel_size,
+        tree.config.height as usize / tree.config.pixel_height(),
+    );
+    let top = tree.config.height as i32 - (height * tree.config.pixel_height()) as i32;
+    render::GroundShadow::from_tree(tree, width, height).render_to(d, top);
+
+    match mode {
+        DrawMode::Debug => {
+            let (alive, dead) = if tree.config.high_contrast {
+                (
+                    Color::new(0, 0x72, 0xb2, 255),
+                    Color::new(0xe6, 0x9f, 0, 255),
+                )
+            } else {
+                (Color::BLUE, Color::RED)
+            };
+            for point in tree.points() {
+                d.draw_circle_v(map_pos(&point.vec()), 0.99, rl::Color::BLACK);
+            }
+            for (node_idx, node) in tree.nodes().enumerate() {
+                let color = if node.alive { alive } else { dead }.to_raylib();
+                let pos = map_pos(&(node.pos.vec() + sway_of(node_idx)));
+                if let Some(spline) = tree.segment_spline(node_idx, &continuations) {
+                    let mut prev = map_pos(&swayed(&node, node_idx, spline.at(0.0), 0.0));
+                    for i in 1..=10 {
+                        let t = i as f32 / 10.0;
+                        let next = map_pos(&swayed(&node, node_idx, spline.at(t), t));
+                        d.draw_line_v(prev, next, color);
+                        prev = next;
+                    }
+                }
+                d.draw_circle_v(pos, tree.radius_of(&node), color);
+            }
+        }
+        DrawMode::Pretty => {
+            for (node_idx, node) in tree.nodes().enumerate().filter(|(_, n)| n.alive) {
+                let mut leaf = false;
+                let radius = tree.radius_of(&node);
+
+                let color = if radius < tree.config.leaf_max_width {
+                    leaf = true;
+                    tree.config.colors.leaf
+                } else if radius < tree.config.sprout_max_width {
+                    tree.config.colors.new_branch
+                } else {
+                    tree.config.colors.old_branch
+                };
+
+                let pos = map_pos(&(node.pos.vec() + sway_of(node_idx)));
+                if let Some(spline) = tree.segment_spline(node_idx, &continuations) {
+                    for i in 0..10 {
+                        let f = i as f32 / 10.0;
+                        let at = swayed(&node, node_idx, spline.at(f), f);
+                        d.draw_circle_v(map_pos(&at), radius, color.to_raylib());
+                    }
+                }
+                d.draw_circle_v(pos, radius, color.to_raylib());
+                if leaf {
+                    d.draw_circle_v(pos, tree.config.leaf_size, color.fade(0.1).to_raylib());
+                }
+            }
+        }
+    }
+}
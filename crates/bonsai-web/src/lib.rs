@@ -0,0 +1,180 @@
+//! The bonsai on a web page: the growth and the renderer built for wasm32, without raylib
+//!
+//! ```text
+//! cargo build -p bonsai-web --release --target wasm32-unknown-unknown
+//! ```
+//!
+//! There are no bindings to the browser, the page drives the module through the functions
+//! exported here, see `index.html`. `bonsai_start` plants a tree, `bonsai_frame` grows it for the
+//! time since the last animation frame and paints it into a `Framebuffer`, whose RGBA bytes the
+//! page puts onto its canvas. Keys pressed on the page come in through `bonsai_key`.
+
+use std::cell::RefCell;
+
+use bonsai_engine::*;
+use bonsai_render::render::{Framebuffer, PrettyRender};
+use rand_chacha::{rand_core::SeedableRng, ChaCha12Rng};
+
+/// The tree on the page and what grows and paints it
+struct Garden {
+    pretty: PrettyRender,
+    ticker: pacing::Ticker,
+    /// draws the seed phrases of the trees, from the entropy the page started with
+    rng: ChaCha12Rng,
+    phrase: String,
+    frame: Framebuffer,
+    /// RGBA bytes of the last frame, row by row from the top, for the page to read
+    pixels: Vec<u8>,
+}
+
+impl Garden {
+    fn new(entropy: u64) -> Self {
+        let mut rng = ChaCha12Rng::seed_from_u64(entropy);
+        let phrase = seed::random_phrase(&mut rng);
+        let config = Config::default();
+        Self {
+            pretty: plant(&phrase),
+            ticker: pacing::Ticker::new(config.tick_rate),
+            rng,
+            phrase,
+            frame: Framebuffer::new(config.width as usize, config.height as usize, config.sky),
+            pixels: vec![],
+        }
+    }
+
+    /// Plants a tree from a new seed phrase in place of the one growing
+    fn regrow(&mut self) {
+        self.phrase = seed::random_phrase(&mut self.rng);
+        self.pretty = plant(&self.phrase);
+    }
+
+    fn key(&mut self, key: char) {
+        match key.to_ascii_lowercase() {
+            'r' => self.regrow(),
+            ' ' => self.ticker.toggle_pause(),
+            '.' => self.ticker.step(),
+            _ => {}
+        }
+    }
+
+    /// Grows the tree for the `seconds` passed and paints it into `pixels`
+    fn frame(&mut self, seconds: f32) {
+        let tree = self.pretty.tree_mut();
+        for _ in self.ticker.advance(seconds) {
+            for _ in 0..tree.config.growth_speed {
+                tree.step();
+            }
+        }
+        self.pretty.advance(seconds);
+        self.frame.clear(self.pretty.tree().config.sky);
+        self.pretty.render(&mut self.frame);
+        self.pixels.clear();
+        for y in 0..self.frame.height() {
+            for x in 0..self.frame.width() {
+                let color = self.frame.get(x, y);
+                self.pixels
+                    .extend_from_slice(&[color.r, color.g, color.b, color.a]);
+            }
+        }
+    }
+}
+
+/// The sapling of the tree the seed phrase grows
+fn plant(phrase: &str) -> PrettyRender {
+    let config = Config {
+        seed: seed::from_phrase(phrase),
+        ..Config::default()
+    };
+    PrettyRender::new(Tree::new_min_growth(config, 5))
+}
+
+thread_local! {
+    static GARDEN: RefCell<Option<Garden>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` on the garden, planting it first if the page didn't call `bonsai_start`
+fn with_garden<T>(f: impl FnOnce(&mut Garden) -> T) -> T {
+    GARDEN.with(|garden| f(garden.borrow_mut().get_or_insert_with(|| Garden::new(0))))
+}
+
+/// Plants the first tree, its seed phrase drawn with the `entropy` the page passes in,
+/// like `Math.random() * 2 ** 32`
+#[no_mangle]
+pub extern "C" fn bonsai_start(entropy: u32) {
+    GARDEN.with(|garden| *garden.borrow_mut() = Some(Garden::new(entropy.into())));
+}
+
+/// Grows the tree for the `seconds` since the last frame and paints it. Returns where the
+/// RGBA bytes of the frame start, they stay there until the next call
+#[no_mangle]
+pub extern "C" fn bonsai_frame(seconds: f32) -> *const u8 {
+    with_garden(|garden| {
+        garden.frame(seconds);
+        garden.pixels.as_ptr()
+    })
+}
+
+/// Width of the frames in pixels
+#[no_mangle]
+pub extern "C" fn bonsai_width() -> u32 {
+    with_garden(|garden| garden.frame.width() as u32)
+}
+
+/// Height of the frames in pixels
+#[no_mangle]
+pub extern "C" fn bonsai_height() -> u32 {
+    with_garden(|garden| garden.frame.height() as u32)
+}
+
+/// A key pressed on the page, as the code point of its character: `r` grows a tree from a new
+/// seed, space holds the growth and `.` grows it by one tick while it is held, like in the window
+#[no_mangle]
+pub extern "C" fn bonsai_key(key: u32) {
+    if let Some(key) = char::from_u32(key) {
+        with_garden(|garden| garden.key(key));
+    }
+}
+
+/// Where the UTF-8 seed phrase of the tree starts, `bonsai_phrase_len` bytes long
+#[no_mangle]
+pub extern "C" fn bonsai_phrase() -> *const u8 {
+    with_garden(|garden| garden.phrase.as_ptr())
+}
+
+#[no_mangle]
+pub extern "C" fn bonsai_phrase_len() -> u32 {
+    with_garden(|garden| garden.phrase.len() as u32)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn no_entropy(_: &mut [u8]) -> Result<(), getrandom::Error> {
+    Err(getrandom::Error::UNSUPPORTED)
+}
+
+#[cfg(target_arch = "wasm32")]
+getrandom::register_custom_getrandom!(no_entropy);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_page_gets_a_frame_and_a_new_tree_on_r() {
+        bonsai_start(7);
+        let (width, height) = (bonsai_width(), bonsai_height());
+        bonsai_frame(0.5);
+        with_garden(|garden| {
+            assert_eq!(garden.pixels.len(), (width * height * 4) as usize);
+            assert!(garden.pretty.tree().node_count() > 1);
+        });
+        let phrase = with_garden(|garden| garden.phrase.clone());
+        bonsai_key('R' as u32);
+        with_garden(|garden| {
+            assert_ne!(garden.phrase, phrase);
+            assert_eq!(
+                garden.pretty.tree().config.seed,
+                seed::from_phrase(&garden.phrase)
+            );
+        });
+    }
+}
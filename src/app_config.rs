@@ -0,0 +1,116 @@
+//! Settings of the program around the trees: the window, the screensaver, the live inputs and
+//! the hooks. They sit at the top of the same config files as the growth settings, but the
+//! trees never see them.
+
+use crate::*;
+use config_file::{AppKeys, Value};
+
+#[derive(Clone, Debug)]
+pub struct AppConfig {
+    /// seconds finished trees stay up with `--daemon` before they fade
+    pub daemon_rest: f32,
+    /// seconds the trees take to fade into the sky with `--daemon`
+    pub daemon_fade: f32,
+    /// size of the text and marks drawn over the trees
+    pub ui_scale: f32,
+    /// language of the overlays, like `de`, else the one of `LANG`
+    pub language: Option<String>,
+    /// UDP port to listen for OSC messages on
+    pub osc_port: Option<u16>,
+    /// twitch channel whose chat steers the tree
+    pub twitch_channel: Option<String>,
+    /// commands run when something notable happens
+    pub hooks: Vec<hooks::Hook>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            daemon_rest: 300.0,
+            daemon_fade: 3.0,
+            ui_scale: 1.0,
+            language: None,
+            osc_port: None,
+            twitch_channel: None,
+            hooks: vec![],
+        }
+    }
+}
+
+impl AppConfig {
+    /// Runs the hooks of the event, telling about the ones that couldn't be started
+    pub fn fire_hooks(&self, event: &hooks::Event) {
+        for failure in hooks::fire(&self.hooks, event) {
+            eprintln!("{}", failure);
+        }
+    }
+}
+
+/// Hooks have no keys, they only come from `--hook`
+impl AppKeys for AppConfig {
+    fn set(&mut self, key: &str, value: &Value) -> Option<Result<(), String>> {
+        let set = match key {
+            "daemon_rest" => value.float().map(|rest| self.daemon_rest = rest),
+            "daemon_fade" => value.float().map(|fade| self.daemon_fade = fade),
+            "ui_scale" => value.float().map(|scale| self.ui_scale = scale.max(0.5)),
+            "language" => value
+                .text()
+                .map(|language| self.language = Some(language.to_string())),
+            "osc_port" => value
+                .count()
+                .and_then(|port| u16::try_from(port).map_err(|_| "port out of range".to_string()))
+                .map(|port| self.osc_port = Some(port)),
+            "twitch_channel" => value
+                .text()
+                .map(|channel| self.twitch_channel = Some(channel.to_string())),
+            _ => return None,
+        };
+        Some(set)
+    }
+
+    fn write(&self) -> Vec<(&'static str, String)> {
+        let mut keys = vec![
+            ("daemon_rest", self.daemon_rest.to_string()),
+            ("daemon_fade", self.daemon_fade.to_string()),
+            ("ui_scale", self.ui_scale.to_string()),
+        ];
+        if let Some(language) = &self.language {
+            keys.push(("language", format!("\"{}\"", language)));
+        }
+        if let Some(port) = self.osc_port {
+            keys.push(("osc_port", port.to_string()));
+        }
+        if let Some(channel) = &self.twitch_channel {
+            keys.push(("twitch_channel", format!("\"{}\"", channel)));
+        }
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn app_keys_stay_out_of_the_config() {
+        let mut config = Config::default();
+        let mut app = AppConfig::default();
+        let text = "seed = 7\nui_scale = 2\nosc_port = 9000\nlanguage = \"de\"\n";
+        config_file::apply_with(text, &mut config, &mut app).unwrap();
+        assert_eq!((config.seed, app.ui_scale), (7, 2.0));
+        assert_eq!(app.osc_port, Some(9000));
+        assert_eq!(app.language.as_deref(), Some("de"));
+        assert!(config_file::apply_override_with("osc_port=70000", &mut config, &mut app).is_err());
+        // plain config files don't know them
+        assert!(config_file::apply("ui_scale = 2", &mut config).is_err());
+
+        let mut again = AppConfig::default();
+        let written = config_file::write_with(&config, &app);
+        config_file::apply_with(&written, &mut Config::default(), &mut again).unwrap();
+        assert_eq!(
+            (again.ui_scale, again.osc_port),
+            (app.ui_scale, app.osc_port)
+        );
+        assert_eq!(again.language, app.language);
+    }
+}
@@ -0,0 +1,109 @@
+//! Audio reactive mode: the loudness of the default input device drives growth speed and leaf color
+
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+use crate::*;
+
+/// Loudness shared between the audio callback and the render thread
+#[derive(Default)]
+struct Levels {
+    /// smoothed RMS of the last samples, stored as f32 bits
+    envelope: AtomicU32,
+    /// slowly moving average of the envelope, used for beat detection
+    average: AtomicU32,
+}
+
+impl Levels {
+    fn feed(&self, rms: f32) {
+        let load = |a: &AtomicU32| f32::from_bits(a.load(Ordering::Relaxed));
+        // fast attack, slow release
+        let envelope = load(&self.envelope);
+        let envelope = if rms > envelope {
+            rms
+        } else {
+            envelope * 0.9 + rms * 0.1
+        };
+        let average = load(&self.average) * 0.99 + envelope * 0.01;
+        self.envelope.store(envelope.to_bits(), Ordering::Relaxed);
+        self.average.store(average.to_bits(), Ordering::Relaxed);
+    }
+}
+
+pub struct AudioListener {
+    _stream: cpal::Stream,
+    levels: Arc<Levels>,
+}
+
+impl AudioListener {
+    /// Starts listening on the default input device
+    pub fn start() -> Option<Self> {
+        let device = cpal::default_host().default_input_device()?;
+        let config = device.default_input_config().ok()?;
+        let levels = Arc::new(Levels::default());
+
+        let rms = |samples: &mut dyn Iterator<Item = f32>| {
+            let (sum, count) = samples.fold((0.0, 0), |(sum, count), s| (sum + s * s, count + 1));
+            (sum / count.max(1) as f32).sqrt()
+        };
+        let on_error = |err| eprintln!("audio input error: {}", err);
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::F32 => {
+                let levels = levels.clone();
+                device.build_input_stream(
+                    &config.into(),
+                    move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                        levels.feed(rms(&mut data.iter().copied()))
+                    },
+                    on_error,
+                    None,
+                )
+            }
+            cpal::SampleFormat::I16 => {
+                let levels = levels.clone();
+                device.build_input_stream(
+                    &config.into(),
+                    move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                        levels.feed(rms(&mut data.iter().map(|s| *s as f32 / i16::MAX as f32)))
+                    },
+                    on_error,
+                    None,
+                )
+            }
+            _ => return None,
+        }
+        .ok()?;
+        stream.play().ok()?;
+        Some(Self {
+            _stream: stream,
+            levels,
+        })
+    }
+
+    /// Current loudness, roughly in 0..1
+    pub fn envelope(&self) -> f32 {
+        f32::from_bits(self.levels.envelope.load(Ordering::Relaxed))
+    }
+
+    /// Whether the current loudness clearly exceeds the recent average
+    pub fn beat(&self) -> bool {
+        let average = f32::from_bits(self.levels.average.load(Ordering::Relaxed));
+        self.envelope() > 1.5 * average && self.envelope() > 0.02
+    }
+
+    /// Modulates the growth speed and leaf colors of `base` by the current loudness
+    pub fn apply(&self, base: &Config, config: &mut Config) {
+        let envelope = self.envelope().min(1.0);
+        let boost = if self.beat() { 1.0 } else { envelope };
+        config.growth_speed = base.growth_speed + (boost * 10.0) as usize;
+        for (leaf, base_leaf) in config.leaves.iter_mut().zip(base.leaves.iter()) {
+            let c = base_leaf.color;
+            let brighten = |v: u8| (v as f32 + (255.0 - v as f32) * boost * 0.5) as u8;
+            leaf.color = Color::new(brighten(c.r), brighten(c.g), brighten(c.b), c.a);
+        }
+    }
+}
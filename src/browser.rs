@@ -0,0 +1,189 @@
+//! Browsing seeds in a grid of quick previews, to pick one before growing it at full quality
+
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use crate::*;
+use bonsai_render::window::ToRaylib;
+use cancel::CancelToken;
+use gallery::{cell_pos, columns, draw_thumbnail, CELL, GAP};
+use raylib::prelude::{KeyboardKey, MouseButton, RaylibDraw, RaylibHandle, Rectangle};
+use render::PrettyRender;
+
+/// Previews grow from this fraction of the attraction points, they keep the rough shape
+/// of the full tree while growing several times faster
+const PREVIEW_POINTS: usize = 4;
+
+/// Initial simulation steps a preview needs to survive, see `Tree::new_min_growth`
+const MIN_GROWTH: usize = 5;
+
+/// Lit pixels of a preview, see `PrettyRender::pixels`
+type Thumbnail = Vec<Vec<Color>>;
+
+pub struct SeedBrowser {
+    /// seed of the first cell on the page
+    first: u64,
+    /// cells on a page, fixed when the browser opens
+    page: usize,
+    selected: usize,
+    thumbnails: Vec<Option<Thumbnail>>,
+    base: Config,
+    /// grows the previews of the page one after another
+    worker: Option<(CancelToken, Receiver<(usize, Thumbnail)>)>,
+}
+
+impl SeedBrowser {
+    /// Opens on the page starting at `first`, sized to fill a `width` × `height` window
+    pub fn open(first: u64, base: &Config, width: i32, height: i32) -> Self {
+        let rows = ((height - GAP * 4) / (CELL + GAP)).max(1) as usize;
+        let mut browser = Self {
+            first,
+            page: columns(width) * rows,
+            selected: 0,
+            thumbnails: vec![],
+            base: base.clone(),
+            worker: None,
+        };
+        browser.start_page();
+        browser
+    }
+
+    fn start_page(&mut self) {
+        self.cancel_worker();
+        self.thumbnails = vec![None; self.page];
+        let cancel = CancelToken::new();
+        let (sender, previews) = mpsc::channel();
+        let configs = (0..self.page)
+            .map(|idx| Config {
+                seed: self.first.wrapping_add(idx as u64),
+                num_points: (self.base.num_points / PREVIEW_POINTS).max(1),
+                ..self.base.clone()
+            })
+            .collect::<Vec<_>>();
+        {
+            let cancel = cancel.clone();
+            thread::spawn(move || {
+                for (idx, config) in configs.into_iter().enumerate() {
+                    let tree =
+                        Tree::new_min_growth_with(config, MIN_GROWTH, || !cancel.is_cancelled());
+                    let Some(mut tree) = tree else {
+                        return;
+                    };
+                    tree.grow_fully(&cancel);
+                    if cancel.is_cancelled() {
+                        return;
+                    }
+                    let pixels = PrettyRender::new(tree).pixels();
+                    // the browser is gone if it got closed meanwhile
+                    if sender.send((idx, pixels)).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+        self.worker = Some((cancel, previews));
+    }
+
+    fn cancel_worker(&mut self) {
+        if let Some((cancel, _)) = self.worker.take() {
+            cancel.cancel();
+        }
+    }
+
+    /// Handles paging and selection, returns the seed to grow when one gets picked
+    pub fn update(&mut self, rl: &RaylibHandle) -> Option<u64> {
+        if let Some((_, previews)) = &self.worker {
+            for (idx, pixels) in previews.try_iter() {
+                self.thumbnails[idx] = Some(pixels);
+            }
+        }
+
+        let columns = columns(rl.get_screen_width());
+        let last = self.page - 1;
+        if rl.is_key_pressed(KeyboardKey::KEY_RIGHT) {
+            self.selected = (self.selected + 1).min(last);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_LEFT) {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.selected = (self.selected + columns).min(last);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.selected = self.selected.saturating_sub(columns);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_PAGE_DOWN) {
+            self.first = self.first.wrapping_add(self.page as u64);
+            self.start_page();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_PAGE_UP) {
+            self.first = self.first.wrapping_sub(self.page as u64);
+            self.start_page();
+        }
+
+        let mut picked = rl.is_key_pressed(KeyboardKey::KEY_ENTER);
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) {
+            let mouse = rl.get_mouse_position();
+            let hit = (0..self.page).find(|&idx| {
+                let (x, y) = cell_pos(idx, columns);
+                (x..x + CELL).contains(&(mouse.x as i32))
+                    && (y..y + CELL).contains(&(mouse.y as i32))
+            });
+            if let Some(idx) = hit {
+                self.selected = idx;
+                picked = true;
+            }
+        }
+
+        picked.then(|| self.first.wrapping_add(self.selected as u64))
+    }
+
+    pub fn draw(&self, d: &mut impl RaylibDraw, hud: &hud::Hud, width: i32, height: i32) {
+        let columns = columns(width);
+        for (idx, thumbnail) in self.thumbnails.iter().enumerate() {
+            let (x, y) = cell_pos(idx, columns);
+            d.draw_rectangle(x, y, CELL, CELL, Color::WHITE.fade(0.4).to_raylib());
+            match thumbnail {
+                Some(pixels) => draw_thumbnail(d, pixels, x, y),
+                None => hud.text(
+                    d,
+                    hud.tr(i18n::Text::StatGrowing),
+                    x + GAP,
+                    y + GAP,
+                    10,
+                    Color::GRAY,
+                ),
+            }
+            let seed = self.first.wrapping_add(idx as u64);
+            hud.text(
+                d,
+                &seed.to_string(),
+                x + GAP,
+                y + CELL - GAP - hud.px(10),
+                10,
+                Color::DARKGRAY,
+            );
+            if idx == self.selected {
+                d.draw_rectangle_lines_ex(
+                    Rectangle::new(x as f32, y as f32, CELL as f32, CELL as f32),
+                    3,
+                    hud.ink(Color::DARKGRAY).to_raylib(),
+                );
+            }
+        }
+        hud.text(
+            d,
+            hud.tr(i18n::Text::BrowserHelp),
+            GAP,
+            height - GAP - hud.px(20),
+            20,
+            Color::DARKGRAY,
+        );
+    }
+}
+
+impl Drop for SeedBrowser {
+    fn drop(&mut self) {
+        self.cancel_worker();
+    }
+}
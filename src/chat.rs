@@ -0,0 +1,168 @@
+//! Twitch chat integration: chat commands read over IRC are turned into actions on the tree
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    sync::mpsc::{channel, Receiver},
+    thread,
+};
+
+use crate::{season::Season, Tree};
+
+/// Part of the tree a `!prune` command cuts away
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Region {
+    Left,
+    Right,
+    Top,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChatCommand {
+    /// `!water`: a growth spurt
+    Water,
+    /// `!prune left|right|top`: kills the branches in that part of the tree
+    Prune(Region),
+    /// `!regrow`: starts over with a new tree
+    Regrow,
+    /// `!season spring|summer|autumn|winter`: shows the tree in that season, a passing cycle
+    /// of the seasons carries on from it
+    Season(Season),
+}
+
+impl ChatCommand {
+    pub fn parse(message: &str) -> Option<Self> {
+        let mut words = message.split_whitespace();
+        match words.next()?.to_lowercase().as_str() {
+            "!water" => Some(ChatCommand::Water),
+            "!regrow" => Some(ChatCommand::Regrow),
+            "!prune" => match words.next()?.to_lowercase().as_str() {
+                "left" => Some(ChatCommand::Prune(Region::Left)),
+                "right" => Some(ChatCommand::Prune(Region::Right)),
+                "top" => Some(ChatCommand::Prune(Region::Top)),
+                _ => None,
+            },
+            "!season" => Season::parse(&words.next()?.to_lowercase()).map(ChatCommand::Season),
+            _ => None,
+        }
+    }
+
+    /// Applies the command to the tree, `Regrow` has to be handled by the caller
+    pub fn apply(&self, tree: &mut Tree) {
+        match self {
+            ChatCommand::Water => {
+                for _ in 0..30 {
+                    tree.step();
+                }
+            }
+            ChatCommand::Prune(region) => {
+                let origin = tree.config.origin;
+                let top = tree
+                    .nodes()
+                    .filter(|n| n.alive)
+                    .map(|n| n.pos.vec().y)
+                    .fold(origin.y, f32::max);
+                let cut_height = origin.y + (top - origin.y) * 2.0 / 3.0;
+                // never cut the trunk itself
+                let trunk = tree.config.grow_dist * 3.0;
+                let victims = tree
+                    .nodes()
+                    .enumerate()
+                    .filter(|(_, n)| n.alive && n.parent.is_some())
+                    .filter(|(_, n)| match region {
+                        Region::Left => n.pos.vec().x < origin.x - trunk,
+                        Region::Right => n.pos.vec().x > origin.x + trunk,
+                        Region::Top => n.pos.vec().y > cut_height,
+                    })
+                    .map(|(idx, _)| idx)
+                    .collect::<Vec<_>>();
+                for idx in victims {
+                    tree.kill_subtree(idx);
+                }
+            }
+            ChatCommand::Season(season) => season.apply(&mut tree.config),
+            ChatCommand::Regrow => {}
+        }
+    }
+}
+
+/// Reads a Twitch channel's chat anonymously and collects the commands in it
+pub struct ChatListener {
+    commands: Receiver<ChatCommand>,
+}
+
+impl ChatListener {
+    pub fn connect(channel_name: &str) -> io::Result<Self> {
+        let mut stream = TcpStream::connect("irc.chat.twitch.tv:6667")?;
+        // justinfan users are anonymous, read-only accounts
+        write!(
+            stream,
+            "NICK justinfan{}\r\nJOIN #{}\r\n",
+            rand::random::<u16>(),
+            channel_name.to_lowercase()
+        )?;
+
+        let (sender, commands) = channel();
+        let mut writer = stream.try_clone()?;
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines() {
+                let Ok(line) = line else { break };
+                if let Some(server) = line.strip_prefix("PING") {
+                    if write!(writer, "PONG{}\r\n", server).is_err() {
+                        break;
+                    }
+                    continue;
+                }
+                // :user!user@user.tmi.twitch.tv PRIVMSG #channel :message
+                let message = line
+                    .split_once(" PRIVMSG ")
+                    .and_then(|(_, rest)| rest.split_once(" :"))
+                    .map(|(_, message)| message);
+                if let Some(command) = message.and_then(ChatCommand::parse) {
+                    if sender.send(command).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        Ok(Self { commands })
+    }
+
+    /// Returns all commands received since the last poll, never blocks
+    pub fn poll(&self) -> Vec<ChatCommand> {
+        self.commands.try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands_in_any_case_and_spacing() {
+        assert_eq!(ChatCommand::parse("!water"), Some(ChatCommand::Water));
+        assert_eq!(ChatCommand::parse("  !ReGrow  "), Some(ChatCommand::Regrow));
+        assert_eq!(
+            ChatCommand::parse("!prune   TOP please"),
+            Some(ChatCommand::Prune(Region::Top))
+        );
+        assert_eq!(
+            ChatCommand::parse("!Season\tWinter"),
+            Some(ChatCommand::Season(Season::Winter))
+        );
+        assert_eq!(
+            ChatCommand::parse("!season fall"),
+            Some(ChatCommand::Season(Season::Autumn))
+        );
+        for message in [
+            "",
+            "water",
+            "!prune",
+            "!prune middle",
+            "!season monsoon",
+            "!dance",
+        ] {
+            assert_eq!(ChatCommand::parse(message), None, "{:?}", message);
+        }
+    }
+}
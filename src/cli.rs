@@ -0,0 +1,427 @@
+use std::path::PathBuf;
+
+use bonsai_engine::{
+    background::Fit, noise::NoiseKind, palette::Palette, pot, precipitation::Precipitation,
+    season::Season, seed, sprites, Color,
+};
+use bonsai_io::wallpaper::Target;
+
+use crate::hooks::Hook;
+
+/// Command line arguments
+#[derive(Debug, Default)]
+pub struct Args {
+    /// config file the flags are applied over
+    pub config: Option<PathBuf>,
+    /// `key=value` overrides of the config, applied after everything else
+    pub overrides: Vec<String>,
+    /// size of the world the tree grows in
+    pub width: Option<f32>,
+    pub height: Option<f32>,
+    /// number of attraction points
+    pub num_points: Option<usize>,
+    /// bark color followed by the blossom colors
+    pub palette: Option<Vec<Color>>,
+    /// built in color scheme, e.g. one that is colorblind-safe
+    pub preset: Option<Palette>,
+    /// palette file to color the tree with, see `palette::apply_file`
+    pub palette_file: Option<PathBuf>,
+    /// size of the text drawn over the trees
+    pub ui_scale: Option<f32>,
+    /// text and overlays in high contrast
+    pub high_contrast: bool,
+    /// language of the text in the window
+    pub language: Option<String>,
+    /// render a wallpaper for this device instead of opening a window
+    pub target: Option<Target>,
+    /// also render a dark variant of the wallpaper
+    pub variants: bool,
+    /// also write the flat colors and the light of the tree as separate images
+    pub passes: bool,
+    /// also write the branches as SVG and the skeleton as JSON
+    pub vectors: bool,
+    /// record the growth as an animated PNG with this many frames instead of a still wallpaper
+    pub timelapse: Option<usize>,
+    /// seed of a second tree, animated turning into the first and back
+    pub morph: Option<u64>,
+    /// config files, or directories of them, recorded as generations turning into one another
+    pub phylogeny: Vec<PathBuf>,
+    /// ticks every timelapse frame is held for, stop-motion style
+    pub stop_motion: Option<usize>,
+    /// where exported images are written to
+    pub out: Option<PathBuf>,
+    /// pick the season from today's date
+    pub calendar: bool,
+    /// a single season the tree is shown in
+    pub season: Option<Season>,
+    /// snow or rain falling in the window
+    pub precipitation: Option<Precipitation>,
+    /// let the seasons pass in the window
+    pub season_cycle: bool,
+    /// hour of the day the tree is lit at
+    pub time_of_day: Option<f32>,
+    /// let the hours of the day pass in the window
+    pub day_cycle: bool,
+    /// draw the moon and stars behind the dark wallpaper
+    pub night_sky: bool,
+    /// show the tree's name on a plate below it
+    pub nameplate: bool,
+    /// height of an output pixel relative to its width
+    pub pixel_aspect: Option<f32>,
+    /// noise the attraction points are scattered by
+    pub noise: Option<NoiseKind>,
+    /// grayscale image the attraction points are scattered by instead of the noise
+    pub density: Option<PathBuf>,
+    /// grayscale image the noise is multiplied with, to grow the crown into its shape
+    pub density_mask: Option<PathBuf>,
+    /// write the density field of the tree as a grayscale image and quit
+    pub export_density: Option<PathBuf>,
+    /// seed of the tree, a number or any phrase
+    pub seed: Option<u64>,
+    /// save the window starts with, growing on where it stopped
+    pub open: Option<PathBuf>,
+    /// extension of the format the tree is exported to, see `export::Registry`
+    pub format: Option<String>,
+    /// size of the thumbnail to write of a save, and the save
+    pub thumbnail: Option<(u32, PathBuf)>,
+    /// exported image to print the seed and config of
+    pub inspect: Option<PathBuf>,
+    /// selection sets of the opened save to cut off
+    pub prune_selection: Vec<String>,
+    /// selection sets of the opened save to paint in a color, `None` for the palette's
+    pub recolor_selection: Vec<(String, Option<Color>)>,
+    /// two saves or seeds to compare
+    pub diff: Option<(String, String)>,
+    /// `key=value` settings the tree is shown with next to its usual look
+    pub compare: Vec<String>,
+    /// number of wallpapers to render, each from its own seed
+    pub batch: Option<usize>,
+    /// threads rendering the batch
+    pub threads: Option<usize>,
+    /// grow quick coarse previews in the window, refined on request
+    pub preview: bool,
+    /// direction the sun shines from, in degrees counterclockwise from the right
+    pub sun_angle: Option<f32>,
+    /// height of the sun above the picture plane, in degrees
+    pub sun_elevation: Option<f32>,
+    /// share of the light coming from the sky dome instead of the sun
+    pub ambient: Option<f32>,
+    /// shades of each color the light is dithered to
+    pub shades: Option<usize>,
+    /// frames per second the window draws at most, 0 for as fast as it can
+    pub fps: Option<u32>,
+    /// wait for the screen's refresh before showing a frame
+    pub vsync: bool,
+    /// grow the trees by frames instead of by the clock, for recordings
+    pub fixed_step: bool,
+    /// draw as fast as possible and print the frame rate when the window closes
+    pub benchmark: bool,
+    /// grow the tree in the terminal instead of a window
+    pub terminal: bool,
+    /// grow, rest, fade out and grow a new tree in the window forever, like a screensaver
+    pub daemon: bool,
+    /// cover the whole screen with the window
+    pub fullscreen: bool,
+    /// leave off the title bar and border of the window
+    pub borderless: bool,
+    /// plant the tree in a pot
+    pub pot: bool,
+    /// shape of the pot, planting the tree in one
+    pub pot_shape: Option<pot::Shape>,
+    /// place an accent plant beside the tree
+    pub kusamono: bool,
+    /// let ivy climb the trunk, with its own seed if one is given
+    pub ivy: Option<Option<u64>>,
+    /// grow roots into the soil below the trunk
+    pub roots: bool,
+    /// grow the crown in three dimensions so it can be turned
+    pub volume: bool,
+    /// direction a crown grown in three dimensions is seen from, in degrees
+    pub yaw: Option<f32>,
+    /// show the tree in a display alcove
+    pub tokonoma: bool,
+    /// draw sky, hills and ground behind the tree
+    pub scenery: bool,
+    /// grow this many trees side by side instead of one
+    pub forest: Option<usize>,
+    /// image shown behind the tree
+    pub background: Option<PathBuf>,
+    /// how the background image is scaled to the output
+    pub background_fit: Option<Fit>,
+    /// average the background image down to the tree's pixels
+    pub pixelate_background: bool,
+    /// sprites the leaves are drawn with instead of spheres
+    pub leaf_sprites: Option<sprites::LeafSprites>,
+    /// commands run on events
+    pub hooks: Vec<Hook>,
+}
+
+const USAGE: &str = "usage: pixeltrees [tree.bonsai] [--config bonsai.toml] [--set key=value] [--width 500] [--height 500] [--num-points 10000] [--palette 8b6354,ffe0e0,ff5173|sakura|autumn|pine|neon|gameboy|okabe-ito|tritan|high-contrast|dusk.palette] [--ui-scale 2] [--high-contrast] [--language de] [--target phone|phone-hd|tablet|desktop|ultrawide] [--variants] [--passes] [--vectors] [--timelapse 120 [--stop-motion 3]] [--morph \"other seed\"] [--phylogeny saves/] [--out file.png] [--calendar] [--season spring|summer|autumn|winter|cycle] [--precipitation snow|rain] [--time-of-day 18.5|cycle] [--night-sky] [--nameplate] [--pixel-aspect 2.0] [--seed \"quiet maple evening\"] [--noise simplex|value|worley] [--export-density density.png] [--density density.png] [--density-mask heart.png] [--open tree.bonsai [--prune-selection apex] [--recolor-selection \"front branch=c04030\"] [--out styled.bonsai]] [--diff a.bonsai|seed b.bonsai|seed] [--thumbnail 256 tree.bonsai --out preview.png] [--inspect image.png] [--format png|svg|json|vox|schem|stl|pdf [--out tree.svg]] [--compare key=value] [--batch 100 [--threads 8]] [--preview] [--fps 144] [--vsync] [--fixed-step] [--benchmark] [--terminal] [--daemon] [--fullscreen] [--borderless] [--sun-angle 150] [--sun-elevation 45] [--ambient 0.4] [--shades 4] [--pot] [--pot-shape oval|rectangle|drum|cascade|tray] [--roots] [--volume [--yaw 30]] [--kusamono] [--ivy [--ivy-seed \"creeping vine\"]] [--tokonoma] [--scenery] [--forest 5] [--background image.png [--background-fit contain|cover] [--pixelate-background]] [--leaf-sprites builtin|sprites/] [--on growth-finished|autosave-written|wallpaper-written \"notify-send 'seed {seed}'\"]";
+
+impl Args {
+    pub fn parse() -> Result<Self, String> {
+        let mut args = Self::default();
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            let mut value = |name: &str| {
+                iter.next()
+                    .ok_or_else(|| format!("{} needs a value\n{}", name, USAGE))
+            };
+            match arg.as_str() {
+                "--target" => {
+                    let name = value("--target")?;
+                    let target = Target::parse(&name)
+                        .ok_or_else(|| format!("unknown target {}\n{}", name, USAGE))?;
+                    args.target = Some(target);
+                }
+                "--variants" => args.variants = true,
+                "--passes" => args.passes = true,
+                "--vectors" => args.vectors = true,
+                "--calendar" => args.calendar = true,
+                "--season" => {
+                    let name = value("--season")?;
+                    match (name.as_str(), Season::parse(&name)) {
+                        ("cycle", _) => args.season_cycle = true,
+                        (_, Some(season)) => args.season = Some(season),
+                        _ => return Err(format!("unknown season {}\n{}", name, USAGE)),
+                    }
+                }
+                "--precipitation" => {
+                    let name = value("--precipitation")?;
+                    let falling = Precipitation::parse(&name)
+                        .ok_or_else(|| format!("unknown precipitation {}\n{}", name, USAGE))?;
+                    args.precipitation = Some(falling);
+                }
+                "--time-of-day" => {
+                    let value = value("--time-of-day")?;
+                    match (value.as_str(), value.parse::<f32>()) {
+                        ("cycle", _) => args.day_cycle = true,
+                        (_, Ok(hour)) if (0.0..=24.0).contains(&hour) => {
+                            args.time_of_day = Some(hour)
+                        }
+                        _ => return Err(format!("invalid --time-of-day {}\n{}", value, USAGE)),
+                    }
+                }
+                "--night-sky" => args.night_sky = true,
+                "--nameplate" => args.nameplate = true,
+                "--high-contrast" => args.high_contrast = true,
+                "--language" => args.language = Some(value("--language")?),
+                "--preview" => args.preview = true,
+                "--terminal" => args.terminal = true,
+                "--daemon" => args.daemon = true,
+                "--fullscreen" => args.fullscreen = true,
+                "--borderless" => args.borderless = true,
+                "--pot" => args.pot = true,
+                "--roots" => args.roots = true,
+                "--volume" => args.volume = true,
+                "--pot-shape" => {
+                    let name = value("--pot-shape")?;
+                    let shape = pot::Shape::parse(&name)
+                        .ok_or_else(|| format!("unknown pot shape {}\n{}", name, USAGE))?;
+                    args.pot_shape = Some(shape);
+                }
+                "--kusamono" => args.kusamono = true,
+                "--ivy" => {
+                    args.ivy.get_or_insert(None);
+                }
+                "--ivy-seed" => args.ivy = Some(Some(seed::parse(&value("--ivy-seed")?))),
+                "--tokonoma" => args.tokonoma = true,
+                "--scenery" => args.scenery = true,
+                "--leaf-sprites" => {
+                    args.leaf_sprites = Some(sprites::LeafSprites::parse(&value("--leaf-sprites")?))
+                }
+                "--background" => args.background = Some(value("--background")?.into()),
+                "--background-fit" => {
+                    let name = value("--background-fit")?;
+                    let fit = Fit::parse(&name)
+                        .ok_or_else(|| format!("unknown fit {}\n{}", name, USAGE))?;
+                    args.background_fit = Some(fit);
+                }
+                "--pixelate-background" => args.pixelate_background = true,
+                "--config" => args.config = Some(value("--config")?.into()),
+                "--set" => args.overrides.push(value("--set")?),
+                "--compare" => args.compare.push(value("--compare")?),
+                "--width" | "--height" => {
+                    let value = value(&arg)?;
+                    let size = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|s| *s > 0.0)
+                        .ok_or_else(|| format!("invalid {} {}\n{}", arg, value, USAGE))?;
+                    match arg.as_str() {
+                        "--width" => args.width = Some(size),
+                        _ => args.height = Some(size),
+                    }
+                }
+                "--palette" => {
+                    let value = value("--palette")?;
+                    if let Some(preset) = Palette::parse(&value) {
+                        args.preset = Some(preset);
+                        continue;
+                    }
+                    if std::path::Path::new(&value).is_file() {
+                        args.palette_file = Some(value.into());
+                        continue;
+                    }
+                    let colors = value
+                        .split(',')
+                        .map(|hex| Color::from_hex(hex.trim().trim_start_matches('#')))
+                        .collect::<Result<Vec<_>, _>>()
+                        .ok()
+                        .filter(|colors| colors.len() >= 2)
+                        .ok_or_else(|| format!("invalid palette {}\n{}", value, USAGE))?;
+                    args.palette = Some(colors);
+                }
+                "--noise" => {
+                    let name = value("--noise")?;
+                    let kind = NoiseKind::parse(&name)
+                        .ok_or_else(|| format!("unknown noise {}\n{}", name, USAGE))?;
+                    args.noise = Some(kind);
+                }
+                "--density" => args.density = Some(value("--density")?.into()),
+                "--density-mask" => args.density_mask = Some(value("--density-mask")?.into()),
+                "--export-density" => args.export_density = Some(value("--export-density")?.into()),
+                "--on" => {
+                    let event = value("--on")?;
+                    let command = value("--on")?;
+                    let hook = Hook::parse(&event, &command)
+                        .map_err(|err| format!("{}\n{}", err, USAGE))?;
+                    args.hooks.push(hook);
+                }
+                "--ui-scale" => {
+                    let value = value("--ui-scale")?;
+                    let scale = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|scale| *scale >= 0.5)
+                        .ok_or_else(|| format!("invalid ui scale {}\n{}", value, USAGE))?;
+                    args.ui_scale = Some(scale);
+                }
+                "--pixel-aspect" => {
+                    let value = value("--pixel-aspect")?;
+                    let aspect = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|a| *a > 0.0)
+                        .ok_or_else(|| format!("invalid pixel aspect {}\n{}", value, USAGE))?;
+                    args.pixel_aspect = Some(aspect);
+                }
+                "--sun-angle" | "--sun-elevation" | "--yaw" => {
+                    let value = value(&arg)?;
+                    let degrees = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|d| d.is_finite())
+                        .ok_or_else(|| format!("invalid {} {}\n{}", arg, value, USAGE))?;
+                    match arg.as_str() {
+                        "--sun-angle" => args.sun_angle = Some(degrees),
+                        "--yaw" => args.yaw = Some(degrees),
+                        _ => args.sun_elevation = Some(degrees.clamp(0.0, 90.0)),
+                    }
+                }
+                "--ambient" => {
+                    let value = value("--ambient")?;
+                    let ambient = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|a| (0.0..=1.0).contains(a))
+                        .ok_or_else(|| format!("invalid ambient {}\n{}", value, USAGE))?;
+                    args.ambient = Some(ambient);
+                }
+                "--fps" => {
+                    let value = value("--fps")?;
+                    let fps = value
+                        .parse::<u32>()
+                        .map_err(|_| format!("invalid fps {}\n{}", value, USAGE))?;
+                    args.fps = Some(fps);
+                }
+                "--vsync" => args.vsync = true,
+                "--fixed-step" => args.fixed_step = true,
+                "--benchmark" => args.benchmark = true,
+                "--seed" => args.seed = Some(seed::parse(&value("--seed")?)),
+                "--open" => args.open = Some(value("--open")?.into()),
+                "--format" => args.format = Some(value("--format")?),
+                "--thumbnail" => {
+                    let size = value("--thumbnail")?;
+                    let size = size
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|size| *size > 0)
+                        .ok_or_else(|| format!("invalid thumbnail size {}\n{}", size, USAGE))?;
+                    args.thumbnail = Some((size, value("--thumbnail")?.into()));
+                }
+                "--inspect" => args.inspect = Some(value("--inspect")?.into()),
+                "--prune-selection" => args.prune_selection.push(value("--prune-selection")?),
+                "--recolor-selection" => {
+                    let value = value("--recolor-selection")?;
+                    let (name, hex) = value.rsplit_once('=').ok_or_else(|| {
+                        format!("--recolor-selection needs name=color\n{}", USAGE)
+                    })?;
+                    let color = match hex.trim() {
+                        "-" => None,
+                        hex => Some(
+                            Color::from_hex(hex.trim_start_matches('#'))
+                                .map_err(|_| format!("invalid color {}\n{}", hex, USAGE))?,
+                        ),
+                    };
+                    args.recolor_selection
+                        .push((name.trim().to_string(), color));
+                }
+                "--diff" => args.diff = Some((value("--diff")?, value("--diff")?)),
+                "--batch" | "--threads" | "--timelapse" | "--stop-motion" | "--num-points"
+                | "--forest" | "--shades" => {
+                    let value = value(&arg)?;
+                    let count = value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| format!("invalid {} {}\n{}", arg, value, USAGE))?;
+                    match arg.as_str() {
+                        "--batch" => args.batch = Some(count),
+                        "--timelapse" => args.timelapse = Some(count),
+                        "--stop-motion" => args.stop_motion = Some(count),
+                        "--num-points" => args.num_points = Some(count),
+                        "--forest" => args.forest = Some(count),
+                        "--shades" => args.shades = Some(count),
+                        _ => args.threads = Some(count),
+                    }
+                }
+                "--out" => args.out = Some(value("--out")?.into()),
+                "--morph" => args.morph = Some(seed::parse(&value("--morph")?)),
+                "--phylogeny" => args.phylogeny.push(value("--phylogeny")?.into()),
+                "--help" | "-h" => return Err(USAGE.to_string()),
+                // a save passed on its own, like a file manager opening it
+                path if path.ends_with(".bonsai") && args.open.is_none() => {
+                    args.open = Some(path.into())
+                }
+                _ => return Err(format!("unknown argument {}\n{}", arg, USAGE)),
+            }
+        }
+        if args.thumbnail.is_some() && args.out.is_none() {
+            return Err(format!("--thumbnail needs an --out\n{}", USAGE));
+        }
+        if args.batch.is_some() && args.target.is_none() {
+            return Err(format!("--batch needs a --target\n{}", USAGE));
+        }
+        if args.timelapse.is_some() && args.target.is_none() {
+            return Err(format!("--timelapse needs a --target\n{}", USAGE));
+        }
+        if args.morph.is_some() && args.target.is_none() {
+            return Err(format!("--morph needs a --target\n{}", USAGE));
+        }
+        if !args.phylogeny.is_empty() && args.target.is_none() {
+            return Err(format!("--phylogeny needs a --target\n{}", USAGE));
+        }
+        let styling = !args.prune_selection.is_empty() || !args.recolor_selection.is_empty();
+        if styling && args.open.is_none() {
+            return Err(format!(
+                "selections are styled on a save, pass --open\n{}",
+                USAGE
+            ));
+        }
+        if args.stop_motion.is_some() && args.timelapse.is_none() {
+            return Err(format!("--stop-motion needs a --timelapse\n{}", USAGE));
+        }
+        Ok(args)
+    }
+}
@@ -0,0 +1,79 @@
+//! One tree in two looks, split by a divider, e.g. to judge a change to the shading
+//!
+//! The right side renders the same branches with the `--compare key=value` settings on top of
+//! the config, so only settings of the look make a difference: the sun, the ambient light, the
+//! colors and the sky. Dragging with the mouse moves the divider.
+
+use crate::*;
+use bonsai_render::window::ToRaylib;
+use raylib::prelude::{MouseButton, RaylibDraw, RaylibMode2DExt, RaylibScissorModeExt};
+use render::PrettyRender;
+
+const DIVIDER: Color = Color::new(40, 40, 40, 255);
+
+/// The config of the right side, `config` with the `key=value` settings applied
+fn other_look(config: &Config, settings: &[String]) -> Result<Config, String> {
+    let mut other = config.clone();
+    for setting in settings {
+        config_file::apply_override(setting, &mut other)?;
+    }
+    Ok(other)
+}
+
+/// Grows a tree and shows it in both looks until the window is closed
+pub fn show(config: Config, app: &AppConfig, settings: &[String]) -> Result<(), String> {
+    // settings that don't exist are caught before the tree grows
+    other_look(&config, settings)?;
+    let slot = config.composition.layout(&config).remove(0);
+    let mut tree = Tree::new_min_growth(slot.config.clone(), 5);
+    tree.grow_fully(&cancel::CancelToken::new());
+    let mut other = tree.clone();
+    // on top of the tree's own config, which is sized to the slot
+    other.config = other_look(&tree.config, settings)?;
+    let (mut pretty_a, mut pretty_b) = (PrettyRender::new(tree), PrettyRender::new(other));
+
+    let (width, height) = (
+        config.composition.output_width as i32,
+        config.composition.output_height as i32,
+    );
+    let (mut rl, thread) = raylib::init()
+        .size(width, height)
+        .title("Sakura compare")
+        .build();
+    rl.set_target_fps(30);
+    let hud = hud::Hud::of(&config, app);
+    let mut divider = width / 2;
+
+    while !rl.window_should_close() {
+        if rl.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON) {
+            divider = rl.get_mouse_x().clamp(0, width);
+        }
+        let camera = slot.camera().to_raylib();
+        let mut d = rl.begin_drawing(&thread);
+        for (pretty, left, right) in [(&mut pretty_a, 0, divider), (&mut pretty_b, divider, width)]
+        {
+            let mut d = d.begin_scissor_mode(left, 0, right - left, height);
+            let sky = pretty.tree().config.sky;
+            d.draw_rectangle(left, 0, right - left, height, sky.to_raylib());
+            pretty.render(&mut d.begin_mode2D(camera));
+        }
+        d.draw_rectangle(
+            divider - hud.px(1),
+            0,
+            hud.px(2),
+            height,
+            DIVIDER.to_raylib(),
+        );
+        hud.text(&mut d, "a", hud.px(10), hud.px(10), 20, DIVIDER);
+        for (idx, setting) in std::iter::once("b")
+            .chain(settings.iter().map(String::as_str))
+            .enumerate()
+        {
+            let size = hud.px(20);
+            let x = width - raylib::text::measure_text(setting, size) - hud.px(10);
+            let y = hud.px(10) + idx as i32 * hud.px(24);
+            hud.text(&mut d, setting, x, y, 20, DIVIDER);
+        }
+    }
+    Ok(())
+}
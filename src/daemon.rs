@@ -0,0 +1,76 @@
+//! Running as a screensaver on a spare monitor with `--daemon`
+//!
+//! The trees grow, stay up for `daemon_rest` seconds once they stopped, and then fade into the
+//! sky over `daemon_fade` seconds. Trees from a new seed grow in their place, and so on forever.
+//! The sky stays drawn over everything until the next trees are ready, so they grow in from
+//! nothing instead of popping up.
+
+use crate::*;
+use bonsai_render::window::ToRaylib;
+use raylib::prelude::{RaylibDraw, RaylibDrawHandle};
+
+enum Phase {
+    Growing,
+    /// since when the trees stand finished
+    Resting(f64),
+    /// since when the trees fade out
+    Fading(f64),
+    /// the sky covers everything while the next trees are generated
+    Gone,
+}
+
+pub struct Daemon {
+    rest: f64,
+    fade: f64,
+    phase: Phase,
+}
+
+impl Daemon {
+    pub fn new(app: &AppConfig) -> Self {
+        Self {
+            rest: f64::from(app.daemon_rest.max(0.0)),
+            fade: f64::from(app.daemon_fade.max(0.0)),
+            phase: Phase::Growing,
+        }
+    }
+
+    /// Moves on at `time` seconds, with whether the trees shown are still `growing`.
+    /// Returns true once the trees faded out and the next ones should be generated
+    pub fn update(&mut self, growing: bool, time: f64) -> bool {
+        match self.phase {
+            // a flush or a cut lets resting trees grow on
+            Phase::Resting(_) if growing => self.phase = Phase::Growing,
+            Phase::Growing if !growing => self.phase = Phase::Resting(time),
+            Phase::Resting(since) if time - since >= self.rest => {
+                self.phase = Phase::Fading(time);
+            }
+            Phase::Fading(since) if time - since >= self.fade => {
+                self.phase = Phase::Gone;
+                return true;
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// The next trees are up, they grow in from an empty sky
+    pub fn replaced(&mut self) {
+        self.phase = Phase::Growing;
+    }
+
+    /// Whether the screen changes on its own, so the window stays awake
+    pub fn is_fading(&self) -> bool {
+        matches!(self.phase, Phase::Fading(_))
+    }
+
+    /// Covers the trees with the `sky` as far as they faded out at `time` seconds
+    pub fn draw(&self, d: &mut RaylibDrawHandle, sky: Color, time: f64) {
+        let cover = match self.phase {
+            Phase::Growing | Phase::Resting(_) => return,
+            Phase::Fading(since) if self.fade > 0.0 => ((time - since) / self.fade).min(1.0) as f32,
+            Phase::Fading(_) | Phase::Gone => 1.0,
+        };
+        let (width, height) = (d.get_screen_width(), d.get_screen_height());
+        d.draw_rectangle(0, 0, width, height, sky.fade(cover).to_raylib());
+    }
+}
@@ -0,0 +1,157 @@
+//! Comparing two trees, e.g. to see the exact effect of one tuned parameter
+
+use std::{io, path::Path};
+
+use crate::*;
+use bonsai_render::window::ToRaylib;
+use raylib::prelude::{RaylibDraw, RaylibMode2DExt};
+use render::{PrettyRender, Surface};
+
+const BOTH: Color = Color::new(150, 150, 150, 255);
+const ONLY_A: Color = Color::new(230, 90, 80, 255);
+const ONLY_B: Color = Color::new(70, 130, 230, 255);
+const SKELETON_BOTH: Color = Color::new(60, 60, 60, 255);
+const SKELETON_A: Color = Color::new(140, 20, 20, 255);
+const SKELETON_B: Color = Color::new(20, 40, 150, 255);
+
+/// A side of the comparison is either a save file or a seed that gets grown to completion
+pub fn load_side(arg: &str, base: &Config) -> io::Result<Tree> {
+    let path = Path::new(arg);
+    if path.exists() {
+        return save::load(path, base);
+    }
+    let mut config = base.clone();
+    config.seed = seed::parse(arg);
+    let mut tree = Tree::new_min_growth(config, 5);
+    tree.grow_fully(&cancel::CancelToken::new());
+    Ok(tree)
+}
+
+/// Canvas cells covered by the branches of the tree, indexed `[x][y]` with y going up
+fn skeleton(tree: &Tree, width: usize, height: usize) -> Vec<Vec<bool>> {
+    let mut cells = vec![vec![false; height]; width];
+    let scale = Vector2::new(
+        1.0 / tree.config.pixel_size as f32,
+        1.0 / tree.config.pixel_height() as f32,
+    );
+    for node in tree.nodes().filter(|node| node.alive) {
+        let Some(parent) = node.parent else { continue };
+        let (from, to) = (tree.node(parent).pos.vec() * scale, node.pos.vec() * scale);
+        let steps = ((to - from).length() * 2.0).ceil() as usize + 1;
+        for i in 0..=steps {
+            let p = from.lerp(to, i as f32 / steps as f32);
+            let (x, y) = (p.x.round() as usize, p.y.round() as usize);
+            if x < width && y < height {
+                cells[x][y] = true;
+            }
+        }
+    }
+    cells
+}
+
+/// Silhouettes and skeletons of both trees, colored by which tree they belong to
+pub fn overlay(a: &Tree, b: &Tree) -> Vec<Vec<Color>> {
+    let (pixels_a, pixels_b) = (
+        PrettyRender::new(a.clone()).pixels(),
+        PrettyRender::new(b.clone()).pixels(),
+    );
+    let width = pixels_a.len().max(pixels_b.len());
+    let height = pixels_a[0].len().max(pixels_b[0].len());
+    let (skeleton_a, skeleton_b) = (skeleton(a, width, height), skeleton(b, width, height));
+    let covered = |pixels: &Vec<Vec<Color>>, x: usize, y: usize| {
+        pixels
+            .get(x)
+            .and_then(|column| column.get(y))
+            .is_some_and(|c| c.a > 0)
+    };
+
+    let mut differing = 0;
+    let result = (0..width)
+        .map(|x| {
+            (0..height)
+                .map(|y| match (skeleton_a[x][y], skeleton_b[x][y]) {
+                    (true, true) => SKELETON_BOTH,
+                    (true, false) => SKELETON_A,
+                    (false, true) => SKELETON_B,
+                    (false, false) => match (covered(&pixels_a, x, y), covered(&pixels_b, x, y)) {
+                        (true, true) => BOTH,
+                        (true, false) => {
+                            differing += 1;
+                            ONLY_A
+                        }
+                        (false, true) => {
+                            differing += 1;
+                            ONLY_B
+                        }
+                        (false, false) => Color::new(0, 0, 0, 0),
+                    },
+                })
+                .collect()
+        })
+        .collect();
+    println!(
+        "a: {} nodes, b: {} nodes, {} silhouette pixels differ",
+        a.node_count(),
+        b.node_count(),
+        differing
+    );
+    result
+}
+
+/// Draws canvas colors indexed `[x][y]` like the pixel renderer places its canvases
+pub fn draw_pixels(d: &mut impl Surface, pixels: &[Vec<Color>], config: &Config) {
+    let (w, h) = (config.pixel_size as i32, config.pixel_height() as i32);
+    let height = pixels.first().map_or(0, Vec::len) as i32;
+    for (x, column) in pixels.iter().enumerate() {
+        for (y, color) in column.iter().enumerate() {
+            if color.a > 0 {
+                d.fill_rect(x as i32 * w, (height - y as i32 + 1) * h, w, h, *color);
+            }
+        }
+    }
+}
+
+/// Shows tree a, the overlay and tree b next to each other until the window is closed
+pub fn show(a: &str, b: &str, config: &Config, app: &AppConfig) -> io::Result<()> {
+    let mut composition = config.composition.clone();
+    composition.output_width *= 3.0;
+    composition.trees = 3;
+    let slots = composition.layout(config);
+
+    let a = load_side(a, &slots[0].config)?;
+    let b = load_side(b, &slots[2].config)?;
+    let overlay = overlay(&a, &b);
+
+    let (mut rl, thread) = raylib::init()
+        .size(
+            composition.output_width as i32,
+            composition.output_height as i32,
+        )
+        .title("Sakura diff")
+        .build();
+    rl.set_target_fps(30);
+    let hud = hud::Hud::of(config, app);
+
+    let (mut pretty_a, mut pretty_b) = (PrettyRender::new(a), PrettyRender::new(b));
+    while !rl.window_should_close() {
+        let mut d = rl.begin_drawing(&thread);
+        d.clear_background(config.sky.to_raylib());
+        pretty_a.render(&mut d.begin_mode2D(slots[0].camera().to_raylib()));
+        draw_pixels(
+            &mut d.begin_mode2D(slots[1].camera().to_raylib()),
+            &overlay,
+            &slots[1].config,
+        );
+        pretty_b.render(&mut d.begin_mode2D(slots[2].camera().to_raylib()));
+        hud.text(&mut d, "a", hud.px(10), hud.px(10), 20, ONLY_A);
+        hud.text(
+            &mut d,
+            "b",
+            composition.output_width as i32 - hud.px(20),
+            hud.px(10),
+            20,
+            ONLY_B,
+        );
+    }
+    Ok(())
+}
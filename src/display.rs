@@ -0,0 +1,38 @@
+//! Noticing when the display under the window changed: the laptop slept and woke up again, the
+//! window moved to another monitor, got resized or was restored after being minimized
+//!
+//! The graphics driver may have dropped what was uploaded to it by then, and what was drawn for
+//! the old size doesn't fit anymore. The trees are drawn from their canvases every frame, which
+//! live in memory and survive all of it, so only the textures need to be made and uploaded again.
+
+use raylib::prelude::RaylibHandle;
+
+/// A frame taking longer than this many seconds means the machine slept or the window hung
+const SLEEP_GAP: f32 = 5.0;
+
+pub struct Display {
+    monitor: i32,
+    size: (i32, i32),
+    minimized: bool,
+}
+
+impl Display {
+    pub fn new(rl: &RaylibHandle) -> Self {
+        Self {
+            monitor: raylib::core::window::get_current_monitor(),
+            size: (rl.get_screen_width(), rl.get_screen_height()),
+            minimized: rl.is_window_minimized(),
+        }
+    }
+
+    /// Whether the display changed since the last frame in a way that may have lost the textures
+    pub fn changed(&mut self, rl: &RaylibHandle) -> bool {
+        let now = Self::new(rl);
+        let changed = now.monitor != self.monitor
+            || now.size != self.size
+            || (self.minimized && !now.minimized)
+            || rl.get_frame_time() > SLEEP_GAP;
+        *self = now;
+        changed
+    }
+}
@@ -0,0 +1,188 @@
+//! Browsing saved trees in a grid of thumbnails
+
+use std::path::{Path, PathBuf};
+
+use crate::*;
+use bonsai_render::window::ToRaylib;
+use raylib::prelude::{KeyboardKey, MouseButton, RaylibDraw, RaylibHandle, Rectangle};
+use render::PrettyRender;
+
+/// Edge length of a thumbnail cell, in screen pixels
+pub(crate) const CELL: i32 = 150;
+pub(crate) const GAP: i32 = 10;
+
+struct Entry {
+    path: PathBuf,
+    tree: Option<Tree>,
+    /// lit pixels of the tree, rendered when the entry first comes into view
+    thumbnail: Option<Vec<Vec<Color>>>,
+    broken: bool,
+}
+
+pub struct Gallery {
+    entries: Vec<Entry>,
+    selected: usize,
+    base: Config,
+}
+
+impl Gallery {
+    /// Lists the saves in the directory, nothing is loaded until it gets drawn
+    pub fn scan(dir: &Path, base: &Config) -> Self {
+        let entries = save::list(dir)
+            .into_iter()
+            .map(|path| Entry {
+                path,
+                tree: None,
+                thumbnail: None,
+                broken: false,
+            })
+            .collect();
+        Self {
+            entries,
+            selected: 0,
+            base: base.clone(),
+        }
+    }
+
+    /// Handles keyboard and mouse selection, returns the tree to show when one gets picked
+    pub fn update(&mut self, rl: &RaylibHandle) -> Option<Tree> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let columns = columns(rl.get_screen_width());
+        let last = self.entries.len() - 1;
+        if rl.is_key_pressed(KeyboardKey::KEY_RIGHT) {
+            self.selected = (self.selected + 1).min(last);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_LEFT) {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.selected = (self.selected + columns).min(last);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.selected = self.selected.saturating_sub(columns);
+        }
+
+        let mut picked = rl.is_key_pressed(KeyboardKey::KEY_ENTER);
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) {
+            let mouse = rl.get_mouse_position();
+            let hit = (0..self.entries.len()).find(|&idx| {
+                let (x, y) = cell_pos(idx, columns);
+                (x..x + CELL).contains(&(mouse.x as i32))
+                    && (y..y + CELL).contains(&(mouse.y as i32))
+            });
+            if let Some(idx) = hit {
+                self.selected = idx;
+                picked = true;
+            }
+        }
+
+        if picked {
+            self.load(self.selected);
+            self.entries[self.selected].tree.clone()
+        } else {
+            None
+        }
+    }
+
+    fn load(&mut self, idx: usize) {
+        let entry = &mut self.entries[idx];
+        if entry.tree.is_some() || entry.broken {
+            return;
+        }
+        match save::load(&entry.path, &self.base) {
+            Ok(tree) => entry.tree = Some(tree),
+            Err(err) => {
+                eprintln!("could not load {}: {}", entry.path.display(), err);
+                entry.broken = true;
+            }
+        }
+    }
+
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, hud: &hud::Hud, width: i32, height: i32) {
+        if self.entries.is_empty() {
+            hud.text(
+                d,
+                &hud.format(i18n::Text::NoSaves, save::SAVE_DIR),
+                GAP,
+                GAP,
+                20,
+                Color::DARKGRAY,
+            );
+            return;
+        }
+
+        let columns = columns(width);
+        let mut rendered_one = false;
+        for idx in 0..self.entries.len() {
+            let (x, y) = cell_pos(idx, columns);
+            if y > height {
+                break;
+            }
+
+            // render at most one new thumbnail per frame to keep the ui responsive
+            if self.entries[idx].thumbnail.is_none() && !self.entries[idx].broken && !rendered_one {
+                self.load(idx);
+                if let Some(tree) = &self.entries[idx].tree {
+                    self.entries[idx].thumbnail = Some(PrettyRender::new(tree.clone()).pixels());
+                }
+                rendered_one = true;
+            }
+
+            let entry = &self.entries[idx];
+            d.draw_rectangle(x, y, CELL, CELL, Color::WHITE.fade(0.4).to_raylib());
+            if let Some(pixels) = &entry.thumbnail {
+                draw_thumbnail(d, pixels, x, y);
+            } else if entry.broken {
+                hud.text(
+                    d,
+                    hud.tr(i18n::Text::Broken),
+                    x + GAP,
+                    y + GAP,
+                    10,
+                    Color::MAROON,
+                );
+            }
+            if idx == self.selected {
+                d.draw_rectangle_lines_ex(
+                    Rectangle::new(x as f32, y as f32, CELL as f32, CELL as f32),
+                    3,
+                    hud.ink(Color::DARKGRAY).to_raylib(),
+                );
+            }
+        }
+    }
+}
+
+/// Number of cells that fit next to each other
+pub(crate) fn columns(width: i32) -> usize {
+    ((width - GAP) / (CELL + GAP)).max(1) as usize
+}
+
+/// Top left corner of the cell at `idx`
+pub(crate) fn cell_pos(idx: usize, columns: usize) -> (i32, i32) {
+    let (col, row) = ((idx % columns) as i32, (idx / columns) as i32);
+    (GAP + col * (CELL + GAP), GAP + row * (CELL + GAP))
+}
+
+/// Draws the lit pixels of a tree centered into the cell at `x`, `y`
+pub(crate) fn draw_thumbnail(d: &mut impl RaylibDraw, pixels: &[Vec<Color>], x: i32, y: i32) {
+    let (w, h) = (pixels.len() as i32, pixels[0].len() as i32);
+    let scale = (CELL / w.max(h)).max(1);
+    let (ox, oy) = (x + (CELL - w * scale) / 2, y + (CELL - h * scale) / 2);
+    for (px, column) in pixels.iter().enumerate() {
+        for (py, color) in column.iter().enumerate() {
+            if color.a > 0 {
+                let py = h - 1 - py as i32;
+                d.draw_rectangle(
+                    ox + px as i32 * scale,
+                    oy + py * scale,
+                    scale,
+                    scale,
+                    color.to_raylib(),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Couch controls: the first gamepad steers the window, for showing trees on a TV
+//!
+//! The left stick pans and the triggers zoom, the right stick moves the sun.
+//! The mouse wheel zooms the same view, see `View::scroll`.
+//! A grows a new tree, X takes a screenshot, Y cycles the foliage through the seasons
+//! and B puts the view back.
+
+use crate::*;
+use composition::Camera;
+use raylib::prelude::{GamepadAxis, GamepadButton, RaylibHandle};
+
+const PAD: i32 = 0;
+/// Stick deflection below this counts as resting, worn sticks don't return to exactly 0
+const DEADZONE: f32 = 0.2;
+/// Screen pixels per second at full deflection
+const PAN_SPEED: f32 = 600.0;
+/// Zoom factor per second with a trigger fully pulled
+const ZOOM_SPEED: f32 = 2.0;
+/// Zoom factor per notch of the mouse wheel
+const WHEEL_ZOOM: f32 = 1.25;
+/// Radians per second at full deflection, like holding an arrow key
+const SUN_SPEED: f32 = 1.5;
+
+/// What the gamepad asks for in one frame
+#[derive(Clone, Debug, Default)]
+pub struct Input {
+    /// camera movement in screen pixels
+    pub pan: Vector2,
+    /// factor the camera zooms by
+    pub zoom: f32,
+    /// change of the sun's azimuth and elevation, in radians
+    pub sun: (f32, f32),
+    pub regrow: bool,
+    pub screenshot: bool,
+    pub next_palette: bool,
+    pub reset_view: bool,
+}
+
+impl Input {
+    /// Reads the first gamepad, nothing happens without one
+    pub fn poll(rl: &RaylibHandle) -> Self {
+        if !rl.is_gamepad_available(PAD) {
+            return Self {
+                zoom: 1.0,
+                ..Self::default()
+            };
+        }
+        let dt = rl.get_frame_time();
+        let axis = |axis| {
+            let value = rl.get_gamepad_axis_movement(PAD, axis);
+            if value.abs() < DEADZONE {
+                0.0
+            } else {
+                value
+            }
+        };
+        // triggers rest at -1 and go up to 1 when pulled
+        let trigger = |axis| (rl.get_gamepad_axis_movement(PAD, axis) + 1.0) / 2.0;
+        let zoom_in = trigger(GamepadAxis::GAMEPAD_AXIS_RIGHT_TRIGGER);
+        let zoom_out = trigger(GamepadAxis::GAMEPAD_AXIS_LEFT_TRIGGER);
+        let pressed = |button| rl.is_gamepad_button_pressed(PAD, button);
+        Self {
+            // moving the stick right moves the tree right
+            pan: Vector2::new(
+                axis(GamepadAxis::GAMEPAD_AXIS_LEFT_X),
+                axis(GamepadAxis::GAMEPAD_AXIS_LEFT_Y),
+            ) * (PAN_SPEED * dt),
+            zoom: ZOOM_SPEED.powf((zoom_in - zoom_out) * dt),
+            sun: (
+                -axis(GamepadAxis::GAMEPAD_AXIS_RIGHT_X) * SUN_SPEED * dt,
+                -axis(GamepadAxis::GAMEPAD_AXIS_RIGHT_Y) * SUN_SPEED * dt,
+            ),
+            regrow: pressed(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN),
+            screenshot: pressed(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT),
+            next_palette: pressed(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP),
+            reset_view: pressed(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT),
+        }
+    }
+
+    /// Whether a stick, trigger or button asks for anything
+    pub fn is_active(&self) -> bool {
+        self.pan != Vector2::zero()
+            || self.zoom != 1.0
+            || self.sun != (0.0, 0.0)
+            || self.regrow
+            || self.screenshot
+            || self.next_palette
+            || self.reset_view
+    }
+}
+
+/// Where the gamepad, the fingers or the mouse moved the view to, on top of the cameras of the slots
+#[derive(Copy, Clone, Debug, Default)]
+pub struct View {
+    view: composition::View,
+}
+
+impl View {
+    pub fn update(&mut self, input: &Input) {
+        if input.reset_view {
+            *self = Self::default();
+            return;
+        }
+        self.view.pan += input.pan;
+        self.view.zoom = (self.view.zoom * input.zoom).clamp(0.25, 8.0);
+    }
+
+    /// Moves and zooms the view the way the fingers did, keeping the spot between them in place
+    pub fn touch(&mut self, gesture: &touch::Gesture, screen: Vector2) {
+        self.view.pan += gesture.pan;
+        self.zoom_around(gesture.zoom, gesture.center, screen);
+    }
+
+    /// Zooms the view by `wheel` notches of the mouse wheel, keeping the spot under the cursor in place
+    pub fn scroll(&mut self, wheel: f32, cursor: Vector2, screen: Vector2) {
+        if wheel != 0.0 {
+            self.zoom_around(WHEEL_ZOOM.powf(wheel), cursor, screen);
+        }
+    }
+
+    fn zoom_around(&mut self, factor: f32, center: Vector2, screen: Vector2) {
+        let view = &mut self.view;
+        let zoom = (view.zoom * factor).clamp(0.25, 8.0);
+        let factor = zoom / view.zoom;
+        let from_center = center - screen * 0.5;
+        view.pan = from_center * (1.0 - factor) + view.pan * factor;
+        view.zoom = zoom;
+    }
+
+    /// The camera of a slot, zoomed around the middle of the screen and then panned.
+    /// Once zoomed, the pixels of the canvases, `pixel_size` world units wide, cover a whole number
+    /// of screen pixels, so the tree turns blockier or finer instead of drawing them unevenly
+    pub fn apply(&self, camera: Camera, screen: Vector2, pixel_size: usize) -> Camera {
+        let mut camera = self.view.apply(camera, screen);
+        if self.view.zoom != 1.0 {
+            let pixel_size = pixel_size as f32;
+            camera.zoom = (camera.zoom * pixel_size).round().max(1.0) / pixel_size;
+        }
+        camera
+    }
+
+    /// How far the view was panned and zoomed
+    pub fn framing(&self) -> composition::View {
+        self.view
+    }
+}
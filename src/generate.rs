@@ -0,0 +1,109 @@
+//! Growing new trees on a worker thread, so the window stays responsive meanwhile
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    mpsc::{self, Receiver},
+    Arc,
+};
+use std::thread;
+
+use crate::*;
+use bonsai_render::window::ToRaylib;
+use cancel::CancelToken;
+use raylib::prelude::RaylibDraw;
+
+/// Initial simulation steps a new tree needs to survive, see `Tree::new_min_growth`
+const MIN_GROWTH: usize = 5;
+
+/// Trees being generated in the background
+pub struct Generation {
+    /// steps done so far, across all trees
+    done: Arc<AtomicUsize>,
+    total: usize,
+    cancel: CancelToken,
+    trees: Receiver<Vec<Tree>>,
+}
+
+impl Generation {
+    /// Starts growing one tree per config
+    pub fn start(configs: Vec<Config>) -> Self {
+        let done = Arc::new(AtomicUsize::new(0));
+        let cancel = CancelToken::new();
+        let total = configs.len() * (MIN_GROWTH + 1);
+        let (sender, trees) = mpsc::channel();
+        {
+            let (done, cancel) = (done.clone(), cancel.clone());
+            thread::spawn(move || {
+                let mut step = || {
+                    done.fetch_add(1, Ordering::Relaxed);
+                    !cancel.is_cancelled()
+                };
+                let grown = configs
+                    .into_iter()
+                    .map(|config| match config.forest {
+                        // a grove grows here in full, the window shows it once it is done
+                        Some(count) => scene::Scene::forest_with(
+                            &config, count, MIN_GROWTH, &mut step,
+                        )
+                        .map(|mut scene| {
+                            scene.grow_fully(&cancel);
+                            scene.merged()
+                        }),
+                        None => Tree::new_min_growth_with(config, MIN_GROWTH, &mut step),
+                    })
+                    .collect::<Option<Vec<_>>>();
+                if let Some(grown) = grown {
+                    // the receiving side is gone if the window closed meanwhile
+                    let _ = sender.send(grown);
+                }
+            });
+        }
+        Self {
+            done,
+            total,
+            cancel,
+            trees,
+        }
+    }
+
+    /// Fraction of the work done, stays at 1 while a stalled tree starts over
+    pub fn progress(&self) -> f32 {
+        (self.done.load(Ordering::Relaxed) as f32 / self.total as f32).min(1.0)
+    }
+
+    /// The trees, once they are ready
+    pub fn poll(&self) -> Option<Vec<Tree>> {
+        self.trees.try_recv().ok()
+    }
+
+    /// Stops the worker at its next step, the trees are never delivered
+    pub fn cancel(self) {
+        self.cancel.cancel();
+    }
+
+    /// Draws a spinner with the progress in the lower left corner
+    pub fn draw(&self, d: &mut impl RaylibDraw, hud: &hud::Hud, time: f64, height: i32) {
+        const DOTS: usize = 8;
+        let center = Vector2::new(hud.px(30) as f32, (height - hud.px(30)) as f32);
+        let turn = (time * 1.5).fract() as f32;
+        for i in 0..DOTS {
+            let angle = (i as f32 / DOTS as f32 + turn) * std::f32::consts::TAU;
+            let pos = center + Vector2::new(angle.cos(), angle.sin()) * hud.px(12) as f32;
+            let alpha = (i + 1) as f32 / DOTS as f32;
+            d.draw_circle_v(
+                pos.to_raylib(),
+                hud.px(3) as f32,
+                hud.ink(Color::DARKGRAY.fade(alpha)).to_raylib(),
+            );
+        }
+        let percent = (self.progress() * 100.0) as i32;
+        hud.text(
+            d,
+            &hud.format(i18n::Text::Growing, percent),
+            hud.px(55),
+            height - hud.px(40),
+            20,
+            Color::DARKGRAY,
+        );
+    }
+}
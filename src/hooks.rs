@@ -0,0 +1,158 @@
+//! User commands run when something notable happens, e.g. to show a desktop notification
+//!
+//! Commands are run directly, without a shell, so a seed or path can't inject anything.
+//! Arguments may contain placeholders like `{seed}` or `{path}`, filled in per event.
+
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Something a hook can react to
+#[derive(Clone, Debug)]
+pub enum Event {
+    /// a tree stopped growing
+    GrowthFinished { seed: u64, nodes: usize },
+    /// the tree got autosaved, only the window autosaves
+    #[cfg_attr(not(feature = "gui"), allow(dead_code))]
+    AutosaveWritten { seed: u64, path: PathBuf },
+    /// a wallpaper image got written
+    WallpaperWritten { seed: u64, path: PathBuf },
+}
+
+impl Event {
+    /// Names hooks are registered with
+    pub const NAMES: [&'static str; 3] =
+        ["growth-finished", "autosave-written", "wallpaper-written"];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::GrowthFinished { .. } => Self::NAMES[0],
+            Self::AutosaveWritten { .. } => Self::NAMES[1],
+            Self::WallpaperWritten { .. } => Self::NAMES[2],
+        }
+    }
+
+    /// Value of a placeholder, `None` if the event doesn't have it
+    fn field(&self, name: &str) -> Option<String> {
+        match (self, name) {
+            (_, "event") => Some(self.name().to_string()),
+            (
+                Self::GrowthFinished { seed, .. }
+                | Self::AutosaveWritten { seed, .. }
+                | Self::WallpaperWritten { seed, .. },
+                "seed",
+            ) => Some(seed.to_string()),
+            (Self::GrowthFinished { nodes, .. }, "nodes") => Some(nodes.to_string()),
+            (Self::AutosaveWritten { path, .. } | Self::WallpaperWritten { path, .. }, "path") => {
+                Some(path.display().to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A command run on every event with the name
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hook {
+    pub event: String,
+    /// the program followed by its arguments, each with placeholders
+    pub command: Vec<String>,
+}
+
+impl Hook {
+    /// Splits the command line into words like a shell would, with quotes but without any expansion
+    pub fn parse(event: &str, command: &str) -> Result<Self, String> {
+        if !Event::NAMES.contains(&event) {
+            return Err(format!(
+                "unknown event {}, expected one of {}",
+                event,
+                Event::NAMES.join(", ")
+            ));
+        }
+        let mut words = vec![];
+        let mut word: Option<String> = None;
+        let mut quote = None;
+        let mut chars = command.chars();
+        while let Some(c) = chars.next() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some('"'), '\\') => {
+                    let escaped = chars.next().ok_or("command ends in a backslash")?;
+                    word.get_or_insert_with(String::new).push(escaped);
+                }
+                (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+                (None, '"' | '\'') => {
+                    quote = Some(c);
+                    word.get_or_insert_with(String::new);
+                }
+                (None, '\\') => {
+                    let escaped = chars.next().ok_or("command ends in a backslash")?;
+                    word.get_or_insert_with(String::new).push(escaped);
+                }
+                (None, c) if c.is_whitespace() => words.extend(word.take()),
+                (None, c) => word.get_or_insert_with(String::new).push(c),
+            }
+        }
+        if quote.is_some() {
+            return Err(format!("unclosed quote in {}", command));
+        }
+        words.extend(word);
+        if words.is_empty() {
+            return Err(format!("no command for {}", event));
+        }
+        Ok(Self {
+            event: event.to_string(),
+            command: words,
+        })
+    }
+
+    /// The program and arguments with the event's placeholders filled in,
+    /// unknown placeholders are left as they are
+    fn expand(&self, event: &Event) -> Vec<String> {
+        self.command
+            .iter()
+            .map(|word| {
+                let mut out = String::new();
+                let mut rest = word.as_str();
+                while let Some(start) = rest.find('{') {
+                    let Some(end) = rest[start..].find('}') else {
+                        break;
+                    };
+                    out.push_str(&rest[..start]);
+                    let placeholder = &rest[start..start + end + 1];
+                    match event.field(&placeholder[1..placeholder.len() - 1]) {
+                        Some(value) => out.push_str(&value),
+                        None => out.push_str(placeholder),
+                    }
+                    rest = &rest[start + end + 1..];
+                }
+                out.push_str(rest);
+                out
+            })
+            .collect()
+    }
+}
+
+/// Runs the hooks registered for the event, without waiting for them to finish,
+/// returning why the ones that couldn't be started couldn't
+pub fn fire(hooks: &[Hook], event: &Event) -> Vec<String> {
+    let mut failed = vec![];
+    for hook in hooks.iter().filter(|hook| hook.event == event.name()) {
+        let command = hook.expand(event);
+        let child = Command::new(&command[0])
+            .args(&command[1..])
+            .stdin(Stdio::null())
+            .spawn();
+        match child {
+            // reaped in the background, a slow notification doesn't hold up the growth
+            Ok(mut child) => {
+                thread::spawn(move || child.wait());
+            }
+            Err(err) => failed.push(format!(
+                "could not run {} hook {}: {}",
+                hook.event, command[0], err
+            )),
+        }
+    }
+    failed
+}
@@ -0,0 +1,82 @@
+//! Text and marks drawn over the trees, scaled by `ui_scale` and, with `high_contrast`,
+//! in black on white backings that stay readable over any sky or canopy
+
+use crate::*;
+use bonsai_render::window::ToRaylib;
+use diagnose::Warning;
+use i18n::{Language, Text};
+use raylib::prelude::RaylibDraw;
+
+/// Room around text on its backing, in unscaled pixels
+const BACKING: i32 = 3;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Hud {
+    scale: f32,
+    high_contrast: bool,
+    language: Language,
+}
+
+impl Hud {
+    pub fn of(config: &Config, app: &AppConfig) -> Self {
+        Self {
+            scale: app.ui_scale,
+            high_contrast: config.high_contrast,
+            language: Language::of(app),
+        }
+    }
+
+    /// The text in the language of the overlays
+    pub fn tr(&self, text: Text) -> &'static str {
+        self.language.text(text)
+    }
+
+    /// The text in the language of the overlays with its `{}` filled in
+    pub fn format(&self, text: Text, value: impl std::fmt::Display) -> String {
+        self.language.format(text, value)
+    }
+
+    /// A growth warning in the language of the overlays
+    pub fn warning(&self, warning: Warning) -> String {
+        self.language.warning(warning)
+    }
+
+    /// A length of the overlays in screen pixels
+    pub fn px(&self, length: i32) -> i32 {
+        (length as f32 * self.scale).round() as i32
+    }
+
+    /// Color of text and marks, black in high contrast
+    pub fn ink(&self, color: Color) -> Color {
+        if self.high_contrast {
+            Color::BLACK.fade(color.a as f32 / 255.0)
+        } else {
+            color
+        }
+    }
+
+    /// Draws `text` with its top left corner at the screen position `x`, `y`, `size` gets scaled
+    pub fn text(
+        &self,
+        d: &mut impl RaylibDraw,
+        text: &str,
+        x: i32,
+        y: i32,
+        size: i32,
+        color: Color,
+    ) {
+        let size = self.px(size);
+        if self.high_contrast {
+            let width = raylib::text::measure_text(text, size);
+            let pad = self.px(BACKING);
+            d.draw_rectangle(
+                x - pad,
+                y - pad,
+                width + 2 * pad,
+                size + 2 * pad,
+                Color::WHITE.to_raylib(),
+            );
+        }
+        d.draw_text(text, x, y, size, self.ink(color).to_raylib());
+    }
+}
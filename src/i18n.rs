@@ -0,0 +1,353 @@
+//! Translations of the text drawn over the trees
+//!
+//! The language comes from `language` in the config, or from the `LANG` environment variable
+//! when it isn't set. Every text has an English entry, others fall back to it until translated.
+//! raylib's default font only has Latin-1 glyphs, which limits the languages to those it can draw.
+
+use crate::*;
+use diagnose::Warning;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+    French,
+    Spanish,
+}
+
+impl Language {
+    /// Reads a language code like `de` or a locale like `de_CH.UTF-8`
+    pub fn parse(code: &str) -> Option<Self> {
+        match code.get(..2)?.to_ascii_lowercase().as_str() {
+            "en" => Some(Language::English),
+            "de" => Some(Language::German),
+            "fr" => Some(Language::French),
+            "es" => Some(Language::Spanish),
+            _ => None,
+        }
+    }
+
+    /// The language of the config, else of the environment, else English
+    pub fn of(app: &AppConfig) -> Self {
+        app.language
+            .as_deref()
+            .and_then(Self::parse)
+            .or_else(|| std::env::var("LANG").ok().as_deref().and_then(Self::parse))
+            .unwrap_or(Language::English)
+    }
+}
+
+/// Everything the overlays say, `{}` stands for a value filled in with `Language::format`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Text {
+    RefinePreview,
+    ResumeAutosave,
+    Growing,
+    NoSaves,
+    Broken,
+    BrowserHelp,
+    HelpTitle,
+    KeyRegrow,
+    KeyCancel,
+    KeySave,
+    KeyLoad,
+    KeyGallery,
+    KeyBrowser,
+    KeySun,
+    KeyDensity,
+    KeyExports,
+    KeyConfig,
+    KeyRefine,
+    KeyTuning,
+    KeyCut,
+    KeyUndo,
+    KeySelection,
+    KeyTimeline,
+    KeyPause,
+    KeyStorm,
+    KeyStats,
+    KeyHelp,
+    StatSeed,
+    StatNodes,
+    StatPoints,
+    StatGrowing,
+    StatFinished,
+    SelectionName,
+    SelectionPicking,
+    TimelineStep,
+    TuningTitle,
+    TuningRegrow,
+    WarnOutOfReach,
+    WarnTooFlat,
+    WarnCrowded,
+    WarnTooDeep,
+    WarnOverPruned,
+}
+
+impl Language {
+    pub fn text(self, text: Text) -> &'static str {
+        use Language::*;
+        use Text::*;
+        match (text, self) {
+            (RefinePreview, German) => "F verfeinert die Vorschau",
+            (RefinePreview, French) => "F affine l'aperçu",
+            (RefinePreview, Spanish) => "F refina la vista previa",
+            (RefinePreview, _) => "F refines the preview",
+
+            (ResumeAutosave, German) => "Enter setzt den automatisch gespeicherten Baum fort",
+            (ResumeAutosave, French) => "Entrée reprend l'arbre sauvegardé automatiquement",
+            (ResumeAutosave, Spanish) => "Intro reanuda el árbol guardado automáticamente",
+            (ResumeAutosave, _) => "Enter resumes the autosaved tree",
+
+            (Growing, German) => "wächst {}%",
+            (Growing, French) => "pousse {}%",
+            (Growing, Spanish) => "creciendo {}%",
+            (Growing, _) => "growing {}%",
+
+            (NoSaves, German) => "keine Spielstände in {}/, S speichert einen Baum",
+            (NoSaves, French) => "aucune sauvegarde dans {}/, S sauvegarde un arbre",
+            (NoSaves, Spanish) => "no hay partidas en {}/, S guarda un árbol",
+            (NoSaves, _) => "no saves in {}/, press S to save a tree",
+
+            (Broken, German) => "defekt",
+            (Broken, French) => "illisible",
+            (Broken, Spanish) => "dañado",
+            (Broken, _) => "broken",
+
+            (BrowserHelp, German) => {
+                "Bild auf/ab für weitere Seeds, Enter oder Klick lässt den Seed in voller Qualität wachsen"
+            }
+            (BrowserHelp, French) => {
+                "Page préc./suiv. pour d'autres graines, Entrée ou un clic la fait pousser en pleine qualité"
+            }
+            (BrowserHelp, Spanish) => {
+                "RePág/AvPág para más semillas, Intro o un clic la hace crecer en calidad completa"
+            }
+            (BrowserHelp, _) => {
+                "PageUp/PageDown for more seeds, Enter or click grows the seed at full quality"
+            }
+
+            (HelpTitle, German) => "Tasten",
+            (HelpTitle, French) => "Touches",
+            (HelpTitle, Spanish) => "Teclas",
+            (HelpTitle, _) => "Keys",
+
+            (KeyRegrow, German) => "R  neuer Baum",
+            (KeyRegrow, French) => "R  nouvel arbre",
+            (KeyRegrow, Spanish) => "R  árbol nuevo",
+            (KeyRegrow, _) => "R  new tree",
+
+            (KeyCancel, German) => "Esc  abbrechen oder beenden",
+            (KeyCancel, French) => "Échap  annuler ou quitter",
+            (KeyCancel, Spanish) => "Esc  cancelar o salir",
+            (KeyCancel, _) => "Esc  cancel or quit",
+
+            (KeySave, German) => "S  speichern",
+            (KeySave, French) => "S  sauvegarder",
+            (KeySave, Spanish) => "S  guardar",
+            (KeySave, _) => "S  save",
+            (KeyLoad, German) => "O  zuletzt gespeicherten Baum öffnen",
+            (KeyLoad, French) => "O  ouvrir le dernier arbre sauvegardé",
+            (KeyLoad, Spanish) => "O  abrir el último árbol guardado",
+            (KeyLoad, _) => "O  open the last saved tree",
+
+            (KeyGallery, German) => "G  Galerie der gespeicherten Bäume",
+            (KeyGallery, French) => "G  galerie des arbres sauvegardés",
+            (KeyGallery, Spanish) => "G  galería de árboles guardados",
+            (KeyGallery, _) => "G  gallery of saved trees",
+
+            (KeyBrowser, German) => "B  Seeds durchblättern",
+            (KeyBrowser, French) => "B  parcourir les graines",
+            (KeyBrowser, Spanish) => "B  explorar semillas",
+            (KeyBrowser, _) => "B  browse seeds",
+
+            (KeySun, German) => "Pfeiltasten  Sonne bewegen",
+            (KeySun, French) => "Flèches  déplacer le soleil",
+            (KeySun, Spanish) => "Flechas  mover el sol",
+            (KeySun, _) => "Arrows  move the sun",
+
+            (KeyDensity, German) => "D  Dichte der Anziehungspunkte",
+            (KeyDensity, French) => "D  densité des points d'attraction",
+            (KeyDensity, Spanish) => "D  densidad de los puntos de atracción",
+            (KeyDensity, _) => "D  density of the attraction points",
+
+            (KeyExports, German) => "V M C X  Voxel, STL, Kreuzstich und SVG exportieren",
+            (KeyExports, French) => "V M C X  exporter voxels, STL, point de croix et SVG",
+            (KeyExports, Spanish) => "V M C X  exportar vóxeles, STL, punto de cruz y SVG",
+            (KeyExports, _) => "V M C X  export voxels, STL, cross-stitch and SVG",
+
+            (KeyConfig, German) => "E  Einstellungen des Baums exportieren",
+            (KeyConfig, French) => "E  exporter la configuration de l'arbre",
+            (KeyConfig, Spanish) => "E  exportar la configuración del árbol",
+            (KeyConfig, _) => "E  export the config of the tree",
+
+            (KeyRefine, German) => "F  Vorschau verfeinern",
+            (KeyRefine, French) => "F  affiner l'aperçu",
+            (KeyRefine, Spanish) => "F  refinar la vista previa",
+            (KeyRefine, _) => "F  refine the preview",
+
+            (KeyTuning, German) => "T  Wachstumsparameter einstellen",
+            (KeyTuning, French) => "T  régler les paramètres de croissance",
+            (KeyTuning, Spanish) => "T  ajustar los parámetros de crecimiento",
+            (KeyTuning, _) => "T  tune the growth parameters",
+
+            (KeyCut, German) => "Klick, Rechtsziehen  Äste schneiden, mit Umschalt nachwachsen lassen",
+            (KeyCut, French) => "Clic, glisser-droit  couper des branches, avec Maj les laisser repousser",
+            (KeyCut, Spanish) => "Clic, arrastrar con derecho  cortar ramas, con Mayús dejarlas rebrotar",
+            (KeyCut, _) => "Click, right drag  cut branches, with shift to let them regrow",
+
+            (KeyUndo, German) => "Strg+Z  Schnitt rückgängig machen",
+            (KeyUndo, French) => "Ctrl+Z  annuler la coupe",
+            (KeyUndo, Spanish) => "Ctrl+Z  deshacer el corte",
+            (KeyUndo, _) => "Ctrl+Z  undo the last cut",
+
+            (KeySelection, German) => "L  Auswahl benennen, Klicks und Rahmen fügen Äste hinzu",
+            (KeySelection, French) => "L  nommer une sélection, clics et cadres y ajoutent des branches",
+            (KeySelection, Spanish) => "L  nombrar una selección, clics y marcos le añaden ramas",
+            (KeySelection, _) => "L  name a selection, clicks and boxes add branches to it",
+
+            (KeyTimeline, German) => "P  durch das Wachstum spulen, mit links und rechts",
+            (KeyTimeline, French) => "P  parcourir la croissance, avec gauche et droite",
+            (KeyTimeline, Spanish) => "P  recorrer el crecimiento, con izquierda y derecha",
+            (KeyTimeline, _) => "P  scrub through the growth with left and right",
+
+            (KeyPause, German) => "Leertaste, .  Wachstum anhalten und schrittweise fortsetzen, Tab spult vor",
+            (KeyPause, French) => "Espace, .  suspendre la croissance et avancer pas à pas, Tab accélère",
+            (KeyPause, Spanish) => "Espacio, .  pausar el crecimiento y avanzar paso a paso, Tab acelera",
+            (KeyPause, _) => "Space, .  pause the growth and step through it, Tab fast-forwards",
+
+            (KeyStorm, German) => "W  einen Sturm aufziehen lassen, der Äste abbrechen kann",
+            (KeyStorm, French) => "W  faire venir une tempête, qui peut casser des branches",
+            (KeyStorm, Spanish) => "W  traer una tormenta, que puede romper ramas",
+            (KeyStorm, _) => "W  call up a storm, which can snap branches",
+
+            (KeyStats, German) => "F3  Zahlen zum Baum und Zeiten der Frames",
+            (KeyStats, French) => "F3  chiffres de l'arbre et durée des images",
+            (KeyStats, Spanish) => "F3  cifras del árbol y tiempos de los fotogramas",
+            (KeyStats, _) => "F3  numbers of the tree and frame times",
+
+            (KeyHelp, German) => "H  diese Hilfe",
+            (KeyHelp, French) => "H  cette aide",
+            (KeyHelp, Spanish) => "H  esta ayuda",
+            (KeyHelp, _) => "H  this help",
+
+            (StatSeed, German) => "Seed: {}",
+            (StatSeed, French) => "Graine : {}",
+            (StatSeed, Spanish) => "Semilla: {}",
+            (StatSeed, _) => "Seed: {}",
+
+            (StatNodes, German) => "Knoten: {}",
+            (StatNodes, French) => "Noeuds : {}",
+            (StatNodes, Spanish) => "Nodos: {}",
+            (StatNodes, _) => "Nodes: {}",
+
+            (StatPoints, German) => "Anziehungspunkte übrig: {}",
+            (StatPoints, French) => "Points d'attraction restants : {}",
+            (StatPoints, Spanish) => "Puntos de atracción restantes: {}",
+            (StatPoints, _) => "Attraction points left: {}",
+
+            (StatGrowing, German) => "wächst",
+            (StatGrowing, French) => "en croissance",
+            (StatGrowing, Spanish) => "creciendo",
+            (StatGrowing, _) => "growing",
+
+            (StatFinished, German) => "ausgewachsen",
+            (StatFinished, French) => "terminé",
+            (StatFinished, Spanish) => "terminado",
+            (StatFinished, _) => "fully grown",
+
+            (SelectionName, German) => "Name der Auswahl: {}",
+            (SelectionName, French) => "Nom de la sélection : {}",
+            (SelectionName, Spanish) => "Nombre de la selección: {}",
+            (SelectionName, _) => "Name of the selection: {}",
+
+            (SelectionPicking, German) => "Klicks und Rahmen wählen \"{}\", L und Enter zum Schneiden",
+            (SelectionPicking, French) => "Clics et cadres choisissent \"{}\", L et Entrée pour couper",
+            (SelectionPicking, Spanish) => "Clics y marcos eligen \"{}\", L y Intro para cortar",
+            (SelectionPicking, _) => "Clicks and boxes pick \"{}\", L and Enter to cut again",
+
+            (TimelineStep, German) => "Schritt {}",
+            (TimelineStep, French) => "étape {}",
+            (TimelineStep, Spanish) => "paso {}",
+            (TimelineStep, _) => "step {}",
+
+            (TuningTitle, German) => "Wachstum",
+            (TuningTitle, French) => "Croissance",
+            (TuningTitle, Spanish) => "Crecimiento",
+            (TuningTitle, _) => "Growth",
+
+            (TuningRegrow, German) => "mit diesen Werten neu wachsen",
+            (TuningRegrow, French) => "refaire pousser avec ces valeurs",
+            (TuningRegrow, Spanish) => "volver a crecer con estos valores",
+            (TuningRegrow, _) => "regrow with these values",
+
+            (WarnOutOfReach, German) => "{}% der Anziehungspunkte außer Reichweite, attraction_dist erhöhen",
+            (WarnOutOfReach, French) => "{}% des points d'attraction hors de portée, augmenter attraction_dist",
+            (WarnOutOfReach, Spanish) => "{}% de los puntos de atracción fuera de alcance, subir attraction_dist",
+            (WarnOutOfReach, _) => "{}% of the attraction points out of reach, raise attraction_dist",
+
+            (WarnTooFlat, German) => "kein neuer Ast steigt um min_y_growth, den Wert senken",
+            (WarnTooFlat, French) => "aucune nouvelle branche ne monte de min_y_growth, le baisser",
+            (WarnTooFlat, Spanish) => "ninguna rama nueva sube min_y_growth, bajarlo",
+            (WarnTooFlat, _) => "no new branch rises by min_y_growth, lower it",
+
+            (WarnCrowded, German) => "neue Knoten liegen zu dicht, node_min_dist senken",
+            (WarnCrowded, French) => "les nouveaux noeuds sont trop serrés, baisser node_min_dist",
+            (WarnCrowded, Spanish) => "los nodos nuevos están demasiado juntos, bajar node_min_dist",
+            (WarnCrowded, _) => "new nodes are too crowded, lower node_min_dist",
+
+            (WarnTooDeep, German) => "die Äste haben max_depth erreicht, den Wert erhöhen",
+            (WarnTooDeep, French) => "les branches ont atteint max_depth, l'augmenter",
+            (WarnTooDeep, Spanish) => "las ramas llegaron a max_depth, subirlo",
+            (WarnTooDeep, _) => "the branches reached max_depth, raise it",
+
+            (WarnOverPruned, German) => "der Rückschnitt hat {}% der Äste entfernt, prune_pow senken",
+            (WarnOverPruned, French) => "la taille a coupé {}% des branches, baisser prune_pow",
+            (WarnOverPruned, Spanish) => "la poda cortó {}% de las ramas, bajar prune_pow",
+            (WarnOverPruned, _) => "pruning cut off {}% of the branches, lower prune_pow",
+        }
+    }
+
+    /// The text with its `{}` filled in by `value`
+    pub fn format(self, text: Text, value: impl std::fmt::Display) -> String {
+        self.text(text).replacen("{}", &value.to_string(), 1)
+    }
+
+    /// A growth warning in this language
+    pub fn warning(self, warning: Warning) -> String {
+        let percent = |share: f32| (share * 100.0).round();
+        match warning {
+            Warning::OutOfReach { unreached } => {
+                self.format(Text::WarnOutOfReach, percent(unreached))
+            }
+            Warning::TooFlat => self.text(Text::WarnTooFlat).to_string(),
+            Warning::Crowded => self.text(Text::WarnCrowded).to_string(),
+            Warning::TooDeep => self.text(Text::WarnTooDeep).to_string(),
+            Warning::OverPruned { pruned } => self.format(Text::WarnOverPruned, percent(pruned)),
+        }
+    }
+}
+
+/// The lines of the key help, in the order they are shown
+pub const HELP: [Text; 20] = [
+    Text::KeyRegrow,
+    Text::KeyCancel,
+    Text::KeySave,
+    Text::KeyLoad,
+    Text::KeyGallery,
+    Text::KeyBrowser,
+    Text::KeySun,
+    Text::KeyDensity,
+    Text::KeyExports,
+    Text::KeyConfig,
+    Text::KeyRefine,
+    Text::KeyTuning,
+    Text::KeyCut,
+    Text::KeyUndo,
+    Text::KeySelection,
+    Text::KeyTimeline,
+    Text::KeyPause,
+    Text::KeyStorm,
+    Text::KeyStats,
+    Text::KeyHelp,
+];
@@ -0,0 +1,71 @@
+//! Sleeping while nothing on the screen changes
+//!
+//! Once the trees are grown and nothing animates, the window slows down to a few frames a second,
+//! just enough to notice a key, the mouse, a resize or a message from OSC or the chat. Any of
+//! them wakes it up to the full frame rate again, for a while after the last one.
+
+use crate::*;
+use raylib::prelude::{MouseButton, RaylibHandle};
+
+/// Frames per second while asleep, which is also how quickly it notices input
+const IDLE_FPS: u32 = 4;
+/// Seconds the window stays awake after the last thing happened
+const WAKE_SECONDS: f32 = 2.0;
+
+pub struct Idle {
+    /// frames per second while awake
+    fps: u32,
+    awake_for: f32,
+    asleep: bool,
+    mouse: Vector2,
+}
+
+impl Idle {
+    pub fn new(fps: u32) -> Self {
+        Self {
+            fps,
+            awake_for: WAKE_SECONDS,
+            asleep: false,
+            mouse: Vector2::zero(),
+        }
+    }
+
+    /// Whether the user did anything since the last frame. Drains the queue of pressed keys,
+    /// so it needs to come after anything typing them
+    pub fn input(&mut self, rl: &mut RaylibHandle) -> bool {
+        let mut input = false;
+        while rl.get_key_pressed_number().is_some() {
+            input = true;
+        }
+        let mouse = rl.get_mouse_position();
+        let mouse = Vector2::new(mouse.x, mouse.y);
+        input |= mouse != self.mouse;
+        self.mouse = mouse;
+        input |= [
+            MouseButton::MOUSE_LEFT_BUTTON,
+            MouseButton::MOUSE_RIGHT_BUTTON,
+            MouseButton::MOUSE_MIDDLE_BUTTON,
+        ]
+        .into_iter()
+        .any(|button| rl.is_mouse_button_down(button));
+        input
+            || rl.get_mouse_wheel_move() != 0.0
+            || rl.get_touch_points_count() > 0
+            || rl.is_window_resized()
+    }
+
+    /// Wakes up when something is `busy` or else falls asleep after a while, `seconds` after
+    /// the last frame
+    pub fn update(&mut self, rl: &mut RaylibHandle, busy: bool, seconds: f32) {
+        self.awake_for = if busy {
+            WAKE_SECONDS
+        } else {
+            self.awake_for - seconds
+        };
+        let asleep = self.awake_for <= 0.0;
+        if asleep != self.asleep {
+            self.asleep = asleep;
+            rl.set_target_fps(if asleep { IDLE_FPS } else { self.fps });
+        }
+    }
+}
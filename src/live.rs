@@ -0,0 +1,236 @@
+//! Live parameter control over OSC (UDP) and optionally MIDI control change messages
+
+use std::net::UdpSocket;
+
+use crate::Config;
+
+/// Parameters that can be changed while the tree is growing
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Param {
+    SunAngle,
+    GrowthSpeed,
+    Wind,
+}
+
+impl Param {
+    /// OSC address of the parameter
+    pub fn address(self) -> &'static str {
+        match self {
+            Param::SunAngle => "/bonsai/sun_angle",
+            Param::GrowthSpeed => "/bonsai/growth_speed",
+            Param::Wind => "/bonsai/wind",
+        }
+    }
+    /// MIDI control change number of the parameter
+    #[cfg(feature = "midi")]
+    pub fn controller(self) -> u8 {
+        match self {
+            Param::SunAngle => 1,
+            Param::GrowthSpeed => 2,
+            Param::Wind => 3,
+        }
+    }
+    pub fn all() -> [Param; 3] {
+        [Param::SunAngle, Param::GrowthSpeed, Param::Wind]
+    }
+}
+
+/// A parameter set to a value in 0..=1, which gets mapped to the range of the parameter
+#[derive(Copy, Clone, Debug)]
+pub struct ParamChange {
+    pub param: Param,
+    pub value: f32,
+}
+
+impl ParamChange {
+    pub fn apply(&self, config: &mut Config) {
+        let value = self.value.clamp(0.0, 1.0);
+        match self.param {
+            Param::SunAngle => config.sun_angle = value * std::f32::consts::TAU,
+            Param::GrowthSpeed => config.growth_speed = (value * 20.0).round() as usize,
+            // up to a storm's worth
+            Param::Wind => config.wind = value * 16.0,
+        }
+    }
+}
+
+/// Receives OSC messages like `/bonsai/sun_angle 0.25` on a UDP port
+pub struct OscListener {
+    socket: UdpSocket,
+}
+
+impl OscListener {
+    pub fn bind(port: u16) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", port))?;
+        socket.set_nonblocking(true)?;
+        Ok(Self { socket })
+    }
+    /// Returns all changes received since the last poll, never blocks
+    pub fn poll(&self) -> Vec<ParamChange> {
+        let mut changes = vec![];
+        let mut buf = [0u8; 1536];
+        while let Ok(len) = self.socket.recv(&mut buf) {
+            parse_packet(&buf[..len], &mut changes);
+        }
+        changes
+    }
+}
+
+/// Reads a null terminated string padded to four bytes
+fn osc_string(data: &[u8]) -> Option<(&str, &[u8])> {
+    let end = data.iter().position(|b| *b == 0)?;
+    let s = std::str::from_utf8(&data[..end]).ok()?;
+    let padded = (end + 4) & !3;
+    Some((s, data.get(padded..)?))
+}
+
+fn osc_u32(data: &[u8]) -> Option<(u32, &[u8])> {
+    let bytes = data.get(..4)?.try_into().ok()?;
+    Some((u32::from_be_bytes(bytes), &data[4..]))
+}
+
+fn parse_packet(data: &[u8], changes: &mut Vec<ParamChange>) -> Option<()> {
+    if data.starts_with(b"#bundle\0") {
+        // skip the time tag, then every element is prefixed with its size
+        let mut rest = data.get(16..)?;
+        while !rest.is_empty() {
+            let (size, tail) = osc_u32(rest)?;
+            let element = tail.get(..size as usize)?;
+            parse_packet(element, changes);
+            rest = &tail[size as usize..];
+        }
+        return Some(());
+    }
+
+    let (address, rest) = osc_string(data)?;
+    let param = Param::all().into_iter().find(|p| p.address() == address)?;
+    let (tags, rest) = osc_string(rest)?;
+    let (raw, _) = osc_u32(rest)?;
+    let value = match tags.as_bytes().get(1)? {
+        b'f' => f32::from_bits(raw),
+        // integers are interpreted like MIDI values
+        b'i' => raw as i32 as f32 / 127.0,
+        _ => return None,
+    };
+    changes.push(ParamChange { param, value });
+    Some(())
+}
+
+#[cfg(feature = "midi")]
+pub use midi::MidiListener;
+
+#[cfg(feature = "midi")]
+mod midi {
+    use std::sync::mpsc::{channel, Receiver};
+
+    use midir::{MidiInput, MidiInputConnection};
+
+    use super::{Param, ParamChange};
+
+    /// Listens for control change messages on the first available MIDI input
+    pub struct MidiListener {
+        _connection: MidiInputConnection<()>,
+        changes: Receiver<ParamChange>,
+    }
+
+    impl MidiListener {
+        pub fn connect() -> Option<Self> {
+            let input = MidiInput::new("pixel-bonsai").ok()?;
+            let port = input.ports().into_iter().next()?;
+            let (sender, changes) = channel();
+            let connection = input
+                .connect(
+                    &port,
+                    "pixel-bonsai-control",
+                    move |_, message, _| {
+                        // control change on any channel
+                        if let [status, controller, value] = *message {
+                            if status & 0xf0 != 0xb0 {
+                                return;
+                            }
+                            let param = Param::all()
+                                .into_iter()
+                                .find(|p| p.controller() == controller);
+                            if let Some(param) = param {
+                                let value = value as f32 / 127.0;
+                                let _ = sender.send(ParamChange { param, value });
+                            }
+                        }
+                    },
+                    (),
+                )
+                .ok()?;
+            Some(Self {
+                _connection: connection,
+                changes,
+            })
+        }
+        /// Returns all changes received since the last poll, never blocks
+        pub fn poll(&self) -> Vec<ParamChange> {
+            self.changes.try_iter().collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An OSC message with a single argument of type `tag`
+    fn message(address: &str, tag: char, raw: u32) -> Vec<u8> {
+        let mut data = vec![];
+        for text in [address.to_string(), format!(",{}", tag)] {
+            data.extend_from_slice(text.as_bytes());
+            // at least one null, padded to four bytes
+            data.resize((data.len() + 4) & !3, 0);
+        }
+        data.extend_from_slice(&raw.to_be_bytes());
+        data
+    }
+
+    fn parse(data: &[u8]) -> Vec<(Param, f32)> {
+        let mut changes = vec![];
+        parse_packet(data, &mut changes);
+        changes
+            .into_iter()
+            .map(|change| (change.param, change.value))
+            .collect()
+    }
+
+    #[test]
+    fn decodes_floats_and_ints_behind_padded_strings() {
+        // 12 bytes of address get four bytes of padding, 17 get three
+        assert_eq!(
+            parse(&message("/bonsai/wind", 'f', 0.25f32.to_bits())),
+            [(Param::Wind, 0.25)]
+        );
+        assert_eq!(
+            parse(&message("/bonsai/sun_angle", 'i', 127)),
+            [(Param::SunAngle, 1.0)]
+        );
+        // strings, unknown addresses and cut off packets change nothing
+        assert!(parse(&message("/bonsai/wind", 's', 0)).is_empty());
+        assert!(parse(&message("/bonsai/rain", 'f', 0)).is_empty());
+        let whole = message("/bonsai/growth_speed", 'f', 0.5f32.to_bits());
+        assert!(parse(&whole[..whole.len() - 2]).is_empty());
+
+        let mut bundle = b"#bundle\0".to_vec();
+        bundle.extend_from_slice(&[0; 8]);
+        for element in [whole, message("/bonsai/wind", 'i', 0)] {
+            bundle.extend_from_slice(&(element.len() as u32).to_be_bytes());
+            bundle.extend_from_slice(&element);
+        }
+        assert_eq!(
+            parse(&bundle),
+            [(Param::GrowthSpeed, 0.5), (Param::Wind, 0.0)]
+        );
+
+        let mut config = Config::default();
+        ParamChange {
+            param: Param::Wind,
+            value: 2.0,
+        }
+        .apply(&mut config);
+        assert_eq!(config.wind, 16.0);
+    }
+}
@@ -1,476 +1,565 @@
-#![feature(drain_filter)]
+mod app_config;
+#[cfg(feature = "audio")]
+mod audio;
+#[cfg(feature = "gui")]
+mod browser;
+#[cfg(feature = "twitch")]
+mod chat;
+mod cli;
+#[cfg(feature = "gui")]
+mod compare;
+#[cfg(feature = "gui")]
+mod daemon;
+#[cfg(feature = "gui")]
+mod diff;
+#[cfg(feature = "gui")]
+mod display;
+#[cfg(feature = "gui")]
+mod gallery;
+#[cfg(feature = "gui")]
+mod gamepad;
+#[cfg(feature = "gui")]
+mod generate;
+mod hooks;
+#[cfg(feature = "gui")]
+mod hud;
+#[cfg(feature = "gui")]
+mod i18n;
+#[cfg(feature = "gui")]
+mod idle;
+#[cfg(feature = "gui")]
+mod live;
+#[cfg(feature = "gui")]
+mod overlay;
+#[cfg(feature = "gui")]
+mod shears;
+mod terminal;
+#[cfg(feature = "gui")]
+mod timeline;
+#[cfg(feature = "gui")]
+mod touch;
+#[cfg(feature = "gui")]
+mod tuning;
+#[cfg(feature = "gui")]
+mod window;
+
+use app_config::AppConfig;
+use bonsai_engine::density::DensitySource;
+use bonsai_engine::*;
+use bonsai_io::{
+    batch, config_file, density, export, morph, phylogeny, save, thumbnail, timelapse, wallpaper,
+};
+#[cfg(feature = "gui")]
+use bonsai_render::render;
 
-mod render;
-
-use std::ops::Add;
-
-use fuss::Simplex;
-use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
-use rand_chacha::ChaCha12Rng;
-use raylib::prelude::*;
-
-struct SimplexDensityPRG {
-    buf: Vec<Vec<f32>>,
-    rows: Vec<f32>,
-    sum: f32,
-}
-
-impl SimplexDensityPRG {
-    pub fn new(width: usize, height: usize) -> Self {
-        let noise = Simplex::new();
-        let mut buf = vec![vec![0f32; height]; width];
-        let mut rows = vec![0f32; width];
-        let mut sum = 0f32;
-        for x in 0..width {
-            for y in 0..height {
-                let noise_val = noise.sum_octave_2d(3, x as _, y as _, 0.5, 0.003).abs();
-                let centering = {
-                    let width = width as f32;
-                    let height = height as f32;
-
-                    let midx = width / 2.0;
-                    let dx = x as f32 - midx;
-
-                    let midy = height / 2.0;
-                    let dy = y as f32 - midy;
-
-                    let edge_pow = 3.5;
-
-                    let dist = dx.abs().powf(edge_pow) + dy.abs().powf(edge_pow);
-                    let fade0 = (0.5 * width.min(height)).powf(edge_pow);
-                    let fade1 = (0.7f32).powf(edge_pow) * fade0;
+pub fn main() {
+    let mut args = match cli::Args::parse() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
 
-                    let v = (dist - fade0) / (fade1 - fade0);
-                    let v = v.max(0.0).min(1.0);
+    if let Some(path) = &args.inspect {
+        std::process::exit(if inspect(path).is_ok() { 0 } else { 1 });
+    }
 
-                    // sin curve mapping 0,1 to 0,1 to have a smooth gradient
-                    (((v - 0.5) * std::f32::consts::PI).sin() + 1.0) * 0.5
-                };
-                let buf_val = noise_val * centering;
-                buf[x][y] = buf_val;
-                rows[x] += buf_val;
-                sum += buf_val;
+    let mut config = Config::default();
+    let mut app = AppConfig::default();
+    // keys set by the config file or the overrides, the flags only replace the ones they name
+    let mut set_keys = vec![];
+    if let Some(path) = &args.config {
+        match config_file::load_with(path, &mut config, &mut app) {
+            Ok(keys) => set_keys = keys,
+            Err(err) => {
+                eprintln!("could not load the config: {}", err);
+                std::process::exit(2);
             }
         }
-        Self { buf, rows, sum }
     }
-    pub fn sample(&self, rand: &mut ThreadRng) -> (usize, usize) {
-        let rand = rand.gen::<f32>();
-        assert!(0.0 <= rand && rand < 1.0);
-        let mut rand = rand * self.sum;
-
-        let mut x = 0;
-        while x < self.buf.len() && rand >= self.rows[x] {
-            rand -= self.rows[x];
-            x += 1;
-        }
-
-        if x == self.buf.len() {
-            x -= 1;
-        }
-
-        let mut y = 0;
-        while y < self.buf[x].len() && rand >= self.buf[x][y] {
-            rand -= self.buf[x][y];
-            y += 1;
-        }
-
-        (x, y)
+    if let Some(aspect) = args.pixel_aspect {
+        config.pixel_aspect = aspect;
     }
-}
-
-#[derive(Debug, Copy, Clone)]
-struct ColorPalette {
-    leaf: Color,
-    new_branch: Color,
-    old_branch: Color,
-}
-
-#[derive(Debug, Clone)]
-struct Config {
-    origin: Vector2,
-    attraction_dist: f32,
-    kill_dist: f32,
-    grow_dist: f32,
-    node_min_dist: f32,
-    width: f32,
-    height: f32,
-    max_children: usize,
-    max_depth: usize,
-    num_points: usize,
-    min_y_growth: f32,
-    parent_dir_factor: f32,
-    weight_display_pow: f32,
-    prune_pow: f32,
-    prune_size_ratio: f32,
-    /// Maximum branch width to grow leaves there
-    leaf_max_width: f32,
-    /// Maximum branch width to color the branch green
-    sprout_max_width: f32,
-    leaf_size: f32,
-    colors: ColorPalette,
-    node_depth_change: f32,
-    node_depth_max: usize,
-    /// How big one "pixel" is (in pixels)
-    pixel_size: usize,
-    leaves: Vec<LeafType>,
-    sky: Color,
-}
-
-#[derive(Copy, Clone, Debug)]
-struct LeafType {
-    color: Color,
-    size: f32,
-    probability: f32,
-}
-
-impl Config {
-    pub fn get_leaf_type(&self, rng: &mut ChaCha12Rng) -> LeafType {
-        let mut choice = rng.gen::<f32>();
-        assert!(!self.leaves.is_empty());
-        loop {
-            for leaf in self.leaves.iter() {
-                assert!(leaf.probability > 0.0);
-                choice -= leaf.probability;
-                if choice < 0.0 {
-                    return *leaf;
-                }
-            }
+    if args.night_sky {
+        config.night_sky = true;
+    }
+    if args.nameplate {
+        config.nameplate = true;
+    }
+    app.hooks.extend(args.hooks.iter().cloned());
+    if let Some(width) = args.width {
+        config.width = width;
+    }
+    if let Some(height) = args.height {
+        config.height = height;
+    }
+    if let Some(num_points) = args.num_points {
+        config.num_points = num_points;
+    }
+    // before the palette, which glazes it
+    if args.pot || args.pot_shape.is_some() {
+        config.pot = Some(pot::Pot::of_shape(args.pot_shape.unwrap_or_default()));
+    }
+    // and colors the hills and the ground
+    if args.scenery {
+        scenery::Scenery::default().apply(&mut config);
+    }
+    if let Some(preset) = args.preset {
+        preset.apply(&mut config);
+    }
+    if let Some(path) = &args.palette_file {
+        let applied = std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| palette::apply_file(&text, &mut config));
+        if let Err(err) = applied {
+            eprintln!("could not load the palette {}: {}", path.display(), err);
+            std::process::exit(2);
         }
     }
-}
-
-#[derive(Debug, Copy, Clone)]
-struct Node {
-    alive: bool,
-    pos: Vector2,
-    parent: Option<usize>,
-    child_count: usize,
-    /// distance to root
-    depth: usize,
-    /// amount of children attached to this node + 1
-    weight: usize,
-    z: f32,
-}
-
-impl Node {
-    fn new_root(pos: Vector2) -> Self {
-        Self {
-            alive: true,
-            pos,
-            parent: None,
-            child_count: 0,
-            depth: 0,
-            weight: 1,
-            z: 0.0,
+    if let Some(scale) = args.ui_scale {
+        app.ui_scale = scale;
+    }
+    if args.high_contrast {
+        config.high_contrast = true;
+    }
+    if let Some(language) = &args.language {
+        app.language = Some(language.clone());
+    }
+    if let Some(palette) = &args.palette {
+        // the blossoms share the canopy evenly, keeping the sizes the kinds had
+        let probability = 1.0 / (palette.len() - 1) as f32;
+        config.colors.old_branch = palette[0];
+        config.leaves = palette[1..]
+            .iter()
+            .enumerate()
+            .map(|(idx, color)| LeafType {
+                color: *color,
+                probability,
+                size: config.leaves.get(idx).map_or(1.0, |leaf| leaf.size),
+            })
+            .collect();
+    }
+    if let Some(degrees) = args.sun_angle {
+        config.sun_angle = degrees.to_radians();
+    }
+    if let Some(degrees) = args.sun_elevation {
+        config.sun_elevation = degrees.to_radians();
+    }
+    if let Some(ambient) = args.ambient {
+        config.ambient = ambient;
+    }
+    if let Some(shades) = args.shades {
+        config.shades = shades;
+    }
+    if let Some(fps) = args.fps {
+        config.target_fps = fps;
+    }
+    if args.vsync {
+        config.vsync = true;
+    }
+    if args.fixed_step {
+        config.fixed_step = true;
+    }
+    if args.benchmark {
+        (config.target_fps, config.vsync, config.idle) = (0, false, false);
+    }
+    if let Some(kind) = args.noise {
+        config.noise.kind = kind;
+    }
+    for assignment in &args.overrides {
+        if let Err(err) = config_file::apply_override_with(assignment, &mut config, &mut app) {
+            eprintln!("invalid --set {}: {}", assignment, err);
+            std::process::exit(2);
         }
+        set_keys.extend(
+            assignment
+                .split_once('=')
+                .map(|(key, _)| key.trim().to_string()),
+        );
     }
-    fn new_branch(pos: Vector2, parent_idx: usize, parent: Node, config: &Config) -> Self {
-        let z_change = (2.0 * rand::random::<f32>() - 1.0) * config.node_depth_change;
-        let z = parent.z + z_change;
-        let z = z.max(0.0).min(config.node_depth_max as _);
-        Self {
-            alive: true,
-            pos,
-            parent: Some(parent_idx),
-            child_count: 0,
-            depth: parent.depth + 1,
-            weight: 1,
-            z,
+    // a seed from the config grows the same tree every time, like one from --seed
+    if args.seed.is_none() && set_keys.iter().any(|key| key == "seed") {
+        args.seed = Some(config.seed);
+    }
+    // a chosen palette without a glaze of its own glazes the pot to match the tree
+    let chosen = args.preset.is_some() || args.palette.is_some() || args.palette_file.is_some();
+    let new_pot = args.pot || args.pot_shape.is_some();
+    if let Some(pot) = config
+        .pot
+        .as_mut()
+        .filter(|pot| chosen && new_pot && pot.decorated)
+    {
+        *pot = pot.colored_from(&config.colors);
+    }
+    if args.roots {
+        config.roots = Some(roots::Roots::default());
+    }
+    if args.volume || args.yaw.is_some() {
+        let volume = config.volume.get_or_insert_with(volume::Volume::default);
+        if let Some(yaw) = args.yaw {
+            volume.yaw = yaw.to_radians();
         }
     }
-}
-
-#[derive(Copy, Clone, PartialEq, Eq)]
-enum DrawMode {
-    Debug,
-    Pretty,
-}
+    // the trunk grows from the soil, high enough above the bottom edge for the pot to show
+    if let Some(pot) = &config.pot {
+        config.origin.y = config.origin.y.max(pot.reach_below() + 10.0);
+    }
+    // and for the roots below it
+    if let Some(roots) = &config.roots {
+        config.origin.y = config.origin.y.max(roots.depth + 10.0);
+    }
+    if args.kusamono {
+        config.kusamono = Some(kusamono::Kusamono::default());
+    }
+    if let Some(seed) = args.ivy {
+        config.ivy = Some(ivy::Ivy {
+            seed: seed.unwrap_or_default(),
+            ..ivy::Ivy::default()
+        });
+    }
+    if let Some(count) = args.forest {
+        config.forest = Some(count);
+    }
 
-#[derive(Clone)]
-struct Tree {
-    nodes: Vec<Node>,
-    config: Config,
-    points: Vec<Vector2>,
-    growing: bool,
-    to_be_added: Vec<Node>,
-}
+    // before the season, which turns it into snow in winter
+    if let Some(falling) = args.precipitation {
+        config.precipitation = Some(falling);
+    }
+    if args.calendar {
+        let season = season::Season::current();
+        println!("following the calendar: {:?}", season);
+        season.apply(&mut config);
+    }
+    if let Some(season) = args.season {
+        season.apply(&mut config);
+    }
+    if args.tokonoma {
+        tokonoma::Tokonoma::default().apply(&mut config);
+    }
+    if let Some(hour) = args.time_of_day {
+        daytime::TimeOfDay::new(hour).apply(&mut config);
+    }
+    if let Some(sprites) = &args.leaf_sprites {
+        config.leaf_sprites = Some(sprites.clone());
+    }
+    if let Some(path) = &args.background {
+        config.background = Some(background::Background {
+            path: path.clone(),
+            fit: args.background_fit.unwrap_or(background::Fit::Contain),
+            pixelate: args.pixelate_background,
+        });
+    }
 
-impl Tree {
-    fn new_min_growth(config: Config, iter: usize) -> Self {
-        let mut tree = Self::new(config.clone());
-        for _ in 0..iter {
-            tree.sim();
-        }
-        if tree.nodes.len() < iter {
-            Self::new_min_growth(config, iter)
-        } else {
-            tree
+    let load_density = |path| match density::load(path) {
+        Ok(map) => std::sync::Arc::new(map),
+        Err(err) => {
+            eprintln!("could not load the density: {}", err);
+            std::process::exit(2);
         }
+    };
+    if let Some(path) = &args.density {
+        config.density = DensitySource::Map(load_density(path));
+    } else if let Some(path) = &args.density_mask {
+        config.density = DensitySource::Mask(load_density(path));
     }
-    fn new(config: Config) -> Self {
-        let prg_map = SimplexDensityPRG::new(config.width as _, config.height as _);
-        let points = (0..config.num_points)
-            .map(|_| {
-                let (x, y) = prg_map.sample(&mut rand::thread_rng());
-                Vector2::new(x as f32, y as f32)
-            })
-            .collect::<Vec<_>>();
-        Self {
-            nodes: vec![Node::new_root(config.origin)],
-            config,
-            points,
-            growing: true,
-            to_be_added: vec![],
-        }
+
+    if let Some(out) = &args.export_density {
+        config.seed = args.seed.unwrap_or_else(random_seed);
+        let written = write_file(out, |file| density::write_png(&config, file));
+        std::process::exit(if written.is_ok() { 0 } else { 1 });
     }
-    fn render(&self, d: &mut RaylibDrawHandle, mode: DrawMode) {
-        let map_pos = |pos: &Vector2| Vector2::new(pos.x, self.config.height - pos.y);
-        match mode {
-            DrawMode::Debug => {
-                for point in &self.points {
-                    d.draw_circle_v(map_pos(point), 0.99, Color::BLACK);
-                }
-                for node in self.nodes.iter() {
-                    let color = if node.alive { Color::BLUE } else { Color::RED };
-                    let pos = map_pos(&node.pos);
-                    if let Some(parent_idx) = node.parent {
-                        d.draw_line_v(map_pos(&self.nodes[parent_idx].pos), pos, color);
-                    }
-                    d.draw_circle_v(pos, self.radius_of(node), color);
-                }
-            }
-            DrawMode::Pretty => {
-                for node in self.nodes.iter().filter(|n| n.alive) {
-                    let mut leaf = false;
-                    let radius = self.radius_of(node);
 
-                    let color = if radius < self.config.leaf_max_width {
-                        leaf = true;
-                        self.config.colors.leaf
-                    } else if radius < self.config.sprout_max_width {
-                        self.config.colors.new_branch
-                    } else {
-                        self.config.colors.old_branch
-                    };
+    if let Some((size, path)) = &args.thumbnail {
+        let tree = save::load(path, &config).unwrap_or_else(|err| {
+            eprintln!("could not open {}: {}", path.display(), err);
+            std::process::exit(1);
+        });
+        let out = args.out.as_ref().unwrap();
+        let written = write_file(out, |file| thumbnail::write_png(tree, *size, file));
+        std::process::exit(if written.is_ok() { 0 } else { 1 });
+    }
 
-                    let pos = map_pos(&node.pos);
-                    if let Some(parent_idx) = node.parent {
-                        for i in 0..10 {
-                            let f = (i as f32 + 1.0) / 10.0;
-                            d.draw_circle_v(
-                                pos.lerp(map_pos(&self.nodes[parent_idx].pos), f),
-                                radius,
-                                color,
-                            );
-                        }
-                    }
-                    d.draw_circle_v(pos, radius, color);
-                    if leaf {
-                        d.draw_circle_v(pos, self.config.leaf_size, color.fade(0.1));
-                    }
-                }
-            }
+    if !args.prune_selection.is_empty() || !args.recolor_selection.is_empty() {
+        let path = args.open.as_ref().unwrap();
+        if style_selections(&args, path, &config).is_err() {
+            std::process::exit(1);
         }
+        return;
     }
 
-    pub fn radius_of(&self, node: &Node) -> f32 {
-        0.5 + (node.weight as f32).powf(self.config.weight_display_pow)
+    if let Some(format) = &args.format {
+        if export_format(&args, format, config).is_err() {
+            std::process::exit(1);
+        }
+        return;
     }
 
-    fn sim(&mut self) {
-        if !self.growing {
+    if !args.compare.is_empty() {
+        #[cfg(feature = "gui")]
+        {
+            config.seed = args.seed.unwrap_or_else(random_seed);
+            if let Err(err) = compare::show(config, &app, &args.compare) {
+                eprintln!("invalid --compare: {}", err);
+                std::process::exit(2);
+            }
             return;
         }
-
-        if !self.to_be_added.is_empty() {
-            let node = self.to_be_added.pop().unwrap();
-            self.nodes[node.parent.unwrap()].child_count += 1;
-            self.nodes.push(node);
-            if !self.to_be_added.is_empty() {
-                return;
+        #[cfg(not(feature = "gui"))]
+        {
+            eprintln!("built without the `gui` feature, can't compare looks");
+            std::process::exit(2);
+        }
+    }
+    if let Some((a, b)) = &args.diff {
+        #[cfg(feature = "gui")]
+        {
+            if let Err(err) = diff::show(a, b, &config, &app) {
+                eprintln!("could not compare the trees: {}", err);
+                std::process::exit(1);
             }
+            return;
+        }
+        #[cfg(not(feature = "gui"))]
+        {
+            eprintln!(
+                "built without the `gui` feature, can't show {} and {}",
+                a, b
+            );
+            std::process::exit(2);
         }
+    }
 
-        let mut new_nodes = vec![];
-        for (node_idx, node) in self.nodes.iter().enumerate() {
-            if node.child_count >= self.config.max_children || !node.alive {
-                continue;
-            }
-            let near_points = self
-                .points
-                .iter()
-                .map(|p| *p - node.pos)
-                .filter(|p| {
-                    p.length_sqr() < self.config.attraction_dist * self.config.attraction_dist
-                })
-                .collect::<Vec<_>>();
-            if near_points.is_empty() {
-                continue;
-            }
-            let avg_dir = near_points
-                .into_iter()
-                .fold(Vector2::zero(), Add::add)
-                .normalized()
-                * self.config.grow_dist;
+    if args.terminal {
+        if let Err(err) = terminal::run(&args, config, &app) {
+            eprintln!("could not draw to the terminal: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
 
-            // in similar dir as parent
-            let prev_dir = if let Some(parent) = node.parent {
-                node.pos - self.nodes[parent].pos
-            } else {
-                Vector2::new(0.0, self.config.grow_dist)
+    if let Some(target) = args.target {
+        config.seed = args.seed.unwrap_or_else(random_seed);
+        let out = args.out.unwrap_or_else(|| "bonsai.png".into());
+        let cancel = cancel::CancelToken::new();
+        cancel::catch_interrupt();
+        if !args.phylogeny.is_empty() {
+            let generations = match phylogeny::lineage(&args.phylogeny, &config) {
+                Ok(generations) => generations,
+                Err(err) => {
+                    eprintln!("could not read the lineage: {}", err);
+                    std::process::exit(1);
+                }
             };
-            let delta = avg_dir.lerp(prev_dir, self.config.parent_dir_factor);
-
-            new_nodes.push(Node::new_branch(
-                node.pos + delta,
-                node_idx,
-                *node,
-                &self.config,
-            ));
-        }
-        self.points
-            .drain_filter(|p| {
-                self.nodes.iter().any(|node| {
-                    (*p - node.pos).length_sqr() < self.config.kill_dist * self.config.kill_dist
-                })
-            })
-            .last();
-        let mut has_change = false;
-        'outer: for node in new_nodes.into_iter() {
-            if node.depth > self.config.max_depth
-                || node.pos.y - self.nodes[node.parent.unwrap()].pos.y < self.config.min_y_growth
-            {
-                continue 'outer;
+            let progress = |idx: usize, count: usize| {
+                println!("growing generation {} of {}", idx + 1, count);
+            };
+            let report = phylogeny::export(&generations, target, &out, &cancel, progress);
+            if tell(&config, &app, report).is_err() {
+                std::process::exit(1);
             }
-            for nod in self.nodes.iter() {
-                if (nod.pos - node.pos).length_sqr()
-                    < self.config.node_min_dist * self.config.node_min_dist
-                {
-                    continue 'outer;
-                }
+            if cancel.is_cancelled() {
+                std::process::exit(130);
             }
-            self.to_be_added.push(node);
-            has_change = true;
+            return;
         }
-        self.growing &= has_change;
-
-        self.prune();
-        self.recalculate_weight();
-    }
-    /// Kills small branches that are too close to big branches
-    fn prune(&mut self) {
-        let mut death_node = vec![];
-        for (node_idx, node) in self.nodes.iter().enumerate() {
-            for conflict in self.nodes.iter() {
-                let distance = (conflict.pos - node.pos).length();
-                if (node.weight as f32) < self.config.prune_size_ratio * conflict.weight as f32
-                    && distance < (conflict.weight as f32).powf(self.config.prune_pow)
-                {
-                    death_node.push(node_idx);
-                }
-            }
-
-            // transitive adding of dead nodes
-            let mut ancestor = node.parent;
-            while let Some(ancestor_idx) = ancestor {
-                if death_node.contains(&ancestor_idx) {
-                    death_node.push(node_idx);
+        let write = |config: &Config, out: &std::path::Path| {
+            let report = match (args.morph, args.timelapse) {
+                (Some(other), _) => morph::export(config, other, target, out, &cancel),
+                (None, Some(frames)) => {
+                    let hold = args
+                        .stop_motion
+                        .map_or(1, |ticks| ticks.min(u16::MAX as usize) as u16);
+                    timelapse::export(config, target, frames, hold, out, &cancel)
                 }
-                ancestor = self.nodes[ancestor_idx].parent;
+                (None, None) => wallpaper::export(
+                    config,
+                    target,
+                    args.variants,
+                    args.passes,
+                    args.vectors,
+                    out,
+                    &cancel,
+                ),
+            };
+            tell(config, &app, report)
+        };
+        if let Some(count) = args.batch {
+            let threads = args.threads.unwrap_or_else(|| {
+                std::thread::available_parallelism().map_or(1, |threads| threads.get())
+            });
+            let finished = batch::run(count, threads, config.seed, &cancel, |idx, seed| {
+                println!("tree {}: seed {}", idx, seed);
+                let config = Config {
+                    seed,
+                    ..config.clone()
+                };
+                write(&config, &batch::job_path(&out, idx))
+            });
+            println!("rendered {} of {} trees", finished, count);
+            if finished < count && !cancel.is_cancelled() {
+                std::process::exit(1);
             }
+        } else if write(&config, &out).is_err() {
+            std::process::exit(1);
         }
-
-        for death in death_node {
-            self.nodes[death].alive = false;
+        if cancel.is_cancelled() {
+            std::process::exit(130);
         }
+        return;
     }
-    fn recalculate_weight(&mut self) {
-        for node in self.nodes.iter_mut() {
-            node.weight = 1;
-        }
 
-        for node_idx in (0..self.nodes.len()).rev() {
-            let node = self.nodes[node_idx].clone();
-            if let Some(parent_idx) = node.parent {
-                self.nodes[parent_idx].weight += node.weight;
-            }
-        }
+    #[cfg(feature = "gui")]
+    window::run(&args, config, &app);
+    #[cfg(not(feature = "gui"))]
+    {
+        eprintln!("built without the `gui` feature, pass --target to render a wallpaper instead");
+        std::process::exit(2);
     }
 }
 
-pub fn main() {
-    let colors = ColorPalette {
-        leaf: Color::GREEN,
-        new_branch: Color::GREEN,
-        old_branch: Color::BROWN,
+/// Writes the save of `--open`, or a tree grown from the seed, in the format named by its extension
+fn export_format(args: &cli::Args, format: &str, mut config: Config) -> Result<(), ()> {
+    let exporters = export::Registry::builtin();
+    let Some(exporter) = exporters.get(format) else {
+        let known = exporters.iter().map(|e| e.extension()).collect::<Vec<_>>();
+        eprintln!("unknown format {}, known are {}", format, known.join(", "));
+        return Err(());
     };
-    let config = Config {
-        origin: Vector2::new(250.0, 20.0),
-        attraction_dist: 20.0,
-        kill_dist: 13.0,
-        grow_dist: 10.0,
-        node_min_dist: 8.0,
-        width: 500.0,
-        height: 500.0,
-        max_children: 3,
-        max_depth: 5000,
-        num_points: 10_000,
-        min_y_growth: 0.0,
-        parent_dir_factor: 0.1,
-        weight_display_pow: 0.45,
-        prune_pow: 0.35,
-        prune_size_ratio: 0.01,
-        leaf_max_width: 1.51,
-        sprout_max_width: 3.5,
-        leaf_size: 20.0,
-        node_depth_change: 1.0,
-        node_depth_max: 5,
-        pixel_size: 6,
-        colors,
-        sky: Color::from_hex("CFF7E5").unwrap(),
-        leaves: vec![
-            LeafType {
-                color: Color::from_hex("ffe0e0").unwrap(),
-                probability: 0.8,
-                size: 2.5,
-            },
-            LeafType {
-                color: Color::from_hex("FF5173").unwrap(),
-                probability: 0.2,
-                size: 1.0,
-            },
-        ],
+    let tree = match &args.open {
+        Some(path) => save::load(path, &config)
+            .map_err(|err| eprintln!("could not open {}: {}", path.display(), err))?,
+        None => {
+            config.seed = args.seed.unwrap_or_else(random_seed);
+            let cancel = cancel::CancelToken::new();
+            cancel::catch_interrupt();
+            let mut tree = Tree::new_min_growth(config, 5);
+            tree.grow_fully(&cancel);
+            tree
+        }
     };
+    let out = args
+        .out
+        .clone()
+        .unwrap_or_else(|| format!("bonsai.{}", exporter.extension()).into());
+    write_file(out, |file| exporter.write(&tree, file))?;
+    for warning in exporter.warnings(&tree) {
+        eprintln!("warning: {}", warning);
+    }
+    Ok(())
+}
 
-    let (mut rl, thread) = raylib::init()
-        .size(config.width as i32 + 100, config.height as i32 + 100)
-        .title("Sakura")
-        .build();
-
-    let mut regenerated = false;
-    let mut idx = 0;
-    'regenerate: while !rl.window_should_close() {
-        let mut tree = Tree::new_min_growth(config.clone(), 5);
-
-        rl.set_target_fps(60);
+/// Prints what an exported image was made from, as its text chunks tell
+fn inspect(path: &std::path::Path) -> Result<(), ()> {
+    let chunks = std::fs::File::open(path)
+        .and_then(|file| export::metadata::read(std::io::BufReader::new(file)))
+        .map_err(|err| eprintln!("could not read {}: {}", path.display(), err))?;
+    if chunks.is_empty() {
+        eprintln!(
+            "{} tells nothing about what it was made from",
+            path.display()
+        );
+        return Err(());
+    }
+    for (keyword, text) in chunks {
+        if text.contains('\n') {
+            println!("{}:\n{}", keyword, text.trim_end());
+        } else {
+            println!("{}: {}", keyword, text);
+        }
+    }
+    Ok(())
+}
 
-        while !rl.window_should_close() {
-            if rl.is_key_down(KeyboardKey::KEY_R) && !regenerated {
-                regenerated = true;
-                continue 'regenerate;
+/// Recolors and cuts off the selection sets of the save at `path` the flags name, writing the
+/// styled tree to `--out` or back over the save
+fn style_selections(args: &cli::Args, path: &std::path::Path, config: &Config) -> Result<(), ()> {
+    let mut tree = save::load(path, config)
+        .map_err(|err| eprintln!("could not open {}: {}", path.display(), err))?;
+    let unknown = |tree: &Tree, name: &str| {
+        let names = tree
+            .selections()
+            .iter()
+            .map(|set| format!("\"{}\"", set.name))
+            .collect::<Vec<_>>();
+        eprintln!(
+            "{} has no selection \"{}\", it has {}",
+            path.display(),
+            name,
+            if names.is_empty() {
+                "none".to_string()
+            } else {
+                names.join(", ")
             }
-            let mut d = rl.begin_drawing(&thread);
-            d.clear_background(config.sky);
-            tree.sim();
-            tree.sim();
-            tree.sim();
-            let pretty = render::PrettyRender::new(tree.clone());
-            pretty.render(&mut d);
-            regenerated = false;
-            idx += 1;
+        );
+    };
+    for (name, color) in &args.recolor_selection {
+        if !tree.recolor_selection(name, *color) {
+            unknown(&tree, name);
+            return Err(());
+        }
+    }
+    for name in &args.prune_selection {
+        if !tree.prune_selection(name) {
+            unknown(&tree, name);
+            return Err(());
+        }
+    }
+    let out = args.out.as_deref().unwrap_or(path);
+    write_file(out, |file| save::write(&tree, file))
+}
+
+/// Writes the file with `export::save`, telling how it went
+fn write_file(
+    path: impl AsRef<std::path::Path>,
+    write: impl FnOnce(&mut std::io::BufWriter<std::fs::File>) -> std::io::Result<()>,
+) -> Result<(), ()> {
+    let path = path.as_ref();
+    match export::save(path, write) {
+        Ok(()) => {
+            println!("wrote {}", path.display());
+            Ok(())
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            Err(())
         }
+    }
+}
 
-        break;
+/// Tells what an export wrote and warned about, and runs the hooks of the tree it grew and the
+/// wallpapers it wrote
+fn tell(
+    config: &Config,
+    app: &AppConfig,
+    report: std::io::Result<export::Report>,
+) -> std::io::Result<()> {
+    let report = match report {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("{}", err);
+            return Err(err);
+        }
+    };
+    for path in &report.written {
+        println!("wrote {}", path.display());
+    }
+    for warning in &report.warnings {
+        eprintln!("warning: {}", warning);
     }
+    let seed = config.seed;
+    if let Some(nodes) = report.grown {
+        app.fire_hooks(&hooks::Event::GrowthFinished { seed, nodes });
+    }
+    for path in report.wallpapers {
+        app.fire_hooks(&hooks::Event::WallpaperWritten { seed, path });
+    }
+    Ok(())
+}
+
+/// Seed for a new random tree, printed as its phrase to grow it again with `--seed`
+fn random_seed() -> u64 {
+    let phrase = seed::random_phrase(&mut rand::thread_rng());
+    println!("seed: {}", phrase);
+    seed::from_phrase(&phrase)
 }
@@ -0,0 +1,64 @@
+//! `F3` shows the numbers of the first tree, see `Tree::stats`, and how long growing and drawing
+//! the trees takes every frame, in the bottom right corner
+//!
+//! The lines name things like the config keys do, in English whatever the language of the overlays.
+
+use std::time::Duration;
+
+use crate::*;
+use raylib::prelude::RaylibDraw;
+
+/// Share a new frame has in the times shown, so they don't flicker from frame to frame
+const SMOOTHING: f32 = 0.1;
+
+#[derive(Clone, Debug, Default)]
+pub struct Overlay {
+    pub open: bool,
+    /// seconds growing the trees took in the last frames, on average
+    grow: f32,
+    /// seconds painting the trees took in the last frames, on average
+    draw: f32,
+}
+
+impl Overlay {
+    /// Notes how long growing and painting the trees took this frame
+    pub fn record(&mut self, grow: Duration, draw: Duration) {
+        self.grow += (grow.as_secs_f32() - self.grow) * SMOOTHING;
+        self.draw += (draw.as_secs_f32() - self.draw) * SMOOTHING;
+    }
+
+    pub fn draw(
+        &self,
+        d: &mut impl RaylibDraw,
+        hud: &hud::Hud,
+        tree: &Tree,
+        width: i32,
+        height: i32,
+    ) {
+        if !self.open {
+            return;
+        }
+        let stats = tree.stats();
+        let lines = [
+            format!(
+                "nodes {} ({} alive, {} dead)",
+                stats.nodes, stats.alive, stats.dead
+            ),
+            format!("attraction points {}", stats.points),
+            format!("step {}", stats.steps),
+            format!("height {:.0}, width {:.0}", stats.height, stats.width),
+            format!("depth {}, branches {}", stats.max_depth, stats.branches),
+            format!(
+                "grow {:.2} ms, draw {:.2} ms",
+                self.grow * 1000.0,
+                self.draw * 1000.0
+            ),
+        ];
+        let size = 16;
+        for (idx, line) in lines.iter().rev().enumerate() {
+            let x = width - hud.px(10) - raylib::text::measure_text(line, hud.px(size));
+            let y = height - hud.px(30) - idx as i32 * hud.px(20);
+            hud.text(d, line, x, y, size, Color::DARKGRAY);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Pruning by hand: a tap cuts off the branch under the finger, a box dragged with the right
+//! mouse button cuts off every branch in it
+//!
+//! Cut branches die like the ones `prune` kills during growth, everything growing from them
+//! with them. Cutting with shift held takes them off the tree instead and frees the attraction
+//! points they had reached, so the rest of the tree grows into the gap. Ctrl+Z puts the last
+//! cuts back, one at a time.
+//!
+//! `L` names a selection set instead, see `bonsai_engine::selection`: while one is named, taps
+//! and boxes add the branches to it rather than cutting them, until `L` and `Enter` end it.
+
+use std::collections::VecDeque;
+
+use crate::*;
+use bonsai_render::window::ToRaylib;
+use raylib::prelude::{KeyboardKey, MouseButton, RaylibDraw, RaylibHandle, Rectangle};
+use render::PrettyRender;
+
+/// How many cuts can be undone
+const UNDO_CUTS: usize = 16;
+
+#[derive(Default)]
+pub struct Shears {
+    /// screen position the right mouse button went down at, while a box is being dragged
+    anchor: Option<Vector2>,
+    /// the trees a cut changed as they were before it, with the slot they are shown in
+    undo: VecDeque<Vec<(usize, Tree)>>,
+    /// name of a selection set as it is being typed
+    naming: Option<String>,
+    /// selection set taps and boxes add to instead of cutting
+    set: Option<String>,
+}
+
+impl Shears {
+    /// Screen corners of the box let go of this frame
+    pub fn poll(&mut self, rl: &RaylibHandle) -> Option<(Vector2, Vector2)> {
+        let mouse = rl.get_mouse_position();
+        let mouse = Vector2::new(mouse.x, mouse.y);
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_RIGHT_BUTTON) {
+            self.anchor = Some(mouse);
+        }
+        if rl.is_mouse_button_released(MouseButton::MOUSE_RIGHT_BUTTON) {
+            return self.anchor.take().map(|anchor| (anchor, mouse));
+        }
+        None
+    }
+
+    /// Whether the keys type the name of a selection set, and do nothing else
+    pub fn is_naming(&self) -> bool {
+        self.naming.is_some()
+    }
+
+    /// Opens the prompt for the name of a selection set with `L` and types into it, `Enter`
+    /// names the set the next branches are added to, an empty name goes back to cutting
+    pub fn name_set(&mut self, rl: &mut RaylibHandle) {
+        let Some(name) = &mut self.naming else {
+            if rl.is_key_pressed(KeyboardKey::KEY_L) {
+                self.naming = Some(String::new());
+                // the L itself isn't part of the name
+                while rl.get_key_pressed_number().is_some() {}
+            }
+            return;
+        };
+        while let Some(key) = rl.get_key_pressed_number() {
+            // raylib numbers letters, digits and the space by their ASCII code
+            match char::from_u32(key).filter(|c| c.is_ascii_alphanumeric() || *c == ' ') {
+                Some(c) => name.push(c.to_ascii_lowercase()),
+                None if key == KeyboardKey::KEY_BACKSPACE as u32 => {
+                    name.pop();
+                }
+                None => {}
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            self.naming = None;
+        } else if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            let name = self.naming.take().unwrap_or_default();
+            let name = name.trim();
+            self.set = (!name.is_empty()).then(|| name.to_string());
+        }
+    }
+
+    /// Cuts the nodes `pick` chooses in the tree of every slot, see the module docs for `release`,
+    /// or adds them to the selection set that is named
+    pub fn cut(
+        &mut self,
+        renders: &mut [PrettyRender],
+        release: bool,
+        mut pick: impl FnMut(usize, &Tree) -> Vec<usize>,
+    ) {
+        if let Some(name) = &self.set {
+            for (slot, pretty) in renders.iter_mut().enumerate() {
+                let picked = pick(slot, pretty.tree());
+                if !picked.is_empty() {
+                    let tree = pretty.tree_mut();
+                    tree.select(name, &picked);
+                    let count = tree.selection(name).map_or(0, |set| set.nodes.len());
+                    println!("selection \"{}\": {} branches", name, count);
+                }
+            }
+            return;
+        }
+        let mut before = vec![];
+        for (slot, pretty) in renders.iter_mut().enumerate() {
+            let cuts = pick(slot, pretty.tree());
+            if cuts.is_empty() {
+                continue;
+            }
+            before.push((slot, pretty.tree().clone()));
+            let tree = pretty.tree_mut();
+            if release {
+                tree.cut_away(&cuts);
+            } else {
+                for idx in cuts {
+                    tree.kill_subtree(idx);
+                }
+            }
+        }
+        if !before.is_empty() {
+            if self.undo.len() == UNDO_CUTS {
+                self.undo.pop_front();
+            }
+            self.undo.push_back(before);
+        }
+    }
+
+    /// Puts the trees of the last cut back, with the light and colors they have now.
+    /// Trees that were replaced by others since stay as they are
+    pub fn undo(&mut self, renders: &mut [PrettyRender]) {
+        let Some(before) = self.undo.pop_back() else {
+            return;
+        };
+        for (slot, mut tree) in before {
+            let Some(pretty) = renders.get_mut(slot) else {
+                continue;
+            };
+            if pretty.tree().config.seed == tree.config.seed {
+                tree.config = pretty.tree().config.clone();
+                *pretty.tree_mut() = tree;
+                pretty.invalidate();
+            }
+        }
+    }
+
+    /// Outlines the box being dragged and tells which set is being named or picked
+    pub fn draw(&self, d: &mut impl RaylibDraw, hud: &hud::Hud, mouse: Vector2, height: i32) {
+        let prompt = match (&self.naming, &self.set) {
+            (Some(name), _) => Some(hud.format(i18n::Text::SelectionName, format!("{}_", name))),
+            (None, Some(name)) => Some(hud.format(i18n::Text::SelectionPicking, name)),
+            (None, None) => None,
+        };
+        if let Some(prompt) = prompt {
+            let y = height - hud.px(34);
+            hud.text(d, &prompt, hud.px(10), y, 20, Color::DARKGRAY);
+        }
+        if let Some(anchor) = self.anchor {
+            let (x, y) = (anchor.x.min(mouse.x), anchor.y.min(mouse.y));
+            let (width, height) = ((anchor.x - mouse.x).abs(), (anchor.y - mouse.y).abs());
+            d.draw_rectangle_lines_ex(
+                Rectangle::new(x, y, width, height),
+                2,
+                Color::DARKGRAY.to_raylib(),
+            );
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Growing a tree in the terminal instead of a window, without raylib
+//!
+//! The tree grows with its usual parameters, only its pixels are sized so the whole
+//! drawing fits the terminal with one pixel per half character cell.
+
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use crate::*;
+use bonsai_render::render::{Framebuffer, PrettyRender};
+use bonsai_render::terminal::{self, ColorMode};
+use composition::Camera;
+
+/// Frames per second of the growth, each grows the tree by `growth_speed` steps
+const FPS: u64 = 30;
+
+/// Grows the tree frame by frame in the terminal and leaves it standing there
+pub fn run(args: &cli::Args, mut config: Config, app: &AppConfig) -> io::Result<()> {
+    config.seed = args.seed.unwrap_or_else(random_seed);
+    let (columns, rows) = terminal::size();
+    // a line stays free below the tree, for the prompt to come back to
+    let (width, height) = (columns, rows.saturating_sub(1).max(1) * 2);
+    // half blocks are square, so the pixels are too
+    config.pixel_aspect = 1.0;
+    let fit =
+        |room: usize, world: f32| world / room.saturating_sub(CANVAS_PADDING + 1).max(1) as f32;
+    config.pixel_size = fit(width, config.width)
+        .max(fit(height, config.height))
+        .ceil()
+        .max(1.0) as usize;
+    let pixel = config.pixel_size as f32;
+    let drawing = Vector2::new(
+        (config.width / pixel).floor() + CANVAS_PADDING as f32,
+        (config.height / pixel).floor() + (CANVAS_PADDING + 1) as f32,
+    );
+    let camera = Camera {
+        // centered, standing on the bottom of the terminal
+        offset: Vector2::new(
+            ((width as f32 - drawing.x) / 2.0).floor(),
+            height as f32 - drawing.y,
+        ),
+        zoom: 1.0 / pixel,
+    };
+
+    let mode = ColorMode::detect();
+    let cancel = cancel::CancelToken::new();
+    cancel::catch_interrupt();
+    let mut out = io::stdout().lock();
+    // clears the screen and hides the cursor while the tree grows
+    write!(out, "\x1b[2J\x1b[?25l")?;
+    let mut pretty = PrettyRender::new(Tree::new(config));
+    let mut frame = Framebuffer::new(width, height, Color::new(0, 0, 0, 0));
+    frame.camera = camera;
+    let drawn = loop {
+        let start = Instant::now();
+        let tree = pretty.tree_mut();
+        let was_growing = tree.is_growing();
+        for _ in 0..tree.config.growth_speed {
+            tree.step();
+        }
+        let growing = tree.is_growing();
+        if was_growing && !growing {
+            let event = hooks::Event::GrowthFinished {
+                seed: tree.config.seed,
+                nodes: tree.node_count(),
+            };
+            app.fire_hooks(&event);
+        }
+        frame.clear(Color::new(0, 0, 0, 0));
+        pretty.render(&mut frame);
+        if let Err(err) = terminal::draw(&frame, mode, &mut out) {
+            break Err(err);
+        }
+        if !growing || cancel.is_cancelled() {
+            break Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(1000 / FPS).saturating_sub(start.elapsed()));
+    };
+    write!(out, "\x1b[?25h")?;
+    out.flush()?;
+    for warning in pretty.tree().warnings() {
+        eprintln!("warning: {}", warning);
+    }
+    drawn
+}
@@ -0,0 +1,95 @@
+//! Scrubbing back and forth through the growth of the trees
+//!
+//! `P` holds the trees where they are and shows them as they stood after any step of their
+//! growth, put together from their history, see `history`. Left and right move through the
+//! steps, faster the longer they are held. `P` again lets the trees grow on from where they are.
+
+use crate::*;
+use raylib::prelude::{KeyboardKey, RaylibDraw, RaylibHandle};
+
+/// Steps per second while left or right is held, it doubles every second they stay held
+const SCRUB_SPEED: f32 = 20.0;
+
+pub struct Timeline {
+    /// step shown, fractional while it moves
+    step: f32,
+    /// seconds left or right has been held
+    held: f32,
+    /// the trees as they stood at the step shown, with the step they were put together for
+    pasts: Vec<Option<(u32, render::PrettyRender)>>,
+}
+
+impl Timeline {
+    /// Starts at the last step of the trees, showing them as they are
+    pub fn new(renders: &[render::PrettyRender]) -> Self {
+        Self {
+            step: Self::steps(renders) as f32,
+            held: 0.0,
+            pasts: vec![],
+        }
+    }
+
+    /// Steps the tree that grew longest took
+    fn steps(renders: &[render::PrettyRender]) -> u32 {
+        renders
+            .iter()
+            .map(|pretty| pretty.tree().history().steps())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The step shown, out of all of them
+    pub fn step(&self) -> u32 {
+        self.step as u32
+    }
+
+    /// Whether left or right is held, moving through the steps
+    pub fn is_scrubbing(&self) -> bool {
+        self.held > 0.0
+    }
+
+    /// Moves through the steps with left and right
+    pub fn update(&mut self, rl: &RaylibHandle, renders: &[render::PrettyRender]) {
+        let dt = rl.get_frame_time();
+        let direction = rl.is_key_down(KeyboardKey::KEY_RIGHT) as i32 as f32
+            - rl.is_key_down(KeyboardKey::KEY_LEFT) as i32 as f32;
+        if direction == 0.0 {
+            self.held = 0.0;
+        } else {
+            // a tap moves a single step
+            let steps = if self.held == 0.0 {
+                1.0
+            } else {
+                SCRUB_SPEED * 2f32.powf(self.held) * dt
+            };
+            self.held += dt;
+            self.step = (self.step + direction * steps).clamp(0.0, Self::steps(renders) as f32);
+        }
+        self.pasts.resize_with(renders.len(), || None);
+    }
+
+    /// Tree `idx` of `renders` as it stood at the step shown, put together again when the step moved
+    pub fn past(&mut self, idx: usize, pretty: &render::PrettyRender) -> &mut render::PrettyRender {
+        let step = self.step();
+        let past = &mut self.pasts[idx];
+        if past.as_ref().is_none_or(|(shown, _)| *shown != step) {
+            *past = Some((step, render::PrettyRender::new(pretty.tree().at_step(step))));
+        }
+        &mut past.as_mut().unwrap().1
+    }
+
+    /// The step shown, in the bottom right corner
+    pub fn draw(
+        &self,
+        d: &mut impl RaylibDraw,
+        hud: &hud::Hud,
+        renders: &[render::PrettyRender],
+        width: i32,
+        height: i32,
+    ) {
+        let steps = format!("{}/{}", self.step(), Self::steps(renders));
+        let line = hud.format(i18n::Text::TimelineStep, steps);
+        let x = width - raylib::text::measure_text(&line, hud.px(20)) - hud.px(10);
+        hud.text(d, &line, x, height - hud.px(40), 20, Color::DARKGRAY);
+    }
+}
@@ -0,0 +1,97 @@
+//! Touch gestures: a tap cuts off the branch under the finger, one finger drags the view
+//! and two fingers pinch it
+//!
+//! On touchscreen laptops the finger arrives as the left mouse button, raylib only reports
+//! touch points on phones and the web, so the mouse stands in for a single finger.
+
+use crate::*;
+use raylib::prelude::{MouseButton, RaylibHandle};
+
+/// A touch that moved further than this many screen pixels was a drag, not a tap
+const TAP_SLOP: f32 = 10.0;
+/// A touch held longer than this many seconds was not a tap either
+const TAP_TIME: f64 = 0.3;
+/// Screen pixels around a tap in which it still hits a branch, about a fingertip
+pub const TAP_REACH: f32 = 24.0;
+
+/// What the fingers asked for in one frame
+#[derive(Clone, Debug)]
+pub struct Gesture {
+    /// camera movement in screen pixels
+    pub pan: Vector2,
+    /// factor the camera zooms by, around `center`
+    pub zoom: f32,
+    pub center: Vector2,
+    /// screen position of a finished tap
+    pub tap: Option<Vector2>,
+}
+
+/// The fingers of the last frame, to tell how they moved since
+#[derive(Clone, Debug, Default)]
+pub struct Touch {
+    fingers: Vec<Vector2>,
+    /// where and when the finger that may become a tap went down
+    tap: Option<(Vector2, f64)>,
+}
+
+impl Touch {
+    pub fn poll(&mut self, rl: &RaylibHandle) -> Gesture {
+        let fingers: Vec<_> = match rl.get_touch_points_count() {
+            0 if rl.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON) => {
+                let mouse = rl.get_mouse_position();
+                vec![Vector2::new(mouse.x, mouse.y)]
+            }
+            0 => vec![],
+            count => (0..count.min(2))
+                .map(|idx| {
+                    let touch = rl.get_touch_position(idx);
+                    Vector2::new(touch.x, touch.y)
+                })
+                .collect(),
+        };
+        let mut gesture = Gesture {
+            pan: Vector2::zero(),
+            zoom: 1.0,
+            center: Vector2::zero(),
+            tap: None,
+        };
+        // a finger going down or up moves the center, which is no movement of the fingers
+        if !fingers.is_empty() && fingers.len() == self.fingers.len() {
+            let (center, spread) = centroid(&fingers);
+            let (last_center, last_spread) = centroid(&self.fingers);
+            gesture.pan = center - last_center;
+            gesture.center = center;
+            if last_spread > 0.0 {
+                gesture.zoom = spread / last_spread;
+            }
+        }
+
+        let now = rl.get_time();
+        match (self.fingers.len(), fingers.len()) {
+            (0, 1) => self.tap = Some((fingers[0], now)),
+            (1, 0) => {
+                gesture.tap = self
+                    .tap
+                    .take()
+                    .filter(|(_, start)| now - start < TAP_TIME)
+                    .map(|(pos, _)| pos)
+            }
+            (_, 1) => {}
+            _ => self.tap = None,
+        }
+        if let (Some((start, _)), [finger]) = (self.tap, fingers.as_slice()) {
+            if (*finger - start).length() > TAP_SLOP {
+                self.tap = None;
+            }
+        }
+        self.fingers = fingers;
+        gesture
+    }
+}
+
+/// Center of the fingers and how far apart they are
+fn centroid(fingers: &[Vector2]) -> (Vector2, f32) {
+    let center = fingers.iter().fold(Vector2::zero(), |sum, f| sum + *f) / fingers.len() as f32;
+    let spread = fingers.iter().map(|f| (*f - center).length()).sum::<f32>();
+    (center, spread)
+}
@@ -0,0 +1,286 @@
+//! Sliders for the growth parameters, to try out tree shapes without editing the config
+//!
+//! T opens the panel. Moving a slider only changes the trees grown next, the button grows
+//! the shown seed again with the parameters as they are. The sliders set the parameters
+//! through their config file keys, so they behave like `--set`.
+
+use crate::*;
+use bonsai_render::window::ToRaylib;
+use i18n::Text;
+use raylib::prelude::{MouseButton, RaylibDraw, RaylibHandle};
+
+/// A growth parameter on a slider
+struct Slider {
+    /// key in the config file
+    key: &'static str,
+    min: f32,
+    max: f32,
+    /// counts, which move in whole steps
+    whole: bool,
+    get: fn(&Config) -> f32,
+}
+
+const SLIDERS: [Slider; 12] = [
+    Slider {
+        key: "attraction_dist",
+        min: 5.0,
+        max: 80.0,
+        whole: false,
+        get: |config| config.attraction_dist,
+    },
+    Slider {
+        key: "kill_dist",
+        min: 1.0,
+        max: 40.0,
+        whole: false,
+        get: |config| config.kill_dist,
+    },
+    Slider {
+        key: "grow_dist",
+        min: 1.0,
+        max: 30.0,
+        whole: false,
+        get: |config| config.grow_dist,
+    },
+    Slider {
+        key: "node_min_dist",
+        min: 0.0,
+        max: 20.0,
+        whole: false,
+        get: |config| config.node_min_dist,
+    },
+    Slider {
+        key: "num_points",
+        min: 500.0,
+        max: 40_000.0,
+        whole: true,
+        get: |config| config.num_points as f32,
+    },
+    Slider {
+        key: "max_children",
+        min: 1.0,
+        max: 8.0,
+        whole: true,
+        get: |config| config.max_children as f32,
+    },
+    Slider {
+        key: "min_y_growth",
+        min: -1.0,
+        max: 1.0,
+        whole: false,
+        get: |config| config.min_y_growth,
+    },
+    Slider {
+        key: "parent_dir_factor",
+        min: 0.0,
+        max: 2.0,
+        whole: false,
+        get: |config| config.parent_dir_factor,
+    },
+    Slider {
+        key: "tropism_y",
+        min: -1.0,
+        max: 1.0,
+        whole: false,
+        get: |config| config.tropism.y,
+    },
+    Slider {
+        key: "canopy_memory",
+        min: 0.0,
+        max: 2.0,
+        whole: false,
+        get: |config| config.canopy_memory,
+    },
+    Slider {
+        key: "prune_pow",
+        min: 0.0,
+        max: 1.0,
+        whole: false,
+        get: |config| config.prune_pow,
+    },
+    Slider {
+        key: "prune_size_ratio",
+        min: 0.0,
+        max: 0.2,
+        whole: false,
+        get: |config| config.prune_size_ratio,
+    },
+];
+
+/// The values on the sliders
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Params([f32; SLIDERS.len()]);
+
+impl Params {
+    pub fn of(config: &Config) -> Self {
+        Self(SLIDERS.each_ref().map(|slider| (slider.get)(config)))
+    }
+
+    /// Sets the parameters of `config` to the values on the sliders
+    pub fn apply(&self, config: &mut Config) {
+        for (slider, value) in SLIDERS.iter().zip(self.0) {
+            // every key of the sliders takes every value in its range
+            let _ = config_file::apply_override(&format!("{}={}", slider.key, value), config);
+        }
+    }
+
+    /// The `--set` flags growing trees like these parameters do
+    pub fn flags(&self) -> String {
+        SLIDERS
+            .iter()
+            .zip(self.0)
+            .map(|(slider, value)| format!("--set {}={}", slider.key, value))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// What the panel was asked for in a frame
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    None,
+    /// a slider was let go at another value
+    Changed,
+    Regrow,
+}
+
+/// Height of a slider with its label, in unscaled pixels
+const ROW: i32 = 40;
+const WIDTH: i32 = 300;
+const MARGIN: i32 = 10;
+
+#[derive(Clone, Debug, Default)]
+pub struct Panel {
+    pub open: bool,
+    /// the slider held with the mouse and its value when it was grabbed
+    dragging: Option<(usize, f32)>,
+}
+
+impl Panel {
+    /// Left, top, width and height of the panel on the screen
+    fn bounds(hud: &hud::Hud, screen_width: i32) -> (i32, i32, i32, i32) {
+        let width = hud.px(WIDTH);
+        let rows = SLIDERS.len() as i32 + 2;
+        (
+            screen_width - width - hud.px(MARGIN),
+            hud.px(MARGIN),
+            width,
+            hud.px(ROW) * rows,
+        )
+    }
+
+    /// Whether the mouse is over the open panel or drags one of its sliders,
+    /// then clicks belong to the panel instead of the trees
+    pub fn holds_mouse(&self, rl: &RaylibHandle, hud: &hud::Hud) -> bool {
+        if !self.open {
+            return false;
+        }
+        let (x, y, width, height) = Self::bounds(hud, rl.get_screen_width());
+        let mouse = rl.get_mouse_position();
+        let inside = (x as f32..(x + width) as f32).contains(&mouse.x)
+            && (y as f32..(y + height) as f32).contains(&mouse.y);
+        inside || self.dragging.is_some()
+    }
+
+    /// Moves the slider under the mouse
+    pub fn update(&mut self, rl: &RaylibHandle, hud: &hud::Hud, params: &mut Params) -> Action {
+        if !self.open {
+            self.dragging = None;
+            return Action::None;
+        }
+        let (x, y, width, _) = Self::bounds(hud, rl.get_screen_width());
+        let mouse = rl.get_mouse_position();
+        let row = ((mouse.y as i32 - y) / hud.px(ROW) - 1).max(-1);
+        let inside = (x..x + width).contains(&(mouse.x as i32));
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) && inside {
+            match row as usize {
+                idx if idx < SLIDERS.len() => self.dragging = Some((idx, params.0[idx])),
+                idx if idx == SLIDERS.len() => return Action::Regrow,
+                _ => {}
+            }
+        }
+        let Some((idx, grabbed)) = self.dragging else {
+            return Action::None;
+        };
+        let slider = &SLIDERS[idx];
+        let (left, right) = (x + hud.px(MARGIN), x + width - hud.px(MARGIN));
+        let t = ((mouse.x - left as f32) / (right - left) as f32).clamp(0.0, 1.0);
+        let mut value = slider.min + (slider.max - slider.min) * t;
+        if slider.whole {
+            value = value.round();
+        }
+        params.0[idx] = value;
+        if rl.is_mouse_button_released(MouseButton::MOUSE_LEFT_BUTTON) {
+            self.dragging = None;
+            if value != grabbed {
+                return Action::Changed;
+            }
+        }
+        Action::None
+    }
+
+    pub fn draw(
+        &self,
+        d: &mut impl RaylibDraw,
+        hud: &hud::Hud,
+        params: &Params,
+        screen_width: i32,
+    ) {
+        if !self.open {
+            return;
+        }
+        let (x, y, width, height) = Self::bounds(hud, screen_width);
+        d.draw_rectangle(x, y, width, height, Color::WHITE.fade(0.85).to_raylib());
+        let (pad, row) = (hud.px(MARGIN), hud.px(ROW));
+        hud.text(
+            d,
+            hud.tr(Text::TuningTitle),
+            x + pad,
+            y + pad,
+            20,
+            Color::BLACK,
+        );
+        for (idx, (slider, value)) in SLIDERS.iter().zip(params.0).enumerate() {
+            let top = y + row * (idx as i32 + 1);
+            let label = if slider.whole {
+                format!("{}  {}", slider.key, value)
+            } else {
+                format!("{}  {:.2}", slider.key, value)
+            };
+            hud.text(d, &label, x + pad, top + hud.px(4), 16, Color::DARKGRAY);
+            let (left, length) = (x + pad, width - 2 * pad);
+            let bar = top + hud.px(26);
+            d.draw_rectangle(
+                left,
+                bar,
+                length,
+                hud.px(4),
+                hud.ink(Color::GRAY).to_raylib(),
+            );
+            let t = (value - slider.min) / (slider.max - slider.min);
+            let knob = left + (t.clamp(0.0, 1.0) * length as f32) as i32;
+            let held = self.dragging.is_some_and(|(held, _)| held == idx);
+            let color = hud.ink(if held { Color::BLACK } else { Color::DARKGRAY });
+            d.draw_circle(knob, bar + hud.px(2), hud.px(7) as f32, color.to_raylib());
+        }
+        // the button below the sliders
+        let top = y + row * (SLIDERS.len() as i32 + 1);
+        d.draw_rectangle(
+            x + pad,
+            top + hud.px(4),
+            width - 2 * pad,
+            row - hud.px(8),
+            hud.ink(Color::DARKGRAY).to_raylib(),
+        );
+        let text = hud.tr(Text::TuningRegrow);
+        let size = hud.px(18);
+        let text_width = raylib::text::measure_text(text, size);
+        d.draw_text(
+            text,
+            x + (width - text_width) / 2,
+            top + (row - size) / 2,
+            size,
+            Color::WHITE.to_raylib(),
+        );
+    }
+}
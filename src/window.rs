@@ -0,0 +1,883 @@
+//! The window, growing and showing trees until it is closed
+
+use std::time::{Duration, Instant};
+
+use crate::*;
+use bonsai_render::density::DensityOverlay;
+use bonsai_render::window::ToRaylib;
+use raylib::prelude::{
+    KeyboardKey, RaylibDraw, RaylibHandle, RaylibMode2DExt, RaylibThread, Texture2D,
+};
+use save::SaveFile;
+
+/// Frames per second the wind sways the trees with, every frame repaints them
+const WIND_FPS: f64 = 12.0;
+
+/// Opens the window and grows trees into the slots of the composition
+pub fn run(args: &cli::Args, config: Config, app: &AppConfig) {
+    let slots = config.composition.layout(&config);
+
+    let mut builder = raylib::init();
+    builder
+        .size(
+            config.composition.output_width as i32,
+            config.composition.output_height as i32,
+        )
+        .title("Sakura");
+    if config.vsync {
+        builder.vsync();
+    }
+    if args.fullscreen {
+        builder.fullscreen();
+    }
+    if args.borderless {
+        builder.undecorated();
+    }
+    let (mut rl, thread) = builder.build();
+
+    let mut backdrop = load_backdrop(&mut rl, &thread, &config, &slots);
+    // the textures are made again when the display changed under the window
+    let mut display = display::Display::new(&rl);
+
+    let osc = app
+        .osc_port
+        .and_then(|port| match live::OscListener::bind(port) {
+            Ok(osc) => Some(osc),
+            Err(err) => {
+                eprintln!("could not listen for OSC on port {}: {}", port, err);
+                None
+            }
+        });
+    #[cfg(feature = "midi")]
+    let midi = live::MidiListener::connect();
+    #[cfg(feature = "audio")]
+    let audio = audio::AudioListener::start();
+    #[cfg(feature = "twitch")]
+    let chat = app.twitch_channel.as_ref().and_then(|channel| {
+        chat::ChatListener::connect(channel)
+            .map_err(|err| eprintln!("could not join twitch chat: {}", err))
+            .ok()
+    });
+
+    // growth parameters on the sliders of the tuning panel, for the trees grown next
+    let tuned = std::cell::Cell::new(tuning::Params::of(&config));
+    // each slot grows its own tree, all from one seed
+    let configs = |seed: u64| {
+        slots
+            .iter()
+            .enumerate()
+            .map(|(i, slot)| {
+                let mut config = slot.config.clone();
+                tuned.get().apply(&mut config);
+                if args.preview {
+                    config = config.preview();
+                }
+                config.seed = seed.wrapping_add(i as u64);
+                config
+            })
+            .collect::<Vec<_>>()
+    };
+    // the seed from the command line is only used for the first tree
+    let first_seed = args.seed.unwrap_or_else(random_seed);
+    let opened = args.open.as_ref().and_then(|path| {
+        save::load(path, &slots[0].config)
+            .map_err(|err| eprintln!("could not open {}: {}", path.display(), err))
+            .ok()
+    });
+    let mut generation = match opened {
+        Some(_) => None,
+        None => Some(generate::Generation::start(configs(first_seed))),
+    };
+    // R pressed while trees are still being generated, starts the next generation after them
+    let mut queued = false;
+    // the trees after the current ones, generated while these are shown so R swaps instantly
+    let mut upcoming: Option<(String, generate::Generation)> = None;
+    // the renderers own the trees, so their canvases get reused from frame to frame
+    let mut renders: Vec<render::PrettyRender> =
+        opened.into_iter().map(render::PrettyRender::new).collect();
+    // moved with the arrow keys, new trees keep the light the last ones had
+    let mut sun = (config.sun_angle, config.sun_elevation);
+
+    // 0 doesn't hold the frames back at all
+    rl.set_target_fps(config.target_fps);
+    // ESC cancels a generation before it closes the window
+    rl.set_exit_key(None);
+    let mut gallery: Option<gallery::Gallery> = None;
+    let mut browser: Option<browser::SeedBrowser> = None;
+    // D shows the density the attraction points were scattered with, over each tree
+    let mut show_density = false;
+    // H lists the keys and a few facts about the tree
+    let mut show_help = false;
+    let mut tuning = tuning::Panel::default();
+    // F3 shows the numbers of the first tree and the frame times
+    let mut overlay = overlay::Overlay::default();
+    // P shows the trees as they were after any step of their growth
+    let mut timeline: Option<timeline::Timeline> = None;
+    let mut densities: Vec<Option<DensityOverlay>> = vec![];
+    // when each tree last stopped growing, for `regrow_after`
+    let mut finished: Vec<Option<f64>> = vec![];
+    // moved around with a gamepad, the fingers or the mouse
+    let mut view = gamepad::View::default();
+    let mut touch = touch::Touch::default();
+    let mut shears = shears::Shears::default();
+    let hud = hud::Hud::of(&config, app);
+    // the seasons passing with `--season cycle`, starting from today's
+    let mut cycle = args
+        .season_cycle
+        .then(|| season::SeasonCycle::new(season::Season::current(), config.season_length));
+    // the hours passing with `--time-of-day cycle`, from sunrise
+    let mut day = args
+        .day_cycle
+        .then(|| daytime::DayCycle::new(6.0, config.day_length, &config));
+    // foliage the gamepad cycled to last, starting after the one the trees grow with
+    let mut palette = season::Season::Spring;
+    // storms now and then with `storm_every`, or when W calls one up
+    let mut weather = storm::Weather::new(first_seed, &config);
+    let mut sun_moving = false;
+    // hands out the growth ticks, coarse trees grow in every few of them
+    let mut ticker = pacing::Ticker::new(config.tick_rate);
+    let mut stats = pacing::FrameStats::default();
+    // slows the window down once nothing changes anymore
+    let mut sleep = config.idle.then(|| idle::Idle::new(config.target_fps));
+
+    // the tree the window showed last time, until it is resumed or a new one is grown
+    let mut resumable = save::load_autosave(&slots[0].config)
+        .map_err(|err| eprintln!("{}", err))
+        .ok()
+        .flatten();
+    if resumable.is_some() {
+        println!("found an autosaved tree, press Enter to resume it");
+    }
+    let mut autosaver = save::Autosaver::new();
+    // with `--daemon` the trees make way for new ones on their own
+    let mut daemon = args.daemon.then(|| daemon::Daemon::new(app));
+    if daemon.is_some() {
+        rl.hide_cursor();
+    }
+
+    // a panic still autosaves the tree before it takes the window down
+    let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        while !rl.window_should_close() {
+            if display.changed(&rl) {
+                backdrop = load_backdrop(&mut rl, &thread, &config, &slots);
+            }
+            let pad = gamepad::Input::poll(&rl);
+            view.update(&pad);
+            // the keys type while a selection set is named, and do nothing else,
+            // including the Enter or Esc closing the prompt
+            let typing = shears.is_naming();
+            shears.name_set(&mut rl);
+            let woken = sleep.as_mut().is_some_and(|sleep| sleep.input(&mut rl));
+            let pressed = |key| !typing && rl.is_key_pressed(key);
+            if pressed(KeyboardKey::KEY_ESCAPE) {
+                match generation.take() {
+                    Some(pending) => {
+                        pending.cancel();
+                        queued = false;
+                        if renders.is_empty() {
+                            break;
+                        }
+                    }
+                    None => break,
+                }
+            }
+            if pressed(KeyboardKey::KEY_R) || pad.regrow {
+                resumable = None;
+                match generation {
+                    Some(_) => queued = true,
+                    None => generation = Some(next_generation(&mut upcoming, configs)),
+                }
+            }
+            if let Some(trees) = generation.as_ref().and_then(generate::Generation::poll) {
+                renders = trees
+                    .into_iter()
+                    .map(|mut tree| {
+                        (tree.config.sun_angle, tree.config.sun_elevation) = sun;
+                        render::PrettyRender::new(tree)
+                    })
+                    .collect();
+                generation = None;
+                timeline = None;
+                if let Some(daemon) = &mut daemon {
+                    daemon.replaced();
+                }
+                if queued {
+                    queued = false;
+                    generation = Some(next_generation(&mut upcoming, configs));
+                }
+            }
+            if generation.is_none() && upcoming.is_none() {
+                upcoming = Some(pregenerate(configs));
+            }
+
+            // exports always use the first tree
+            if let Some(first) = renders.first() {
+                if !typing {
+                    handle_export_keys(&rl, first.tree());
+                }
+                if pressed(KeyboardKey::KEY_S) {
+                    match save::save_new(first.tree()) {
+                        Ok(path) => println!("wrote {}", path.display()),
+                        Err(err) => eprintln!("{}", err),
+                    }
+                }
+                if pressed(KeyboardKey::KEY_E) {
+                    let shown = shown_config(&config, &first.tree().config, view.framing());
+                    match config_file::export_new(&shown, app) {
+                        Ok(path) => println!("wrote {}", path.display()),
+                        Err(err) => eprintln!("could not export the config: {}", err),
+                    }
+                }
+            }
+            if pressed(KeyboardKey::KEY_B) {
+                gallery = None;
+                browser = match browser {
+                    Some(_) => None,
+                    None => {
+                        // starts at the seed of the tree that is shown
+                        let seed = renders
+                            .first()
+                            .map_or(first_seed, |first| first.tree().config.seed);
+                        Some(browser::SeedBrowser::open(
+                            seed,
+                            &slots[0].config,
+                            rl.get_screen_width(),
+                            rl.get_screen_height(),
+                        ))
+                    }
+                };
+            }
+            if let Some(seed) = browser.as_mut().and_then(|browser| browser.update(&rl)) {
+                browser = None;
+                resumable = None;
+                queued = false;
+                if let Some(pending) = generation.take() {
+                    pending.cancel();
+                }
+                println!("seed: {}", seed);
+                generation = Some(generate::Generation::start(configs(seed)));
+            }
+            if pressed(KeyboardKey::KEY_G) {
+                browser = None;
+                gallery = match gallery {
+                    Some(_) => None,
+                    None => Some(gallery::Gallery::scan(
+                        std::path::Path::new(save::SAVE_DIR),
+                        &slots[0].config,
+                    )),
+                };
+            }
+            let resumed = match (&gallery, &browser) {
+                (None, None) if pressed(KeyboardKey::KEY_ENTER) => resumable.take(),
+                (None, None) if pressed(KeyboardKey::KEY_O) => newest_save(&slots[0].config),
+                _ => None,
+            };
+            if let Some(picked) =
+                resumed.or_else(|| gallery.as_mut().and_then(|gallery| gallery.update(&rl)))
+            {
+                let picked = render::PrettyRender::new(picked);
+                match renders.first_mut() {
+                    Some(first) => *first = picked,
+                    None => renders.push(picked),
+                }
+                gallery = None;
+            }
+
+            if pressed(KeyboardKey::KEY_T) {
+                tuning.open = !tuning.open;
+            }
+            let on_panel = tuning.holds_mouse(&rl, &hud);
+            let mut params = tuned.get();
+            let action = tuning.update(&rl, &hud, &mut params);
+            tuned.set(params);
+            if action != tuning::Action::None {
+                // the trees prepared in the background grew with the old parameters
+                if let Some((_, pending)) = upcoming.take() {
+                    pending.cancel();
+                }
+                println!("parameters: {}", params.flags());
+            }
+            if action == tuning::Action::Regrow {
+                resumable = None;
+                queued = false;
+                if let Some(pending) = generation.take() {
+                    pending.cancel();
+                }
+                let seed = renders
+                    .first()
+                    .map_or(first_seed, |first| first.tree().config.seed);
+                generation = Some(generate::Generation::start(configs(seed)));
+            }
+
+            // clicks and taps belong to the gallery, the browser and the tuning panel while they are open
+            if gallery.is_none() && browser.is_none() {
+                let screen =
+                    Vector2::new(rl.get_screen_width() as f32, rl.get_screen_height() as f32);
+                let gesture = touch.poll(&rl);
+                if !on_panel {
+                    view.touch(&gesture, screen);
+                    let cursor = rl.get_mouse_position();
+                    view.scroll(
+                        rl.get_mouse_wheel_move(),
+                        Vector2::new(cursor.x, cursor.y),
+                        screen,
+                    );
+                }
+                let release = rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+                    || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+                let camera = |idx: usize, tree: &Tree| {
+                    view.apply(
+                        slots[idx].camera_fitting(tree.top()),
+                        screen,
+                        slots[idx].config.pixel_size,
+                    )
+                };
+                if let Some(tap) = gesture.tap.filter(|_| !on_panel) {
+                    shears.cut(&mut renders, release, |idx, tree| {
+                        let camera = camera(idx, tree);
+                        let world = slots[idx].to_world(camera, tap);
+                        let reach = touch::TAP_REACH / camera.zoom;
+                        tree.node_at(world.into(), reach).into_iter().collect()
+                    });
+                }
+                if let Some((from, to)) = shears.poll(&rl).filter(|_| !on_panel) {
+                    shears.cut(&mut renders, release, |idx, tree| {
+                        let camera = camera(idx, tree);
+                        let (a, b) = (
+                            slots[idx].to_world(camera, from),
+                            slots[idx].to_world(camera, to),
+                        );
+                        let min = Vector2::new(a.x.min(b.x), a.y.min(b.y));
+                        let max = Vector2::new(a.x.max(b.x), a.y.max(b.y));
+                        tree.nodes_within(min.into(), max.into())
+                    });
+                }
+                let ctrl = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+                    || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+                if ctrl && pressed(KeyboardKey::KEY_Z) {
+                    shears.undo(&mut renders);
+                }
+            }
+
+            // the arrow keys move the sun, which only relights the painted canvases.
+            // Trees grown with a volume turn with left and right instead, and the timeline
+            // takes them while it is shown
+            if gallery.is_none() && browser.is_none() && timeline.is_none() {
+                let turn = rl.get_frame_time() * 1.5;
+                let held = |key| if rl.is_key_down(key) { turn } else { 0.0 };
+                let sideways = held(KeyboardKey::KEY_LEFT) - held(KeyboardKey::KEY_RIGHT);
+                let turnable = renders
+                    .iter()
+                    .any(|pretty| pretty.tree().config.volume.is_some());
+                if turnable && sideways != 0.0 {
+                    for pretty in renders.iter_mut() {
+                        if let Some(volume) = &mut pretty.tree_mut().config.volume {
+                            volume.yaw = (volume.yaw + sideways).rem_euclid(std::f32::consts::TAU);
+                        }
+                    }
+                }
+                let azimuth = if turnable { 0.0 } else { sideways } + pad.sun.0;
+                let elevation = held(KeyboardKey::KEY_UP) - held(KeyboardKey::KEY_DOWN) + pad.sun.1;
+                let moving = azimuth != 0.0 || elevation != 0.0;
+                if moving {
+                    sun = (
+                        (sun.0 + azimuth).rem_euclid(std::f32::consts::TAU),
+                        (sun.1 + elevation).clamp(0.0, std::f32::consts::FRAC_PI_2),
+                    );
+                    for pretty in renders.iter_mut() {
+                        let config = &mut pretty.tree_mut().config;
+                        (config.sun_angle, config.sun_elevation) = sun;
+                    }
+                }
+                if sun_moving && !moving {
+                    println!(
+                        "sun: --sun-angle {:.0} --sun-elevation {:.0}",
+                        sun.0.to_degrees(),
+                        sun.1.to_degrees()
+                    );
+                }
+                sun_moving = moving;
+
+                if pad.next_palette {
+                    palette = palette.next();
+                    println!("foliage: {:?}", palette);
+                    for pretty in renders.iter_mut() {
+                        pretty.tree_mut().config.leaves = palette.leaves();
+                    }
+                }
+            }
+
+            // F grows the previews out to full quality, within the silhouette they already have
+            if pressed(KeyboardKey::KEY_F) {
+                for (pretty, slot) in renders.iter_mut().zip(slots.iter()) {
+                    if pretty.tree().config.preview {
+                        pretty.tree_mut().refine(&slot.config);
+                    }
+                }
+            }
+
+            if pressed(KeyboardKey::KEY_D) {
+                show_density = !show_density;
+            }
+            if pressed(KeyboardKey::KEY_F3) {
+                overlay.open = !overlay.open;
+            }
+            if pressed(KeyboardKey::KEY_H) {
+                show_help = !show_help;
+            }
+            if pressed(KeyboardKey::KEY_P) {
+                timeline = match timeline {
+                    Some(_) => None,
+                    None => Some(timeline::Timeline::new(&renders)),
+                };
+            }
+            if let Some(timeline) = &mut timeline {
+                timeline.update(&rl, &renders);
+            }
+
+            let changes = osc.iter().flat_map(|osc| osc.poll()).collect::<Vec<_>>();
+            #[cfg(feature = "midi")]
+            let changes = [changes, midi.iter().flat_map(|midi| midi.poll()).collect()].concat();
+            for pretty in renders.iter_mut() {
+                for change in changes.iter() {
+                    change.apply(&mut pretty.tree_mut().config);
+                }
+            }
+            #[cfg(feature = "audio")]
+            if let Some(audio) = &audio {
+                for (pretty, slot) in renders.iter_mut().zip(slots.iter()) {
+                    audio.apply(&slot.config, &mut pretty.tree_mut().config);
+                }
+            }
+            #[cfg(feature = "twitch")]
+            let commands = chat.iter().flat_map(|chat| chat.poll()).collect::<Vec<_>>();
+            #[cfg(feature = "twitch")]
+            for &command in &commands {
+                if let (chat::ChatCommand::Season(picked), Some(cycle)) = (command, &mut cycle) {
+                    *cycle = season::SeasonCycle::new(picked, config.season_length);
+                }
+                if command == chat::ChatCommand::Regrow {
+                    if generation.is_none() {
+                        generation = Some(next_generation(&mut upcoming, configs));
+                    }
+                    continue;
+                }
+                for pretty in renders.iter_mut() {
+                    command.apply(pretty.tree_mut());
+                }
+            }
+
+            let dt = rl.get_frame_time();
+            stats.record(dt);
+            // Space holds the growth, `.` steps it while held and Tab fast-forwards
+            if pressed(KeyboardKey::KEY_SPACE) {
+                ticker.toggle_pause();
+            }
+            if pressed(KeyboardKey::KEY_PERIOD) {
+                ticker.step();
+            }
+            let fast = !typing && rl.is_key_down(KeyboardKey::KEY_TAB);
+            ticker.set_speed(if fast { pacing::FAST_FORWARD } else { 1.0 });
+            let ticks = match config.target_fps {
+                fps if config.fixed_step && fps > 0 => ticker.advance(1.0 / fps as f32),
+                _ => ticker.advance(dt),
+            };
+            if let Some(cycle) = &mut cycle {
+                cycle.advance(dt);
+            }
+            if let Some(day) = &mut day {
+                day.advance(dt);
+            }
+            if pressed(KeyboardKey::KEY_W) {
+                weather.brew();
+            }
+            weather.advance(dt, &config);
+            for pretty in renders.iter_mut() {
+                if let Some(cycle) = &cycle {
+                    cycle.apply(&mut pretty.tree_mut().config);
+                }
+                if let Some(day) = &day {
+                    // darkens the sky of the season rather than the config's
+                    day.apply(&mut pretty.tree_mut().config, cycle.is_some());
+                }
+                pretty.set_storm(weather.extra_wind(&pretty.tree().config));
+                pretty.advance(dt);
+            }
+            let sky = match renders.first() {
+                Some(first) if cycle.is_some() || day.is_some() => first.tree().config.sky,
+                _ => config.sky,
+            };
+
+            if let Some(sleep) = &mut sleep {
+                let busy = woken
+                    || pad.is_active()
+                    || sun_moving
+                    || typing
+                    || !changes.is_empty()
+                    || generation.is_some()
+                    || cycle.is_some()
+                    || day.is_some()
+                    || gallery.is_some()
+                    || browser.is_some()
+                    || timeline
+                        .as_ref()
+                        .is_some_and(timeline::Timeline::is_scrubbing)
+                    || daemon.as_ref().is_some_and(daemon::Daemon::is_fading)
+                    || renders.iter().any(render::PrettyRender::is_animating);
+                #[cfg(feature = "audio")]
+                let busy = busy || audio.is_some();
+                #[cfg(feature = "twitch")]
+                let busy = busy || !commands.is_empty();
+                sleep.update(&mut rl, busy, dt);
+            }
+
+            let time = rl.get_time();
+            let mut d = rl.begin_drawing(&thread);
+            d.clear_background(sky.to_raylib());
+            let (width, height) = (d.get_screen_width(), d.get_screen_height());
+            if let Some(texture) = &backdrop {
+                d.draw_texture(texture, 0, 0, Color::WHITE.to_raylib());
+            }
+            if let Some(gallery) = &mut gallery {
+                gallery.draw(&mut d, &hud, width, height);
+                continue;
+            }
+            if let Some(browser) = &browser {
+                browser.draw(&mut d, &hud, width, height);
+                continue;
+            }
+            densities.resize_with(renders.len(), || None);
+            let (mut grow_time, mut draw_time) = (Duration::ZERO, Duration::ZERO);
+            finished.resize(renders.len(), None);
+            for (idx, (((pretty, slot), density), finished)) in renders
+                .iter_mut()
+                .zip(slots.iter())
+                .zip(&mut densities)
+                .zip(&mut finished)
+                .enumerate()
+            {
+                let screen = Vector2::new(width as f32, height as f32);
+                let camera = view.apply(
+                    slot.camera_fitting(pretty.tree().top()),
+                    screen,
+                    slot.config.pixel_size,
+                );
+                // trees off the screen wait, far ones grow in coarser steps
+                let detail = slot.detail(camera, screen, config.composition.detail_zoom);
+                if detail == composition::Detail::Paused {
+                    continue;
+                }
+                // the trees hold still while the timeline shows their past
+                if let Some(timeline) = &mut timeline {
+                    let past = timeline.past(idx, pretty);
+                    let camera = view.apply(
+                        slot.camera_fitting(past.tree().top()),
+                        screen,
+                        slot.config.pixel_size,
+                    );
+                    past.render(&mut d.begin_mode2D(camera.to_raylib()));
+                    continue;
+                }
+                let tree = pretty.tree_mut();
+                let was_growing = tree.is_growing();
+                let started = Instant::now();
+                for tick in ticks.clone() {
+                    for _ in 0..detail.steps(tree.config.growth_speed, tick) {
+                        tree.step();
+                    }
+                }
+                grow_time += started.elapsed();
+                if was_growing && !tree.is_growing() {
+                    let event = hooks::Event::GrowthFinished {
+                        seed: tree.config.seed,
+                        nodes: tree.node_count(),
+                    };
+                    app.fire_hooks(&event);
+                    for warning in tree.warnings() {
+                        eprintln!("warning: {}", warning);
+                    }
+                }
+                // a cut starts the tree growing again, and so the wait for the next flush
+                if tree.is_growing() {
+                    *finished = None;
+                } else {
+                    // looked after while it stands grown, until the eggs in its nest hatch
+                    let hatched = nest::hatched(tree);
+                    tree.tend(dt);
+                    if !hatched && nest::hatched(tree) && nest::find(tree).is_some() {
+                        println!("the eggs in the nest hatched");
+                    }
+                    let since = *finished.get_or_insert(time);
+                    let regrow_after = f64::from(tree.config.regrow_after);
+                    if regrow_after > 0.0 && time - since >= regrow_after {
+                        tree.flush();
+                        *finished = None;
+                    }
+                }
+                if tree.config.wind > 0.0 || weather.is_stormy() {
+                    // the sway moves in steps, like the frames of an animated sprite
+                    pretty.set_wind(Some((time * WIND_FPS).floor() as f32 / WIND_FPS as f32));
+                } else {
+                    pretty.set_wind(None);
+                }
+                match pretty.age() {
+                    Some(aging::Aging::Cracked(_)) => println!("a branch died back to deadwood"),
+                    Some(aging::Aging::Snapped(_)) => println!("an old branch snapped off"),
+                    None => {}
+                }
+                let camera = view.apply(
+                    slot.camera_fitting(pretty.tree().top()),
+                    screen,
+                    slot.config.pixel_size,
+                );
+                let mut d = d.begin_mode2D(camera.to_raylib());
+                let started = Instant::now();
+                pretty.render(&mut d);
+                draw_time += started.elapsed();
+                if let Some(err) = pretty.take_sprite_error() {
+                    eprintln!("{}", err);
+                }
+                if weather.is_stormy() {
+                    let snapped = pretty.snap();
+                    if snapped > 0 {
+                        println!("the storm snapped {} branches", snapped);
+                    }
+                }
+                if show_density {
+                    let config = &pretty.tree().config;
+                    if !density
+                        .as_ref()
+                        .is_some_and(|density| density.is_for(config))
+                    {
+                        *density = Some(DensityOverlay::new(config));
+                    }
+                    if let Some(density) = density {
+                        density.draw(&mut d, config);
+                    }
+                }
+            }
+            overlay.record(grow_time, draw_time);
+            // the autosave from last time is kept until it is resumed or dismissed
+            if let (Some(first), None) = (renders.first(), &resumable) {
+                autosaved(app, first.tree(), autosaver.tick(first.tree(), false));
+            }
+            if let Some(pending) = &generation {
+                pending.draw(&mut d, &hud, time, height);
+            }
+            if let Some(timeline) = &timeline {
+                timeline.draw(&mut d, &hud, &renders, width, height);
+            }
+            if let Some(daemon) = &mut daemon {
+                let growing = renders.iter().any(|pretty| pretty.tree().is_growing());
+                if daemon.update(growing, time) && generation.is_none() {
+                    resumable = None;
+                    generation = Some(next_generation(&mut upcoming, configs));
+                }
+                daemon.draw(&mut d, sky, time);
+            }
+            let mouse = d.get_mouse_position();
+            shears.draw(&mut d, &hud, Vector2::new(mouse.x, mouse.y), height);
+
+            if renders.iter().any(|pretty| pretty.tree().config.preview) {
+                hud.text(
+                    &mut d,
+                    hud.tr(i18n::Text::RefinePreview),
+                    hud.px(10),
+                    hud.px(40),
+                    20,
+                    Color::DARKGRAY,
+                );
+            }
+            if resumable.is_some() {
+                hud.text(
+                    &mut d,
+                    hud.tr(i18n::Text::ResumeAutosave),
+                    hud.px(10),
+                    hud.px(10),
+                    20,
+                    Color::DARKGRAY,
+                );
+            }
+            // only the first tree speaks up, the others usually share its parameters
+            let warnings = renders.first().map(|first| first.tree().warnings());
+            for (idx, warning) in warnings.into_iter().flatten().enumerate() {
+                let y = height - hud.px(34) - idx as i32 * hud.px(24);
+                let line = hud.warning(warning);
+                hud.text(&mut d, &line, hud.px(10), y, 20, Color::MAROON);
+            }
+            if show_help {
+                let tree = renders.first().map(render::PrettyRender::tree);
+                let stats = tree.map(|tree| {
+                    [
+                        name::of(tree),
+                        hud.format(i18n::Text::StatSeed, tree.config.seed),
+                        hud.format(
+                            i18n::Text::StatNodes,
+                            tree.nodes().filter(|node| node.alive).count(),
+                        ),
+                        hud.format(i18n::Text::StatPoints, tree.points().len()),
+                        hud.tr(if tree.is_growing() {
+                            i18n::Text::StatGrowing
+                        } else {
+                            i18n::Text::StatFinished
+                        })
+                        .to_string(),
+                    ]
+                });
+                let lines = std::iter::once(hud.tr(i18n::Text::HelpTitle).to_string())
+                    .chain(i18n::HELP.iter().map(|text| format!("  {}", hud.tr(*text))))
+                    .chain(std::iter::once(String::new()))
+                    .chain(stats.into_iter().flatten());
+                for (idx, line) in lines.enumerate() {
+                    let y = hud.px(70) + idx as i32 * hud.px(24);
+                    hud.text(&mut d, &line, hud.px(10), y, 20, Color::DARKGRAY);
+                }
+            }
+            if let Some(first) = renders.first() {
+                overlay.draw(&mut d, &hud, first.tree(), width, height);
+            }
+            tuning.draw(&mut d, &hud, &tuned.get(), width);
+            // taken once the frame is finished, of everything on screen
+            drop(d);
+            if pad.screenshot {
+                let seed = renders
+                    .first()
+                    .map_or(first_seed, |first| first.tree().config.seed);
+                let name = format!("screenshot-{}.png", seed);
+                rl.take_screenshot(&thread, &name);
+                println!("wrote {}", name);
+            }
+        }
+    }));
+
+    if let (Some(first), None) = (renders.first(), &resumable) {
+        autosaved(app, first.tree(), autosaver.tick(first.tree(), true));
+    }
+    if let Err(panic) = outcome {
+        std::panic::resume_unwind(panic);
+    }
+    if args.benchmark {
+        println!("benchmark: {}", stats.summary());
+    }
+}
+
+/// The background image behind the trees, made for the size of the window and uploaded
+fn load_backdrop(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    config: &Config,
+    slots: &[composition::Slot],
+) -> Option<Texture2D> {
+    let background = config.background.as_ref()?;
+    let block = (slots[0].config.pixel_size as f32 * slots[0].zoom) as usize;
+    let (width, height) = (rl.get_screen_width(), rl.get_screen_height());
+    let frame = bonsai_render::background::render(
+        background,
+        width as _,
+        height as _,
+        block,
+        slots[0].offset,
+        config.sky,
+    )
+    .map_err(|err| err.to_string())
+    .and_then(|frame| bonsai_render::window::texture(rl, thread, &frame));
+    match frame {
+        Ok(texture) => Some(texture),
+        Err(err) => {
+            eprintln!("could not load {}: {}", background.path.display(), err);
+            None
+        }
+    }
+}
+
+/// Starts generating trees from a random seed in the background, the seed phrase comes along
+fn pregenerate(configs: impl Fn(u64) -> Vec<Config>) -> (String, generate::Generation) {
+    let phrase = seed::random_phrase(&mut rand::thread_rng());
+    let generation = generate::Generation::start(configs(seed::from_phrase(&phrase)));
+    (phrase, generation)
+}
+
+/// The trees to show next, the pre-generated ones if there are any, printing their seed phrase
+fn next_generation(
+    upcoming: &mut Option<(String, generate::Generation)>,
+    configs: impl Fn(u64) -> Vec<Config>,
+) -> generate::Generation {
+    let (phrase, generation) = upcoming.take().unwrap_or_else(|| pregenerate(configs));
+    println!("seed: {}", phrase);
+    generation
+}
+
+/// Tells how the autosave went, running the hooks once one is written
+fn autosaved(app: &AppConfig, tree: &Tree, written: Option<std::io::Result<std::path::PathBuf>>) {
+    match written {
+        Some(Ok(path)) => {
+            let seed = tree.config.seed;
+            app.fire_hooks(&hooks::Event::AutosaveWritten { seed, path });
+        }
+        Some(Err(err)) => eprintln!("autosave failed: {}", err),
+        None => {}
+    }
+}
+
+/// The tree last saved with `S`, to grow on where it stopped
+fn newest_save(base: &Config) -> Option<Tree> {
+    let path = save::list(std::path::Path::new(save::SAVE_DIR))
+        .into_iter()
+        .rfind(|path| !path.ends_with(save::AUTOSAVE))?;
+    Tree::load(&path, base)
+        .map_err(|err| eprintln!("could not open {}: {}", path.display(), err))
+        .ok()
+}
+
+/// The config growing and framing the first tree like the window shows it, for `--config`.
+/// The tree's own config carries the seed, the light and the colors it was changed to live,
+/// the size and layout come from the window's config the slots were made from
+fn shown_config(base: &Config, tree: &Config, view: composition::View) -> Config {
+    let mut config = Config {
+        width: base.width,
+        height: base.height,
+        origin: base.origin,
+        composition: base.composition.clone(),
+        ..tree.clone()
+    };
+    // a preview grows into the full tree once refined
+    if tree.preview {
+        config = Config {
+            attraction_dist: base.attraction_dist,
+            kill_dist: base.kill_dist,
+            grow_dist: base.grow_dist,
+            node_min_dist: base.node_min_dist,
+            subdivide_max_length: base.subdivide_max_length,
+            num_points: base.num_points,
+            preview: false,
+            ..config
+        };
+    }
+    config.composition.view = base.composition.view.then(view);
+    config
+}
+
+fn handle_export_keys(rl: &RaylibHandle, tree: &Tree) {
+    const KEYS: [(KeyboardKey, &[&str]); 4] = [
+        (KeyboardKey::KEY_V, &["bonsai.vox", "bonsai.schem"]),
+        (KeyboardKey::KEY_C, &["bonsai-chart.pdf"]),
+        (KeyboardKey::KEY_M, &["bonsai.stl"]),
+        (KeyboardKey::KEY_X, &["bonsai.svg", "bonsai-skeleton.json"]),
+    ];
+    let exporters = export::Registry::builtin();
+    for (key, files) in KEYS {
+        if rl.is_key_pressed(key) {
+            for file in files {
+                match exporters.save(tree, std::path::Path::new(file)) {
+                    Ok(warnings) => {
+                        println!("wrote {}", file);
+                        for warning in warnings {
+                            eprintln!("warning: {}", warning);
+                        }
+                    }
+                    Err(err) => eprintln!("{}", err),
+                }
+            }
+        }
+    }
+}